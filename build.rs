@@ -0,0 +1,26 @@
+use std::env;
+use std::process::Command;
+
+/// Embed the commit hash shown by /about, `GIT_HASH` wins for builds
+/// without a checkout (Docker)
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+
+    let hash = env::var("GIT_HASH")
+        .ok()
+        .filter(|hash| !hash.is_empty())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "--short", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .map(|hash| hash.trim().to_string())
+        })
+        .unwrap_or_else(|| "unknown".into());
+
+    println!("cargo:rustc-env=GIT_HASH={hash}");
+}
@@ -0,0 +1,122 @@
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{info, warn};
+
+/// Events a subscriber can fall behind by before it starts missing some
+const FEED_CAPACITY: usize = 256;
+
+/// Something that happened to a tracked account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityEvent {
+    AccountTracked {
+        guild_id: u64,
+        player_id: i64,
+        /// Discord user who tracked it
+        by: u64,
+    },
+    AccountUntracked {
+        guild_id: u64,
+        player_id: i64,
+    },
+    /// The guild's alert channel was set, moved or cleared
+    AlertChannelChanged {
+        guild_id: u64,
+    },
+    /// The dispatcher is done with the alert of a match
+    MatchProcessed {
+        player_id: i64,
+        match_id: String,
+    },
+    AlertSent {
+        guild_id: u64,
+        player_id: i64,
+        match_id: String,
+    },
+}
+
+/// Broadcasts account activity to whichever subsystems subscribe (audit log,
+/// stats...), so the commands and the poller don't need to know about them.
+/// Unlike `EventBus`, which routes Discord gateway events, these are the
+/// bot's own events.
+#[derive(Debug, Clone)]
+pub struct ActivityFeed {
+    tx: broadcast::Sender<ActivityEvent>,
+}
+
+impl Default for ActivityFeed {
+    fn default() -> Self {
+        Self {
+            tx: broadcast::channel(FEED_CAPACITY).0,
+        }
+    }
+}
+
+impl ActivityFeed {
+    /// Publishing without subscribers is fine, the event is dropped
+    pub fn publish(&self, event: ActivityEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ActivityEvent> {
+        self.tx.subscribe()
+    }
+}
+
+/// Audit log subscriber: every event goes to the logs
+pub async fn log_activity(mut rx: broadcast::Receiver<ActivityEvent>) {
+    loop {
+        match rx.recv().await {
+            Ok(ActivityEvent::AccountTracked {
+                guild_id,
+                player_id,
+                by,
+            }) => info!(guild_id, player_id, by, "📋 Account tracked"),
+            Ok(ActivityEvent::AccountUntracked {
+                guild_id,
+                player_id,
+            }) => info!(guild_id, player_id, "📋 Account untracked"),
+            Ok(ActivityEvent::AlertChannelChanged { guild_id }) => {
+                info!(guild_id, "📋 Alert channel changed")
+            }
+            Ok(ActivityEvent::MatchProcessed {
+                player_id,
+                match_id,
+            }) => info!(player_id, match_id, "📋 Match processed"),
+            Ok(ActivityEvent::AlertSent {
+                guild_id,
+                player_id,
+                match_id,
+            }) => info!(guild_id, player_id, match_id, "📋 Alert sent"),
+            Err(RecvError::Lagged(missed)) => {
+                warn!(missed, "📋 ⚠️ Audit log fell behind, events skipped");
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ActivityEvent, ActivityFeed};
+
+    #[tokio::test]
+    async fn every_subscriber_gets_the_events() {
+        let feed = ActivityFeed::default();
+        // Nobody listening yet
+        feed.publish(ActivityEvent::AccountUntracked {
+            guild_id: 1,
+            player_id: 1,
+        });
+
+        let (mut a, mut b) = (feed.subscribe(), feed.subscribe());
+        let event = ActivityEvent::AccountTracked {
+            guild_id: 1,
+            player_id: 2,
+            by: 3,
+        };
+        feed.publish(event.clone());
+
+        assert_eq!(a.recv().await.unwrap(), event);
+        assert_eq!(b.recv().await.unwrap(), event);
+        assert!(a.try_recv().is_err());
+    }
+}
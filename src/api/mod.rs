@@ -0,0 +1,31 @@
+mod roster;
+
+use std::net::SocketAddr;
+
+use axum::Router;
+use axum::routing::get;
+use tokio::net::TcpListener;
+use tracing::{error, info};
+
+use crate::db::Repository;
+
+/// Serve the read-only public API until the process exits
+pub async fn start_public_api(db: Repository, addr: SocketAddr) {
+    let app = Router::new()
+        .route("/roster", get(roster::get_roster))
+        .with_state(db);
+
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!(error = ?e, %addr, "🌐 ❌ Failed to bind public API");
+            return;
+        }
+    };
+
+    info!(%addr, "🌐 Public API listening");
+
+    if let Err(e) = axum::serve(listener, app).await {
+        error!(error = ?e, "🌐 ❌ Public API stopped");
+    }
+}
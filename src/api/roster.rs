@@ -0,0 +1,212 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+
+use crate::db::{GuildPlayer, RankInfo, Repository};
+
+/// Most players served per request, larger rosters are paged with `offset`
+const ROSTER_PAGE_MAX: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct RosterQuery {
+    token: Option<String>,
+    #[serde(default)]
+    offset: u32,
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct RosterResponse {
+    /// String, snowflakes don't fit in a JavaScript number
+    guild_id: String,
+    /// Players tracked in the guild, across every page
+    total: u32,
+    offset: u32,
+    players: Vec<RosterEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct RosterEntry {
+    riot_id: String,
+    region: String,
+    profile_icon_id: Option<i32>,
+    solo: Option<RankEntry>,
+    flex: Option<RankEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct RankEntry {
+    tier: String,
+    rank: String,
+    lp: i32,
+}
+
+impl From<RankInfo> for RankEntry {
+    fn from(rank: RankInfo) -> Self {
+        Self {
+            tier: rank.tier,
+            rank: rank.rank,
+            lp: rank.lp,
+        }
+    }
+}
+
+impl From<&GuildPlayer> for RosterEntry {
+    fn from(tracked: &GuildPlayer) -> Self {
+        let player = &tracked.player;
+        Self {
+            riot_id: player.display_name(),
+            region: player.region.clone(),
+            profile_icon_id: player.profile_icon_id.filter(|_| !player.is_hidden()),
+            solo: player.solo_rank_info().map(Into::into),
+            flex: player.flex_rank_info().map(Into::into),
+        }
+    }
+}
+
+/// Token from `Authorization: Bearer <token>`, or `?token=` for plain widget embeds
+fn request_token(headers: &HeaderMap, query: &RosterQuery) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+        .or_else(|| query.token.clone())
+        .filter(|token| !token.is_empty())
+}
+
+fn etag_for(body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// `GET /roster`: tracked players of the token's guild with their latest ranks
+pub async fn get_roster(
+    State(db): State<Repository>,
+    headers: HeaderMap,
+    Query(query): Query<RosterQuery>,
+) -> Response {
+    let Some(token) = request_token(&headers, &query) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    let guild = match db.get_guild_by_public_api_token(&token).await {
+        Ok(Some(guild)) => guild,
+        Ok(None) => return StatusCode::UNAUTHORIZED.into_response(),
+        Err(e) => {
+            error!(error = ?e, "🌐 ❌ Failed to resolve API token");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let guild_id = guild.id.get();
+    let limit = query
+        .limit
+        .unwrap_or(ROSTER_PAGE_MAX)
+        .clamp(1, ROSTER_PAGE_MAX);
+    let loaded = tokio::try_join!(
+        db.count_guild_players(guild_id),
+        db.get_guild_players_page(guild_id, query.offset, limit)
+    );
+    let (total, players) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!(error = ?e, guild_id, "🌐 ❌ Failed to load roster");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let roster = RosterResponse {
+        guild_id: guild_id.to_string(),
+        total,
+        offset: query.offset,
+        players: players.iter().map(RosterEntry::from).collect(),
+    };
+    let body = match serde_json::to_vec(&roster) {
+        Ok(body) => body,
+        Err(e) => {
+            error!(error = ?e, guild_id, "🌐 ❌ Failed to serialize roster");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let etag = etag_for(&body);
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+
+    let mut response = if not_modified {
+        debug!(guild_id, "🌐 Roster not modified");
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        debug!(
+            guild_id,
+            players = roster.players.len(),
+            "🌐 Serving roster"
+        );
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )],
+            body,
+        )
+            .into_response()
+    };
+
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, value);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, header};
+
+    use super::{RosterQuery, etag_for, request_token};
+
+    #[test]
+    fn bearer_token_wins_over_query() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer header-token"),
+        );
+        let query = RosterQuery {
+            token: Some("query-token".into()),
+            offset: 0,
+            limit: None,
+        };
+        assert_eq!(
+            request_token(&headers, &query).as_deref(),
+            Some("header-token")
+        );
+
+        let query = RosterQuery {
+            token: Some(String::new()),
+            offset: 0,
+            limit: None,
+        };
+        assert_eq!(request_token(&HeaderMap::new(), &query), None);
+    }
+
+    #[test]
+    fn etag_changes_with_body() {
+        assert_eq!(etag_for(b"[]"), etag_for(b"[]"));
+        assert_ne!(etag_for(b"[]"), etag_for(b"[1]"));
+    }
+}
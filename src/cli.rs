@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use sqlx::SqlitePool;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use crate::clock::{Clock, SystemClock};
+use crate::data_dir::DataDir;
+use crate::db::{self, InstanceLock, Repository};
+use crate::error::AppError;
+use crate::riot::normalize_riot_id;
+
+/// Discord bot following League of Legends accounts. The database comes
+/// from DATABASE_URL or DATA_DIR, as for the bot.
+#[derive(Debug, Parser)]
+#[command(name = "tentrackule", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+impl Cli {
+    /// What the binary was asked to do, the bot when nothing was
+    pub fn command(self) -> Command {
+        self.command.unwrap_or(Command::Run)
+    }
+}
+
+/// What the binary was asked to do
+#[derive(Debug, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Start the bot (default)
+    Run,
+    /// Apply database migrations and exit
+    Migrate,
+    /// Copy the database to a new file
+    Backup { file: PathBuf },
+    /// Replace the database with a backup, bot stopped
+    Restore { file: PathBuf },
+    /// List tracked accounts
+    ListAccounts,
+    /// Stop tracking an account in a server
+    Untrack {
+        /// Server ID
+        #[arg(long = "guild")]
+        guild_id: u64,
+        /// Account as NAME#TAG
+        #[arg(long)]
+        riot_id: String,
+    },
+}
+
+/// Run an administration command against the database, without Discord
+pub async fn run(command: Command) -> Result<(), AppError> {
+    dotenvy::dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").ok().filter(|v| !v.is_empty());
+    let options = DataDir::resolve().database_options(database_url.as_deref())?;
+
+    match command {
+        // Served by main, which starts the bot
+        Command::Run => {}
+        Command::Migrate => {
+            let pool = connect(options.create_if_missing(true)).await?;
+            db::check_integrity(&pool).await?;
+            db::run_migrations(&pool).await?;
+            println!("Database is up to date");
+        }
+        Command::Backup { file: target } => {
+            if target.exists() {
+                return Err(AppError::Config(format!(
+                    "{} already exists",
+                    target.display()
+                )));
+            }
+            let repository = open(options).await?;
+            repository.backup_into(&target).await?;
+            println!("Database copied to {}", target.display());
+        }
+        Command::Restore { file } => restore(&file, &options).await?,
+        Command::ListAccounts => {
+            let repository = open(options).await?;
+            let players = repository.get_all_tracked_players().await?;
+            for player in &players {
+                let guilds = repository.get_guilds_tracking_player(player.id).await?;
+                let guild_ids = guilds
+                    .iter()
+                    .map(|guild| guild.id.get().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!("{}\t{}\t{}", player.riot_id(), player.region, guild_ids);
+            }
+            println!("{} tracked account(s)", players.len());
+        }
+        Command::Untrack { guild_id, riot_id } => {
+            let (game_name, tag_line) = riot_id.split_once('#').ok_or_else(|| {
+                AppError::InvalidRiotId(format!("\"{riot_id}\" is not a NAME#TAG riot id"))
+            })?;
+            let (game_name, tag_line) = normalize_riot_id(game_name, tag_line)?;
+
+            let repository = open(options).await?;
+            let player = repository
+                .get_player_by_riot_id(&game_name, &tag_line)
+                .await?
+                .ok_or(AppError::PlayerNotFound {
+                    game_name: game_name.clone(),
+                    tag_line: tag_line.clone(),
+                })?;
+            if !repository
+                .remove_player_from_guild(guild_id, player.id, SystemClock.unix_now())
+                .await?
+            {
+                return Err(AppError::PlayerNotTracked);
+            }
+            println!("Stopped tracking {} in {guild_id}", player.riot_id());
+        }
+    }
+
+    Ok(())
+}
+
+async fn connect(options: SqliteConnectOptions) -> Result<SqlitePool, AppError> {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?;
+    Ok(pool)
+}
+
+/// The existing database, at the version this build expects
+async fn open(options: SqliteConnectOptions) -> Result<Repository, AppError> {
+    let pool = connect(options.create_if_missing(false)).await?;
+    db::check_schema_version(&pool).await?;
+    Ok(Repository::new(pool))
+}
+
+/// Check the backup, then copy it over the database. The bot must be stopped,
+/// it would keep writing to the replaced file otherwise.
+async fn restore(source: &Path, options: &SqliteConnectOptions) -> Result<(), AppError> {
+    let target = options.get_filename();
+    // Held until the copy is done, so no bot starts halfway through
+    let _lock = InstanceLock::exclusive(target)?;
+
+    let backup = connect(SqliteConnectOptions::new().filename(source).read_only(true)).await?;
+    db::check_integrity(&backup).await?;
+    backup.close().await;
+
+    tokio::fs::copy(source, target).await?;
+    // Journal files of the replaced database would be replayed onto the backup
+    for suffix in ["-wal", "-shm"] {
+        let mut journal = target.as_os_str().to_owned();
+        journal.push(suffix);
+        match tokio::fs::remove_file(&journal).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+
+    println!(
+        "Restored {} from {}, migrations run on the next start",
+        target.display(),
+        source.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::{Cli, Command};
+
+    fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+        Cli::try_parse_from(std::iter::once("tentrackule").chain(args.iter().copied()))
+            .map(Cli::command)
+    }
+
+    #[test]
+    fn parses_subcommands() {
+        assert_eq!(parse(&[]).unwrap(), Command::Run);
+        assert_eq!(
+            parse(&["backup", "copy.db"]).unwrap(),
+            Command::Backup {
+                file: "copy.db".into()
+            }
+        );
+        assert_eq!(
+            parse(&["untrack", "--riot-id", "Faker#KR1", "--guild", "42"]).unwrap(),
+            Command::Untrack {
+                guild_id: 42,
+                riot_id: "Faker#KR1".into()
+            }
+        );
+        assert!(parse(&["untrack", "--guild", "42"]).is_err());
+        assert!(parse(&["untrack", "--guild", "x", "--riot-id", "Faker#KR1"]).is_err());
+        assert!(parse(&["backup"]).is_err());
+        assert!(parse(&["migrate", "now"]).is_err());
+        assert!(parse(&["serve"]).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use std::fmt::Debug;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source of wall-clock time, injected so tests can control time
+pub trait Clock: Send + Sync + Debug {
+    fn now(&self) -> SystemTime;
+
+    /// Seconds since the Unix epoch
+    fn unix_now(&self) -> i64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs() as i64
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Clock that only moves when told to
+#[cfg(test)]
+#[derive(Debug)]
+pub struct ManualClock {
+    now: std::sync::Mutex<SystemTime>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(unix_secs: u64) -> Self {
+        Self {
+            now: std::sync::Mutex::new(UNIX_EPOCH + Duration::from_secs(unix_secs)),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap()
+    }
+}
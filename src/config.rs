@@ -1,16 +1,140 @@
 use std::env;
+use std::fmt;
+use std::net::SocketAddr;
 use std::num::NonZeroU32;
+use std::path::PathBuf;
 
+use crate::data_dir::DataDir;
 use crate::error::AppError;
 
+/// How long history rows are kept before the vacuum job prunes them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionPolicy {
+    /// Drop rows older than this many days
+    pub max_age_days: Option<u32>,
+    /// Keep at most this many rows per player
+    pub max_rows_per_player: Option<u32>,
+}
+
+/// Faster re-polling of accounts that just finished a game, since players
+/// often queue again right away.
+#[derive(Debug, Clone, Copy)]
+pub struct BurstPolling {
+    /// Interval between polls of a bursting account
+    pub interval_secs: u64,
+    /// How long after a detected game the account keeps bursting
+    pub window_secs: u64,
+}
+
+/// Bounds the poll interval is tuned within, from Riot 429s and how long
+/// cycles take, so one configuration suits small and large deployments
+#[derive(Debug, Clone, Copy)]
+pub struct AutoTune {
+    pub min_interval_secs: u64,
+    pub max_interval_secs: u64,
+}
+
+/// When the match poller checks accounts
+#[derive(Debug, Clone, Copy)]
+pub struct PollingCadence {
+    pub interval_secs: u64,
+    /// Disabled when unset
+    pub burst: Option<BurstPolling>,
+    /// Fixed interval when unset
+    pub auto_tune: Option<AutoTune>,
+}
+
+/// A chat outside Discord mirroring the match alerts of this deployment
+#[derive(Clone)]
+pub enum RelayTarget {
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+    },
+    Matrix {
+        homeserver: String,
+        access_token: String,
+        room_id: String,
+    },
+}
+
+impl RelayTarget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Telegram { .. } => "telegram",
+            Self::Matrix { .. } => "matrix",
+        }
+    }
+}
+
+// Tokens stay out of logs
+impl fmt::Debug for RelayTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Telegram { chat_id, .. } => f
+                .debug_struct("Telegram")
+                .field("chat_id", chat_id)
+                .finish(),
+            Self::Matrix {
+                homeserver,
+                room_id,
+                ..
+            } => f
+                .debug_struct("Matrix")
+                .field("homeserver", homeserver)
+                .field("room_id", room_id)
+                .finish(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub discord_token: String,
     pub riot_api_key: String,
-    pub database_url: String,
-    pub polling_interval_secs: u64,
+    /// Explicit sqlx url, the data directory database otherwise
+    pub database_url: Option<String>,
+    pub data_dir: DataDir,
+    pub polling: PollingCadence,
+    pub alert_queue_capacity: usize,
+    /// Alerts that could not reach Discord for longer than this are dropped
+    pub alert_buffer_max_age_minutes: u64,
     pub riot_rate_limit_per_second: NonZeroU32,
     pub ddragon_version: String,
+    pub vacuum_interval_hours: u64,
+    /// Daily UTC time of the vacuum run, replaces the interval when set
+    pub vacuum_at: Option<(u32, u32)>,
+    pub ladder_watch_interval_hours: u64,
+    /// How often ranks are checked for changes without a game, disabled when unset
+    pub rank_watch_interval_hours: Option<u64>,
+    pub retention: RetentionPolicy,
+    /// Channel receiving operator notices
+    pub ops_channel_id: Option<u64>,
+    /// Run the whole pipeline but never post alerts to guild channels
+    pub shadow_mode: bool,
+    /// Where shadow alerts are written, if anywhere
+    pub shadow_output_dir: Option<PathBuf>,
+    /// Listen address of the read-only public API, disabled when unset
+    pub public_api_addr: Option<SocketAddr>,
+    /// Server the bot uploads its tier emojis to
+    pub emoji_guild_id: Option<u64>,
+    /// How often "game in progress" messages are refreshed, disabled when unset
+    pub live_game_interval_secs: Option<u64>,
+    /// Read messages for prefix commands (needs the Message Content intent)
+    pub prefix_commands: bool,
+    /// Let players hold back their own alerts while online in a server (needs
+    /// the Presence intent)
+    pub quiet_when_online: bool,
+    /// Serve read commands only, no polling nor writes
+    pub read_only: bool,
+    /// JSON rules rewriting names and text of alerts, see `ContentFilter`
+    pub content_rules_file: Option<PathBuf>,
+    /// Chats outside Discord receiving match alerts too
+    pub relays: Vec<RelayTarget>,
+    /// Messages each relay may send per minute, extra alerts are dropped
+    pub relay_rate_limit_per_minute: NonZeroU32,
+    /// Most accounts a guild may track, guilds can only lower it
+    pub track_limit: Option<u32>,
 }
 
 impl Config {
@@ -18,8 +142,18 @@ impl Config {
         dotenvy::dotenv().ok();
 
         const DEFAULT_POLLING_INTERVAL_SECS: u64 = 60;
+        const DEFAULT_BURST_WINDOW_MINUTES: u64 = 30;
+        const DEFAULT_AUTO_TUNE_MIN_INTERVAL_SECS: u64 = 30;
+        const DEFAULT_AUTO_TUNE_MAX_INTERVAL_SECS: u64 = 600;
+        const DEFAULT_ALERT_QUEUE_CAPACITY: usize = 32;
+        const DEFAULT_ALERT_BUFFER_MAX_AGE_MINUTES: u64 = 360;
         const DEFAULT_RIOT_RATE_LIMIT_PER_SECOND: u32 = 20;
         const DEFAULT_DDRAGON_VERSION: &str = "16.1.1";
+        const DEFAULT_VACUUM_INTERVAL_HOURS: u64 = 24;
+        const DEFAULT_LADDER_WATCH_INTERVAL_HOURS: u64 = 24;
+        const DEFAULT_RETENTION_DAYS: u32 = 90;
+        const DEFAULT_RELAY_RATE_LIMIT_PER_MINUTE: u32 = 20;
+        const DEFAULT_TRACK_LIMIT: u32 = 100;
 
         let discord_token = env::var("DISCORD_TOKEN")
             .map_err(|_| AppError::Config("DISCORD_TOKEN must be set".into()))?;
@@ -27,33 +161,372 @@ impl Config {
         let riot_api_key = env::var("RIOT_API_KEY")
             .map_err(|_| AppError::Config("RIOT_API_KEY must be set".into()))?;
 
-        let database_url =
-            env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:tentrackule.db".into());
+        let database_url = env::var("DATABASE_URL").ok().filter(|v| !v.is_empty());
+        let data_dir = DataDir::resolve();
 
         let polling_interval_secs = env::var("POLLING_INTERVAL_SECS")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(DEFAULT_POLLING_INTERVAL_SECS);
 
+        // Only worth it when faster than the regular cadence
+        let burst_polling = env::var("BURST_POLLING_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&secs| secs > 0 && secs < polling_interval_secs)
+            .map(|interval_secs| BurstPolling {
+                interval_secs,
+                window_secs: env::var("BURST_WINDOW_MINUTES")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&minutes| minutes > 0)
+                    .unwrap_or(DEFAULT_BURST_WINDOW_MINUTES)
+                    * 60,
+            });
+
+        let auto_tune = env::var("POLL_AUTO_TUNE")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false)
+            .then(|| {
+                let bound = |name: &str, default: u64| {
+                    env::var(name)
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .filter(|&secs| secs > 0)
+                        .unwrap_or(default)
+                };
+                let min_interval_secs = bound(
+                    "AUTO_TUNE_MIN_INTERVAL_SECS",
+                    DEFAULT_AUTO_TUNE_MIN_INTERVAL_SECS,
+                );
+                AutoTune {
+                    min_interval_secs,
+                    max_interval_secs: bound(
+                        "AUTO_TUNE_MAX_INTERVAL_SECS",
+                        DEFAULT_AUTO_TUNE_MAX_INTERVAL_SECS,
+                    )
+                    .max(min_interval_secs),
+                }
+            });
+
+        let alert_queue_capacity = env::var("ALERT_QUEUE_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&capacity| capacity > 0)
+            .unwrap_or(DEFAULT_ALERT_QUEUE_CAPACITY);
+
+        let alert_buffer_max_age_minutes = env::var("ALERT_BUFFER_MAX_AGE_MINUTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_ALERT_BUFFER_MAX_AGE_MINUTES);
+
         let riot_rate_limit_per_second = env::var("RIOT_RATE_LIMIT_PER_SECOND")
             .ok()
             .and_then(|v| v.parse().ok())
             .and_then(NonZeroU32::new)
             .unwrap_or_else(|| {
-                NonZeroU32::new(DEFAULT_RIOT_RATE_LIMIT_PER_SECOND)
-                    .unwrap_or(NonZeroU32::MIN)
+                NonZeroU32::new(DEFAULT_RIOT_RATE_LIMIT_PER_SECOND).unwrap_or(NonZeroU32::MIN)
             });
 
         let ddragon_version =
             env::var("DDRAGON_VERSION").unwrap_or_else(|_| DEFAULT_DDRAGON_VERSION.into());
 
+        let vacuum_interval_hours = env::var("VACUUM_INTERVAL_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&hours| hours > 0)
+            .unwrap_or(DEFAULT_VACUUM_INTERVAL_HOURS);
+
+        let vacuum_at = env::var("VACUUM_AT")
+            .ok()
+            .map(|v| {
+                parse_time_of_day(&v).ok_or(AppError::Config(format!("Invalid VACUUM_AT: {v}")))
+            })
+            .transpose()?;
+
+        let ladder_watch_interval_hours = env::var("LADDER_WATCH_INTERVAL_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&hours| hours > 0)
+            .unwrap_or(DEFAULT_LADDER_WATCH_INTERVAL_HOURS);
+
+        // "0" disables a limit, unset falls back to the default
+        let rank_watch_interval_hours = env::var("RANK_WATCH_INTERVAL_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&hours| hours > 0);
+
+        let retention = RetentionPolicy {
+            max_age_days: match env::var("RETENTION_DAYS") {
+                Ok(v) => v.parse().ok().filter(|&days| days > 0),
+                Err(_) => Some(DEFAULT_RETENTION_DAYS),
+            },
+            max_rows_per_player: env::var("RETENTION_MAX_ROWS_PER_PLAYER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&rows| rows > 0),
+        };
+
+        let ops_channel_id = env::var("OPS_CHANNEL_ID").ok().and_then(|v| v.parse().ok());
+
+        let shadow_mode = env::var("SHADOW_MODE")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let shadow_output_dir = env::var("SHADOW_OUTPUT_DIR").ok().map(PathBuf::from);
+
+        let public_api_addr = env::var("PUBLIC_API_ADDR")
+            .ok()
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| AppError::Config(format!("Invalid PUBLIC_API_ADDR: {v}")))
+            })
+            .transpose()?;
+
+        let emoji_guild_id = env::var("EMOJI_GUILD_ID").ok().and_then(|v| v.parse().ok());
+
+        let prefix_commands = env::var("PREFIX_COMMANDS")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let quiet_when_online = env::var("QUIET_WHEN_ONLINE")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let read_only = env::var("READ_ONLY")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let content_rules_file = env::var("CONTENT_RULES_FILE").ok().map(PathBuf::from);
+
+        let relays = relay_targets()?;
+
+        let relay_rate_limit_per_minute = env::var("RELAY_RATE_LIMIT_PER_MINUTE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .and_then(NonZeroU32::new)
+            .unwrap_or_else(|| {
+                NonZeroU32::new(DEFAULT_RELAY_RATE_LIMIT_PER_MINUTE).unwrap_or(NonZeroU32::MIN)
+            });
+
+        // Every account is polled, this keeps one guild from eating the Riot budget
+        let track_limit = match env::var("TRACK_LIMIT") {
+            Ok(v) => v.parse().ok().filter(|&limit| limit > 0),
+            Err(_) => Some(DEFAULT_TRACK_LIMIT),
+        };
+
+        let live_game_interval_secs = env::var("LIVE_GAME_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&secs| secs > 0);
+
         Ok(Self {
             discord_token,
             riot_api_key,
             database_url,
-            polling_interval_secs,
+            data_dir,
+            polling: PollingCadence {
+                interval_secs: polling_interval_secs,
+                burst: burst_polling,
+                auto_tune,
+            },
+            alert_queue_capacity,
+            alert_buffer_max_age_minutes,
             riot_rate_limit_per_second,
             ddragon_version,
+            vacuum_interval_hours,
+            vacuum_at,
+            ladder_watch_interval_hours,
+            rank_watch_interval_hours,
+            retention,
+            ops_channel_id,
+            shadow_mode,
+            shadow_output_dir,
+            public_api_addr,
+            emoji_guild_id,
+            live_game_interval_secs,
+            prefix_commands,
+            quiet_when_online,
+            read_only,
+            content_rules_file,
+            relays,
+            relay_rate_limit_per_minute,
+            track_limit,
         })
     }
+
+    /// Non-secret settings as (name, value) pairs, for operators checking
+    /// what a running instance uses
+    pub fn summary(&self) -> Vec<(&'static str, String)> {
+        let enabled = |on: bool| if on { "on" } else { "off" }.to_string();
+        let or_off = |value: Option<String>| value.unwrap_or_else(|| "off".into());
+
+        vec![
+            (
+                "Polling",
+                format!(
+                    "every {}s{}{}",
+                    self.polling.interval_secs,
+                    self.polling
+                        .auto_tune
+                        .map(|tune| format!(
+                            " (auto-tuned within {}-{}s)",
+                            tune.min_interval_secs, tune.max_interval_secs
+                        ))
+                        .unwrap_or_default(),
+                    self.polling
+                        .burst
+                        .map(|burst| format!(
+                            ", {}s for {} min after a game",
+                            burst.interval_secs,
+                            burst.window_secs / 60
+                        ))
+                        .unwrap_or_default()
+                ),
+            ),
+            (
+                "Live games",
+                or_off(
+                    self.live_game_interval_secs
+                        .map(|secs| format!("every {secs}s")),
+                ),
+            ),
+            (
+                "Ladder watch",
+                format!("every {}h", self.ladder_watch_interval_hours),
+            ),
+            (
+                "Rank watch",
+                or_off(
+                    self.rank_watch_interval_hours
+                        .map(|hours| format!("every {hours}h")),
+                ),
+            ),
+            (
+                "Alert queue",
+                format!(
+                    "{} slots, buffered up to {} min",
+                    self.alert_queue_capacity, self.alert_buffer_max_age_minutes
+                ),
+            ),
+            (
+                "Track limit",
+                or_off(
+                    self.track_limit
+                        .map(|limit| format!("{limit} accounts/guild")),
+                ),
+            ),
+            (
+                "Riot rate limit",
+                format!("{} req/s", self.riot_rate_limit_per_second),
+            ),
+            ("Data Dragon", self.ddragon_version.clone()),
+            (
+                "Retention",
+                format!(
+                    "{} days, {} rows/player, vacuum {}",
+                    or_off(self.retention.max_age_days.map(|d| d.to_string())),
+                    or_off(self.retention.max_rows_per_player.map(|r| r.to_string())),
+                    match self.vacuum_at {
+                        Some((hour, minute)) => format!("daily at {hour:02}:{minute:02} UTC"),
+                        None => format!("every {}h", self.vacuum_interval_hours),
+                    }
+                ),
+            ),
+            (
+                "Data dir",
+                match self.database_url {
+                    Some(_) => format!("{} (custom DATABASE_URL)", self.data_dir.root().display()),
+                    None => self.data_dir.root().display().to_string(),
+                },
+            ),
+            (
+                "Ops channel",
+                or_off(self.ops_channel_id.map(|id| format!("<#{id}>"))),
+            ),
+            ("Read-only", enabled(self.read_only)),
+            ("Shadow mode", enabled(self.shadow_mode)),
+            ("Prefix commands", enabled(self.prefix_commands)),
+            ("Quiet when online", enabled(self.quiet_when_online)),
+            (
+                "Public API",
+                or_off(self.public_api_addr.map(|addr| addr.to_string())),
+            ),
+            (
+                "Emoji server",
+                or_off(self.emoji_guild_id.map(|id| id.to_string())),
+            ),
+            (
+                "Content rules",
+                or_off(
+                    self.content_rules_file
+                        .as_ref()
+                        .map(|path| path.display().to_string()),
+                ),
+            ),
+            (
+                "Relays",
+                if self.relays.is_empty() {
+                    "off".to_string()
+                } else {
+                    format!(
+                        "{}, {} msg/min each",
+                        self.relays
+                            .iter()
+                            .map(RelayTarget::name)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        self.relay_rate_limit_per_minute
+                    )
+                },
+            ),
+        ]
+    }
+}
+
+/// Relays whose variables are all set. A relay configured halfway is an error
+/// rather than silently left out.
+fn relay_targets() -> Result<Vec<RelayTarget>, AppError> {
+    let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+    let mut relays = Vec::new();
+
+    match (var("TELEGRAM_BOT_TOKEN"), var("TELEGRAM_CHAT_ID")) {
+        (Some(bot_token), Some(chat_id)) => {
+            relays.push(RelayTarget::Telegram { bot_token, chat_id })
+        }
+        (None, None) => {}
+        _ => {
+            return Err(AppError::Config(
+                "TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID must be set together".into(),
+            ));
+        }
+    }
+
+    match (
+        var("MATRIX_HOMESERVER"),
+        var("MATRIX_ACCESS_TOKEN"),
+        var("MATRIX_ROOM_ID"),
+    ) {
+        (Some(homeserver), Some(access_token), Some(room_id)) => relays.push(RelayTarget::Matrix {
+            homeserver: homeserver.trim_end_matches('/').to_string(),
+            access_token,
+            room_id,
+        }),
+        (None, None, None) => {}
+        _ => {
+            return Err(AppError::Config(
+                "MATRIX_HOMESERVER, MATRIX_ACCESS_TOKEN and MATRIX_ROOM_ID must be set together"
+                    .into(),
+            ));
+        }
+    }
+
+    Ok(relays)
+}
+
+/// "HH:MM" to (hour, minute)
+fn parse_time_of_day(value: &str) -> Option<(u32, u32)> {
+    let (hour, minute) = value.trim().split_once(':')?;
+    let (hour, minute) = (hour.parse().ok()?, minute.parse().ok()?);
+    (hour < 24 && minute < 60).then_some((hour, minute))
 }
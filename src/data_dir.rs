@@ -0,0 +1,137 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use sqlx::sqlite::SqliteConnectOptions;
+
+use crate::error::AppError;
+
+const DATABASE_FILE: &str = "tentrackule.db";
+
+/// Where the bot keeps its files (database, image cache)
+#[derive(Debug, Clone)]
+pub struct DataDir {
+    root: PathBuf,
+    /// Files found in the working directory from before `DATA_DIR` existed,
+    /// keep their original layout
+    legacy: bool,
+}
+
+impl DataDir {
+    /// `DATA_DIR` when set, the working directory if it already holds a
+    /// database, otherwise the platform data directory
+    pub fn resolve() -> Self {
+        let cwd_has_database = Path::new(DATABASE_FILE).exists();
+        Self::resolve_with(
+            |name| env::var_os(name).map(PathBuf::from),
+            cwd_has_database,
+        )
+    }
+
+    fn resolve_with(var: impl Fn(&str) -> Option<PathBuf>, cwd_has_database: bool) -> Self {
+        if let Some(root) = var("DATA_DIR").filter(|p| !p.as_os_str().is_empty()) {
+            return Self {
+                root,
+                legacy: false,
+            };
+        }
+        if cwd_has_database {
+            return Self {
+                root: PathBuf::from("."),
+                legacy: true,
+            };
+        }
+
+        let root = if cfg!(windows) {
+            var("APPDATA").map(|dir| dir.join("Tentrackule"))
+        } else if cfg!(target_os = "macos") {
+            var("HOME").map(|home| home.join("Library/Application Support/Tentrackule"))
+        } else {
+            var("XDG_DATA_HOME")
+                .filter(|dir| dir.is_absolute())
+                .or_else(|| var("HOME").map(|home| home.join(".local/share")))
+                .map(|dir| dir.join("tentrackule"))
+        };
+
+        match root {
+            Some(root) => Self {
+                root,
+                legacy: false,
+            },
+            None => Self {
+                root: PathBuf::from("."),
+                legacy: true,
+            },
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn database(&self) -> PathBuf {
+        self.root.join(DATABASE_FILE)
+    }
+
+    /// Connection options of the database, `database_url` (`DATABASE_URL`)
+    /// winning over the data directory, which is created when missing
+    pub fn database_options(
+        &self,
+        database_url: Option<&str>,
+    ) -> Result<SqliteConnectOptions, AppError> {
+        match database_url {
+            Some(url) => Ok(url.parse::<SqliteConnectOptions>()?),
+            None => {
+                std::fs::create_dir_all(&self.root)?;
+                Ok(SqliteConnectOptions::new().filename(self.database()))
+            }
+        }
+    }
+
+    pub fn image_cache(&self) -> PathBuf {
+        if self.legacy {
+            self.root.join(".cache").join("images")
+        } else {
+            self.root.join("cache").join("images")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::DataDir;
+
+    fn resolve(vars: &[(&str, &str)], cwd_has_database: bool) -> DataDir {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        DataDir::resolve_with(|name| vars.get(name).map(PathBuf::from), cwd_has_database)
+    }
+
+    #[test]
+    fn data_dir_prefers_override_then_existing_database() {
+        let dir = resolve(&[("DATA_DIR", "/srv/bot"), ("HOME", "/home/me")], true);
+        assert_eq!(dir.database(), PathBuf::from("/srv/bot/tentrackule.db"));
+        assert_eq!(dir.image_cache(), PathBuf::from("/srv/bot/cache/images"));
+
+        let dir = resolve(&[("HOME", "/home/me")], true);
+        assert_eq!(dir.database(), PathBuf::from("./tentrackule.db"));
+        assert_eq!(dir.image_cache(), PathBuf::from("./.cache/images"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn data_dir_follows_xdg() {
+        let dir = resolve(&[("HOME", "/home/me")], false);
+        assert_eq!(
+            dir.root(),
+            PathBuf::from("/home/me/.local/share/tentrackule")
+        );
+
+        let dir = resolve(&[("HOME", "/home/me"), ("XDG_DATA_HOME", "/xdg")], false);
+        assert_eq!(dir.root(), PathBuf::from("/xdg/tentrackule"));
+
+        let dir = resolve(&[], false);
+        assert_eq!(dir.root(), PathBuf::from("."));
+    }
+}
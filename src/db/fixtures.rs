@@ -0,0 +1,24 @@
+//! Rows built in memory for tests that don't need a database
+
+use super::Player;
+
+/// Unranked account with nothing but its Riot ID
+pub fn player(game_name: &str, tag_line: &str) -> Player {
+    Player {
+        id: 1,
+        puuid: format!("{game_name}-puuid"),
+        game_name: game_name.to_string(),
+        tag_line: tag_line.to_string(),
+        region: "euw1".to_string(),
+        profile_icon_id: None,
+        last_match_id: None,
+        last_rank_solo_tier: None,
+        last_rank_solo_rank: None,
+        last_rank_solo_lp: None,
+        last_rank_flex_tier: None,
+        last_rank_flex_rank: None,
+        last_rank_flex_lp: None,
+        privacy_alias: None,
+        no_matches_since: None,
+    }
+}
@@ -0,0 +1,81 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+/// Lock file next to the database, held by every running bot. Instances
+/// share it (blue/green deploys, shadow and read-only ones), offline
+/// maintenance such as a restore needs it alone. The OS drops the lock with
+/// the process, a crashed bot never leaves it behind.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Taken by the bot for as long as it runs
+    pub fn shared(database: &Path) -> Result<Self, AppError> {
+        let file = open(database)?;
+        match file.try_lock_shared() {
+            Ok(()) => Ok(Self { _file: file }),
+            // Only a restore holds it exclusively, and briefly
+            Err(TryLockError::WouldBlock) => Err(AppError::Config(format!(
+                "{} is being restored, start the bot once it is done",
+                database.display()
+            ))),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
+    /// Taken by commands replacing the database, fails while a bot runs on it
+    pub fn exclusive(database: &Path) -> Result<Self, AppError> {
+        let file = open(database)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Self { _file: file }),
+            Err(TryLockError::WouldBlock) => Err(AppError::Config(format!(
+                "A bot instance is running on {}, stop it first",
+                database.display()
+            ))),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+}
+
+fn open(database: &Path) -> Result<File, AppError> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_path(database))?;
+    Ok(file)
+}
+
+fn lock_path(database: &Path) -> PathBuf {
+    let mut path = database.as_os_str().to_owned();
+    path.push(".lock");
+    path.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InstanceLock;
+
+    #[test]
+    fn a_running_bot_blocks_maintenance() {
+        let dir = std::env::temp_dir().join(format!("tentrackule-lock-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let database = dir.join("tentrackule.db");
+
+        let bot = InstanceLock::shared(&database).unwrap();
+        let other_bot = InstanceLock::shared(&database).unwrap();
+        assert!(InstanceLock::exclusive(&database).is_err());
+
+        drop((bot, other_bot));
+        let restore = InstanceLock::exclusive(&database).unwrap();
+        assert!(InstanceLock::shared(&database).is_err());
+
+        drop(restore);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+use sqlx::SqlitePool;
+use tracing::{error, info};
+
+use crate::error::AppError;
+
+/// Problems reported before giving up, the full list can be huge
+const MAX_REPORTED_ERRORS: u32 = 10;
+
+/// Run SQLite's integrity check so a corrupted file stops the bot at startup
+/// with a clear message, instead of failing queries at random later on
+pub async fn check_integrity(pool: &SqlitePool) -> Result<(), AppError> {
+    let problems: Vec<String> =
+        sqlx::query_scalar(&format!("PRAGMA integrity_check({MAX_REPORTED_ERRORS})"))
+            .fetch_all(pool)
+            .await?;
+
+    if problems.iter().all(|row| row == "ok") {
+        info!("🗄️ Database integrity check passed");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        error!(problem = %problem, "🗄️ ❌ Database integrity check failed");
+    }
+    Err(AppError::DatabaseCorrupted(problems.join("; ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::check_integrity;
+
+    #[tokio::test]
+    async fn healthy_database_passes() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+
+        check_integrity(&pool).await.unwrap();
+    }
+}
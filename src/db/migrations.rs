@@ -1,8 +1,18 @@
+use std::time::Duration;
+
+use rand::Rng;
+use rand::distr::Alphanumeric;
 use sqlx::SqlitePool;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::error::AppError;
 
+/// A process that died mid-migration releases the lock after this long
+const LOCK_TTL_SECS: i64 = 300;
+
+/// Pause between attempts while another process holds the lock
+const LOCK_RETRY: Duration = Duration::from_secs(1);
+
 const SCHEMA: &str = r#"
 CREATE TABLE IF NOT EXISTS players (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -37,12 +47,547 @@ CREATE TABLE IF NOT EXISTS guild_players (
     FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
 );
 
+CREATE TABLE IF NOT EXISTS alert_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    player_id INTEGER NOT NULL,
+    guild_id INTEGER NOT NULL,
+    match_id TEXT NOT NULL,
+    sent_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+);
+
+-- Held by the process applying migrations, when several share the database
+CREATE TABLE IF NOT EXISTS migration_lock (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    owner TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+
 CREATE INDEX IF NOT EXISTS idx_players_puuid ON players(puuid);
 CREATE INDEX IF NOT EXISTS idx_guild_players_guild ON guild_players(guild_id);
+CREATE INDEX IF NOT EXISTS idx_alert_log_player ON alert_log(player_id, sent_at);
 "#;
 
+/// Incremental changes applied on top of `SCHEMA`, in order.
+/// Progress is tracked through `PRAGMA user_version`; only ever append.
+const MIGRATIONS: &[&str] = &[
+    // 1: per-guild timezone
+    "ALTER TABLE guilds ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC';",
+    // 2: apex ladder watches
+    r#"
+    CREATE TABLE ladder_watches (
+        guild_id INTEGER NOT NULL,
+        region TEXT NOT NULL,
+        tier TEXT NOT NULL,
+        top_cutoff_lp INTEGER,
+        PRIMARY KEY (guild_id, region, tier),
+        FOREIGN KEY (guild_id) REFERENCES guilds(id) ON DELETE CASCADE
+    );
+
+    CREATE TABLE ladder_watch_members (
+        guild_id INTEGER NOT NULL,
+        region TEXT NOT NULL,
+        tier TEXT NOT NULL,
+        player_id INTEGER NOT NULL,
+        PRIMARY KEY (guild_id, region, tier, player_id),
+        FOREIGN KEY (guild_id, region, tier)
+            REFERENCES ladder_watches(guild_id, region, tier) ON DELETE CASCADE,
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+    "#,
+    // 3: per-guild alert detail level
+    "ALTER TABLE guilds ADD COLUMN alert_details INTEGER NOT NULL DEFAULT 1;",
+    // 4: per-guild stat field layout (JSON array, NULL for the default)
+    "ALTER TABLE guilds ADD COLUMN alert_fields TEXT;",
+    // 5: token for the read-only public roster API
+    r#"
+    ALTER TABLE guilds ADD COLUMN public_api_token TEXT;
+    CREATE UNIQUE INDEX idx_guilds_public_api_token ON guilds(public_api_token);
+    "#,
+    // 6: custom emojis uploaded to (or found on) the emoji server
+    r#"
+    CREATE TABLE emojis (
+        name TEXT PRIMARY KEY,
+        emoji_id INTEGER NOT NULL
+    );
+    "#,
+    // 7: per-game account identities, backfilled with the League puuids
+    r#"
+    CREATE TABLE player_identities (
+        player_id INTEGER NOT NULL,
+        game TEXT NOT NULL,
+        puuid TEXT NOT NULL,
+        PRIMARY KEY (player_id, game),
+        UNIQUE (game, puuid),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+
+    INSERT INTO player_identities (player_id, game, puuid)
+    SELECT id, 'lol', puuid FROM players;
+    "#,
+    // 8: soft-deleted guild tracking, purged after a grace period
+    "ALTER TABLE guild_players ADD COLUMN removed_at INTEGER;",
+    // 9: per-queue league snapshots with wins/losses
+    r#"
+    CREATE TABLE rank_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        player_id INTEGER NOT NULL,
+        queue TEXT NOT NULL,
+        tier TEXT NOT NULL,
+        rank TEXT NOT NULL,
+        lp INTEGER NOT NULL,
+        wins INTEGER NOT NULL,
+        losses INTEGER NOT NULL,
+        recorded_at INTEGER NOT NULL DEFAULT (unixepoch()),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX idx_rank_history_player ON rank_history(player_id, queue, recorded_at);
+    "#,
+    // 10: games per champion, counted since the account is tracked
+    r#"
+    CREATE TABLE champion_plays (
+        player_id INTEGER NOT NULL,
+        champion TEXT NOT NULL,
+        games INTEGER NOT NULL,
+        last_match_id TEXT NOT NULL,
+        PRIMARY KEY (player_id, champion),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+    "#,
+    // 11: matches handed to the dispatcher, to resume alerts across restarts
+    r#"
+    CREATE TABLE processed_matches (
+        match_id TEXT NOT NULL,
+        player_id INTEGER NOT NULL,
+        processed_at INTEGER NOT NULL DEFAULT (unixepoch()),
+        dispatched_at INTEGER,
+        PRIMARY KEY (match_id, player_id),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+    "#,
+    // 12: "game in progress" messages, replaced by the result alert
+    r#"
+    CREATE TABLE live_messages (
+        player_id INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL,
+        match_id TEXT NOT NULL,
+        channel_id INTEGER NOT NULL,
+        message_id INTEGER NOT NULL,
+        started_at INTEGER NOT NULL DEFAULT (unixepoch()),
+        PRIMARY KEY (player_id, guild_id),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+    "#,
+    // 13: normal game scores and weekly guild MVPs
+    r#"
+    CREATE TABLE game_scores (
+        player_id INTEGER NOT NULL,
+        match_id TEXT NOT NULL,
+        score REAL NOT NULL,
+        played_at INTEGER NOT NULL DEFAULT (unixepoch()),
+        PRIMARY KEY (player_id, match_id),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX idx_game_scores_played ON game_scores(played_at);
+
+    CREATE TABLE mvp_history (
+        guild_id INTEGER NOT NULL,
+        week_start INTEGER NOT NULL,
+        player_id INTEGER NOT NULL,
+        avg_score REAL NOT NULL,
+        games INTEGER NOT NULL,
+        PRIMARY KEY (guild_id, week_start),
+        FOREIGN KEY (guild_id) REFERENCES guilds(id) ON DELETE CASCADE,
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+
+    ALTER TABLE guilds ADD COLUMN last_recap_week INTEGER;
+    "#,
+    // 14: per-guild prefix for message commands, disabled when unset
+    "ALTER TABLE guilds ADD COLUMN command_prefix TEXT;",
+    // 15: alias shown in alerts instead of the riot id, NULL when not hidden
+    "ALTER TABLE players ADD COLUMN privacy_alias TEXT;",
+    // 16: alerts held back while Discord was unreachable
+    r#"
+    CREATE TABLE pending_alerts (
+        player_id INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL,
+        match_id TEXT NOT NULL,
+        channel_id INTEGER NOT NULL,
+        image BLOB NOT NULL,
+        queued_at INTEGER NOT NULL DEFAULT (unixepoch()),
+        PRIMARY KEY (player_id, guild_id, match_id),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+    "#,
+    // 17: last change of players, guilds and tracking rows, kept by triggers
+    // so every write path stamps it
+    r#"
+    ALTER TABLE players ADD COLUMN updated_at INTEGER;
+    ALTER TABLE guilds ADD COLUMN updated_at INTEGER;
+    ALTER TABLE guild_players ADD COLUMN updated_at INTEGER;
+
+    UPDATE players SET updated_at = created_at;
+    UPDATE guilds SET updated_at = created_at;
+    UPDATE guild_players SET updated_at = COALESCE(removed_at, added_at);
+
+    CREATE TRIGGER players_stamp_insert AFTER INSERT ON players
+    BEGIN
+        UPDATE players SET updated_at = unixepoch() WHERE id = NEW.id;
+    END;
+    CREATE TRIGGER players_stamp_update AFTER UPDATE ON players
+    WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE players SET updated_at = unixepoch() WHERE id = NEW.id;
+    END;
+
+    CREATE TRIGGER guilds_stamp_insert AFTER INSERT ON guilds
+    BEGIN
+        UPDATE guilds SET updated_at = unixepoch() WHERE id = NEW.id;
+    END;
+    CREATE TRIGGER guilds_stamp_update AFTER UPDATE ON guilds
+    WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE guilds SET updated_at = unixepoch() WHERE id = NEW.id;
+    END;
+
+    CREATE TRIGGER guild_players_stamp_insert AFTER INSERT ON guild_players
+    BEGIN
+        UPDATE guild_players SET updated_at = unixepoch()
+        WHERE guild_id = NEW.guild_id AND player_id = NEW.player_id;
+    END;
+    CREATE TRIGGER guild_players_stamp_update AFTER UPDATE ON guild_players
+    WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE guild_players SET updated_at = unixepoch()
+        WHERE guild_id = NEW.guild_id AND player_id = NEW.player_id;
+    END;
+    "#,
+    // 18: last run of scheduled jobs, so restarts don't rerun them early
+    r#"
+    CREATE TABLE job_runs (
+        name TEXT PRIMARY KEY,
+        last_run_at INTEGER NOT NULL
+    );
+    "#,
+    // 19: when the match list was first seen empty, cleared by the first game
+    "ALTER TABLE players ADD COLUMN no_matches_since INTEGER;",
+    // 20: runtime feature switches, guild_id 0 applies to the whole bot
+    r#"
+    CREATE TABLE feature_flags (
+        feature TEXT NOT NULL,
+        guild_id INTEGER NOT NULL,
+        enabled INTEGER NOT NULL,
+        PRIMARY KEY (feature, guild_id)
+    );
+    "#,
+    // 21: channel of the weekly recaps, the alert channel when unset
+    "ALTER TABLE guilds ADD COLUMN recap_channel_id INTEGER;",
+    // 22: moderation notes on tracked accounts, shown to server admins only
+    "ALTER TABLE guild_players ADD COLUMN admin_note TEXT;",
+    // 23: notices of LP changes without a game (decay, dodges)
+    "ALTER TABLE guilds ADD COLUMN rank_notices INTEGER NOT NULL DEFAULT 1;",
+    // 24: hourly cap of match alerts per guild, unlimited when NULL
+    "ALTER TABLE guilds ADD COLUMN alert_rate_limit INTEGER;",
+    // 25: games-to-next-division estimate on ranked alerts
+    "ALTER TABLE guilds ADD COLUMN alert_forecast INTEGER NOT NULL DEFAULT 0;",
+    // 26: alerts for rotating and event modes, generic layout
+    "ALTER TABLE guilds ADD COLUMN rotating_alerts INTEGER NOT NULL DEFAULT 1;",
+    // 27: guild wording of alerts, per queue and outcome
+    r#"
+    CREATE TABLE alert_templates (
+        guild_id INTEGER NOT NULL,
+        queue TEXT NOT NULL,
+        outcome TEXT NOT NULL,
+        template TEXT NOT NULL,
+        PRIMARY KEY (guild_id, queue, outcome),
+        FOREIGN KEY (guild_id) REFERENCES guilds(id) ON DELETE CASCADE
+    );
+    "#,
+    // 28: bot owner announcements in the alert channel, opt-out
+    "ALTER TABLE guilds ADD COLUMN announcements INTEGER NOT NULL DEFAULT 1;",
+    // 29: queues a guild gets no alerts for, by `Queue::key`
+    r#"
+    CREATE TABLE guild_muted_queues (
+        guild_id INTEGER NOT NULL,
+        queue TEXT NOT NULL,
+        PRIMARY KEY (guild_id, queue),
+        FOREIGN KEY (guild_id) REFERENCES guilds(id) ON DELETE CASCADE
+    );
+    "#,
+    // 30: guild cap on tracked accounts, below the instance one
+    "ALTER TABLE guilds ADD COLUMN track_limit INTEGER;",
+    // 31: results of alerted games and the daily or weekly recap built from them
+    r#"
+    CREATE TABLE match_history (
+        player_id INTEGER NOT NULL,
+        match_id TEXT NOT NULL,
+        queue_id INTEGER NOT NULL,
+        win INTEGER NOT NULL,
+        kills INTEGER NOT NULL,
+        deaths INTEGER NOT NULL,
+        assists INTEGER NOT NULL,
+        lp_delta INTEGER,
+        played_at INTEGER NOT NULL,
+        PRIMARY KEY (player_id, match_id),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX idx_match_history_played ON match_history(played_at);
+
+    ALTER TABLE guilds ADD COLUMN recap_frequency TEXT;
+    ALTER TABLE guilds ADD COLUMN recap_hour INTEGER NOT NULL DEFAULT 20;
+    ALTER TABLE guilds ADD COLUMN last_results_recap INTEGER;
+    "#,
+    // 32: LP chart attached to the weekly recap
+    "ALTER TABLE guilds ADD COLUMN recap_chart INTEGER NOT NULL DEFAULT 1;",
+    // 33: extended stats of alerted games, shown by the alert's Details button
+    r#"
+    CREATE TABLE match_details (
+        player_id INTEGER NOT NULL,
+        match_id TEXT NOT NULL,
+        details TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        PRIMARY KEY (player_id, match_id),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+    "#,
+    // 34: rosters read a page at a time, in name order
+    r#"
+    CREATE INDEX idx_guild_players_roster ON guild_players(guild_id, removed_at, player_id);
+    CREATE INDEX idx_players_game_name ON players(game_name);
+    "#,
+    // 35: release that last migrated the database, named when an older one opens it
+    r#"
+    CREATE TABLE schema_info (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        app_version TEXT NOT NULL,
+        migrated_at INTEGER NOT NULL
+    );
+    "#,
+    // 36: Discord user whose presence holds back the account's alerts in the guild
+    r#"
+    ALTER TABLE guild_players ADD COLUMN quiet_user_id INTEGER;
+    "#,
+    // 37: rank before the game, for alerts resumed after a restart
+    r#"
+    ALTER TABLE processed_matches ADD COLUMN old_tier TEXT;
+    ALTER TABLE processed_matches ADD COLUMN old_rank TEXT;
+    ALTER TABLE processed_matches ADD COLUMN old_lp INTEGER;
+    "#,
+    // 38: buffered alerts of guilds without image alerts, posted as an embed
+    r#"
+    CREATE TABLE pending_alerts_new (
+        player_id INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL,
+        match_id TEXT NOT NULL,
+        channel_id INTEGER NOT NULL,
+        image BLOB,
+        embed TEXT,
+        queued_at INTEGER NOT NULL DEFAULT (unixepoch()),
+        PRIMARY KEY (player_id, guild_id, match_id),
+        FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+    );
+    INSERT INTO pending_alerts_new (player_id, guild_id, match_id, channel_id, image, queued_at)
+    SELECT player_id, guild_id, match_id, channel_id, image, queued_at FROM pending_alerts;
+    DROP TABLE pending_alerts;
+    ALTER TABLE pending_alerts_new RENAME TO pending_alerts;
+    "#,
+    // 39: guild wording and live game message of buffered alerts
+    r#"
+    ALTER TABLE pending_alerts ADD COLUMN content TEXT;
+    ALTER TABLE pending_alerts ADD COLUMN live_channel_id INTEGER;
+    ALTER TABLE pending_alerts ADD COLUMN live_message_id INTEGER;
+    "#,
+];
+
 pub async fn run_migrations(pool: &SqlitePool) -> Result<(), AppError> {
     sqlx::raw_sql(SCHEMA).execute(pool).await?;
+
+    // Another instance (blue/green deploy) may be migrating the same file
+    let owner: String = rand::rng()
+        .sample_iter(Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect();
+    acquire_lock(pool, &owner).await?;
+    let result = apply_migrations(pool).await;
+    release_lock(pool, &owner).await?;
+    result?;
+
     info!("🗄️ Database migrations completed");
     Ok(())
 }
+
+/// Fail unless the database already has every migration, for read-only
+/// instances that can't apply them
+pub async fn check_schema_version(pool: &SqlitePool) -> Result<(), AppError> {
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    if version < MIGRATIONS.len() as i64 {
+        return Err(AppError::Config(format!(
+            "Database schema is at version {version}, this build needs {}; run a writable instance first",
+            MIGRATIONS.len()
+        )));
+    }
+    check_not_newer(pool, version).await
+}
+
+/// Fail on a database migrated by a newer release, whose schema this build
+/// would misread or overwrite
+async fn check_not_newer(pool: &SqlitePool, version: i64) -> Result<(), AppError> {
+    if version <= MIGRATIONS.len() as i64 {
+        return Ok(());
+    }
+    let migrated_by: Option<String> =
+        sqlx::query_scalar("SELECT app_version FROM schema_info WHERE id = 1")
+            .fetch_optional(pool)
+            .await
+            .unwrap_or_default();
+    Err(AppError::Config(format!(
+        "Database schema is at version {version}{}, newer than the {} this build (v{}) knows; \
+         run that release or newer, or restore a backup taken before the upgrade",
+        migrated_by
+            .map(|app_version| format!(" (migrated by v{app_version})"))
+            .unwrap_or_default(),
+        MIGRATIONS.len(),
+        env!("CARGO_PKG_VERSION"),
+    )))
+}
+
+/// Wait until the lock is free or expired, then take it
+async fn acquire_lock(pool: &SqlitePool, owner: &str) -> Result<(), AppError> {
+    let mut waiting = false;
+    loop {
+        let taken = sqlx::query(
+            r#"
+            INSERT INTO migration_lock (id, owner, expires_at)
+            VALUES (1, ?, unixepoch() + ?)
+            ON CONFLICT (id) DO UPDATE SET
+                owner = excluded.owner,
+                expires_at = excluded.expires_at
+            WHERE migration_lock.expires_at < unixepoch()
+            "#,
+        )
+        .bind(owner)
+        .bind(LOCK_TTL_SECS)
+        .execute(pool)
+        .await?
+        .rows_affected()
+            > 0;
+        if taken {
+            return Ok(());
+        }
+
+        if !waiting {
+            warn!("🗄️ ⚠️ Another process is migrating the database, waiting");
+            waiting = true;
+        }
+        tokio::time::sleep(LOCK_RETRY).await;
+    }
+}
+
+async fn release_lock(pool: &SqlitePool, owner: &str) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM migration_lock WHERE owner = ?")
+        .bind(owner)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Apply the migrations newer than the database, must hold the lock
+async fn apply_migrations(pool: &SqlitePool) -> Result<(), AppError> {
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    check_not_newer(pool, version).await?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let mut tx = pool.begin().await?;
+        sqlx::raw_sql(migration).execute(&mut *tx).await?;
+        sqlx::raw_sql(&format!("PRAGMA user_version = {}", index + 1))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        info!(version = index + 1, "🗄️ Applied migration");
+    }
+
+    if version < MIGRATIONS.len() as i64 {
+        sqlx::query(
+            r#"
+            INSERT INTO schema_info (id, app_version, migrated_at) VALUES (1, ?, unixepoch())
+            ON CONFLICT (id) DO UPDATE SET
+                app_version = excluded.app_version,
+                migrated_at = excluded.migrated_at
+            "#,
+        )
+        .bind(env!("CARGO_PKG_VERSION"))
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::{MIGRATIONS, acquire_lock, check_schema_version, release_lock, run_migrations};
+
+    #[tokio::test]
+    async fn migrations_wait_for_a_held_lock() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        acquire_lock(&pool, "other").await.unwrap();
+        let blocked = tokio::time::timeout(
+            std::time::Duration::from_millis(100),
+            acquire_lock(&pool, "me"),
+        )
+        .await;
+        assert!(blocked.is_err());
+
+        // An expired lock is taken over
+        sqlx::query("UPDATE migration_lock SET expires_at = 0")
+            .execute(&pool)
+            .await
+            .unwrap();
+        acquire_lock(&pool, "me").await.unwrap();
+        release_lock(&pool, "me").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn newer_databases_are_refused() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        check_schema_version(&pool).await.unwrap();
+
+        // As left by a release with one more migration
+        sqlx::raw_sql(&format!("PRAGMA user_version = {}", MIGRATIONS.len() + 1))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE schema_info SET app_version = '99.0.0'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let error = run_migrations(&pool).await.unwrap_err().to_string();
+        assert!(error.contains("migrated by v99.0.0"), "{error}");
+        assert!(check_schema_version(&pool).await.is_err());
+        // The failed run left the lock free
+        acquire_lock(&pool, "me").await.unwrap();
+    }
+}
@@ -1,7 +1,18 @@
+#[cfg(test)]
+pub mod fixtures;
+mod instance_lock;
+mod integrity;
 mod migrations;
 mod models;
 mod repository;
+mod snowflake;
 
-pub use migrations::run_migrations;
-pub use models::{Player, RankInfo};
-pub use repository::Repository;
+pub use instance_lock::InstanceLock;
+pub use integrity::check_integrity;
+pub use migrations::{check_schema_version, run_migrations};
+pub use models::{
+    BufferedAlert, Game, Guild, GuildPlayer, LadderWatch, MatchResult, Player, RankInfo, RankPoint,
+    RankSnapshot, RecapGame, ResolvedAccount, WeeklyMvp,
+};
+pub use repository::{Repository, UNTRACK_GRACE_DAYS};
+pub use snowflake::SnowflakeColumn;
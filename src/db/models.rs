@@ -1,5 +1,9 @@
+use chrono::DateTime;
+use chrono_tz::Tz;
 use sqlx::FromRow;
 
+use super::SnowflakeColumn;
+
 #[derive(Debug, Clone, FromRow)]
 pub struct Player {
     pub id: i64,
@@ -15,6 +19,9 @@ pub struct Player {
     pub last_rank_flex_tier: Option<String>,
     pub last_rank_flex_rank: Option<String>,
     pub last_rank_flex_lp: Option<i32>,
+    pub privacy_alias: Option<String>,
+    /// Set while the account has never played a game (Unix seconds)
+    pub no_matches_since: Option<i64>,
 }
 
 impl Player {
@@ -22,6 +29,15 @@ impl Player {
         format!("{}#{}", self.game_name, self.tag_line)
     }
 
+    pub fn is_hidden(&self) -> bool {
+        self.privacy_alias.is_some()
+    }
+
+    /// Name broadcast in alerts, the alias when the account is hidden
+    pub fn display_name(&self) -> String {
+        self.privacy_alias.clone().unwrap_or_else(|| self.riot_id())
+    }
+
     pub fn solo_rank_info(&self) -> Option<RankInfo> {
         match (
             &self.last_rank_solo_tier,
@@ -53,6 +69,157 @@ impl Player {
     }
 }
 
+/// League standing of a player in one queue at a point in time
+#[derive(Debug, Clone, FromRow)]
+pub struct RankSnapshot {
+    /// Riot queue type, e.g. `RANKED_SOLO_5x5`
+    pub queue: String,
+    pub tier: String,
+    pub rank: String,
+    pub lp: i32,
+    pub wins: i32,
+    pub losses: i32,
+}
+
+impl RankSnapshot {
+    pub fn rank_info(&self) -> RankInfo {
+        RankInfo {
+            tier: self.tier.clone(),
+            rank: self.rank.clone(),
+            lp: self.lp,
+        }
+    }
+}
+
+/// A player as tracked in a guild, for rosters
+#[derive(Debug, Clone, FromRow)]
+pub struct GuildPlayer {
+    #[sqlx(flatten)]
+    pub player: Player,
+    /// When the guild started tracking it (Unix seconds)
+    pub added_at: i64,
+    pub admin_note: Option<String>,
+}
+
+/// Result of an alerted game, for recaps
+#[derive(Debug, Clone, FromRow)]
+pub struct MatchResult {
+    pub match_id: String,
+    pub queue_id: i32,
+    pub win: bool,
+    pub kills: i32,
+    pub deaths: i32,
+    pub assists: i32,
+    /// Ranked games only
+    pub lp_delta: Option<i32>,
+    pub played_at: i64,
+}
+
+/// A game of a guild's player over a recap period
+#[derive(Debug, Clone, FromRow)]
+pub struct RecapGame {
+    pub player_id: i64,
+    pub game_name: String,
+    pub tag_line: String,
+    pub privacy_alias: Option<String>,
+    pub win: bool,
+    pub kills: i32,
+    pub deaths: i32,
+    pub assists: i32,
+    pub lp_delta: Option<i32>,
+}
+
+impl RecapGame {
+    /// Name broadcast in recaps, the alias when the account is hidden
+    pub fn display_name(&self) -> String {
+        self.privacy_alias
+            .clone()
+            .unwrap_or_else(|| format!("{}#{}", self.game_name, self.tag_line))
+    }
+}
+
+/// A player's standing at a point in time, for LP charts
+#[derive(Debug, Clone, FromRow)]
+pub struct RankPoint {
+    pub player_id: i64,
+    pub game_name: String,
+    pub tag_line: String,
+    pub privacy_alias: Option<String>,
+    pub recorded_at: i64,
+    pub tier: String,
+    pub rank: String,
+    pub lp: i32,
+}
+
+impl RankPoint {
+    /// Name broadcast in recaps, the alias when the account is hidden
+    pub fn display_name(&self) -> String {
+        self.privacy_alias
+            .clone()
+            .unwrap_or_else(|| format!("{}#{}", self.game_name, self.tag_line))
+    }
+
+    pub fn rank_info(&self) -> RankInfo {
+        RankInfo {
+            tier: self.tier.clone(),
+            rank: self.rank.clone(),
+            lp: self.lp,
+        }
+    }
+}
+
+/// A match whose alert was queued but never sent
+#[derive(Debug, Clone, FromRow)]
+pub struct InterruptedMatch {
+    pub player_id: i64,
+    pub match_id: String,
+    pub old_tier: Option<String>,
+    pub old_rank: Option<String>,
+    pub old_lp: Option<i32>,
+}
+
+impl InterruptedMatch {
+    /// Rank in the game's queue before it was played, the player's row
+    /// already holds the one after
+    pub fn old_rank_info(&self) -> Option<RankInfo> {
+        match (&self.old_tier, &self.old_rank, self.old_lp) {
+            (Some(tier), Some(rank), Some(lp)) => Some(RankInfo {
+                tier: tier.clone(),
+                rank: rank.clone(),
+                lp,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// An account looked up on Riot, ready to be tracked
+#[derive(Debug, Clone)]
+pub struct ResolvedAccount {
+    pub puuid: String,
+    pub game_name: String,
+    pub tag_line: String,
+    pub region: String,
+    pub profile_icon_id: i32,
+    /// Latest game when tracked, so older ones are never alerted on
+    pub last_match_id: Option<String>,
+}
+
+/// Riot game an account identity belongs to. Each game hands out its own
+/// puuids, stored in `player_identities` so new games need no new columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Game {
+    Lol,
+}
+
+impl Game {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lol => "lol",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RankInfo {
     pub tier: String,
@@ -62,6 +229,126 @@ pub struct RankInfo {
 
 #[derive(Debug, Clone, FromRow)]
 pub struct Guild {
-    pub id: i64,
-    pub alert_channel_id: Option<i64>,
+    pub id: SnowflakeColumn,
+    pub alert_channel_id: Option<SnowflakeColumn>,
+    pub timezone: String,
+    /// Extra details (team objectives) on ranked alerts
+    pub alert_details: bool,
+    /// JSON array of the stat fields shown on alerts, in order
+    pub alert_fields: Option<String>,
+    /// Token granting read access to the public roster API, unset when disabled
+    pub public_api_token: Option<String>,
+    /// Start of the last week a recap was posted for (Unix seconds)
+    pub last_recap_week: Option<i64>,
+    /// Where weekly recaps go instead of the alert channel
+    pub recap_channel_id: Option<SnowflakeColumn>,
+    /// Post LP changes that no game explains (decay, dodges)
+    pub rank_notices: bool,
+    /// Most match alerts posted per hour, unlimited when unset
+    pub alert_rate_limit: Option<u32>,
+    /// Games-to-next-division estimate on ranked alerts
+    pub alert_forecast: bool,
+    /// Alerts for rotating and event modes (Arena, URF...)
+    pub rotating_alerts: bool,
+    /// Bot owner announcements in the alert channel
+    pub announcements: bool,
+    /// Most accounts tracked at once, the instance limit when unset
+    pub track_limit: Option<u32>,
+    /// `daily` or `weekly` results recap, none when unset
+    pub recap_frequency: Option<String>,
+    /// Local hour the results recap is posted at
+    pub recap_hour: u32,
+    /// End of the last period a results recap was posted for (Unix seconds)
+    pub last_results_recap: Option<i64>,
+    /// LP chart of the week on the weekly recap
+    pub recap_chart: bool,
+}
+
+impl Guild {
+    /// IANA timezone used for this guild's dates, UTC if unset or invalid
+    pub fn timezone(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    /// Channel of the weekly recaps
+    pub fn recap_channel(&self) -> Option<SnowflakeColumn> {
+        self.recap_channel_id.or(self.alert_channel_id)
+    }
+
+    /// Tracked accounts allowed here. A guild can only go below the instance
+    /// limit, which protects the Riot rate limit.
+    pub fn track_limit(&self, instance_limit: Option<u32>) -> Option<u32> {
+        match (self.track_limit, instance_limit) {
+            (Some(guild), Some(instance)) => Some(guild.min(instance)),
+            (guild, instance) => guild.or(instance),
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct LadderWatch {
+    pub guild_id: SnowflakeColumn,
+    pub alert_channel_id: Option<SnowflakeColumn>,
+    pub region: String,
+    pub tier: String,
+    pub top_cutoff_lp: Option<i32>,
+}
+
+/// A "game in progress" message posted in a guild
+#[derive(Debug, Clone, FromRow)]
+pub struct LiveMessage {
+    pub guild_id: SnowflakeColumn,
+    pub match_id: String,
+    pub channel_id: SnowflakeColumn,
+    pub message_id: SnowflakeColumn,
+    pub started_at: i64,
+}
+
+/// An alert that could not reach Discord, waiting to be sent again
+#[derive(Debug, Clone, FromRow)]
+pub struct BufferedAlert {
+    pub player_id: i64,
+    pub guild_id: SnowflakeColumn,
+    pub match_id: String,
+    pub channel_id: SnowflakeColumn,
+    /// The rendered result, unset when the guild gets it as an embed
+    pub image: Option<Vec<u8>>,
+    /// JSON of the result summary posted as an embed, without an image
+    pub embed: Option<String>,
+    /// The guild's own wording
+    pub content: Option<String>,
+    /// "Game in progress" message the alert replaces
+    pub live_channel_id: Option<SnowflakeColumn>,
+    pub live_message_id: Option<SnowflakeColumn>,
+    pub queued_at: i64,
+}
+
+/// Best average normal game score of a guild over a week
+#[derive(Debug, Clone, FromRow)]
+pub struct WeeklyMvp {
+    pub week_start: i64,
+    pub player_id: i64,
+    pub game_name: String,
+    pub tag_line: String,
+    pub privacy_alias: Option<String>,
+    pub avg_score: f64,
+    pub games: i64,
+}
+
+impl WeeklyMvp {
+    pub fn riot_id(&self) -> String {
+        format!("{}#{}", self.game_name, self.tag_line)
+    }
+
+    /// Name broadcast in recaps, the alias when the account is hidden
+    pub fn display_name(&self) -> String {
+        self.privacy_alias.clone().unwrap_or_else(|| self.riot_id())
+    }
+
+    /// Local date the week starts on, e.g. "2024-05-27"
+    pub fn week_label(&self, tz: Tz) -> String {
+        DateTime::from_timestamp(self.week_start, 0)
+            .map(|start| start.with_timezone(&tz).format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    }
 }
@@ -1,9 +1,16 @@
-use sqlx::SqlitePool;
+use std::path::Path;
 
-use super::models::{Guild, Player, RankInfo};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use super::SnowflakeColumn;
+use super::models::{
+    BufferedAlert, Game, Guild, GuildPlayer, InterruptedMatch, LadderWatch, LiveMessage,
+    MatchResult, Player, RankInfo, RankPoint, RankSnapshot, RecapGame, ResolvedAccount, WeeklyMvp,
+};
+use crate::config::RetentionPolicy;
 use crate::error::AppError;
 
-const PLAYER_COLUMN_NAMES: [&str; 13] = [
+const PLAYER_COLUMN_NAMES: [&str; 15] = [
     "id",
     "puuid",
     "game_name",
@@ -17,6 +24,8 @@ const PLAYER_COLUMN_NAMES: [&str; 13] = [
     "last_rank_flex_tier",
     "last_rank_flex_rank",
     "last_rank_flex_lp",
+    "privacy_alias",
+    "no_matches_since",
 ];
 
 fn player_columns(alias: Option<&str>) -> String {
@@ -28,6 +37,63 @@ fn player_columns(alias: Option<&str>) -> String {
         .join(", ")
 }
 
+/// Columns of the last known rank in a League-v4 queue type, e.g.
+/// `last_rank_solo` for `RANKED_SOLO_5x5`
+fn rank_column_prefix(queue_type: &str) -> Result<&'static str, AppError> {
+    match queue_type {
+        "RANKED_SOLO_5x5" => Ok("last_rank_solo"),
+        "RANKED_FLEX_SR" => Ok("last_rank_flex"),
+        _ => Err(AppError::Config(format!(
+            "No rank is kept for queue {queue_type}"
+        ))),
+    }
+}
+
+/// Per-player history tables the retention policy applies to, with the
+/// column dating their rows. `processed_matches` is left out: it is how the
+/// poller knows a match was already handled, pruning it would alert again
+const HISTORY_TABLES: [(&str, &str); 5] = [
+    ("alert_log", "sent_at"),
+    ("rank_history", "recorded_at"),
+    ("game_scores", "played_at"),
+    ("match_history", "played_at"),
+    ("match_details", "recorded_at"),
+];
+
+const GUILD_COLUMN_NAMES: [&str; 18] = [
+    "id",
+    "alert_channel_id",
+    "timezone",
+    "alert_details",
+    "alert_fields",
+    "public_api_token",
+    "last_recap_week",
+    "recap_channel_id",
+    "rank_notices",
+    "alert_rate_limit",
+    "alert_forecast",
+    "rotating_alerts",
+    "announcements",
+    "track_limit",
+    "recap_frequency",
+    "recap_hour",
+    "last_results_recap",
+    "recap_chart",
+];
+
+fn guild_columns(alias: Option<&str>) -> String {
+    let prefix = alias.map(|a| format!("{a}.")).unwrap_or_default();
+    GUILD_COLUMN_NAMES
+        .iter()
+        .map(|col| format!("{prefix}{col}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// How long an untracked player can be restored with `/undo_untrack`
+pub const UNTRACK_GRACE_DAYS: i64 = 7;
+const UNTRACK_GRACE_SECS: i64 = UNTRACK_GRACE_DAYS * 86400;
+
 #[derive(Clone, Debug)]
 pub struct Repository {
     pool: SqlitePool,
@@ -40,12 +106,27 @@ impl Repository {
 
     // === Player operations ===
 
+    /// Insert the player, or refresh the Riot ID of a known PUUID. Match and
+    /// rank history are kept, and blank values never erase known ones.
     pub async fn get_or_create_player(
         &self,
         puuid: &str,
         game_name: &str,
         tag_line: &str,
         region: &str,
+    ) -> Result<Player, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let player = Self::upsert_player(&mut tx, puuid, game_name, tag_line, region).await?;
+        tx.commit().await?;
+        Ok(player)
+    }
+
+    async fn upsert_player(
+        conn: &mut SqliteConnection,
+        puuid: &str,
+        game_name: &str,
+        tag_line: &str,
+        region: &str,
     ) -> Result<Player, AppError> {
         let columns = player_columns(None);
         let query = format!(
@@ -53,20 +134,62 @@ impl Repository {
             INSERT INTO players (puuid, game_name, tag_line, region)
             VALUES (?, ?, ?, ?)
             ON CONFLICT(puuid) DO UPDATE SET
-                game_name = excluded.game_name,
-                tag_line = excluded.tag_line,
-                region = excluded.region
+                game_name = COALESCE(NULLIF(excluded.game_name, ''), players.game_name),
+                tag_line = COALESCE(NULLIF(excluded.tag_line, ''), players.tag_line),
+                region = COALESCE(NULLIF(excluded.region, ''), players.region)
             RETURNING {columns}
             "#
         );
-
         let player = sqlx::query_as::<_, Player>(&query)
             .bind(puuid)
             .bind(game_name)
             .bind(tag_line)
             .bind(region)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await?;
+        Self::upsert_identity(conn, player.id, Game::Lol, puuid).await?;
+        Ok(player)
+    }
+
+    async fn upsert_identity(
+        conn: &mut SqliteConnection,
+        player_id: i64,
+        game: Game,
+        puuid: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO player_identities (player_id, game, puuid) VALUES (?, ?, ?)
+            ON CONFLICT(player_id, game) DO UPDATE SET puuid = excluded.puuid
+            "#,
+        )
+        .bind(player_id)
+        .bind(game.as_str())
+        .bind(puuid)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Player owning `puuid` in `game`
+    pub async fn get_player_by_identity(
+        &self,
+        game: Game,
+        puuid: &str,
+    ) -> Result<Option<Player>, AppError> {
+        let columns = player_columns(Some("p"));
+        let player = sqlx::query_as::<_, Player>(&format!(
+            r#"
+            SELECT {columns}
+            FROM players p
+            INNER JOIN player_identities i ON p.id = i.player_id
+            WHERE i.game = ? AND i.puuid = ?
+            "#
+        ))
+        .bind(game.as_str())
+        .bind(puuid)
+        .fetch_optional(&self.pool)
+        .await?;
         Ok(player)
     }
 
@@ -94,7 +217,7 @@ impl Repository {
             FROM players p
             INNER JOIN guild_players gp ON p.id = gp.player_id
             INNER JOIN guilds g ON gp.guild_id = g.id
-            WHERE g.alert_channel_id IS NOT NULL
+            WHERE g.alert_channel_id IS NOT NULL AND gp.removed_at IS NULL
             "#
         ))
         .fetch_all(&self.pool)
@@ -107,7 +230,7 @@ impl Repository {
         player_id: i64,
         match_id: &str,
     ) -> Result<(), AppError> {
-        sqlx::query("UPDATE players SET last_match_id = ? WHERE id = ?")
+        sqlx::query("UPDATE players SET last_match_id = ?, no_matches_since = NULL WHERE id = ?")
             .bind(match_id)
             .bind(player_id)
             .execute(&self.pool)
@@ -115,6 +238,24 @@ impl Repository {
         Ok(())
     }
 
+    /// Remember the account has no games yet, `false` if it was already known
+    pub async fn mark_player_without_matches(
+        &self,
+        player_id: i64,
+        now: i64,
+    ) -> Result<bool, AppError> {
+        let marked = sqlx::query(
+            "UPDATE players SET no_matches_since = ? WHERE id = ? AND no_matches_since IS NULL",
+        )
+        .bind(now)
+        .bind(player_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected()
+            > 0;
+        Ok(marked)
+    }
+
     pub async fn update_player_profile_icon(
         &self,
         player_id: i64,
@@ -128,6 +269,20 @@ impl Repository {
         Ok(())
     }
 
+    /// Hide the player's riot id behind `alias` in alerts, `None` shows it again
+    pub async fn set_player_privacy_alias(
+        &self,
+        player_id: i64,
+        alias: Option<&str>,
+    ) -> Result<(), AppError> {
+        sqlx::query("UPDATE players SET privacy_alias = ? WHERE id = ?")
+            .bind(alias)
+            .bind(player_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     pub async fn update_player_rank(
         &self,
         player_id: i64,
@@ -158,17 +313,114 @@ impl Repository {
         Ok(())
     }
 
+    /// Rows sharing a Riot ID and region, grouped, newest first in each
+    /// group. Past PUUID migrations left some accounts with several rows;
+    /// a shared Riot ID alone is no proof though, names get freed and taken
+    /// by other accounts.
+    pub async fn get_riot_id_duplicates(&self) -> Result<Vec<Vec<Player>>, AppError> {
+        let columns = player_columns(Some("p"));
+        let players = sqlx::query_as::<_, Player>(&format!(
+            r#"
+            SELECT {columns}
+            FROM players p
+            INNER JOIN (
+                SELECT LOWER(game_name) AS name, LOWER(tag_line) AS tag, region
+                FROM players
+                GROUP BY LOWER(game_name), LOWER(tag_line), region
+                HAVING COUNT(*) > 1
+            ) d ON LOWER(p.game_name) = d.name AND LOWER(p.tag_line) = d.tag AND p.region = d.region
+            ORDER BY LOWER(p.game_name), LOWER(p.tag_line), p.region, p.id DESC
+            "#
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut groups: Vec<Vec<Player>> = Vec::new();
+        for player in players {
+            // SQLite's LOWER only folds ASCII
+            let same_account = |other: &Player| {
+                other.game_name.eq_ignore_ascii_case(&player.game_name)
+                    && other.tag_line.eq_ignore_ascii_case(&player.tag_line)
+                    && other.region == player.region
+            };
+            match groups.last_mut() {
+                Some(group) if same_account(&group[0]) => group.push(player),
+                _ => groups.push(vec![player]),
+            }
+        }
+        Ok(groups)
+    }
+
+    /// Fold the `duplicate_id` row into `keeper_id`: every row referencing
+    /// the duplicate (guild relations, history, identities...) moves to the
+    /// keeper, which also gets the match/rank data it is missing. Rows the
+    /// keeper already has an equivalent of are dropped with the duplicate.
+    pub async fn merge_player_into(
+        &self,
+        duplicate_id: i64,
+        keeper_id: i64,
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE players SET
+                profile_icon_id = COALESCE(players.profile_icon_id, d.profile_icon_id),
+                last_match_id = COALESCE(players.last_match_id, d.last_match_id),
+                last_rank_solo_tier = COALESCE(players.last_rank_solo_tier, d.last_rank_solo_tier),
+                last_rank_solo_rank = COALESCE(players.last_rank_solo_rank, d.last_rank_solo_rank),
+                last_rank_solo_lp = COALESCE(players.last_rank_solo_lp, d.last_rank_solo_lp),
+                last_rank_flex_tier = COALESCE(players.last_rank_flex_tier, d.last_rank_flex_tier),
+                last_rank_flex_rank = COALESCE(players.last_rank_flex_rank, d.last_rank_flex_rank),
+                last_rank_flex_lp = COALESCE(players.last_rank_flex_lp, d.last_rank_flex_lp),
+                privacy_alias = COALESCE(players.privacy_alias, d.privacy_alias)
+            FROM (SELECT * FROM players WHERE id = ?) AS d
+            WHERE players.id = ?
+            "#,
+        )
+        .bind(duplicate_id)
+        .bind(keeper_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Every foreign key to players, so tables added later move too
+        let references: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT m.name, f."from"
+            FROM sqlite_master m, pragma_foreign_key_list(m.name) f
+            WHERE m.type = 'table' AND f."table" = 'players'
+            "#,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for (table, column) in references {
+            sqlx::query(&format!(
+                r#"UPDATE OR IGNORE "{table}" SET "{column}" = ? WHERE "{column}" = ?"#
+            ))
+            .bind(keeper_id)
+            .bind(duplicate_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("DELETE FROM players WHERE id = ?")
+            .bind(duplicate_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     // === Guild operations ===
 
     pub async fn get_or_create_guild(&self, guild_id: u64) -> Result<Guild, AppError> {
-        let id = guild_id as i64;
-
         if let Some(guild) = self.get_guild(guild_id).await? {
             return Ok(guild);
         }
 
         sqlx::query("INSERT INTO guilds (id) VALUES (?)")
-            .bind(id)
+            .bind(SnowflakeColumn::from(guild_id))
             .execute(&self.pool)
             .await?;
 
@@ -178,9 +430,10 @@ impl Repository {
     }
 
     pub async fn get_guild(&self, guild_id: u64) -> Result<Option<Guild>, AppError> {
+        let columns = guild_columns(None);
         let guild =
-            sqlx::query_as::<_, Guild>("SELECT id, alert_channel_id FROM guilds WHERE id = ?")
-                .bind(guild_id as i64)
+            sqlx::query_as::<_, Guild>(&format!("SELECT {columns} FROM guilds WHERE id = ?"))
+                .bind(SnowflakeColumn::from(guild_id))
                 .fetch_optional(&self.pool)
                 .await?;
         Ok(guild)
@@ -194,91 +447,2916 @@ impl Repository {
         self.get_or_create_guild(guild_id).await?;
 
         sqlx::query("UPDATE guilds SET alert_channel_id = ? WHERE id = ?")
-            .bind(channel_id as i64)
-            .bind(guild_id as i64)
+            .bind(SnowflakeColumn::from(channel_id))
+            .bind(SnowflakeColumn::from(guild_id))
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
-    // === Guild-Player relations ===
+    /// Unset the alert channel of the guild if it is `channel_id`, true when it was
+    pub async fn clear_guild_alert_channel(
+        &self,
+        guild_id: u64,
+        channel_id: u64,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "UPDATE guilds SET alert_channel_id = NULL WHERE id = ? AND alert_channel_id = ?",
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(SnowflakeColumn::from(channel_id))
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
 
-    pub async fn add_player_to_guild(
+    /// Send recaps to `channel_id`, back to the alert channel when `None`
+    pub async fn set_guild_recap_channel(
         &self,
         guild_id: u64,
-        player_id: i64,
-        added_by: u64,
+        channel_id: Option<u64>,
     ) -> Result<(), AppError> {
         self.get_or_create_guild(guild_id).await?;
 
-        sqlx::query(
-            "INSERT OR IGNORE INTO guild_players (guild_id, player_id, added_by) VALUES (?, ?, ?)",
+        sqlx::query("UPDATE guilds SET recap_channel_id = ? WHERE id = ?")
+            .bind(channel_id.map(SnowflakeColumn::from))
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Unset the recap channel of the guild if it is `channel_id`, true when it was
+    pub async fn clear_guild_recap_channel(
+        &self,
+        guild_id: u64,
+        channel_id: u64,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "UPDATE guilds SET recap_channel_id = NULL WHERE id = ? AND recap_channel_id = ?",
         )
-        .bind(guild_id as i64)
-        .bind(player_id)
-        .bind(added_by as i64)
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(SnowflakeColumn::from(channel_id))
         .execute(&self.pool)
         .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn set_guild_timezone(&self, guild_id: u64, timezone: &str) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET timezone = ? WHERE id = ?")
+            .bind(timezone)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
-    pub async fn remove_player_from_guild(
+    /// `None` stops the results recap
+    pub async fn set_guild_recap_schedule(
         &self,
         guild_id: u64,
-        player_id: i64,
-    ) -> Result<bool, AppError> {
-        let result = sqlx::query("DELETE FROM guild_players WHERE guild_id = ? AND player_id = ?")
-            .bind(guild_id as i64)
-            .bind(player_id)
+        frequency: Option<&str>,
+        hour: u32,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET recap_frequency = ?, recap_hour = ? WHERE id = ?")
+            .bind(frequency)
+            .bind(hour)
+            .bind(SnowflakeColumn::from(guild_id))
             .execute(&self.pool)
             .await?;
-        Ok(result.rows_affected() > 0)
+        Ok(())
     }
 
-    pub async fn get_guild_players(&self, guild_id: u64) -> Result<Vec<Player>, AppError> {
-        let columns = player_columns(Some("p"));
-        let players = sqlx::query_as::<_, Player>(&format!(
-            r#"
-            SELECT {columns}
-            FROM players p
-            INNER JOIN guild_players gp ON p.id = gp.player_id
-            WHERE gp.guild_id = ?
-            ORDER BY p.game_name ASC
-            "#
-        ))
-        .bind(guild_id as i64)
-        .fetch_all(&self.pool)
-        .await?;
-        Ok(players)
+    pub async fn set_guild_recap_chart(
+        &self,
+        guild_id: u64,
+        enabled: bool,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET recap_chart = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
     }
 
-    pub async fn get_guilds_tracking_player(&self, player_id: i64) -> Result<Vec<Guild>, AppError> {
-        let guilds = sqlx::query_as::<_, Guild>(
-            r#"
-            SELECT g.id, g.alert_channel_id
-            FROM guilds g
-            INNER JOIN guild_players gp ON g.id = gp.guild_id
-            WHERE gp.player_id = ? AND g.alert_channel_id IS NOT NULL
-            "#,
+    pub async fn set_guild_rank_notices(
+        &self,
+        guild_id: u64,
+        enabled: bool,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET rank_notices = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// `None` lifts the limit
+    pub async fn set_guild_alert_rate_limit(
+        &self,
+        guild_id: u64,
+        per_hour: Option<u32>,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET alert_rate_limit = ? WHERE id = ?")
+            .bind(per_hour)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// `None` falls back to the instance limit
+    pub async fn set_guild_track_limit(
+        &self,
+        guild_id: u64,
+        limit: Option<u32>,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET track_limit = ? WHERE id = ?")
+            .bind(limit)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_guild_alert_forecast(
+        &self,
+        guild_id: u64,
+        enabled: bool,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET alert_forecast = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_guild_rotating_alerts(
+        &self,
+        guild_id: u64,
+        enabled: bool,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET rotating_alerts = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Text posted with the alerts of a queue and outcome, `None` if unset
+    pub async fn get_alert_template(
+        &self,
+        guild_id: u64,
+        queue: &str,
+        outcome: &str,
+    ) -> Result<Option<String>, AppError> {
+        let template = sqlx::query_scalar(
+            "SELECT template FROM alert_templates WHERE guild_id = ? AND queue = ? AND outcome = ?",
         )
-        .bind(player_id)
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(queue)
+        .bind(outcome)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(template)
+    }
+
+    /// `None` removes the template
+    pub async fn set_alert_template(
+        &self,
+        guild_id: u64,
+        queue: &str,
+        outcome: &str,
+        template: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        match template {
+            Some(template) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO alert_templates (guild_id, queue, outcome, template) VALUES (?, ?, ?, ?)
+                    ON CONFLICT (guild_id, queue, outcome) DO UPDATE SET template = excluded.template
+                    "#,
+                )
+                .bind(SnowflakeColumn::from(guild_id))
+                .bind(queue)
+                .bind(outcome)
+                .bind(template)
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    "DELETE FROM alert_templates WHERE guild_id = ? AND queue = ? AND outcome = ?",
+                )
+                .bind(SnowflakeColumn::from(guild_id))
+                .bind(queue)
+                .bind(outcome)
+                .execute(&self.pool)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn set_guild_announcements(
+        &self,
+        guild_id: u64,
+        enabled: bool,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET announcements = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_guilds_with_alert_channel(&self) -> Result<Vec<Guild>, AppError> {
+        let columns = guild_columns(None);
+        let guilds = sqlx::query_as::<_, Guild>(&format!(
+            "SELECT {columns} FROM guilds WHERE alert_channel_id IS NOT NULL"
+        ))
         .fetch_all(&self.pool)
         .await?;
         Ok(guilds)
     }
 
-    pub async fn is_player_tracked_in_guild(
+    pub async fn set_guild_alert_details(
         &self,
         guild_id: u64,
-        player_id: i64,
-    ) -> Result<bool, AppError> {
-        let exists = sqlx::query_scalar::<_, i32>(
-            "SELECT 1 FROM guild_players WHERE guild_id = ? AND player_id = ?",
-        )
-        .bind(guild_id as i64)
+        enabled: bool,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET alert_details = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Store the stat field layout as JSON, `None` restores the default
+    pub async fn set_guild_alert_fields(
+        &self,
+        guild_id: u64,
+        fields: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET alert_fields = ? WHERE id = ?")
+            .bind(fields)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Set or clear (`None`) the guild's public API token
+    pub async fn set_guild_public_api_token(
+        &self,
+        guild_id: u64,
+        token: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET public_api_token = ? WHERE id = ?")
+            .bind(token)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_guild_command_prefix(
+        &self,
+        guild_id: u64,
+        prefix: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        sqlx::query("UPDATE guilds SET command_prefix = ? WHERE id = ?")
+            .bind(prefix)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Message command prefixes of the guilds that set one
+    pub async fn get_command_prefixes(&self) -> Result<Vec<(SnowflakeColumn, String)>, AppError> {
+        let prefixes = sqlx::query_as(
+            "SELECT id, command_prefix FROM guilds WHERE command_prefix IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(prefixes)
+    }
+
+    #[cfg(feature = "public-api")]
+    pub async fn get_guild_by_public_api_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<Guild>, AppError> {
+        let columns = guild_columns(None);
+        let guild = sqlx::query_as::<_, Guild>(&format!(
+            "SELECT {columns} FROM guilds WHERE public_api_token = ?"
+        ))
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(guild)
+    }
+
+    // === Queue alerts ===
+
+    /// Queues the guild gets no alerts for
+    pub async fn get_muted_queues(&self, guild_id: u64) -> Result<Vec<String>, AppError> {
+        let queues = sqlx::query_scalar("SELECT queue FROM guild_muted_queues WHERE guild_id = ?")
+            .bind(SnowflakeColumn::from(guild_id))
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(queues)
+    }
+
+    /// Replace every muted queue of the guild at once
+    pub async fn set_queue_alerts_bulk(
+        &self,
+        guild_id: u64,
+        muted: &[&str],
+    ) -> Result<(), AppError> {
+        self.get_or_create_guild(guild_id).await?;
+        let guild = SnowflakeColumn::from(guild_id);
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM guild_muted_queues WHERE guild_id = ?")
+            .bind(guild)
+            .execute(&mut *tx)
+            .await?;
+        for queue in muted {
+            sqlx::query("INSERT INTO guild_muted_queues (guild_id, queue) VALUES (?, ?)")
+                .bind(guild)
+                .bind(queue)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // === Guild-Player relations ===
+
+    /// Returns `false` when the player was already tracked in the guild
+    pub async fn add_player_to_guild(
+        &self,
+        guild_id: u64,
+        player_id: i64,
+        added_by: u64,
+        now: i64,
+    ) -> Result<bool, AppError> {
+        self.get_or_create_guild(guild_id).await?;
+        let mut conn = self.pool.acquire().await?;
+        Self::link_player(&mut conn, guild_id, player_id, added_by, now).await
+    }
+
+    /// Track several accounts at once, all or none of them. Returns each
+    /// player with `false` when the guild already tracked it.
+    pub async fn track_accounts(
+        &self,
+        guild_id: u64,
+        added_by: u64,
+        accounts: &[ResolvedAccount],
+        now: i64,
+    ) -> Result<Vec<(Player, bool)>, AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut tracked = Vec::with_capacity(accounts.len());
+        for account in accounts {
+            let player = Self::upsert_player(
+                &mut tx,
+                &account.puuid,
+                &account.game_name,
+                &account.tag_line,
+                &account.region,
+            )
+            .await?;
+            sqlx::query("UPDATE players SET profile_icon_id = ? WHERE id = ?")
+                .bind(account.profile_icon_id)
+                .bind(player.id)
+                .execute(&mut *tx)
+                .await?;
+            // Same start as a single /track: skip the history, or note there is none
+            if player.last_match_id.is_none() {
+                match &account.last_match_id {
+                    Some(match_id) => {
+                        sqlx::query(
+                            "UPDATE players SET last_match_id = ?, no_matches_since = NULL WHERE id = ?",
+                        )
+                        .bind(match_id)
+                        .bind(player.id)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                    None => {
+                        sqlx::query(
+                            "UPDATE players SET no_matches_since = ? WHERE id = ? AND no_matches_since IS NULL",
+                        )
+                        .bind(now)
+                        .bind(player.id)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+            }
+            let added = Self::link_player(&mut tx, guild_id, player.id, added_by, now).await?;
+            tracked.push((player, added));
+        }
+        tx.commit().await?;
+        Ok(tracked)
+    }
+
+    async fn link_player(
+        conn: &mut SqliteConnection,
+        guild_id: u64,
+        player_id: i64,
+        added_by: u64,
+        now: i64,
+    ) -> Result<bool, AppError> {
+        // Tracking again after an untrack starts over rather than restoring the old row
+        let result = sqlx::query(
+            r#"
+            INSERT INTO guild_players (guild_id, player_id, added_by, added_at) VALUES (?, ?, ?, ?)
+            ON CONFLICT(guild_id, player_id) DO UPDATE SET
+                added_by = excluded.added_by,
+                added_at = excluded.added_at,
+                removed_at = NULL,
+                admin_note = NULL,
+                quiet_user_id = NULL
+            WHERE guild_players.removed_at IS NOT NULL
+            "#,
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(player_id)
+        .bind(SnowflakeColumn::from(added_by))
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Soft-delete: the row stays restorable until `purge_untracked` drops it
+    pub async fn remove_player_from_guild(
+        &self,
+        guild_id: u64,
+        player_id: i64,
+        now: i64,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE guild_players SET removed_at = ?
+            WHERE guild_id = ? AND player_id = ? AND removed_at IS NULL
+            "#,
+        )
+        .bind(now)
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(player_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Untrack every player of the guild at once, returns how many were tracked
+    pub async fn remove_all_players_from_guild(
+        &self,
+        guild_id: u64,
+        now: i64,
+    ) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            "UPDATE guild_players SET removed_at = ? WHERE guild_id = ? AND removed_at IS NULL",
+        )
+        .bind(now)
+        .bind(SnowflakeColumn::from(guild_id))
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Most recently untracked player of the guild still within the grace period
+    pub async fn get_last_untracked_player(
+        &self,
+        guild_id: u64,
+        now: i64,
+    ) -> Result<Option<Player>, AppError> {
+        let columns = player_columns(Some("p"));
+        let player = sqlx::query_as::<_, Player>(&format!(
+            r#"
+            SELECT {columns}
+            FROM players p
+            INNER JOIN guild_players gp ON p.id = gp.player_id
+            WHERE gp.guild_id = ?
+              AND gp.removed_at >= ?
+            ORDER BY gp.removed_at DESC
+            LIMIT 1
+            "#
+        ))
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(now - UNTRACK_GRACE_SECS)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(player)
+    }
+
+    /// Undo a soft-delete made within the grace period
+    pub async fn restore_player_in_guild(
+        &self,
+        guild_id: u64,
+        player_id: i64,
+        now: i64,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE guild_players SET removed_at = NULL
+            WHERE guild_id = ? AND player_id = ? AND removed_at >= ?
+            "#,
+        )
+        .bind(SnowflakeColumn::from(guild_id))
         .bind(player_id)
+        .bind(now - UNTRACK_GRACE_SECS)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Accounts currently tracked by the guild
+    pub async fn count_guild_players(&self, guild_id: u64) -> Result<u32, AppError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM guild_players WHERE guild_id = ? AND removed_at IS NULL",
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count as u32)
+    }
+
+    /// `limit` players of the guild from `offset`, in the order of `get_guild_players`
+    pub async fn get_guild_players_page(
+        &self,
+        guild_id: u64,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<GuildPlayer>, AppError> {
+        let columns = player_columns(Some("p"));
+        let players = sqlx::query_as::<_, GuildPlayer>(&format!(
+            r#"
+            SELECT {columns}, gp.added_at, gp.admin_note
+            FROM players p
+            INNER JOIN guild_players gp ON p.id = gp.player_id
+            WHERE gp.guild_id = ? AND gp.removed_at IS NULL
+            ORDER BY p.game_name ASC, p.id ASC
+            LIMIT ? OFFSET ?
+            "#
+        ))
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(players)
+    }
+
+    pub async fn get_guild_players(&self, guild_id: u64) -> Result<Vec<Player>, AppError> {
+        let columns = player_columns(Some("p"));
+        let players = sqlx::query_as::<_, Player>(&format!(
+            r#"
+            SELECT {columns}
+            FROM players p
+            INNER JOIN guild_players gp ON p.id = gp.player_id
+            WHERE gp.guild_id = ? AND gp.removed_at IS NULL
+            ORDER BY p.game_name ASC
+            "#
+        ))
+        .bind(SnowflakeColumn::from(guild_id))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(players)
+    }
+
+    /// Players of the guild ranked in the League-v4 queue type
+    pub async fn count_guild_ranked_players(
+        &self,
+        guild_id: u64,
+        queue_type: &str,
+    ) -> Result<u32, AppError> {
+        let prefix = rank_column_prefix(queue_type)?;
+        let count: i64 = sqlx::query_scalar(&format!(
+            r#"
+            SELECT COUNT(*)
+            FROM players p
+            INNER JOIN guild_players gp ON p.id = gp.player_id
+            WHERE gp.guild_id = ? AND gp.removed_at IS NULL
+              AND p.{prefix}_tier IS NOT NULL AND p.{prefix}_rank IS NOT NULL AND p.{prefix}_lp IS NOT NULL
+            "#
+        ))
+        .bind(SnowflakeColumn::from(guild_id))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count as u32)
+    }
+
+    /// `limit` players of the guild ranked in the League-v4 queue type from
+    /// `offset`, best first as `stats::compare_ranks` orders them
+    pub async fn get_guild_ladder_page(
+        &self,
+        guild_id: u64,
+        queue_type: &str,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<Player>, AppError> {
+        let prefix = rank_column_prefix(queue_type)?;
+        let columns = player_columns(Some("p"));
+        let players = sqlx::query_as::<_, Player>(&format!(
+            r#"
+            SELECT {columns}
+            FROM players p
+            INNER JOIN guild_players gp ON p.id = gp.player_id
+            WHERE gp.guild_id = ? AND gp.removed_at IS NULL
+              AND p.{prefix}_tier IS NOT NULL AND p.{prefix}_rank IS NOT NULL AND p.{prefix}_lp IS NOT NULL
+            ORDER BY
+                CASE UPPER(p.{prefix}_tier)
+                    WHEN 'BRONZE' THEN 400
+                    WHEN 'SILVER' THEN 800
+                    WHEN 'GOLD' THEN 1200
+                    WHEN 'PLATINUM' THEN 1600
+                    WHEN 'EMERALD' THEN 2000
+                    WHEN 'DIAMOND' THEN 2400
+                    WHEN 'MASTER' THEN 2800
+                    WHEN 'GRANDMASTER' THEN 2800
+                    WHEN 'CHALLENGER' THEN 2800
+                    ELSE 0
+                END
+                + CASE WHEN UPPER(p.{prefix}_tier) IN ('MASTER', 'GRANDMASTER', 'CHALLENGER') THEN 0
+                    ELSE CASE p.{prefix}_rank WHEN 'III' THEN 100 WHEN 'II' THEN 200 WHEN 'I' THEN 300 ELSE 0 END
+                END
+                + p.{prefix}_lp DESC,
+                CASE UPPER(p.{prefix}_tier)
+                    WHEN 'CHALLENGER' THEN 3
+                    WHEN 'GRANDMASTER' THEN 2
+                    WHEN 'MASTER' THEN 1
+                    ELSE 0
+                END DESC,
+                p.game_name ASC,
+                p.id ASC
+            LIMIT ? OFFSET ?
+            "#
+        ))
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(players)
+    }
+
+    /// Player the guild tracks under this Riot ID, case insensitive
+    pub async fn get_guild_player_by_riot_id(
+        &self,
+        guild_id: u64,
+        game_name: &str,
+        tag_line: &str,
+    ) -> Result<Option<Player>, AppError> {
+        let columns = player_columns(Some("p"));
+        let player = sqlx::query_as::<_, Player>(&format!(
+            r#"
+            SELECT {columns}
+            FROM players p
+            INNER JOIN guild_players gp ON p.id = gp.player_id
+            WHERE gp.guild_id = ? AND gp.removed_at IS NULL
+                AND LOWER(p.game_name) = LOWER(?) AND LOWER(p.tag_line) = LOWER(?)
+            "#
+        ))
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(game_name)
+        .bind(tag_line)
         .fetch_optional(&self.pool)
         .await?;
-        Ok(exists.is_some())
+        Ok(player)
+    }
+
+    pub async fn get_guilds_tracking_player(&self, player_id: i64) -> Result<Vec<Guild>, AppError> {
+        let columns = guild_columns(Some("g"));
+        let guilds = sqlx::query_as::<_, Guild>(&format!(
+            r#"
+            SELECT {columns}
+            FROM guilds g
+            INNER JOIN guild_players gp ON g.id = gp.guild_id
+            WHERE gp.player_id = ? AND g.alert_channel_id IS NOT NULL AND gp.removed_at IS NULL
+            "#
+        ))
+        .bind(player_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(guilds)
+    }
+
+    /// Set or clear the admin note of a player tracked in the guild, false
+    /// when it isn't tracked there
+    pub async fn set_admin_note(
+        &self,
+        guild_id: u64,
+        player_id: i64,
+        note: Option<&str>,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "UPDATE guild_players SET admin_note = ? WHERE guild_id = ? AND player_id = ? AND removed_at IS NULL",
+        )
+        .bind(note)
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(player_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Hold back the player's alerts in the guild while `user_id` is online
+    /// there, `quiet = false` sends them again. Only whoever tracked the
+    /// account may ask, false when the player isn't theirs.
+    pub async fn set_quiet_user(
+        &self,
+        guild_id: u64,
+        player_id: i64,
+        user_id: u64,
+        quiet: bool,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "UPDATE guild_players SET quiet_user_id = ? WHERE guild_id = ? AND player_id = ? AND added_by = ? AND removed_at IS NULL",
+        )
+        .bind(quiet.then(|| SnowflakeColumn::from(user_id)))
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(player_id)
+        .bind(SnowflakeColumn::from(user_id))
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// (guild id, user id) pairs holding back the player's alerts while online
+    pub async fn get_quiet_users(
+        &self,
+        player_id: i64,
+    ) -> Result<Vec<(SnowflakeColumn, SnowflakeColumn)>, AppError> {
+        let users = sqlx::query_as(
+            "SELECT guild_id, quiet_user_id FROM guild_players WHERE player_id = ? AND quiet_user_id IS NOT NULL AND removed_at IS NULL",
+        )
+        .bind(player_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(users)
+    }
+
+    /// (guild id, user id) of everyone quieting at least one account
+    pub async fn get_quiet_members(
+        &self,
+    ) -> Result<Vec<(SnowflakeColumn, SnowflakeColumn)>, AppError> {
+        let members = sqlx::query_as(
+            "SELECT DISTINCT guild_id, quiet_user_id FROM guild_players WHERE quiet_user_id IS NOT NULL AND removed_at IS NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(members)
+    }
+
+    /// Whether the user quiets any account tracked in the guild
+    pub async fn is_quiet_member(&self, guild_id: u64, user_id: u64) -> Result<bool, AppError> {
+        let quiet = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM guild_players WHERE guild_id = ? AND quiet_user_id = ? AND removed_at IS NULL)",
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(SnowflakeColumn::from(user_id))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(quiet)
+    }
+
+    /// When the player started being tracked in the guild (Unix seconds),
+    /// `None` if it isn't
+    pub async fn get_tracked_since(
+        &self,
+        guild_id: u64,
+        player_id: i64,
+    ) -> Result<Option<i64>, AppError> {
+        let added_at = sqlx::query_scalar::<_, i64>(
+            "SELECT added_at FROM guild_players WHERE guild_id = ? AND player_id = ? AND removed_at IS NULL",
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(player_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(added_at)
+    }
+
+    // === Ladder watches ===
+
+    pub async fn add_ladder_watch(
+        &self,
+        guild_id: u64,
+        region: &str,
+        tier: &str,
+    ) -> Result<bool, AppError> {
+        self.get_or_create_guild(guild_id).await?;
+
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO ladder_watches (guild_id, region, tier) VALUES (?, ?, ?)",
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(region)
+        .bind(tier)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn remove_ladder_watch(
+        &self,
+        guild_id: u64,
+        region: &str,
+        tier: &str,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "DELETE FROM ladder_watches WHERE guild_id = ? AND region = ? AND tier = ?",
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(region)
+        .bind(tier)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Watches of guilds with an alert channel configured
+    pub async fn get_ladder_watches(&self) -> Result<Vec<LadderWatch>, AppError> {
+        let watches = sqlx::query_as::<_, LadderWatch>(
+            r#"
+            SELECT lw.guild_id, g.alert_channel_id, lw.region, lw.tier, lw.top_cutoff_lp
+            FROM ladder_watches lw
+            INNER JOIN guilds g ON lw.guild_id = g.id
+            WHERE g.alert_channel_id IS NOT NULL
+            ORDER BY lw.region, lw.tier
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(watches)
+    }
+
+    pub async fn get_ladder_watch_members(
+        &self,
+        watch: &LadderWatch,
+    ) -> Result<Vec<i64>, AppError> {
+        let members = sqlx::query_scalar::<_, i64>(
+            "SELECT player_id FROM ladder_watch_members WHERE guild_id = ? AND region = ? AND tier = ?",
+        )
+        .bind(watch.guild_id)
+        .bind(&watch.region)
+        .bind(&watch.tier)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(members)
+    }
+
+    /// Replace the last observed state of a watch
+    pub async fn update_ladder_watch(
+        &self,
+        watch: &LadderWatch,
+        top_cutoff_lp: Option<i32>,
+        member_ids: &[i64],
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE ladder_watches SET top_cutoff_lp = ? WHERE guild_id = ? AND region = ? AND tier = ?",
+        )
+        .bind(top_cutoff_lp)
+        .bind(watch.guild_id)
+        .bind(&watch.region)
+        .bind(&watch.tier)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "DELETE FROM ladder_watch_members WHERE guild_id = ? AND region = ? AND tier = ?",
+        )
+        .bind(watch.guild_id)
+        .bind(&watch.region)
+        .bind(&watch.tier)
+        .execute(&mut *tx)
+        .await?;
+
+        for player_id in member_ids {
+            sqlx::query(
+                "INSERT INTO ladder_watch_members (guild_id, region, tier, player_id) VALUES (?, ?, ?, ?)",
+            )
+            .bind(watch.guild_id)
+            .bind(&watch.region)
+            .bind(&watch.tier)
+            .bind(player_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // === Rank history ===
+
+    pub async fn record_rank_snapshot(
+        &self,
+        player_id: i64,
+        snapshot: &RankSnapshot,
+        recorded_at: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO rank_history (player_id, queue, tier, rank, lp, wins, losses, recorded_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(player_id)
+        .bind(&snapshot.queue)
+        .bind(&snapshot.tier)
+        .bind(&snapshot.rank)
+        .bind(snapshot.lp)
+        .bind(snapshot.wins)
+        .bind(snapshot.losses)
+        .bind(recorded_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Last standing recorded for the player in the queue
+    pub async fn get_latest_rank_snapshot(
+        &self,
+        player_id: i64,
+        queue: &str,
+    ) -> Result<Option<RankSnapshot>, AppError> {
+        let snapshot = sqlx::query_as::<_, RankSnapshot>(
+            r#"
+            SELECT queue, tier, rank, lp, wins, losses
+            FROM rank_history
+            WHERE player_id = ? AND queue = ?
+            ORDER BY recorded_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(player_id)
+        .bind(queue)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(snapshot)
+    }
+
+    /// Standings of the guild's players in a queue over `[from, to)`, oldest first
+    pub async fn get_guild_rank_history(
+        &self,
+        guild_id: u64,
+        queue: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<RankPoint>, AppError> {
+        let points = sqlx::query_as::<_, RankPoint>(
+            r#"
+            SELECT p.id AS player_id, p.game_name, p.tag_line, p.privacy_alias,
+                rh.recorded_at, rh.tier, rh.rank, rh.lp
+            FROM rank_history rh
+            INNER JOIN players p ON rh.player_id = p.id
+            INNER JOIN guild_players gp ON gp.player_id = p.id
+            WHERE gp.guild_id = ? AND gp.removed_at IS NULL AND rh.queue = ?
+                AND rh.recorded_at >= ? AND rh.recorded_at < ?
+            ORDER BY rh.recorded_at, rh.id
+            "#,
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(queue)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(points)
+    }
+
+    /// Standing at the start of a session beginning at `since` (Unix seconds):
+    /// the last snapshot taken before it, else the first one taken after it
+    pub async fn get_session_baseline(
+        &self,
+        player_id: i64,
+        queue: &str,
+        since: i64,
+    ) -> Result<Option<RankSnapshot>, AppError> {
+        let snapshot = sqlx::query_as::<_, RankSnapshot>(
+            r#"
+            SELECT queue, tier, rank, lp, wins, losses
+            FROM rank_history
+            WHERE player_id = ? AND queue = ?
+            ORDER BY recorded_at < ? DESC,
+                     CASE WHEN recorded_at < ? THEN -recorded_at ELSE recorded_at END,
+                     id
+            LIMIT 1
+            "#,
+        )
+        .bind(player_id)
+        .bind(queue)
+        .bind(since)
+        .bind(since)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(snapshot)
+    }
+
+    // === Champion pool ===
+
+    /// Count a game on a champion. Recording the same match twice (a retried
+    /// poll) only counts it once.
+    pub async fn record_champion_play(
+        &self,
+        player_id: i64,
+        champion: &str,
+        match_id: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO champion_plays (player_id, champion, games, last_match_id)
+            VALUES (?, ?, 1, ?)
+            ON CONFLICT(player_id, champion) DO UPDATE SET
+                games = games + (last_match_id != excluded.last_match_id),
+                last_match_id = excluded.last_match_id
+            "#,
+        )
+        .bind(player_id)
+        .bind(champion)
+        .bind(match_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Games per champion, most played first
+    pub async fn get_champion_pool(&self, player_id: i64) -> Result<Vec<(String, i64)>, AppError> {
+        let pool = sqlx::query_as(
+            r#"
+            SELECT champion, games FROM champion_plays
+            WHERE player_id = ?
+            ORDER BY games DESC, champion
+            "#,
+        )
+        .bind(player_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(pool)
+    }
+
+    // === Emojis ===
+
+    /// Known custom emojis as (name, emoji id)
+    pub async fn get_emojis(&self) -> Result<Vec<(String, SnowflakeColumn)>, AppError> {
+        let emojis = sqlx::query_as("SELECT name, emoji_id FROM emojis")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(emojis)
+    }
+
+    pub async fn set_emoji(&self, name: &str, emoji_id: u64) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO emojis (name, emoji_id) VALUES (?, ?)
+            ON CONFLICT(name) DO UPDATE SET emoji_id = excluded.emoji_id
+            "#,
+        )
+        .bind(name)
+        .bind(SnowflakeColumn::from(emoji_id))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // === Alert log ===
+
+    pub async fn record_alert(
+        &self,
+        player_id: i64,
+        guild_id: u64,
+        match_id: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query("INSERT INTO alert_log (player_id, guild_id, match_id) VALUES (?, ?, ?)")
+            .bind(player_id)
+            .bind(SnowflakeColumn::from(guild_id))
+            .bind(match_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the alert for this match was already sent to `guild_id`
+    pub async fn was_alert_sent(
+        &self,
+        player_id: i64,
+        guild_id: u64,
+        match_id: &str,
+    ) -> Result<bool, AppError> {
+        let sent = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM alert_log
+                WHERE player_id = ? AND guild_id = ? AND match_id = ?
+            )
+            "#,
+        )
+        .bind(player_id)
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(match_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(sent)
+    }
+
+    // === Processed matches ===
+
+    /// Mark a match as handed to the dispatcher, before its alert is sent,
+    /// with the rank the player had in its queue before the game
+    pub async fn mark_match_processed(
+        &self,
+        player_id: i64,
+        match_id: &str,
+        old_rank: Option<&RankInfo>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO processed_matches (match_id, player_id, old_tier, old_rank, old_lp)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(match_id)
+        .bind(player_id)
+        .bind(old_rank.map(|rank| &rank.tier))
+        .bind(old_rank.map(|rank| &rank.rank))
+        .bind(old_rank.map(|rank| rank.lp))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark the alert of a processed match as sent
+    pub async fn mark_match_dispatched(
+        &self,
+        player_id: i64,
+        match_id: &str,
+        now: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE processed_matches SET dispatched_at = ?
+            WHERE match_id = ? AND player_id = ?
+            "#,
+        )
+        .bind(now)
+        .bind(match_id)
+        .bind(player_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `None` if the match was never processed, otherwise whether its alert
+    /// went out
+    pub async fn get_match_dispatched(
+        &self,
+        player_id: i64,
+        match_id: &str,
+    ) -> Result<Option<bool>, AppError> {
+        let dispatched = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT dispatched_at IS NOT NULL FROM processed_matches
+            WHERE match_id = ? AND player_id = ?
+            "#,
+        )
+        .bind(match_id)
+        .bind(player_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(dispatched)
+    }
+
+    /// Processed matches whose alert never went out
+    pub async fn get_undispatched_matches(&self) -> Result<Vec<InterruptedMatch>, AppError> {
+        let matches = sqlx::query_as(
+            r#"
+            SELECT player_id, match_id, old_tier, old_rank, old_lp
+            FROM processed_matches
+            WHERE dispatched_at IS NULL
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(matches)
+    }
+
+    // === Pending alerts ===
+
+    pub async fn buffer_alert(&self, alert: &BufferedAlert) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO pending_alerts
+                (player_id, guild_id, match_id, channel_id, image, embed, content,
+                 live_channel_id, live_message_id, queued_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(alert.player_id)
+        .bind(alert.guild_id)
+        .bind(&alert.match_id)
+        .bind(alert.channel_id)
+        .bind(&alert.image)
+        .bind(&alert.embed)
+        .bind(&alert.content)
+        .bind(alert.live_channel_id)
+        .bind(alert.live_message_id)
+        .bind(alert.queued_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Buffered alerts, oldest first
+    pub async fn get_buffered_alerts(&self) -> Result<Vec<BufferedAlert>, AppError> {
+        let alerts = sqlx::query_as::<_, BufferedAlert>(
+            r#"
+            SELECT player_id, guild_id, match_id, channel_id, image, embed, content,
+                live_channel_id, live_message_id, queued_at
+            FROM pending_alerts
+            ORDER BY queued_at, rowid
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(alerts)
+    }
+
+    pub async fn remove_buffered_alert(
+        &self,
+        player_id: i64,
+        guild_id: u64,
+        match_id: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "DELETE FROM pending_alerts WHERE player_id = ? AND guild_id = ? AND match_id = ?",
+        )
+        .bind(player_id)
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(match_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Drop alerts buffered before `cutoff`, too old to be worth posting
+    pub async fn drop_stale_buffered_alerts(&self, cutoff: i64) -> Result<u64, AppError> {
+        let dropped = sqlx::query("DELETE FROM pending_alerts WHERE queued_at < ?")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(dropped)
+    }
+
+    // === Live games ===
+
+    /// "Game in progress" messages of a player, whatever the match
+    pub async fn get_live_messages(&self, player_id: i64) -> Result<Vec<LiveMessage>, AppError> {
+        let messages = sqlx::query_as::<_, LiveMessage>(
+            r#"
+            SELECT guild_id, match_id, channel_id, message_id, started_at
+            FROM live_messages
+            WHERE player_id = ?
+            "#,
+        )
+        .bind(player_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(messages)
+    }
+
+    /// Remember the message posted in a guild for a game, replacing the one
+    /// of a previous game
+    pub async fn set_live_message(
+        &self,
+        player_id: i64,
+        guild_id: u64,
+        match_id: &str,
+        channel_id: u64,
+        message_id: u64,
+        started_at: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO live_messages
+                (player_id, guild_id, match_id, channel_id, message_id, started_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(player_id)
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(match_id)
+        .bind(SnowflakeColumn::from(channel_id))
+        .bind(SnowflakeColumn::from(message_id))
+        .bind(started_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_live_message(&self, player_id: i64, guild_id: u64) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM live_messages WHERE player_id = ? AND guild_id = ?")
+            .bind(player_id)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // === Weekly MVP ===
+
+    pub async fn record_game_score(
+        &self,
+        player_id: i64,
+        match_id: &str,
+        score: f64,
+        played_at: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO game_scores (player_id, match_id, score, played_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(player_id)
+        .bind(match_id)
+        .bind(score)
+        .bind(played_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_match_result(
+        &self,
+        player_id: i64,
+        result: &MatchResult,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO match_history
+                (player_id, match_id, queue_id, win, kills, deaths, assists, lp_delta, played_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(player_id)
+        .bind(&result.match_id)
+        .bind(result.queue_id)
+        .bind(result.win)
+        .bind(result.kills)
+        .bind(result.deaths)
+        .bind(result.assists)
+        .bind(result.lp_delta)
+        .bind(result.played_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Store the extended stats of an alerted game, as JSON
+    pub async fn record_match_details(
+        &self,
+        player_id: i64,
+        match_id: &str,
+        details: &str,
+        now: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO match_details (player_id, match_id, details, recorded_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(player_id)
+        .bind(match_id)
+        .bind(details)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_match_details(
+        &self,
+        player_id: i64,
+        match_id: &str,
+    ) -> Result<Option<String>, AppError> {
+        let details = sqlx::query_scalar::<_, String>(
+            "SELECT details FROM match_details WHERE player_id = ? AND match_id = ?",
+        )
+        .bind(player_id)
+        .bind(match_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(details)
+    }
+
+    /// Last `limit` recorded games of the player, newest first
+    pub async fn get_recent_match_results(
+        &self,
+        player_id: i64,
+        limit: i64,
+    ) -> Result<Vec<MatchResult>, AppError> {
+        let results = sqlx::query_as::<_, MatchResult>(
+            r#"
+            SELECT match_id, queue_id, win, kills, deaths, assists, lp_delta, played_at
+            FROM match_history
+            WHERE player_id = ?
+            ORDER BY played_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(player_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(results)
+    }
+
+    /// Games the guild's players finished in `[from, to)`
+    pub async fn get_recap_games(
+        &self,
+        guild_id: u64,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<RecapGame>, AppError> {
+        let games = sqlx::query_as::<_, RecapGame>(
+            r#"
+            SELECT p.id AS player_id, p.game_name, p.tag_line, p.privacy_alias,
+                mh.win, mh.kills, mh.deaths, mh.assists, mh.lp_delta
+            FROM match_history mh
+            INNER JOIN players p ON mh.player_id = p.id
+            INNER JOIN guild_players gp ON gp.player_id = p.id
+            WHERE gp.guild_id = ? AND gp.removed_at IS NULL
+                AND mh.played_at >= ? AND mh.played_at < ?
+            ORDER BY mh.played_at
+            "#,
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(games)
+    }
+
+    /// Mark the results recap of the period ending at `period_end` as posted
+    pub async fn record_results_recap(
+        &self,
+        guild_id: u64,
+        period_end: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query("UPDATE guilds SET last_results_recap = ? WHERE id = ?")
+            .bind(period_end)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Guilds with a recap or alert channel, which get the weekly recap
+    pub async fn get_guilds_with_recap_channel(&self) -> Result<Vec<Guild>, AppError> {
+        let columns = guild_columns(None);
+        let guilds = sqlx::query_as::<_, Guild>(&format!(
+            "SELECT {columns} FROM guilds WHERE recap_channel_id IS NOT NULL OR alert_channel_id IS NOT NULL"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(guilds)
+    }
+
+    /// Tracked player of the guild with the best average score over the
+    /// week starting at `week_start` (Unix seconds, up to `week_end`), among
+    /// those with at least `min_games` games
+    pub async fn get_weekly_mvp(
+        &self,
+        guild_id: u64,
+        week_start: i64,
+        week_end: i64,
+        min_games: i64,
+    ) -> Result<Option<WeeklyMvp>, AppError> {
+        let mvp = sqlx::query_as::<_, WeeklyMvp>(
+            r#"
+            SELECT ? AS week_start, p.id AS player_id, p.game_name, p.tag_line, p.privacy_alias,
+                AVG(gs.score) AS avg_score, COUNT(*) AS games
+            FROM game_scores gs
+            INNER JOIN players p ON gs.player_id = p.id
+            INNER JOIN guild_players gp ON gp.player_id = p.id
+            WHERE gp.guild_id = ? AND gp.removed_at IS NULL
+                AND gs.played_at >= ? AND gs.played_at < ?
+            GROUP BY p.id
+            HAVING COUNT(*) >= ?
+            ORDER BY avg_score DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(week_start)
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(week_start)
+        .bind(week_end)
+        .bind(min_games)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(mvp)
+    }
+
+    /// Mark the recap of a week as posted, with its MVP if there was one
+    pub async fn record_weekly_recap(
+        &self,
+        guild_id: u64,
+        week_start: i64,
+        mvp: Option<&WeeklyMvp>,
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        if let Some(mvp) = mvp {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO mvp_history (guild_id, week_start, player_id, avg_score, games)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(SnowflakeColumn::from(guild_id))
+            .bind(week_start)
+            .bind(mvp.player_id)
+            .bind(mvp.avg_score)
+            .bind(mvp.games)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("UPDATE guilds SET last_recap_week = ? WHERE id = ?")
+            .bind(week_start)
+            .bind(SnowflakeColumn::from(guild_id))
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Past MVPs of a guild, latest first
+    pub async fn get_mvp_history(
+        &self,
+        guild_id: u64,
+        limit: i64,
+    ) -> Result<Vec<WeeklyMvp>, AppError> {
+        let history = sqlx::query_as::<_, WeeklyMvp>(
+            r#"
+            SELECT mh.week_start, mh.player_id, p.game_name, p.tag_line, p.privacy_alias, mh.avg_score, mh.games
+            FROM mvp_history mh
+            INNER JOIN players p ON mh.player_id = p.id
+            WHERE mh.guild_id = ?
+            ORDER BY mh.week_start DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(SnowflakeColumn::from(guild_id))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(history)
+    }
+
+    // === Scheduled jobs ===
+
+    pub async fn get_job_last_run(&self, name: &str) -> Result<Option<i64>, AppError> {
+        let last_run =
+            sqlx::query_scalar::<_, i64>("SELECT last_run_at FROM job_runs WHERE name = ?")
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(last_run)
+    }
+
+    pub async fn set_job_last_run(&self, name: &str, at: i64) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO job_runs (name, last_run_at) VALUES (?, ?)
+            ON CONFLICT (name) DO UPDATE SET last_run_at = excluded.last_run_at
+            "#,
+        )
+        .bind(name)
+        .bind(at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // === Feature flags ===
+
+    /// Every feature switch as (feature, guild id or 0 for the bot, enabled)
+    pub async fn get_feature_flags(
+        &self,
+    ) -> Result<Vec<(String, SnowflakeColumn, bool)>, AppError> {
+        let flags = sqlx::query_as("SELECT feature, guild_id, enabled FROM feature_flags")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(flags)
+    }
+
+    /// Switch a feature on or off for a guild (0 for the whole bot), `None`
+    /// removes the switch
+    pub async fn set_feature_flag(
+        &self,
+        feature: &str,
+        guild_id: u64,
+        enabled: Option<bool>,
+    ) -> Result<(), AppError> {
+        match enabled {
+            Some(enabled) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO feature_flags (feature, guild_id, enabled) VALUES (?, ?, ?)
+                    ON CONFLICT (feature, guild_id) DO UPDATE SET enabled = excluded.enabled
+                    "#,
+                )
+                .bind(feature)
+                .bind(SnowflakeColumn::from(guild_id))
+                .bind(enabled)
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM feature_flags WHERE feature = ? AND guild_id = ?")
+                    .bind(feature)
+                    .bind(SnowflakeColumn::from(guild_id))
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    // === Data purge ===
+
+    /// Forget which tracked accounts a Discord user added or quieted, the
+    /// only places user ids are kept. The accounts stay tracked by their
+    /// servers. Returns the number of rows scrubbed.
+    pub async fn purge_user_data(&self, user_id: u64) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE guild_players
+            SET added_by = CASE WHEN added_by = ?1 THEN 0 ELSE added_by END,
+                quiet_user_id = CASE WHEN quiet_user_id = ?1 THEN NULL ELSE quiet_user_id END
+            WHERE added_by = ?1 OR quiet_user_id = ?1
+            "#,
+        )
+        .bind(SnowflakeColumn::from(user_id))
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete everything kept about a guild: settings, tracked accounts,
+    /// ladder watches, MVPs and alert history. Accounts no other guild tracks
+    /// are deleted with their history and aliases. Returns the number of
+    /// accounts deleted.
+    pub async fn purge_guild_data(&self, guild_id: u64) -> Result<u64, AppError> {
+        let guild = SnowflakeColumn::from(guild_id);
+        let mut tx = self.pool.begin().await?;
+
+        let players = sqlx::query(
+            r#"
+            DELETE FROM players
+            WHERE id IN (SELECT player_id FROM guild_players WHERE guild_id = ?)
+              AND id NOT IN (SELECT player_id FROM guild_players WHERE guild_id != ?)
+            "#,
+        )
+        .bind(guild)
+        .bind(guild)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // Tables without a foreign key to guilds
+        for table in [
+            "alert_log",
+            "pending_alerts",
+            "live_messages",
+            "feature_flags",
+        ] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE guild_id = ?"))
+                .bind(guild)
+                .execute(&mut *tx)
+                .await?;
+        }
+        // Cascades to tracking rows, ladder watches, MVP history and alert settings
+        sqlx::query("DELETE FROM guilds WHERE id = ?")
+            .bind(guild)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(players)
+    }
+
+    // === Maintenance ===
+
+    /// Write a consistent copy of the database to a new file at `path`, safe
+    /// while the bot is running
+    pub async fn backup_into(&self, path: &Path) -> Result<(), AppError> {
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().as_ref())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete history rows falling outside the retention policy, relative to
+    /// `now` (Unix seconds). Returns the number of rows removed.
+    pub async fn prune_history(&self, policy: &RetentionPolicy, now: i64) -> Result<u64, AppError> {
+        let mut removed = 0;
+
+        if let Some(days) = policy.max_age_days {
+            let cutoff = now - i64::from(days) * 86400;
+            for (table, recorded_at) in HISTORY_TABLES {
+                removed += sqlx::query(&format!("DELETE FROM {table} WHERE {recorded_at} < ?"))
+                    .bind(cutoff)
+                    .execute(&self.pool)
+                    .await?
+                    .rows_affected();
+            }
+        }
+
+        if let Some(max_rows) = policy.max_rows_per_player {
+            for (table, recorded_at) in HISTORY_TABLES {
+                removed += sqlx::query(&format!(
+                    r#"
+                    DELETE FROM {table} WHERE rowid IN (
+                        SELECT rowid FROM (
+                            SELECT rowid, ROW_NUMBER() OVER (
+                                PARTITION BY player_id ORDER BY {recorded_at} DESC, rowid DESC
+                            ) AS position
+                            FROM {table}
+                        )
+                        WHERE position > ?
+                    )
+                    "#
+                ))
+                .bind(max_rows)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Drop untracks older than the grace period, then the players no guild
+    /// references anymore along with their history. Returns the players removed.
+    pub async fn purge_untracked(&self, now: i64) -> Result<u64, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM guild_players WHERE removed_at < ?")
+            .bind(now - UNTRACK_GRACE_SECS)
+            .execute(&mut *tx)
+            .await?;
+
+        let purged = sqlx::query(
+            "DELETE FROM players WHERE id NOT IN (SELECT player_id FROM guild_players)",
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        Ok(purged)
+    }
+
+    /// Reclaim free pages and refresh query planner statistics
+    pub async fn vacuum(&self) -> Result<(), AppError> {
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        sqlx::query("PRAGMA optimize").execute(&self.pool).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::{Repository, UNTRACK_GRACE_DAYS};
+    use crate::clock::{Clock, SystemClock};
+    use crate::config::RetentionPolicy;
+    use crate::db::{BufferedAlert, Game, MatchResult, RankInfo, ResolvedAccount, run_migrations};
+
+    async fn test_repository() -> Repository {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("in-memory database");
+        run_migrations(&pool).await.expect("migrations");
+        Repository::new(pool)
+    }
+
+    #[tokio::test]
+    async fn merged_players_keep_guilds_and_history() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+
+        let old = repo
+            .get_or_create_player("old-puuid", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+        let rank = RankInfo {
+            tier: "GOLD".to_string(),
+            rank: "II".to_string(),
+            lp: 45,
+        };
+        repo.update_player_rank(old.id, Some(&rank), None)
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, old.id, 10, now).await.unwrap();
+        repo.add_player_to_guild(2, old.id, 10, now).await.unwrap();
+        let result = MatchResult {
+            match_id: "EUW1_1".into(),
+            queue_id: 420,
+            win: true,
+            kills: 5,
+            deaths: 2,
+            assists: 7,
+            lp_delta: Some(21),
+            played_at: 1_700_000_000,
+        };
+        repo.record_match_result(old.id, &result).await.unwrap();
+        repo.record_game_score(old.id, "EUW1_1", 7.5, 1_700_000_000)
+            .await
+            .unwrap();
+        repo.record_champion_play(old.id, "Ahri", "EUW1_1")
+            .await
+            .unwrap();
+        repo.record_match_details(old.id, "EUW1_1", "{}", 1_700_000_000)
+            .await
+            .unwrap();
+
+        // The same account under a newer PUUID, and a namesake elsewhere
+        let new = repo
+            .get_or_create_player("new-puuid", "foo", "euw", "euw1")
+            .await
+            .unwrap();
+        repo.add_player_to_guild(2, new.id, 20, now).await.unwrap();
+        repo.get_or_create_player("na-puuid", "Foo", "EUW", "na1")
+            .await
+            .unwrap();
+
+        let groups = repo.get_riot_id_duplicates().await.unwrap();
+        let ids: Vec<Vec<i64>> = groups
+            .iter()
+            .map(|group| group.iter().map(|p| p.id).collect())
+            .collect();
+        assert_eq!(ids, vec![vec![new.id, old.id]], "newest row first");
+
+        repo.merge_player_into(old.id, new.id).await.unwrap();
+        assert!(repo.get_riot_id_duplicates().await.unwrap().is_empty());
+
+        assert!(repo.get_tracked_since(1, new.id).await.unwrap().is_some());
+        assert!(repo.get_tracked_since(2, new.id).await.unwrap().is_some());
+        let merged = repo.get_guild_players(1).await.unwrap();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].puuid, "new-puuid");
+        assert_eq!(
+            merged[0].solo_rank_info().map(|r| r.lp),
+            Some(45),
+            "missing rank data is filled from the removed row"
+        );
+
+        for table in [
+            "match_history",
+            "game_scores",
+            "champion_plays",
+            "match_details",
+        ] {
+            let rows: i64 =
+                sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table} WHERE player_id = ?"))
+                    .bind(new.id)
+                    .fetch_one(&repo.pool)
+                    .await
+                    .unwrap();
+            assert_eq!(rows, 1, "{table} moved to the kept row");
+        }
+        let owner = repo
+            .get_player_by_identity(Game::Lol, "new-puuid")
+            .await
+            .unwrap();
+        assert_eq!(owner.map(|p| p.id), Some(new.id));
+        assert!(
+            repo.get_player_by_identity(Game::Lol, "old-puuid")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn untrack_is_undoable_until_purged() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let player = repo
+            .get_or_create_player("puuid", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+        assert!(
+            repo.add_player_to_guild(1, player.id, 10, now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .add_player_to_guild(1, player.id, 20, now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.get_tracked_since(1, player.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        assert!(
+            repo.remove_player_from_guild(1, player.id, now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .remove_player_from_guild(1, player.id, now)
+                .await
+                .unwrap()
+        );
+        assert!(repo.get_guild_players(1).await.unwrap().is_empty());
+
+        let last = repo.get_last_untracked_player(1, now).await.unwrap();
+        assert_eq!(last.map(|p| p.id), Some(player.id));
+        assert!(
+            repo.restore_player_in_guild(1, player.id, now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.get_tracked_since(1, player.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // Past the grace period the player and its history are gone
+        repo.remove_player_from_guild(1, player.id, now)
+            .await
+            .unwrap();
+        assert_eq!(repo.purge_untracked(now).await.unwrap(), 0);
+        let later = now + (UNTRACK_GRACE_DAYS + 1) * 86400;
+        assert!(
+            repo.get_last_untracked_player(1, later)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            !repo
+                .restore_player_in_guild(1, player.id, later)
+                .await
+                .unwrap()
+        );
+        assert_eq!(repo.purge_untracked(later).await.unwrap(), 1);
+        assert!(
+            repo.get_player_by_riot_id("Foo", "EUW")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn writes_stamp_updated_at() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let player = repo
+            .get_or_create_player("puuid", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, player.id, 10, now)
+            .await
+            .unwrap();
+
+        let stamps = || async {
+            sqlx::query_as::<_, (Option<i64>, Option<i64>, Option<i64>)>(
+                r#"
+                SELECT p.updated_at, g.updated_at, gp.updated_at
+                FROM guild_players gp
+                INNER JOIN players p ON p.id = gp.player_id
+                INNER JOIN guilds g ON g.id = gp.guild_id
+                "#,
+            )
+            .fetch_one(&repo.pool)
+            .await
+            .unwrap()
+        };
+        let (player_at, guild_at, tracking_at) = stamps().await;
+        assert!(player_at.is_some() && guild_at.is_some() && tracking_at.is_some());
+
+        sqlx::query("UPDATE players SET updated_at = 0")
+            .execute(&repo.pool)
+            .await
+            .unwrap();
+        repo.update_player_last_match(player.id, "EUW1_1")
+            .await
+            .unwrap();
+        assert!(stamps().await.0 > Some(0));
+
+        let roster = repo.get_guild_players_page(1, 0, 10).await.unwrap();
+        assert!(roster[0].added_at > 0);
+    }
+
+    #[tokio::test]
+    async fn retracking_keeps_player_history() {
+        let repo = test_repository().await;
+        let player = repo
+            .get_or_create_player("puuid", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+        repo.update_player_last_match(player.id, "EUW1_1")
+            .await
+            .unwrap();
+        repo.set_player_privacy_alias(player.id, Some("Mystery"))
+            .await
+            .unwrap();
+
+        // Renamed account, history stays
+        let renamed = repo
+            .get_or_create_player("puuid", "Bar", "EUW", "euw1")
+            .await
+            .unwrap();
+        assert_eq!(renamed.id, player.id);
+        assert_eq!(renamed.riot_id(), "Bar#EUW");
+        assert_eq!(renamed.last_match_id.as_deref(), Some("EUW1_1"));
+        assert_eq!(renamed.privacy_alias.as_deref(), Some("Mystery"));
+
+        // A lookup missing the Riot ID doesn't blank it
+        let partial = repo
+            .get_or_create_player("puuid", "", "", "")
+            .await
+            .unwrap();
+        assert_eq!(partial.riot_id(), "Bar#EUW");
+        assert_eq!(partial.region, "euw1");
+    }
+
+    #[tokio::test]
+    async fn accounts_without_matches_are_marked_once() {
+        let repo = test_repository().await;
+        let player = repo
+            .get_or_create_player("puuid", "Fresh", "EUW", "euw1")
+            .await
+            .unwrap();
+
+        assert!(
+            repo.mark_player_without_matches(player.id, 100)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .mark_player_without_matches(player.id, 200)
+                .await
+                .unwrap()
+        );
+        let fresh = repo
+            .get_player_by_riot_id("Fresh", "EUW")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fresh.no_matches_since, Some(100));
+
+        // The first game clears it
+        repo.update_player_last_match(player.id, "EUW1_1")
+            .await
+            .unwrap();
+        let played = repo
+            .get_player_by_riot_id("Fresh", "EUW")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(played.no_matches_since, None);
+    }
+
+    #[tokio::test]
+    async fn players_are_found_by_game_identity() {
+        let repo = test_repository().await;
+        let player = repo
+            .get_or_create_player("lol-puuid", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+
+        let found = repo
+            .get_player_by_identity(Game::Lol, "lol-puuid")
+            .await
+            .unwrap();
+        assert_eq!(found.map(|p| p.id), Some(player.id));
+        assert!(
+            repo.get_player_by_identity(Game::Lol, "other")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn duplicates_missing_rows_and_unicode_names() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+
+        // Duplicate inserts return the existing rows
+        let player = repo
+            .get_or_create_player("puuid", "Fâkër 李", "KR1", "kr")
+            .await
+            .unwrap();
+        let again = repo
+            .get_or_create_player("puuid", "Fâkër 李", "KR1", "kr")
+            .await
+            .unwrap();
+        assert_eq!(again.id, player.id);
+        assert!(
+            repo.add_player_to_guild(1, player.id, 10, now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .add_player_to_guild(1, player.id, 10, now)
+                .await
+                .unwrap()
+        );
+        assert_eq!(repo.get_guild_players(1).await.unwrap().len(), 1);
+
+        // Unicode names round-trip, lookups ignore ASCII case
+        let found = repo
+            .get_player_by_riot_id("Fâkër 李", "kr1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.riot_id(), "Fâkër 李#KR1");
+
+        // Missing rows are reported, not errors
+        assert!(repo.get_guild(2).await.unwrap().is_none());
+        assert!(
+            repo.get_player_by_riot_id("Nobody", "EUW")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            !repo
+                .remove_player_from_guild(2, player.id, now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .set_admin_note(2, player.id, Some("note"))
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.get_latest_rank_snapshot(player.id, "RANKED_SOLO_5x5")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn prune_history_keeps_latest_rows_per_player() {
+        let repo = test_repository().await;
+        let a = repo
+            .get_or_create_player("a", "A", "EUW", "euw1")
+            .await
+            .unwrap();
+        let b = repo
+            .get_or_create_player("b", "B", "EUW", "euw1")
+            .await
+            .unwrap();
+
+        for i in 0..5 {
+            repo.record_alert(a.id, 1, &format!("EUW1_{i}"))
+                .await
+                .unwrap();
+        }
+        repo.record_alert(b.id, 1, "EUW1_9").await.unwrap();
+        let now = SystemClock.unix_now();
+        // The cap applies to the other history tables, not only alerts
+        for i in 0..4 {
+            repo.mark_match_processed(a.id, &format!("EUW1_{i}"), None)
+                .await
+                .unwrap();
+            repo.record_game_score(a.id, &format!("EUW1_{i}"), 5.0, now - i)
+                .await
+                .unwrap();
+        }
+
+        let policy = RetentionPolicy {
+            max_age_days: Some(30),
+            max_rows_per_player: Some(2),
+        };
+        assert_eq!(repo.prune_history(&policy, now).await.unwrap(), 5);
+        assert_eq!(repo.prune_history(&policy, now).await.unwrap(), 0);
+        let scores: Vec<String> =
+            sqlx::query_scalar("SELECT match_id FROM game_scores ORDER BY played_at DESC")
+                .fetch_all(&repo.pool)
+                .await
+                .unwrap();
+        assert_eq!(scores, ["EUW1_0", "EUW1_1"]);
+
+        // Once the retention window has passed, everything else goes too
+        let later = now + 31 * 86400;
+        assert_eq!(repo.prune_history(&policy, later).await.unwrap(), 5);
+
+        // Processed matches are never pruned, they stop duplicate alerts
+        let processed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM processed_matches")
+            .fetch_one(&repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(processed, 4);
+    }
+
+    #[tokio::test]
+    async fn processed_matches_track_dispatch() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let player = repo
+            .get_or_create_player("puuid", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+
+        let dispatched = || repo.get_match_dispatched(player.id, "EUW1_1");
+        assert_eq!(dispatched().await.unwrap(), None);
+
+        let old_rank = RankInfo {
+            tier: "GOLD".to_string(),
+            rank: "II".to_string(),
+            lp: 80,
+        };
+        repo.mark_match_processed(player.id, "EUW1_1", Some(&old_rank))
+            .await
+            .unwrap();
+        assert_eq!(dispatched().await.unwrap(), Some(false));
+        let interrupted = repo.get_undispatched_matches().await.unwrap();
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!(
+            (interrupted[0].player_id, interrupted[0].match_id.as_str()),
+            (player.id, "EUW1_1")
+        );
+        assert_eq!(interrupted[0].old_rank_info().map(|rank| rank.lp), Some(80));
+        repo.mark_match_dispatched(player.id, "EUW1_1", now)
+            .await
+            .unwrap();
+        assert_eq!(dispatched().await.unwrap(), Some(true));
+        assert!(repo.get_undispatched_matches().await.unwrap().is_empty());
+        // Processing again after a restart keeps the marker as is
+        repo.mark_match_processed(player.id, "EUW1_1", None)
+            .await
+            .unwrap();
+        assert_eq!(dispatched().await.unwrap(), Some(true));
+
+        assert!(!repo.was_alert_sent(player.id, 1, "EUW1_1").await.unwrap());
+        repo.record_alert(player.id, 1, "EUW1_1").await.unwrap();
+        assert!(repo.was_alert_sent(player.id, 1, "EUW1_1").await.unwrap());
+        assert!(!repo.was_alert_sent(player.id, 2, "EUW1_1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn buffered_alerts_expire() {
+        let repo = test_repository().await;
+        let player = repo
+            .get_or_create_player("puuid", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+
+        let alert =
+            |guild_id: u64, match_id: &str, image: Option<&[u8]>, queued_at| BufferedAlert {
+                player_id: player.id,
+                guild_id: guild_id.into(),
+                match_id: match_id.into(),
+                channel_id: (guild_id * 10).into(),
+                image: image.map(<[u8]>::to_vec),
+                embed: image.is_none().then(|| "{}".to_string()),
+                content: Some("GG".into()),
+                live_channel_id: None,
+                live_message_id: Some(99.into()),
+                queued_at,
+            };
+        repo.buffer_alert(&alert(1, "EUW1_1", Some(b"old"), 100))
+            .await
+            .unwrap();
+        repo.buffer_alert(&alert(2, "EUW1_2", None, 200))
+            .await
+            .unwrap();
+        let buffered = repo.get_buffered_alerts().await.unwrap();
+        assert_eq!(buffered.len(), 2);
+        assert_eq!(buffered[0].image.as_deref(), Some(&b"old"[..]));
+        assert_eq!(buffered[1].embed.as_deref(), Some("{}"));
+        assert_eq!(buffered[1].content.as_deref(), Some("GG"));
+        assert_eq!(buffered[1].live_message_id.map(|id| id.get()), Some(99));
+
+        assert_eq!(repo.drop_stale_buffered_alerts(150).await.unwrap(), 1);
+        repo.remove_buffered_alert(player.id, 2, "EUW1_2")
+            .await
+            .unwrap();
+        assert!(repo.get_buffered_alerts().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn weekly_mvp_needs_enough_games() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let regular = repo
+            .get_or_create_player("a", "Regular", "EUW", "euw1")
+            .await
+            .unwrap();
+        let casual = repo
+            .get_or_create_player("b", "Casual", "EUW", "euw1")
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, regular.id, 10, now)
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, casual.id, 10, now)
+            .await
+            .unwrap();
+
+        let week = 1_716_768_000;
+        for i in 0..5 {
+            repo.record_game_score(regular.id, &format!("EUW1_{i}"), 8.0, week + i)
+                .await
+                .unwrap();
+        }
+        // Better but too few games, and one played the week before
+        for i in 5..9 {
+            repo.record_game_score(casual.id, &format!("EUW1_{i}"), 15.0, week + i)
+                .await
+                .unwrap();
+        }
+        repo.record_game_score(casual.id, "EUW1_9", 15.0, week - 1)
+            .await
+            .unwrap();
+
+        let mvp = repo
+            .get_weekly_mvp(1, week, week + 7 * 86400, 5)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((mvp.player_id, mvp.games), (regular.id, 5));
+        assert!(
+            repo.get_weekly_mvp(2, week, week + 7 * 86400, 5)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        repo.record_weekly_recap(1, week, Some(&mvp)).await.unwrap();
+        let guild = repo.get_guild(1).await.unwrap().unwrap();
+        assert_eq!(guild.last_recap_week, Some(week));
+        let history = repo.get_mvp_history(1, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].riot_id(), "Regular#EUW");
+    }
+
+    #[tokio::test]
+    async fn privacy_alias_replaces_the_riot_id() {
+        let repo = test_repository().await;
+        let player = repo
+            .get_or_create_player("puuid", "Streamer", "EUW", "euw1")
+            .await
+            .unwrap();
+        assert_eq!(player.display_name(), "Streamer#EUW");
+
+        repo.set_player_privacy_alias(player.id, Some("Mystery"))
+            .await
+            .unwrap();
+        let hidden = repo
+            .get_player_by_riot_id("Streamer", "EUW")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(hidden.is_hidden());
+        assert_eq!(hidden.display_name(), "Mystery");
+
+        repo.set_player_privacy_alias(player.id, None)
+            .await
+            .unwrap();
+        let shown = repo
+            .get_player_by_riot_id("Streamer", "EUW")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(shown.display_name(), "Streamer#EUW");
+    }
+
+    #[tokio::test]
+    async fn only_the_alert_channel_is_cleared() {
+        let repo = test_repository().await;
+        repo.set_guild_alert_channel(1, 10).await.unwrap();
+
+        assert!(!repo.clear_guild_alert_channel(1, 11).await.unwrap());
+        assert!(repo.clear_guild_alert_channel(1, 10).await.unwrap());
+
+        let guild = repo.get_guild(1).await.unwrap().unwrap();
+        assert_eq!(guild.alert_channel_id, None);
+        assert!(!repo.clear_guild_alert_channel(1, 10).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn guild_purge_keeps_accounts_other_guilds_track() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let shared = repo
+            .get_or_create_player("shared", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+        let own = repo
+            .get_or_create_player("own", "Bar", "EUW", "euw1")
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, shared.id, 10, now)
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, own.id, 10, now).await.unwrap();
+        repo.add_player_to_guild(2, shared.id, 20, now)
+            .await
+            .unwrap();
+        assert!(repo.set_quiet_user(2, shared.id, 20, true).await.unwrap());
+        repo.set_feature_flag("digests", 1, Some(false))
+            .await
+            .unwrap();
+
+        assert_eq!(repo.purge_user_data(20).await.unwrap(), 1);
+        assert_eq!(repo.purge_guild_data(1).await.unwrap(), 1);
+
+        assert!(repo.get_guild(1).await.unwrap().is_none());
+        assert!(repo.get_feature_flags().await.unwrap().is_empty());
+        assert!(
+            repo.get_player_by_riot_id("Bar", "EUW")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(repo.get_guild_players(2).await.unwrap().len(), 1);
+        let added_by: i64 = sqlx::query_scalar("SELECT added_by FROM guild_players")
+            .fetch_one(&repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(added_by, 0);
+        assert!(repo.get_quiet_users(shared.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn admin_notes_stay_in_their_guild() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let player = repo
+            .get_or_create_player("puuid", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, player.id, 10, now)
+            .await
+            .unwrap();
+        repo.add_player_to_guild(2, player.id, 10, now)
+            .await
+            .unwrap();
+
+        assert!(
+            repo.set_admin_note(1, player.id, Some("smurf of Bar"))
+                .await
+                .unwrap()
+        );
+        assert!(!repo.set_admin_note(3, player.id, Some("x")).await.unwrap());
+        let note = async |guild_id| {
+            repo.get_guild_players_page(guild_id, 0, 10).await.unwrap()[0]
+                .admin_note
+                .clone()
+        };
+        assert_eq!(note(1).await.as_deref(), Some("smurf of Bar"));
+        assert_eq!(note(2).await, None);
+
+        // Tracking again starts without the old note
+        repo.remove_player_from_guild(1, player.id, now)
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, player.id, 10, now)
+            .await
+            .unwrap();
+        assert_eq!(note(1).await, None);
+    }
+
+    #[tokio::test]
+    async fn only_the_tracker_quiets_an_account() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let player = repo
+            .get_or_create_player("puuid", "Foo", "EUW", "euw1")
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, player.id, 10, now)
+            .await
+            .unwrap();
+
+        assert!(!repo.set_quiet_user(1, player.id, 11, true).await.unwrap());
+        assert!(repo.set_quiet_user(1, player.id, 10, true).await.unwrap());
+        let quiet = repo.get_quiet_users(player.id).await.unwrap();
+        assert_eq!(quiet.len(), 1);
+        assert_eq!((quiet[0].0.get(), quiet[0].1.get()), (1, 10));
+        assert_eq!(repo.get_quiet_members().await.unwrap().len(), 1);
+        assert!(repo.is_quiet_member(1, 10).await.unwrap());
+
+        repo.set_quiet_user(1, player.id, 10, false).await.unwrap();
+        assert!(repo.get_quiet_users(player.id).await.unwrap().is_empty());
+        assert!(!repo.is_quiet_member(1, 10).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn recaps_fall_back_to_the_alert_channel() {
+        let repo = test_repository().await;
+        repo.set_guild_recap_channel(1, Some(20)).await.unwrap();
+        repo.get_or_create_guild(2).await.unwrap();
+
+        let guilds = repo.get_guilds_with_recap_channel().await.unwrap();
+        assert_eq!(guilds.len(), 1);
+        assert_eq!(guilds[0].recap_channel().map(|id| id.get()), Some(20));
+
+        repo.set_guild_alert_channel(1, 10).await.unwrap();
+        assert!(repo.clear_guild_recap_channel(1, 20).await.unwrap());
+        let guild = repo.get_guild(1).await.unwrap().unwrap();
+        assert_eq!(guild.recap_channel().map(|id| id.get()), Some(10));
+    }
+
+    #[tokio::test]
+    async fn announcement_opt_out_is_stored() {
+        let repo = test_repository().await;
+        repo.set_guild_alert_channel(1, 10).await.unwrap();
+        repo.set_guild_alert_channel(2, 20).await.unwrap();
+        repo.get_or_create_guild(3).await.unwrap();
+        repo.set_guild_announcements(2, false).await.unwrap();
+
+        let guilds = repo.get_guilds_with_alert_channel().await.unwrap();
+        assert_eq!(
+            guilds
+                .iter()
+                .map(|g| (g.id.get(), g.announcements))
+                .collect::<Vec<_>>(),
+            [(1, true), (2, false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn track_limit_counts_current_accounts_only() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        for (i, puuid) in ["a", "b", "c"].into_iter().enumerate() {
+            let player = repo
+                .get_or_create_player(puuid, puuid, "EUW", "euw1")
+                .await
+                .unwrap();
+            repo.add_player_to_guild(1, player.id, 99, now)
+                .await
+                .unwrap();
+            if i == 2 {
+                repo.remove_player_from_guild(1, player.id, now)
+                    .await
+                    .unwrap();
+            }
+        }
+        assert_eq!(repo.count_guild_players(1).await.unwrap(), 2);
+        assert_eq!(repo.count_guild_players(2).await.unwrap(), 0);
+
+        repo.set_guild_track_limit(1, Some(5)).await.unwrap();
+        let guild = repo.get_guild(1).await.unwrap().unwrap();
+        assert_eq!(guild.track_limit(Some(50)), Some(5));
+        // The instance limit wins over a higher guild one
+        assert_eq!(guild.track_limit(Some(3)), Some(3));
+        assert_eq!(guild.track_limit(None), Some(5));
+
+        repo.set_guild_track_limit(1, None).await.unwrap();
+        let guild = repo.get_guild(1).await.unwrap().unwrap();
+        assert_eq!(guild.track_limit(Some(50)), Some(50));
+    }
+
+    #[tokio::test]
+    async fn accounts_are_tracked_in_one_go() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let known = repo
+            .get_or_create_player("a", "Old", "EUW", "euw1")
+            .await
+            .unwrap();
+        repo.update_player_last_match(known.id, "EUW1_1")
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, known.id, 99, now)
+            .await
+            .unwrap();
+
+        let account = |puuid: &str, last_match_id: Option<&str>| ResolvedAccount {
+            puuid: puuid.into(),
+            game_name: format!("Name {puuid}"),
+            tag_line: "EUW".into(),
+            region: "euw1".into(),
+            profile_icon_id: 7,
+            last_match_id: last_match_id.map(Into::into),
+        };
+        let tracked = repo
+            .track_accounts(
+                1,
+                42,
+                &[account("a", Some("EUW1_9")), account("b", None)],
+                1_000,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            tracked
+                .iter()
+                .map(|(player, added)| (player.puuid.as_str(), *added))
+                .collect::<Vec<_>>(),
+            [("a", false), ("b", true)]
+        );
+        let players = repo.get_guild_players(1).await.unwrap();
+        assert_eq!(players.len(), 2);
+        // A known account keeps its progress, the renamed one is picked up
+        assert_eq!(players[0].game_name, "Name a");
+        assert_eq!(players[0].last_match_id.as_deref(), Some("EUW1_1"));
+        assert_eq!(players[1].profile_icon_id, Some(7));
+        assert_eq!(players[1].no_matches_since, Some(1_000));
+    }
+
+    #[tokio::test]
+    async fn recap_games_cover_the_period_and_current_players() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let result = |match_id: &str, played_at| MatchResult {
+            match_id: match_id.into(),
+            queue_id: 420,
+            win: true,
+            kills: 5,
+            deaths: 2,
+            assists: 7,
+            lp_delta: Some(21),
+            played_at,
+        };
+        let tracked = repo
+            .get_or_create_player("a", "Tracked", "EUW", "euw1")
+            .await
+            .unwrap();
+        let untracked = repo
+            .get_or_create_player("b", "Gone", "EUW", "euw1")
+            .await
+            .unwrap();
+        for player in [&tracked, &untracked] {
+            repo.add_player_to_guild(1, player.id, 99, now)
+                .await
+                .unwrap();
+            repo.record_match_result(player.id, &result("EUW1_1", 100))
+                .await
+                .unwrap();
+        }
+        repo.remove_player_from_guild(1, untracked.id, now)
+            .await
+            .unwrap();
+        repo.record_match_result(tracked.id, &result("EUW1_2", 200))
+            .await
+            .unwrap();
+        // Recorded twice by a resumed alert
+        repo.record_match_result(tracked.id, &result("EUW1_1", 100))
+            .await
+            .unwrap();
+
+        let games = repo.get_recap_games(1, 0, 200).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].display_name(), "Tracked#EUW");
+        assert_eq!(games[0].lp_delta, Some(21));
+        assert_eq!(repo.get_recap_games(1, 0, 201).await.unwrap().len(), 2);
+
+        repo.set_guild_recap_schedule(1, Some("daily"), 9)
+            .await
+            .unwrap();
+        repo.record_results_recap(1, 200).await.unwrap();
+        let guild = repo.get_guild(1).await.unwrap().unwrap();
+        assert_eq!(
+            (guild.recap_frequency.as_deref(), guild.recap_hour),
+            (Some("daily"), 9)
+        );
+        assert_eq!(guild.last_results_recap, Some(200));
+    }
+
+    #[tokio::test]
+    async fn stats_find_the_guild_player_and_latest_games() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let player = repo
+            .get_or_create_player("a", "Faker", "KR1", "kr")
+            .await
+            .unwrap();
+        repo.add_player_to_guild(1, player.id, 99, now)
+            .await
+            .unwrap();
+        for (match_id, played_at) in [("KR_1", 100), ("KR_3", 300), ("KR_2", 200)] {
+            let result = MatchResult {
+                match_id: match_id.into(),
+                queue_id: 420,
+                win: played_at != 200,
+                kills: 1,
+                deaths: 1,
+                assists: 1,
+                lp_delta: None,
+                played_at,
+            };
+            repo.record_match_result(player.id, &result).await.unwrap();
+        }
+
+        let found = repo
+            .get_guild_player_by_riot_id(1, "faker", "kr1")
+            .await
+            .unwrap();
+        assert_eq!(found.map(|p| p.id), Some(player.id));
+        assert!(
+            repo.get_guild_player_by_riot_id(2, "Faker", "KR1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let recent = repo.get_recent_match_results(player.id, 2).await.unwrap();
+        let ids: Vec<_> = recent.iter().map(|r| r.match_id.as_str()).collect();
+        assert_eq!(ids, ["KR_3", "KR_2"]);
+        assert!(!recent[1].win);
+    }
+
+    #[tokio::test]
+    async fn guild_players_are_read_a_page_at_a_time() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let mut ids = Vec::new();
+        for (puuid, name) in [("a", "Alpha"), ("c", "Charlie"), ("b", "Bravo")] {
+            let player = repo
+                .get_or_create_player(puuid, name, "EUW", "euw1")
+                .await
+                .unwrap();
+            repo.add_player_to_guild(1, player.id, 99, now)
+                .await
+                .unwrap();
+            ids.push(player.id);
+        }
+        repo.set_admin_note(1, ids[0], Some("main")).await.unwrap();
+
+        assert_eq!(repo.count_guild_players(1).await.unwrap(), 3);
+        let first = repo.get_guild_players_page(1, 0, 2).await.unwrap();
+        let names: Vec<_> = first.iter().map(|p| p.player.game_name.as_str()).collect();
+        assert_eq!(names, ["Alpha", "Bravo"]);
+        assert_eq!(first[0].admin_note.as_deref(), Some("main"));
+        let last = repo.get_guild_players_page(1, 2, 2).await.unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].player.game_name, "Charlie");
+        assert!(
+            repo.get_guild_players_page(1, 3, 2)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn ladder_pages_are_ordered_by_rank() {
+        let repo = test_repository().await;
+        let now = SystemClock.unix_now();
+        let rank = |tier: &str, division: &str, lp| RankInfo {
+            tier: tier.to_string(),
+            rank: division.to_string(),
+            lp,
+        };
+        for (puuid, solo) in [
+            ("a", Some(rank("GOLD", "I", 50))),
+            ("b", Some(rank("MASTER", "I", 20))),
+            ("c", None),
+            ("d", Some(rank("GRANDMASTER", "I", 20))),
+            ("e", Some(rank("GOLD", "II", 99))),
+        ] {
+            let player = repo
+                .get_or_create_player(puuid, puuid, "EUW", "euw1")
+                .await
+                .unwrap();
+            repo.add_player_to_guild(1, player.id, 99, now)
+                .await
+                .unwrap();
+            repo.update_player_rank(player.id, solo.as_ref(), None)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            repo.count_guild_ranked_players(1, "RANKED_SOLO_5x5")
+                .await
+                .unwrap(),
+            4
+        );
+        assert_eq!(
+            repo.count_guild_ranked_players(1, "RANKED_FLEX_SR")
+                .await
+                .unwrap(),
+            0
+        );
+        let first = repo
+            .get_guild_ladder_page(1, "RANKED_SOLO_5x5", 0, 3)
+            .await
+            .unwrap();
+        let names: Vec<_> = first.iter().map(|p| p.puuid.as_str()).collect();
+        assert_eq!(names, ["d", "b", "a"]);
+        let last = repo
+            .get_guild_ladder_page(1, "RANKED_SOLO_5x5", 3, 3)
+            .await
+            .unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].puuid, "e");
+        assert!(
+            repo.get_guild_ladder_page(1, "RANKED_TFT", 0, 3)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn queue_alerts_are_replaced_as_a_whole() {
+        let repo = test_repository().await;
+        assert!(repo.get_muted_queues(1).await.unwrap().is_empty());
+
+        repo.set_queue_alerts_bulk(1, &["aram", "normal_blind"])
+            .await
+            .unwrap();
+        repo.set_queue_alerts_bulk(2, &["aram"]).await.unwrap();
+        let mut muted = repo.get_muted_queues(1).await.unwrap();
+        muted.sort();
+        assert_eq!(muted, ["aram", "normal_blind"]);
+
+        repo.set_queue_alerts_bulk(1, &["quickplay"]).await.unwrap();
+        assert_eq!(repo.get_muted_queues(1).await.unwrap(), ["quickplay"]);
+        assert_eq!(repo.get_muted_queues(2).await.unwrap(), ["aram"]);
+    }
+
+    #[tokio::test]
+    async fn snowflakes_above_i64_max_round_trip() {
+        let repo = test_repository().await;
+        let guild_id = u64::MAX;
+        let channel_id = i64::MAX as u64 + 1;
+
+        repo.set_guild_alert_channel(guild_id, channel_id)
+            .await
+            .unwrap();
+
+        let guild = repo.get_guild(guild_id).await.unwrap().unwrap();
+        assert_eq!(guild.id.get(), guild_id);
+        assert_eq!(guild.alert_channel_id.map(|id| id.get()), Some(channel_id));
     }
 }
@@ -0,0 +1,32 @@
+/// Discord snowflake (guild, channel or user ID) as stored in SQLite.
+///
+/// SQLite integers are signed, so IDs are kept as the two's complement `i64`
+/// with the same bits as the `u64` snowflake. Always convert through this type
+/// rather than with `as` casts so reads and writes agree for IDs above `i64::MAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+pub struct SnowflakeColumn(i64);
+
+impl SnowflakeColumn {
+    pub fn get(self) -> u64 {
+        u64::from_ne_bytes(self.0.to_ne_bytes())
+    }
+}
+
+impl From<u64> for SnowflakeColumn {
+    fn from(id: u64) -> Self {
+        Self(i64::from_ne_bytes(id.to_ne_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnowflakeColumn;
+
+    #[test]
+    fn round_trips_full_u64_range() {
+        for id in [0, 1, i64::MAX as u64, i64::MAX as u64 + 1, u64::MAX] {
+            assert_eq!(SnowflakeColumn::from(id).get(), id);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::Guild;
+use crate::error::AppError;
+use crate::riot::InfoDto;
+
+/// Column of the stats bar at the bottom of a match alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatField {
+    Cs,
+    Damage,
+    Vision,
+    Role,
+    Gold,
+}
+
+impl StatField {
+    pub const ALL: [Self; 5] = [Self::Cs, Self::Damage, Self::Vision, Self::Role, Self::Gold];
+
+    /// Layout used when a guild never configured one
+    pub const DEFAULT: [Self; 4] = [Self::Cs, Self::Damage, Self::Vision, Self::Role];
+
+    /// Layout used for ARAM when none of the configured fields apply
+    const ARAM_DEFAULT: [Self; 2] = [Self::Cs, Self::Damage];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Cs => "cs",
+            Self::Damage => "damage",
+            Self::Vision => "vision",
+            Self::Role => "role",
+            Self::Gold => "gold",
+        }
+    }
+
+    /// Vision and role carry no meaning on the single-lane ARAM map
+    fn applies_to_aram(&self) -> bool {
+        matches!(self, Self::Cs | Self::Damage | Self::Gold)
+    }
+}
+
+impl fmt::Display for StatField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+impl FromStr for StatField {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.key().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| AppError::Config(format!("Unknown alert field: {s}")))
+    }
+}
+
+/// Parse a comma-separated field list such as `cs, damage, role`, keeping its order
+pub fn parse_fields(input: &str) -> Result<Vec<StatField>, AppError> {
+    let mut fields = Vec::new();
+    for field in input.split(',').filter(|s| !s.trim().is_empty()) {
+        let field: StatField = field.parse()?;
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+
+    if fields.is_empty() {
+        return Err(AppError::Config(
+            "At least one alert field is required".into(),
+        ));
+    }
+    Ok(fields)
+}
+
+/// Fields configured by a guild, the default layout if unset or unreadable
+pub fn guild_fields(guild: &Guild) -> Vec<StatField> {
+    guild
+        .alert_fields
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<Vec<StatField>>(json).ok())
+        .filter(|fields| !fields.is_empty())
+        .unwrap_or_else(|| StatField::DEFAULT.to_vec())
+}
+
+/// Everything a guild can customize about how an alert is rendered.
+/// Alerts are rendered once per distinct style among the tracking guilds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlertStyle {
+    /// Team objectives line on ranked games
+    pub show_objectives: bool,
+    /// Games-to-next-division estimate on ranked games
+    pub show_forecast: bool,
+    /// Stat columns, left to right
+    pub fields: Vec<StatField>,
+}
+
+impl Default for AlertStyle {
+    fn default() -> Self {
+        Self {
+            show_objectives: true,
+            show_forecast: false,
+            fields: StatField::DEFAULT.to_vec(),
+        }
+    }
+}
+
+impl AlertStyle {
+    /// Style a guild wants for this match. Settings that don't affect the match
+    /// are normalized away so guilds end up sharing the same rendered image.
+    pub fn for_guild(guild: &Guild, match_info: &InfoDto) -> Self {
+        let fields = guild_fields(guild);
+
+        Self {
+            show_objectives: guild.alert_details && match_info.is_ranked(),
+            show_forecast: guild.alert_forecast && match_info.is_ranked(),
+            fields: Self::fields_for_match(fields, match_info),
+        }
+    }
+
+    fn fields_for_match(fields: Vec<StatField>, match_info: &InfoDto) -> Vec<StatField> {
+        // Generic layout: result, KDA and champion only, as the stats of
+        // rotating modes vary too much
+        if match_info.is_rotating() {
+            return Vec::new();
+        }
+        if match_info.queue_id != 450 {
+            return fields;
+        }
+
+        let aram: Vec<_> = fields
+            .into_iter()
+            .filter(StatField::applies_to_aram)
+            .collect();
+        if aram.is_empty() {
+            StatField::ARAM_DEFAULT.to_vec()
+        } else {
+            aram
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StatField, parse_fields};
+
+    #[test]
+    fn parse_fields_keeps_order_and_drops_duplicates() {
+        let fields = parse_fields("Role, cs,role ,gold").unwrap();
+        assert_eq!(fields, [StatField::Role, StatField::Cs, StatField::Gold]);
+
+        assert!(parse_fields("cs, mana").is_err());
+        assert!(parse_fields(" , ").is_err());
+    }
+
+    #[test]
+    fn fields_round_trip_as_json() {
+        let json = serde_json::to_string(&StatField::DEFAULT).unwrap();
+        assert_eq!(json, r#"["cs","damage","vision","role"]"#);
+        let fields: Vec<StatField> = serde_json::from_str(&json).unwrap();
+        assert_eq!(fields, StatField::DEFAULT);
+    }
+}
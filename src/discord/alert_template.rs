@@ -0,0 +1,188 @@
+use poise::ChoiceParameter;
+
+use crate::error::AppError;
+use crate::riot::{GameEnding, InfoDto, ParticipantDto, Queue};
+
+/// Longest template a guild can set
+const MAX_TEMPLATE_LEN: usize = 200;
+
+/// Placeholders a template can use
+pub const PLACEHOLDERS: [&str; 5] = ["player", "champion", "kda", "lp", "queue"];
+
+/// Queues a guild can word its alerts for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ChoiceParameter)]
+pub enum TemplateQueue {
+    #[name = "Ranked Solo/Duo"]
+    RankedSolo,
+    #[name = "Ranked Flex"]
+    RankedFlex,
+    #[name = "Normal games"]
+    Normal,
+    #[name = "ARAM"]
+    Aram,
+    #[name = "Clash"]
+    Clash,
+    #[name = "Rotating modes"]
+    Rotating,
+}
+
+impl TemplateQueue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RankedSolo => "ranked_solo",
+            Self::RankedFlex => "ranked_flex",
+            Self::Normal => "normal",
+            Self::Aram => "aram",
+            Self::Clash => "clash",
+            Self::Rotating => "rotating",
+        }
+    }
+
+    pub fn for_match(info: &InfoDto) -> Option<Self> {
+        Some(match Queue::from_id(info.queue_id)? {
+            Queue::RankedSolo => Self::RankedSolo,
+            Queue::RankedFlex => Self::RankedFlex,
+            Queue::NormalDraft | Queue::NormalBlind | Queue::Quickplay => Self::Normal,
+            Queue::Aram => Self::Aram,
+            Queue::Clash => Self::Clash,
+            Queue::Rotating(_) => Self::Rotating,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ChoiceParameter)]
+pub enum Outcome {
+    Victory,
+    Defeat,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Victory => "victory",
+            Self::Defeat => "defeat",
+        }
+    }
+
+    /// `None` for remakes, which keep the plain alert
+    pub fn for_game(info: &InfoDto, participant: &ParticipantDto) -> Option<Self> {
+        match (info.ending(), participant.win) {
+            (GameEnding::Remake, _) => None,
+            (_, true) => Some(Self::Victory),
+            (_, false) => Some(Self::Defeat),
+        }
+    }
+}
+
+/// What the placeholders of a template stand for in one game
+#[derive(Debug)]
+pub struct TemplateValues<'a> {
+    pub player: &'a str,
+    pub champion: &'a str,
+    /// e.g. "10/2/8"
+    pub kda: String,
+    /// e.g. "+18 LP", empty outside ranked
+    pub lp: String,
+    pub queue: &'a str,
+}
+
+impl TemplateValues<'_> {
+    fn get(&self, placeholder: &str) -> &str {
+        match placeholder {
+            "player" => self.player,
+            "champion" => self.champion,
+            "kda" => &self.kda,
+            "lp" => &self.lp,
+            "queue" => self.queue,
+            _ => "",
+        }
+    }
+}
+
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+fn parse(template: &str) -> Result<Vec<Part<'_>>, AppError> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err(AppError::Config("Unmatched `}` in the template".into()));
+        }
+        parts.push(Part::Text(&rest[..start]));
+        let after = &rest[start + 1..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| AppError::Config("Unclosed `{` in the template".into()))?;
+        let name = &after[..end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(AppError::Config(format!(
+                "Unknown placeholder `{{{name}}}`, use {}",
+                PLACEHOLDERS.map(|p| format!("`{{{p}}}`")).join(", ")
+            )));
+        }
+        parts.push(Part::Placeholder(name));
+        rest = &after[end + 1..];
+    }
+    parts.push(Part::Text(rest));
+    Ok(parts)
+}
+
+/// Check a template before storing it
+pub fn validate(template: &str) -> Result<(), AppError> {
+    if template.trim().is_empty() {
+        return Err(AppError::Config("The template is empty".into()));
+    }
+    if template.chars().count() > MAX_TEMPLATE_LEN {
+        return Err(AppError::Config(format!(
+            "Templates are limited to {MAX_TEMPLATE_LEN} characters"
+        )));
+    }
+    parse(template).map(|_| ())
+}
+
+/// The template with its placeholders filled in. Stored templates were
+/// validated, one that no longer parses is shown as is.
+pub fn render(template: &str, values: &TemplateValues<'_>) -> String {
+    let Ok(parts) = parse(template) else {
+        return template.to_string();
+    };
+    parts
+        .into_iter()
+        .map(|part| match part {
+            Part::Text(text) => text,
+            Part::Placeholder(name) => values.get(name),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TemplateValues, render, validate};
+
+    #[test]
+    fn placeholders_are_checked_and_filled() {
+        let values = TemplateValues {
+            player: "Faker#KR1",
+            champion: "Ahri",
+            kda: "10/2/8".into(),
+            lp: "+18 LP".into(),
+            queue: "Ranked Solo/Duo",
+        };
+        let template = "GG {player}! {champion} {kda} ({lp})";
+        validate(template).unwrap();
+        assert_eq!(
+            render(template, &values),
+            "GG Faker#KR1! Ahri 10/2/8 (+18 LP)"
+        );
+        assert_eq!(render("{queue}", &values), "Ranked Solo/Duo");
+
+        assert!(validate("GG {name}").is_err());
+        assert!(validate("GG {player").is_err());
+        assert!(validate("GG player}").is_err());
+        assert!(validate("  ").is_err());
+        assert!(validate(&"a".repeat(201)).is_err());
+    }
+}
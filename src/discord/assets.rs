@@ -0,0 +1,124 @@
+//! Where the images drawn on alerts come from. Data Dragon is the official
+//! CDN but lags behind on new champions and icons, Community Dragon mirrors
+//! the game files and usually has them first.
+
+const DDRAGON_URL: &str = "https://ddragon.leagueoflegends.com/cdn";
+const CDRAGON_URL: &str = "https://cdn.communitydragon.org/latest";
+
+/// An image drawn on alerts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Asset<'a> {
+    Champion(&'a str),
+    ProfileIcon(i32),
+    Item(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetProvider {
+    DataDragon,
+    CommunityDragon,
+}
+
+impl AssetProvider {
+    /// URL of the asset on this CDN, `None` if it doesn't serve that kind
+    pub fn url(self, version: &str, asset: Asset<'_>) -> Option<String> {
+        match (self, asset) {
+            (Self::DataDragon, Asset::Champion(name)) => {
+                Some(format!("{DDRAGON_URL}/{version}/img/champion/{name}.png"))
+            }
+            (Self::DataDragon, Asset::ProfileIcon(id)) => {
+                Some(format!("{DDRAGON_URL}/{version}/img/profileicon/{id}.png"))
+            }
+            (Self::DataDragon, Asset::Item(id)) => {
+                Some(format!("{DDRAGON_URL}/{version}/img/item/{id}.png"))
+            }
+            // Always the latest patch, which is what a missing asset needs
+            (Self::CommunityDragon, Asset::Champion(name)) => {
+                Some(format!("{CDRAGON_URL}/champion/{name}/square"))
+            }
+            (Self::CommunityDragon, Asset::ProfileIcon(id)) => {
+                Some(format!("{CDRAGON_URL}/profile-icon/{id}"))
+            }
+            // Item icons are only listed by file name, which the match doesn't give
+            (Self::CommunityDragon, Asset::Item(_)) => None,
+        }
+    }
+}
+
+/// Tries each CDN in turn for an asset
+#[derive(Debug, Clone)]
+pub struct AssetResolver {
+    ddragon_version: String,
+    providers: Vec<AssetProvider>,
+}
+
+impl AssetResolver {
+    pub fn new(ddragon_version: String) -> Self {
+        Self {
+            ddragon_version,
+            providers: vec![AssetProvider::DataDragon, AssetProvider::CommunityDragon],
+        }
+    }
+
+    /// Data Dragon item list, for item names
+    pub fn item_data_url(&self) -> String {
+        format!(
+            "{DDRAGON_URL}/{}/data/en_US/item.json",
+            self.ddragon_version
+        )
+    }
+
+    /// Candidate URLs for the asset, in the order they should be tried
+    pub fn urls(&self, asset: Asset<'_>) -> Vec<String> {
+        self.providers
+            .iter()
+            .filter_map(|provider| provider.url(&self.ddragon_version, asset))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Asset, AssetProvider, AssetResolver};
+
+    #[test]
+    fn data_dragon_urls_are_versioned() {
+        let url = |asset| AssetProvider::DataDragon.url("14.1.1", asset).unwrap();
+        assert_eq!(
+            url(Asset::Champion("Ahri")),
+            "https://ddragon.leagueoflegends.com/cdn/14.1.1/img/champion/Ahri.png"
+        );
+        assert_eq!(
+            url(Asset::ProfileIcon(29)),
+            "https://ddragon.leagueoflegends.com/cdn/14.1.1/img/profileicon/29.png"
+        );
+        assert_eq!(
+            url(Asset::Item(3031)),
+            "https://ddragon.leagueoflegends.com/cdn/14.1.1/img/item/3031.png"
+        );
+    }
+
+    #[test]
+    fn community_dragon_serves_the_latest_patch() {
+        let url = |asset| AssetProvider::CommunityDragon.url("14.1.1", asset);
+        assert_eq!(
+            url(Asset::Champion("Ahri")).as_deref(),
+            Some("https://cdn.communitydragon.org/latest/champion/Ahri/square")
+        );
+        assert_eq!(
+            url(Asset::ProfileIcon(29)).as_deref(),
+            Some("https://cdn.communitydragon.org/latest/profile-icon/29")
+        );
+        assert_eq!(url(Asset::Item(3031)), None);
+    }
+
+    #[test]
+    fn data_dragon_is_tried_first() {
+        let resolver = AssetResolver::new("14.1.1".into());
+        let urls = resolver.urls(Asset::Champion("Ahri"));
+        assert_eq!(urls.len(), 2);
+        assert!(urls[0].starts_with("https://ddragon"));
+        assert!(urls[1].starts_with("https://cdn.communitydragon"));
+        assert_eq!(resolver.urls(Asset::Item(3031)).len(), 1);
+    }
+}
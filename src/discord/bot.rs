@@ -2,18 +2,52 @@ use std::sync::Arc;
 
 use tracing::{error, info, warn};
 
+use crate::activity::ActivityFeed;
+use crate::clock::Clock;
+use crate::config::Config;
 use crate::db::Repository;
 use crate::error::AppError;
+use crate::poller::DeliveryLatency;
 use crate::riot::RiotClient;
 
 use super::commands;
+use super::emojis::EmojiStore;
+use super::events::EventBus;
+use super::features::FeatureGate;
+use super::gateway::GatewayMonitor;
 use super::image_gen::ImageGenerator;
+use super::prefixes::PrefixStore;
+use super::presence::OnlineMembers;
+
+/// Commands that never write, the only ones served in read-only mode
+const READ_COMMANDS: &[&str] = &[
+    "about",
+    "api_usage",
+    "check_permissions",
+    "list",
+    "list_admin",
+    "mvp_history",
+    "stats",
+    "leaderboard",
+    "preview_alert",
+];
 
 /// Shared data accessible in all commands
 pub struct Data {
     pub db: Repository,
     pub riot: RiotClient,
     pub image_gen: Arc<ImageGenerator>,
+    pub emojis: Arc<EmojiStore>,
+    pub prefixes: Arc<PrefixStore>,
+    pub features: Arc<FeatureGate>,
+    pub alert_latency: Arc<DeliveryLatency>,
+    pub gateway: Arc<GatewayMonitor>,
+    pub config: Arc<Config>,
+    pub clock: Arc<dyn Clock>,
+    pub events: Arc<EventBus>,
+    pub activity: ActivityFeed,
+    /// Quiet members' presence, when `QUIET_WHEN_ONLINE` is on
+    pub online: Option<Arc<OnlineMembers>>,
 }
 
 impl std::fmt::Debug for Data {
@@ -22,6 +56,16 @@ impl std::fmt::Debug for Data {
             .field("db", &self.db)
             .field("riot", &self.riot)
             .field("image_gen", &"<ImageGenerator>")
+            .field("emojis", &self.emojis)
+            .field("prefixes", &self.prefixes)
+            .field("features", &self.features)
+            .field("alert_latency", &self.alert_latency)
+            .field("gateway", &self.gateway)
+            .field("config", &"<Config>")
+            .field("clock", &self.clock)
+            .field("events", &self.events)
+            .field("activity", &self.activity)
+            .field("online", &self.online)
             .finish()
     }
 }
@@ -33,11 +77,57 @@ pub fn create_framework(data: Data) -> poise::Framework<Data, AppError> {
         .options(poise::FrameworkOptions {
             commands: vec![
                 commands::track(),
+                commands::track_multiple(),
                 commands::untrack(),
+                commands::untrack_all(),
+                commands::undo_untrack(),
                 commands::list(),
+                commands::set_privacy(),
+                commands::quiet_when_online(),
+                commands::set_admin_note(),
+                commands::purge_my_data(),
+                commands::purge_guild_data(),
+                commands::list_admin(),
+                commands::mvp_history(),
+                commands::stats(),
+                commands::leaderboard(),
                 commands::config(),
+                commands::preview_alert(),
+                commands::check_permissions(),
+                commands::ladder(),
+                commands::create_inhouse(),
+                commands::about(),
+                commands::api_usage(),
+                commands::feature(),
+                commands::broadcast(),
                 commands::dev_test_alert(),
             ],
+            // Message commands for guilds that set a prefix with /config prefix
+            prefix_options: poise::PrefixFrameworkOptions {
+                dynamic_prefix: Some(|ctx| {
+                    Box::pin(async move {
+                        Ok(ctx
+                            .guild_id
+                            .and_then(|guild_id| ctx.data.prefixes.get(guild_id.get())))
+                    })
+                }),
+                ..Default::default()
+            },
+            command_check: Some(|ctx| {
+                Box::pin(async move {
+                    let name = ctx.command().qualified_name.as_str();
+                    if ctx.data().config.read_only && !READ_COMMANDS.contains(&name) {
+                        return Err(AppError::ReadOnly);
+                    }
+                    Ok(true)
+                })
+            }),
+            event_handler: |ctx, event, _framework, data| {
+                Box::pin(async move {
+                    data.events.dispatch(ctx, event, data).await;
+                    Ok(())
+                })
+            },
             on_error: |error| {
                 Box::pin(async move {
                     handle_error(error).await;
@@ -110,6 +200,13 @@ async fn handle_error(error: poise::FrameworkError<'_, Data, AppError>) {
                     .await;
             }
         }
+        poise::FrameworkError::CommandCheckFailed {
+            error: Some(error),
+            ctx,
+            ..
+        } => {
+            let _ = ctx.say(error.to_string()).await;
+        }
         other => {
             error!(error = ?other, "🎮 ❌ Unhandled framework error");
         }
@@ -0,0 +1,104 @@
+use poise::BoxFuture;
+use poise::serenity_prelude::{self as serenity, CreateMessage};
+use tracing::{info, warn};
+
+use crate::activity::ActivityEvent;
+use crate::error::AppError;
+
+use super::bot::Data;
+use super::events::EventSubscriber;
+
+/// Unsets the alert channel of a guild as soon as it is deleted and tells the
+/// server, rather than every later alert failing on a missing channel. A
+/// deleted recap channel is unset too.
+#[derive(Debug)]
+pub struct AlertChannelWatch;
+
+impl AlertChannelWatch {
+    async fn channel_deleted(
+        ctx: &serenity::Context,
+        data: &Data,
+        channel: &serenity::GuildChannel,
+    ) -> Result<(), AppError> {
+        // The writable instance sees the same event, and shadow deployments
+        // stay off guild channels and owners' DMs
+        if data.config.read_only || data.config.shadow_mode {
+            return Ok(());
+        }
+        let guild_id = channel.guild_id;
+        // Recaps quietly go back to the alert channel
+        if data
+            .db
+            .clear_guild_recap_channel(guild_id.get(), channel.id.get())
+            .await?
+        {
+            info!(
+                guild_id = guild_id.get(),
+                channel_id = channel.id.get(),
+                "🎮 Recap channel deleted, recaps back to the alert channel"
+            );
+        }
+        if !data
+            .db
+            .clear_guild_alert_channel(guild_id.get(), channel.id.get())
+            .await?
+        {
+            return Ok(());
+        }
+        data.activity.publish(ActivityEvent::AlertChannelChanged {
+            guild_id: guild_id.get(),
+        });
+        info!(
+            guild_id = guild_id.get(),
+            channel_id = channel.id.get(),
+            "🎮 Alert channel deleted, setting cleared"
+        );
+
+        // The system channel when there is one, the owner's DMs otherwise
+        let guild = guild_id.to_partial_guild(&ctx.http).await?;
+        let message = CreateMessage::new().content(format!(
+            "⚠️ The alert channel **#{}** of **{}** was deleted, alerts are paused until an admin picks a new one with `/config channel`.",
+            channel.name, guild.name
+        ));
+        let sent = match guild.system_channel_id {
+            Some(system_channel) => system_channel.send_message(&ctx.http, message).await,
+            None => guild.owner_id.direct_message(&ctx.http, message).await,
+        };
+        if let Err(e) = sent {
+            warn!(
+                error = ?e,
+                guild_id = guild_id.get(),
+                owner_id = guild.owner_id.get(),
+                "🎮 ⚠️ Failed to tell the server its alert channel is gone"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl EventSubscriber for AlertChannelWatch {
+    fn name(&self) -> &'static str {
+        "alert_channel_watch"
+    }
+
+    fn events(&self) -> &'static [&'static str] {
+        &["channel_delete"]
+    }
+
+    fn handle<'a>(
+        &'a self,
+        ctx: &'a serenity::Context,
+        event: &'a serenity::FullEvent,
+        data: &'a Data,
+    ) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            match event {
+                serenity::FullEvent::ChannelDelete { channel, .. } => {
+                    Self::channel_deleted(ctx, data, channel).await
+                }
+                _ => Ok(()),
+            }
+        })
+    }
+}
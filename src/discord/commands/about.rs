@@ -0,0 +1,38 @@
+use poise::serenity_prelude as serenity;
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+
+/// Show the running version, plus its configuration for bot owners
+#[poise::command(slash_command, prefix_command, ephemeral)]
+pub async fn about(ctx: Context<'_>) -> Result<(), AppError> {
+    let mut embed = serenity::CreateEmbed::new()
+        .title("Tentrackule")
+        .color(0x0099ff)
+        .field("Version", env!("CARGO_PKG_VERSION"), true)
+        .field("Commit", env!("GIT_HASH"), true);
+
+    // Settings stay with the owners, guild members only get the version
+    if ctx.framework().options().owners.contains(&ctx.author().id) {
+        let features = if cfg!(feature = "public-api") {
+            "public-api"
+        } else {
+            "none"
+        };
+        let settings = ctx
+            .data()
+            .config
+            .summary()
+            .into_iter()
+            .map(|(name, value)| format!("**{name}**: {value}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        embed = embed
+            .field("Features", features, true)
+            .field("Configuration", settings, false);
+    }
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use poise::serenity_prelude as serenity;
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+
+/// [OWNER] Show current Riot API quota usage, alert latency and gateway health
+#[poise::command(slash_command, owners_only, ephemeral)]
+pub async fn api_usage(ctx: Context<'_>) -> Result<(), AppError> {
+    let riot = &ctx.data().riot;
+    let usage = riot.metrics().snapshot();
+    let limit = riot.rate_limit_per_second().get();
+    let saturation = usage.last_second as f64 / f64::from(limit) * 100.0;
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Riot API Usage")
+        .color(0x0099ff)
+        .field("Last minute", usage.last_minute.to_string(), true)
+        .field("Last 2 hours", usage.last_two_hours.to_string(), true)
+        .field("Since start", usage.total_requests.to_string(), true)
+        .field(
+            "429 responses",
+            format!(
+                "{} (1m) • {} (2h) • {} total",
+                usage.rate_limited_last_minute,
+                usage.rate_limited_last_two_hours,
+                usage.total_rate_limited
+            ),
+            false,
+        )
+        .field(
+            "League cache",
+            format!(
+                "{} hits • {} misses ({:.0}% hit rate)",
+                usage.cache_hits,
+                usage.cache_misses,
+                usage.cache_hit_rate() * 100.0
+            ),
+            false,
+        )
+        .field(
+            "Limiter saturation",
+            format!(
+                "{:.0}% ({}/{} req/s)",
+                saturation, usage.last_second, limit
+            ),
+            false,
+        );
+
+    let endpoints = usage
+        .endpoints
+        .iter()
+        .map(|e| {
+            format!(
+                "`{:<8}` {} (1m) • {} (2h) • {} total • avg {} ms • max {} ms",
+                e.family.as_str(),
+                e.last_minute,
+                e.last_two_hours,
+                e.total,
+                e.avg_latency.as_millis(),
+                e.max_latency.as_millis()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let embed = if endpoints.is_empty() {
+        embed
+    } else {
+        embed.field("Endpoints", endpoints, false)
+    };
+
+    // Game end to alert in the channel, mostly the poll interval
+    let latency = match ctx.data().alert_latency.snapshot() {
+        Some(latency) => format!(
+            "p50 {}s • p95 {}s (last {} alerts)",
+            latency.p50, latency.p95, latency.samples
+        ),
+        None => "No alert delivered yet".to_string(),
+    };
+    let embed = embed.field("Alert latency", latency, false);
+
+    let gateway = ctx.data().gateway.snapshot();
+    let embed = embed.field(
+        "Gateway",
+        format!(
+            "{} disconnects • {} resumes since start",
+            gateway.disconnects, gateway.resumes
+        ),
+        false,
+    );
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! Bounds of command arguments. `#[min]`, `#[max]` and `#[max_length]` only
+//! shape Discord's slash command form, prefix invocations skip them, so
+//! commands check their arguments again with these.
+
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+use crate::error::AppError;
+
+/// `value` when it is within `range`, a user-facing error naming the
+/// argument otherwise
+pub(super) fn in_range<T: PartialOrd + Display>(
+    name: &str,
+    value: T,
+    range: RangeInclusive<T>,
+) -> Result<T, AppError> {
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        Err(AppError::Config(format!(
+            "{name} must be between {} and {}",
+            range.start(),
+            range.end()
+        )))
+    }
+}
+
+/// `value` when it is at least `min`, a user-facing error naming the
+/// argument otherwise
+pub(super) fn at_least<T: PartialOrd + Display>(
+    name: &str,
+    value: T,
+    min: T,
+) -> Result<T, AppError> {
+    if value >= min {
+        Ok(value)
+    } else {
+        Err(AppError::Config(format!("{name} must be at least {min}")))
+    }
+}
+
+/// Fail with a user-facing error naming the argument when `text` has more
+/// than `max` characters
+pub(super) fn max_length(name: &str, text: &str, max: usize) -> Result<(), AppError> {
+    if text.chars().count() <= max {
+        Ok(())
+    } else {
+        Err(AppError::Config(format!(
+            "{name} must be at most {max} characters"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{at_least, in_range, max_length};
+
+    #[test]
+    fn values_outside_the_range_are_refused() {
+        assert_eq!(in_range("Team size", 5, 1..=5).unwrap(), 5);
+        let error = in_range("Team size", 0, 1..=5).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Configuration error: Team size must be between 1 and 5"
+        );
+        assert!(in_range("Hour", 24, 0..=23).is_err());
+        assert!(at_least("Track limit", 0, 1).is_err());
+
+        assert!(max_length("Title", "héhé", 4).is_ok());
+        assert!(max_length("Title", "héhé!", 4).is_err());
+    }
+}
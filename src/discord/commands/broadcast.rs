@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::{self as serenity, ChannelId, CreateMessage};
+use tracing::{info, instrument, warn};
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+
+use super::bounds::max_length;
+use super::confirm::confirm;
+
+/// Pause between two announcements, well under Discord's global rate limit
+const BROADCAST_PACE: Duration = Duration::from_millis(250);
+
+fn announcement(title: &str, message: &str) -> serenity::CreateEmbed {
+    serenity::CreateEmbed::new()
+        .title(format!("📣 {title}"))
+        .description(message)
+        .color(0x5865f2)
+        .footer(serenity::CreateEmbedFooter::new(
+            "Server admins can turn these off with /config announcements",
+        ))
+}
+
+/// [OWNER] Post an announcement in the alert channel of every server
+#[poise::command(slash_command, prefix_command, owners_only, ephemeral)]
+#[instrument(skip(ctx, message), fields(user_id = %ctx.author().id))]
+pub async fn broadcast(
+    ctx: Context<'_>,
+    #[description = "Title, e.g. \"Maintenance tonight\""]
+    #[max_length = 200]
+    title: String,
+    #[description = "Announcement text"]
+    #[max_length = 2000]
+    message: String,
+) -> Result<(), AppError> {
+    max_length("Title", &title, 200)?;
+    max_length("Announcement text", &message, 2000)?;
+
+    let (guilds, opted_out): (Vec<_>, Vec<_>) = ctx
+        .data()
+        .db
+        .get_guilds_with_alert_channel()
+        .await?
+        .into_iter()
+        .partition(|guild| guild.announcements);
+    if guilds.is_empty() {
+        ctx.say("No server has an alert channel taking announcements.")
+            .await?;
+        return Ok(());
+    }
+
+    let prompt = announcement(&title, &message).field(
+        "Recipients",
+        format!(
+            "{} alert channel(s), {} server(s) opted out",
+            guilds.len(),
+            opted_out.len()
+        ),
+        false,
+    );
+    if !confirm(ctx, prompt, "Send").await? {
+        return Ok(());
+    }
+
+    let http = ctx.serenity_context().http.clone();
+    let embed = announcement(&title, &message);
+    let mut sent = 0;
+    for guild in &guilds {
+        let Some(channel_id) = guild.alert_channel_id else {
+            continue;
+        };
+        match ChannelId::new(channel_id.get())
+            .send_message(&http, CreateMessage::new().embed(embed.clone()))
+            .await
+        {
+            Ok(_) => sent += 1,
+            Err(e) => warn!(
+                error = ?e,
+                guild_id = guild.id.get(),
+                "🎮 ⚠️ Failed to post announcement"
+            ),
+        }
+        tokio::time::sleep(BROADCAST_PACE).await;
+    }
+
+    ctx.say(format!(
+        "Announcement posted in **{sent}** of {} alert channel(s).",
+        guilds.len()
+    ))
+    .await?;
+
+    info!(sent, total = guilds.len(), "🎮 Announcement broadcast");
+
+    Ok(())
+}
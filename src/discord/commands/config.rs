@@ -1,15 +1,50 @@
+use std::time::Duration;
+
+use chrono_tz::{TZ_VARIANTS, Tz};
+use poise::ChoiceParameter;
 use poise::serenity_prelude::{self as serenity, Mentionable};
+use rand::Rng;
+use rand::distr::Alphanumeric;
 use tracing::{info, instrument};
 
+use crate::activity::ActivityEvent;
+use crate::discord::alert_layout::{StatField, guild_fields, parse_fields};
+use crate::discord::alert_template::{self, Outcome, PLACEHOLDERS, TemplateQueue, TemplateValues};
 use crate::discord::bot::Context;
 use crate::error::AppError;
+use crate::poller::RecapFrequency;
+use crate::riot::Queue;
+
+use super::bounds::{at_least, in_range};
+
+/// How long the invoking user has to pick queues
+const QUEUE_MENU_TIMEOUT: Duration = Duration::from_secs(120);
 
 /// Configure the bot for this server
 #[poise::command(
     slash_command,
+    prefix_command,
     guild_only,
     required_permissions = "MANAGE_GUILD",
-    subcommands("channel")
+    subcommands(
+        "channel",
+        "recap_channel",
+        "recap_schedule",
+        "recap_chart",
+        "timezone",
+        "details",
+        "rank_notices",
+        "forecast",
+        "queues",
+        "rotating_modes",
+        "alert_rate_limit",
+        "alert_template",
+        "track_limit",
+        "announcements",
+        "fields",
+        "prefix",
+        "public_api"
+    )
 )]
 pub async fn config(_ctx: Context<'_>) -> Result<(), AppError> {
     // Parent command, subcommands handle the actual work
@@ -17,7 +52,12 @@ pub async fn config(_ctx: Context<'_>) -> Result<(), AppError> {
 }
 
 /// Set the channel for game alerts
-#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
 #[instrument(
     skip(ctx),
     fields(
@@ -41,6 +81,11 @@ pub async fn channel(
         .db
         .set_guild_alert_channel(guild_id.get(), channel.id.get())
         .await?;
+    ctx.data()
+        .activity
+        .publish(ActivityEvent::AlertChannelChanged {
+            guild_id: guild_id.get(),
+        });
 
     let embed = serenity::CreateEmbed::new()
         .title("Configuration Updated")
@@ -56,3 +101,894 @@ pub async fn channel(
 
     Ok(())
 }
+
+/// Send weekly recaps to another channel than game alerts
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn recap_channel(
+    ctx: Context<'_>,
+    #[description = "Channel for weekly recaps (leave empty to use the alert channel)"]
+    #[channel_types("Text")]
+    channel: Option<serenity::GuildChannel>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    ctx.data()
+        .db
+        .set_guild_recap_channel(guild_id.get(), channel.as_ref().map(|c| c.id.get()))
+        .await?;
+
+    let description = match &channel {
+        Some(channel) => format!("Weekly recaps will now be sent to {}", channel.mention()),
+        None => "Weekly recaps will now be sent to the alert channel".to_string(),
+    };
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(description)
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(channel_id = ?channel.map(|c| c.id.get()), "Recap channel configured");
+
+    Ok(())
+}
+
+async fn autocomplete_timezone(_ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let partial = partial.to_lowercase();
+    TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .filter(|name| name.to_lowercase().contains(&partial))
+        .take(25)
+        .map(String::from)
+        .collect()
+}
+
+/// Post a daily or weekly recap of the players' results
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn recap_schedule(
+    ctx: Context<'_>,
+    #[description = "How often, leave empty to stop the recap"] frequency: Option<RecapFrequency>,
+    #[description = "Hour it is posted at, in the server's timezone (default 20)"]
+    #[min = 0]
+    #[max = 23]
+    hour: Option<u32>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let hour = in_range("Hour", hour.unwrap_or(20), 0..=23)?;
+    ctx.data()
+        .db
+        .set_guild_recap_schedule(guild_id.get(), frequency.map(|f| f.as_str()), hour)
+        .await?;
+
+    let description = match frequency {
+        Some(RecapFrequency::Daily) => {
+            format!("Results recap posted every day at **{hour:02}:00**, server time")
+        }
+        Some(RecapFrequency::Weekly) => {
+            format!("Results recap posted every Monday at **{hour:02}:00**, server time")
+        }
+        None => "Results recaps are off".to_string(),
+    };
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(description)
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(
+        frequency = frequency.map(|f| f.as_str()),
+        hour, "Recap schedule configured"
+    );
+
+    Ok(())
+}
+
+/// Draw the LP of the week's players on the weekly recap
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn recap_chart(
+    ctx: Context<'_>,
+    #[description = "Attach an LP chart to the weekly recap"] enabled: bool,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    ctx.data()
+        .db
+        .set_guild_recap_chart(guild_id.get(), enabled)
+        .await?;
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(if enabled {
+            "The weekly recap will include an LP chart"
+        } else {
+            "The weekly recap will no longer include an LP chart"
+        })
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(enabled, "Recap chart configured");
+
+    Ok(())
+}
+
+/// Set the timezone used for dates and schedules
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(
+    skip(ctx),
+    fields(
+        guild_id,
+        user_id = %ctx.author().id
+    )
+)]
+pub async fn timezone(
+    ctx: Context<'_>,
+    #[description = "IANA timezone, e.g. Europe/Paris (leave empty to show the current one)"]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: Option<String>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let Some(timezone) = timezone else {
+        let tz = ctx
+            .data()
+            .db
+            .get_guild(guild_id.get())
+            .await?
+            .map(|guild| guild.timezone())
+            .unwrap_or(Tz::UTC);
+        let now = chrono::Utc::now().with_timezone(&tz);
+        ctx.say(format!(
+            "Current timezone: **{}** (currently {})",
+            tz.name(),
+            now.format("%H:%M")
+        ))
+        .await?;
+        return Ok(());
+    };
+
+    let tz: Tz = timezone
+        .parse()
+        .map_err(|_| AppError::InvalidTimezone(timezone.clone()))?;
+
+    ctx.data()
+        .db
+        .set_guild_timezone(guild_id.get(), tz.name())
+        .await?;
+
+    let now = chrono::Utc::now().with_timezone(&tz);
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(format!(
+            "Timezone set to **{}** (currently {})",
+            tz.name(),
+            now.format("%H:%M")
+        ))
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(timezone = tz.name(), "Timezone configured");
+
+    Ok(())
+}
+
+/// Show extra details (team objectives) on ranked alerts
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn details(
+    ctx: Context<'_>,
+    #[description = "Show extra details on ranked alerts"] enabled: bool,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    ctx.data()
+        .db
+        .set_guild_alert_details(guild_id.get(), enabled)
+        .await?;
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(if enabled {
+            "Ranked alerts will show team objectives"
+        } else {
+            "Ranked alerts will be compact"
+        })
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(enabled, "Alert details configured");
+
+    Ok(())
+}
+
+/// Estimate the games to the next division on ranked alerts
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn forecast(
+    ctx: Context<'_>,
+    #[description = "Show games to the next division on ranked alerts"] enabled: bool,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    ctx.data()
+        .db
+        .set_guild_alert_forecast(guild_id.get(), enabled)
+        .await?;
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(if enabled {
+            "Ranked alerts will estimate the games to the next division at the current winrate"
+        } else {
+            "Ranked alerts will no longer estimate the games to the next division"
+        })
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(enabled, "Division forecast configured");
+
+    Ok(())
+}
+
+/// Pick the queues this server gets alerts for
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    ephemeral,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn queues(ctx: Context<'_>) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let muted = ctx.data().db.get_muted_queues(guild_id.get()).await?;
+    let options = Queue::ALL
+        .map(|queue| {
+            serenity::CreateSelectMenuOption::new(queue.name(), queue.key())
+                .default_selection(!muted.iter().any(|key| key == queue.key()))
+        })
+        .to_vec();
+    let menu_id = format!("{}-queues", ctx.id());
+    let menu = serenity::CreateSelectMenu::new(
+        &menu_id,
+        serenity::CreateSelectMenuKind::String { options },
+    )
+    .placeholder("No alerts")
+    .min_values(0)
+    .max_values(Queue::ALL.len() as u8);
+    let prompt = serenity::CreateEmbed::new()
+        .title("Queue Alerts")
+        .description(
+            "Select the queues to post alerts for, the others are muted.\n\
+             Rotating modes are switched with `/config rotating_modes`.",
+        )
+        .color(0x0099ff);
+    let reply = ctx
+        .send(
+            poise::CreateReply::default()
+                .embed(prompt.clone())
+                .components(vec![serenity::CreateActionRow::SelectMenu(menu)]),
+        )
+        .await?;
+
+    let interaction = serenity::ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(QUEUE_MENU_TIMEOUT)
+        .filter(move |mci| mci.data.custom_id == menu_id)
+        .await;
+    let Some(interaction) = interaction else {
+        reply
+            .edit(
+                ctx,
+                poise::CreateReply::default()
+                    .content("Timed out, nothing was changed.")
+                    .embed(prompt)
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let selected = match &interaction.data.kind {
+        serenity::ComponentInteractionDataKind::StringSelect { values } => values.as_slice(),
+        _ => &[],
+    };
+    let (enabled, muted): (Vec<Queue>, Vec<Queue>) = Queue::ALL
+        .into_iter()
+        .partition(|queue| selected.iter().any(|key| key == queue.key()));
+    ctx.data()
+        .db
+        .set_queue_alerts_bulk(
+            guild_id.get(),
+            &muted.iter().map(|queue| queue.key()).collect::<Vec<_>>(),
+        )
+        .await?;
+
+    let description = if enabled.is_empty() {
+        "No alerts will be posted for any queue".to_string()
+    } else {
+        format!(
+            "Alerts will be posted for: {}",
+            enabled
+                .iter()
+                .map(|queue| format!("**{}**", queue.name()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+    interaction
+        .create_response(
+            ctx,
+            serenity::CreateInteractionResponse::UpdateMessage(
+                serenity::CreateInteractionResponseMessage::new()
+                    .embed(
+                        serenity::CreateEmbed::new()
+                            .title("Configuration Updated")
+                            .description(description)
+                            .color(0x00ff00),
+                    )
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    info!(muted = muted.len(), "Queue alerts configured");
+
+    Ok(())
+}
+
+/// Alert on rotating and event modes (Arena, URF...) with a generic layout
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn rotating_modes(
+    ctx: Context<'_>,
+    #[description = "Post alerts for rotating game modes"] enabled: bool,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    ctx.data()
+        .db
+        .set_guild_rotating_alerts(guild_id.get(), enabled)
+        .await?;
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(if enabled {
+            "Games in rotating modes (Arena, URF...) will be posted with the result, KDA and champion"
+        } else {
+            "Games in rotating modes will no longer be posted"
+        })
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(enabled, "Rotating mode alerts configured");
+
+    Ok(())
+}
+
+/// Receive announcements from the bot's operators in the alert channel
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn announcements(
+    ctx: Context<'_>,
+    #[description = "Post maintenance and update announcements"] enabled: bool,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    ctx.data()
+        .db
+        .set_guild_announcements(guild_id.get(), enabled)
+        .await?;
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(if enabled {
+            "Maintenance and update announcements will be posted in the alert channel"
+        } else {
+            "Announcements will no longer be posted"
+        })
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(enabled, "Announcements configured");
+
+    Ok(())
+}
+
+/// Post LP changes that no game explains, such as decay or a dodge
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn rank_notices(
+    ctx: Context<'_>,
+    #[description = "Post LP decay and dodge notices"] enabled: bool,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    ctx.data()
+        .db
+        .set_guild_rank_notices(guild_id.get(), enabled)
+        .await?;
+
+    let mut description = if enabled {
+        "LP changes without a game (decay, dodges) will be posted"
+    } else {
+        "LP changes without a game will no longer be posted"
+    }
+    .to_string();
+    if enabled && ctx.data().config.rank_watch_interval_hours.is_none() {
+        description.push_str(
+            "\n⚠️ This bot instance doesn't check ranks between games (RANK_WATCH_INTERVAL_HOURS), ask its operator to enable it",
+        );
+    }
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(description)
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(enabled, "Rank notices configured");
+
+    Ok(())
+}
+
+/// Limit how many accounts this server can track
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn track_limit(
+    ctx: Context<'_>,
+    #[description = "Most tracked accounts, leave empty for the bot's limit"]
+    #[min = 1]
+    limit: Option<u32>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let limit = limit
+        .map(|limit| at_least("Track limit", limit, 1))
+        .transpose()?;
+    let instance_limit = ctx.data().config.track_limit;
+    ctx.data()
+        .db
+        .set_guild_track_limit(guild_id.get(), limit)
+        .await?;
+
+    let description = match (limit, instance_limit) {
+        (Some(limit), Some(instance)) if limit > instance => format!(
+            "This bot allows at most **{instance}** tracked accounts per server, that limit applies"
+        ),
+        (Some(limit), _) => format!("At most **{limit}** accounts can be tracked here"),
+        (None, Some(instance)) => {
+            format!("At most **{instance}** accounts can be tracked here, the bot's limit")
+        }
+        (None, None) => "Any number of accounts can be tracked here".to_string(),
+    };
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(description)
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(limit, "Track limit configured");
+
+    Ok(())
+}
+
+/// Limit how many match alerts this server gets per hour
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn alert_rate_limit(
+    ctx: Context<'_>,
+    #[description = "Alerts per hour, leave empty for no limit"]
+    #[min = 1]
+    #[max = 60]
+    per_hour: Option<u32>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let per_hour = per_hour
+        .map(|per_hour| in_range("Alerts per hour", per_hour, 1..=60))
+        .transpose()?;
+
+    ctx.data()
+        .db
+        .set_guild_alert_rate_limit(guild_id.get(), per_hour)
+        .await?;
+
+    let description = match per_hour {
+        Some(per_hour) => format!(
+            "At most **{per_hour}** match alerts per hour, the rest are summed up in one message"
+        ),
+        None => "Every match alert will be posted".to_string(),
+    };
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(description)
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(per_hour, "Alert rate limit configured");
+
+    Ok(())
+}
+
+/// Word the message posted with alerts of a queue, for wins or losses
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn alert_template(
+    ctx: Context<'_>,
+    #[description = "Queue the template applies to"] queue: TemplateQueue,
+    #[description = "Wins or losses"] outcome: Outcome,
+    #[description = "e.g. \"GG {player}, {champion} {kda} {lp}\", leave empty to remove"]
+    template: Option<String>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    if let Some(template) = &template {
+        alert_template::validate(template)?;
+    }
+    ctx.data()
+        .db
+        .set_alert_template(
+            guild_id.get(),
+            queue.as_str(),
+            outcome.as_str(),
+            template.as_deref(),
+        )
+        .await?;
+
+    let description = match &template {
+        Some(template) => {
+            let example = TemplateValues {
+                player: "Faker#KR1",
+                champion: "Ahri",
+                kda: "10/2/8".into(),
+                lp: "+18 LP".into(),
+                queue: queue.name(),
+            };
+            format!(
+                "{} alerts in **{}** will say:\n> {}",
+                outcome.name(),
+                queue.name(),
+                alert_template::render(template, &example)
+            )
+        }
+        None => format!(
+            "{} alerts in **{}** no longer carry a message\nPlaceholders: {}",
+            outcome.name(),
+            queue.name(),
+            PLACEHOLDERS.map(|p| format!("`{{{p}}}`")).join(", ")
+        ),
+    };
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(description)
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(
+        queue = queue.as_str(),
+        outcome = outcome.as_str(),
+        set = template.is_some(),
+        "Alert template configured"
+    );
+
+    Ok(())
+}
+
+fn format_fields(fields: &[StatField]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("`{field}`"))
+        .collect::<Vec<_>>()
+        .join(" → ")
+}
+
+/// Suggest the remaining fields for the last entry of the comma-separated list
+async fn autocomplete_fields(_ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let (chosen, current) = partial.rsplit_once(',').unwrap_or(("", partial));
+    let chosen: Vec<&str> = chosen.split(',').map(str::trim).collect();
+    let prefix = if chosen.iter().all(|s| s.is_empty()) {
+        String::new()
+    } else {
+        format!("{},", chosen.join(","))
+    };
+
+    StatField::ALL
+        .iter()
+        .map(StatField::key)
+        .filter(|key| !chosen.contains(key) && key.starts_with(current.trim()))
+        .map(|key| format!("{prefix}{key}"))
+        .collect()
+}
+
+/// Choose and order the stat fields shown on alerts
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn fields(
+    ctx: Context<'_>,
+    #[description = "Comma-separated fields in order (cs, damage, vision, role, gold), or \"default\""]
+    #[autocomplete = "autocomplete_fields"]
+    layout: Option<String>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let Some(layout) = layout else {
+        let current = ctx
+            .data()
+            .db
+            .get_guild(guild_id.get())
+            .await?
+            .map(|guild| guild_fields(&guild))
+            .unwrap_or_else(|| StatField::DEFAULT.to_vec());
+        ctx.say(format!("Current alert fields: {}", format_fields(&current)))
+            .await?;
+        return Ok(());
+    };
+
+    let fields = if layout.trim().eq_ignore_ascii_case("default") {
+        ctx.data()
+            .db
+            .set_guild_alert_fields(guild_id.get(), None)
+            .await?;
+        StatField::DEFAULT.to_vec()
+    } else {
+        let fields = parse_fields(&layout)?;
+        let json = serde_json::to_string(&fields)
+            .map_err(|e| AppError::Config(format!("Invalid alert fields: {e}")))?;
+        ctx.data()
+            .db
+            .set_guild_alert_fields(guild_id.get(), Some(&json))
+            .await?;
+        fields
+    };
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(format!(
+            "Alerts will show {}\nARAM alerts only keep the fields that apply to it",
+            format_fields(&fields)
+        ))
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(fields = ?fields, "Alert fields configured");
+
+    Ok(())
+}
+
+/// Set the prefix of message commands, for servers without slash commands
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn prefix(
+    ctx: Context<'_>,
+    #[description = "Prefix such as ! (leave empty to turn message commands off)"] prefix: Option<
+        String,
+    >,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let prefixes = &ctx.data().prefixes;
+    prefixes
+        .set(&ctx.data().db, guild_id.get(), prefix.as_deref())
+        .await?;
+
+    let mut description = match &prefix {
+        Some(prefix) => format!("Commands also work as messages, e.g. `{prefix}list`"),
+        None => "Message commands are turned off, slash commands still work".to_string(),
+    };
+    if prefix.is_some() && !prefixes.enabled() {
+        description.push_str(
+            "\n⚠️ This bot instance doesn't read messages (PREFIX_COMMANDS), ask its operator to enable them",
+        );
+    }
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(description)
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(prefix = ?prefix, "Command prefix configured");
+
+    Ok(())
+}
+
+/// Enable the public roster API for website widgets, or revoke its token
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    ephemeral
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn public_api(
+    ctx: Context<'_>,
+    #[description = "Issue a new token (revoking the old one), or disable the API"] enabled: Option<
+        bool,
+    >,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let Some(enabled) = enabled else {
+        let active = ctx
+            .data()
+            .db
+            .get_guild(guild_id.get())
+            .await?
+            .is_some_and(|guild| guild.public_api_token.is_some());
+        ctx.say(if active {
+            "Public roster API is **enabled**"
+        } else {
+            "Public roster API is **disabled**"
+        })
+        .await?;
+        return Ok(());
+    };
+
+    let token: Option<String> = enabled.then(|| {
+        rand::rng()
+            .sample_iter(Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect()
+    });
+
+    ctx.data()
+        .db
+        .set_guild_public_api_token(guild_id.get(), token.as_deref())
+        .await?;
+
+    let description = match &token {
+        Some(token) => format!(
+            "Roster API token: `{token}`\n\
+             Fetch `GET /roster?token={token}` on the bot's public API.\n\
+             Anyone with this token can read the tracked roster; run this again to rotate it."
+        ),
+        None => "Public roster API disabled, the previous token no longer works".to_string(),
+    };
+    let embed = serenity::CreateEmbed::new()
+        .title("Configuration Updated")
+        .description(description)
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(enabled, "Public API configured");
+
+    Ok(())
+}
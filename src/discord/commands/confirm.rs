@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::{
+    self as serenity, ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+
+/// How long the invoking user has to click a button
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Show `prompt` with Confirm/Cancel buttons to the invoking user only and wait
+/// for their choice. The buttons are removed once answered or timed out.
+/// Returns `true` when confirmed.
+pub(super) async fn confirm(
+    ctx: Context<'_>,
+    prompt: serenity::CreateEmbed,
+    confirm_label: &str,
+) -> Result<bool, AppError> {
+    let confirm_id = format!("{}-confirm", ctx.id());
+    let cancel_id = format!("{}-cancel", ctx.id());
+
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(&confirm_id)
+            .label(confirm_label)
+            .style(ButtonStyle::Success),
+        CreateButton::new(&cancel_id)
+            .label("Cancel")
+            .style(ButtonStyle::Secondary),
+    ]);
+    let reply = ctx
+        .send(
+            poise::CreateReply::default()
+                .embed(prompt.clone())
+                .components(vec![buttons])
+                .ephemeral(true),
+        )
+        .await?;
+
+    let prefix = ctx.id().to_string();
+    let interaction = ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(CONFIRM_TIMEOUT)
+        .filter(move |mci| mci.data.custom_id.starts_with(&prefix))
+        .await;
+
+    let Some(interaction) = interaction else {
+        reply
+            .edit(
+                ctx,
+                poise::CreateReply::default()
+                    .content("Timed out, nothing was changed.")
+                    .embed(prompt)
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(false);
+    };
+
+    let confirmed = interaction.data.custom_id == confirm_id;
+    let status = if confirmed {
+        "Confirmed."
+    } else {
+        "Cancelled, nothing was changed."
+    };
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(status)
+                    .embed(prompt)
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(confirmed)
+}
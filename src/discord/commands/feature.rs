@@ -0,0 +1,68 @@
+use poise::ChoiceParameter;
+use poise::serenity_prelude as serenity;
+use tracing::{info, instrument};
+
+use crate::discord::bot::Context;
+use crate::discord::features::Feature;
+use crate::error::AppError;
+
+/// [OWNER] Switch a feature on or off for the bot or a single server
+#[poise::command(slash_command, prefix_command, owners_only, ephemeral)]
+#[instrument(skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn feature(
+    ctx: Context<'_>,
+    #[description = "Feature to switch, lists the switches when empty"] feature: Option<Feature>,
+    #[description = "On or off, removes the switch when empty"] enabled: Option<bool>,
+    #[description = "Server ID, the whole bot when empty"] guild_id: Option<String>,
+) -> Result<(), AppError> {
+    let features = &ctx.data().features;
+
+    let Some(feature) = feature else {
+        let switches = features
+            .switches()
+            .into_iter()
+            .map(|(feature, guild_id, enabled)| {
+                let scope = guild_id.map_or("whole bot".to_string(), |id| format!("server {id}"));
+                let state = if enabled { "on" } else { "off" };
+                format!("**{}** ({scope}): {state}", feature.name())
+            })
+            .collect::<Vec<_>>();
+        let description = if switches.is_empty() {
+            "No switches set, every feature is on".to_string()
+        } else {
+            switches.join("\n")
+        };
+        let embed = serenity::CreateEmbed::new()
+            .title("Feature switches")
+            .description(description)
+            .color(0x0099ff);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let guild_id = guild_id
+        .map(|id| {
+            id.trim()
+                .parse::<u64>()
+                .map_err(|_| AppError::Config(format!("\"{id}\" is not a server ID")))
+        })
+        .transpose()?;
+
+    features
+        .set(&ctx.data().db, feature, guild_id, enabled)
+        .await?;
+    info!(
+        feature = feature.as_str(),
+        guild_id, enabled, "🎮 Feature switch changed"
+    );
+
+    let scope = guild_id.map_or("the whole bot".to_string(), |id| format!("server {id}"));
+    let message = match enabled {
+        Some(true) => format!("✅ **{}** is now on for {scope}", feature.name()),
+        Some(false) => format!("⛔ **{}** is now off for {scope}", feature.name()),
+        None => format!("↩️ Removed the **{}** switch of {scope}", feature.name()),
+    };
+    ctx.say(message).await?;
+
+    Ok(())
+}
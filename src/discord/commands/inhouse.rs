@@ -0,0 +1,188 @@
+use poise::serenity_prelude as serenity;
+use rand::seq::SliceRandom;
+use tracing::{info, instrument, warn};
+
+use crate::db::{Player, RankInfo};
+use crate::discord::bot::Context;
+use crate::discord::emojis::EmojiStore;
+use crate::discord::image_gen::capitalize;
+use crate::error::AppError;
+use crate::stats::{balance_teams, rank_to_lp};
+
+use super::bounds::in_range;
+
+/// Rating of unranked players when nobody in the draft has a rank (Silver IV)
+const UNRANKED_RATING: i32 = 800;
+
+/// How long an external in-house event lasts, Discord requires an end time
+const EVENT_DURATION_SECS: i64 = 2 * 3600;
+
+/// Draft balanced in-house teams from the tracked players
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+#[instrument(skip(ctx, voice_channel), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn create_inhouse(
+    ctx: Context<'_>,
+    #[description = "Players per team (default 5)"]
+    #[min = 1]
+    #[max = 5]
+    team_size: Option<u8>,
+    #[description = "Create a server event starting in this many minutes"]
+    #[min = 5]
+    #[max = 10080]
+    starts_in: Option<u32>,
+    #[description = "Voice channel hosting the event"]
+    #[channel_types("Voice")]
+    voice_channel: Option<serenity::GuildChannel>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let team_size = usize::from(in_range("Team size", team_size.unwrap_or(5), 1..=5)?);
+    let starts_in = starts_in
+        .map(|minutes| in_range("Start delay", minutes, 5..=10080))
+        .transpose()?;
+    let mut players = ctx.data().db.get_guild_players(guild_id.get()).await?;
+    if players.len() < team_size * 2 {
+        ctx.say(format!(
+            "Not enough tracked players for {team_size}v{team_size}: {} tracked, {} needed.",
+            players.len(),
+            team_size * 2
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    // A random pick of the roster when there are more players than seats
+    players.shuffle(&mut rand::rng());
+    players.truncate(team_size * 2);
+
+    let ranks: Vec<Option<RankInfo>> = players
+        .iter()
+        .map(|p| p.solo_rank_info().or_else(|| p.flex_rank_info()))
+        .collect();
+    let ratings = ratings(&ranks);
+    let (blue, red) = balance_teams(&ratings);
+
+    let emojis = &ctx.data().emojis;
+    let team_field = |team: &[usize]| {
+        let total: i32 = team.iter().map(|&i| ratings[i]).sum();
+        let lines: Vec<String> = team
+            .iter()
+            .map(|&i| player_line(emojis, &players[i], ranks[i].as_ref()))
+            .collect();
+        (total, lines.join("\n"))
+    };
+    let (blue_total, blue_lines) = team_field(&blue);
+    let (red_total, red_lines) = team_field(&red);
+
+    let mut embed = serenity::CreateEmbed::new()
+        .title(format!("In-house Draft {team_size}v{team_size}"))
+        .field(
+            format!("🔵 Blue side (avg {} LP)", blue_total / team_size as i32),
+            blue_lines,
+            true,
+        )
+        .field(
+            format!("🔴 Red side (avg {} LP)", red_total / team_size as i32),
+            red_lines,
+            true,
+        )
+        .footer(serenity::CreateEmbedFooter::new(format!(
+            "Rating gap: {} LP • unranked players count as the draft average",
+            (blue_total - red_total).abs()
+        )))
+        .color(0x0099ff);
+
+    if let Some(minutes) = starts_in {
+        match create_event(&ctx, guild_id, minutes, voice_channel.as_ref()).await {
+            Ok(event) => {
+                embed = embed.description(format!(
+                    "Scheduled for <t:{}:R>",
+                    event.start_time.unix_timestamp()
+                ));
+            }
+            Err(e) => {
+                warn!(error = ?e, "🎮 ⚠️ Failed to create in-house event");
+                embed = embed.description(format!("⚠️ Could not create the server event: {e}"));
+            }
+        }
+    }
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(
+        team_size,
+        scheduled = starts_in.is_some(),
+        "In-house drafted"
+    );
+
+    Ok(())
+}
+
+/// Ladder LP of each player, unranked ones get the average of the ranked
+fn ratings(ranks: &[Option<RankInfo>]) -> Vec<i32> {
+    let ranked: Vec<i32> = ranks.iter().flatten().map(rank_to_lp).collect();
+    let fallback = match ranked.len() {
+        0 => UNRANKED_RATING,
+        n => ranked.iter().sum::<i32>() / n as i32,
+    };
+
+    ranks
+        .iter()
+        .map(|rank| rank.as_ref().map_or(fallback, rank_to_lp))
+        .collect()
+}
+
+fn player_line(emojis: &EmojiStore, player: &Player, rank: Option<&RankInfo>) -> String {
+    let rank = rank
+        .map(|r| {
+            format!(
+                "{} {} {} {} LP",
+                emojis.tier(&r.tier),
+                capitalize(&r.tier),
+                r.rank,
+                r.lp
+            )
+        })
+        .unwrap_or_else(|| "Unranked".to_string());
+    format!("**{}** • {}", player.display_name(), rank)
+}
+
+async fn create_event(
+    ctx: &Context<'_>,
+    guild_id: serenity::GuildId,
+    starts_in: u32,
+    voice_channel: Option<&serenity::GuildChannel>,
+) -> Result<serenity::ScheduledEvent, AppError> {
+    let start = ctx.data().clock.unix_now() + i64::from(starts_in) * 60;
+    let timestamp = |secs: i64| {
+        serenity::Timestamp::from_unix_timestamp(secs)
+            .map_err(|e| AppError::Config(format!("Invalid event time: {e}")))
+    };
+
+    let event = match voice_channel {
+        Some(channel) => serenity::CreateScheduledEvent::new(
+            serenity::ScheduledEventType::Voice,
+            "In-house",
+            timestamp(start)?,
+        )
+        .channel_id(channel.id),
+        None => serenity::CreateScheduledEvent::new(
+            serenity::ScheduledEventType::External,
+            "In-house",
+            timestamp(start)?,
+        )
+        .location("Custom game")
+        .end_time(timestamp(start + EVENT_DURATION_SECS)?),
+    }
+    .description("Teams drafted by Tentrackule from the tracked players");
+
+    Ok(guild_id.create_scheduled_event(ctx.http(), event).await?)
+}
@@ -0,0 +1,113 @@
+use poise::ChoiceParameter;
+use poise::serenity_prelude as serenity;
+use tracing::{info, instrument};
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+use crate::riot::{ApexTier, Platform};
+
+/// Watch an apex Solo/Duo ladder
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("watch", "unwatch")
+)]
+pub async fn ladder(_ctx: Context<'_>) -> Result<(), AppError> {
+    // Parent command, subcommands handle the actual work
+    Ok(())
+}
+
+/// Post daily top 10 cutoff changes and tracked players entering or leaving a tier
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn watch(
+    ctx: Context<'_>,
+    #[description = "Server region"] region: Platform,
+    #[description = "Ladder tier"] tier: ApexTier,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let added = ctx
+        .data()
+        .db
+        .add_ladder_watch(guild_id.get(), region.as_str(), tier.as_str())
+        .await?;
+
+    if !added {
+        ctx.say(format!(
+            "The {} ladder on **{}** is already watched in this server.",
+            tier.name(),
+            region.display_name()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Ladder Watched")
+        .description(format!(
+            "Daily {} ladder updates for **{}** will be posted in the alert channel",
+            tier.name(),
+            region.display_name()
+        ))
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(region = %region, tier = tier.as_str(), "Ladder watch added");
+
+    Ok(())
+}
+
+/// Stop watching an apex ladder
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn unwatch(
+    ctx: Context<'_>,
+    #[description = "Server region"] region: Platform,
+    #[description = "Ladder tier"] tier: ApexTier,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let removed = ctx
+        .data()
+        .db
+        .remove_ladder_watch(guild_id.get(), region.as_str(), tier.as_str())
+        .await?;
+
+    let message = if removed {
+        info!(region = %region, tier = tier.as_str(), "Ladder watch removed");
+        format!(
+            "Stopped watching the {} ladder on **{}**.",
+            tier.name(),
+            region.display_name()
+        )
+    } else {
+        format!(
+            "The {} ladder on **{}** is not watched in this server.",
+            tier.name(),
+            region.display_name()
+        )
+    };
+    ctx.say(message).await?;
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use poise::ChoiceParameter;
+use poise::serenity_prelude as serenity;
+
+use crate::db::{Player, RankInfo};
+use crate::discord::bot::Context;
+use crate::discord::image_gen::capitalize;
+use crate::error::AppError;
+
+use super::paginate::paginate;
+
+/// Players per page, keeps the embed under Discord's description limit
+const PLAYERS_PER_PAGE: usize = 15;
+
+/// Ranked queue a leaderboard is drawn from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum LeaderboardQueue {
+    #[name = "Solo/Duo"]
+    SoloDuo,
+    Flex,
+}
+
+impl LeaderboardQueue {
+    fn rank_of(self, player: &Player) -> Option<RankInfo> {
+        match self {
+            Self::SoloDuo => player.solo_rank_info(),
+            Self::Flex => player.flex_rank_info(),
+        }
+    }
+
+    /// League-v4 queue type the ranks are kept under
+    fn queue_type(self) -> &'static str {
+        match self {
+            Self::SoloDuo => "RANKED_SOLO_5x5",
+            Self::Flex => "RANKED_FLEX_SR",
+        }
+    }
+}
+
+/// Rank the players tracked in this server
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn leaderboard(
+    ctx: Context<'_>,
+    #[description = "Ranked queue (default Solo/Duo)"] queue: Option<LeaderboardQueue>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    let queue = queue.unwrap_or(LeaderboardQueue::SoloDuo);
+
+    let db = &ctx.data().db;
+    let ranked = db
+        .count_guild_ranked_players(guild_id.get(), queue.queue_type())
+        .await?;
+    if ranked == 0 {
+        ctx.say(format!(
+            "No player tracked in this server is ranked in {} yet.",
+            queue.name()
+        ))
+        .await?;
+        return Ok(());
+    }
+    let unranked = db.count_guild_players(guild_id.get()).await? - ranked;
+
+    // Sorted by the database, read a page at a time as the user browses
+    let page_count = (ranked as usize).div_ceil(PLAYERS_PER_PAGE);
+    paginate(ctx, page_count, |page| async move {
+        let offset = page * PLAYERS_PER_PAGE;
+        let players = db
+            .get_guild_ladder_page(
+                guild_id.get(),
+                queue.queue_type(),
+                offset as u32,
+                PLAYERS_PER_PAGE as u32,
+            )
+            .await?;
+        let emojis = &ctx.data().emojis;
+        let lines: Vec<String> = players
+            .iter()
+            .enumerate()
+            .filter_map(|(i, player)| {
+                let rank = queue.rank_of(player)?;
+                Some(format!(
+                    "`#{}` **{}** • {} {} {} {} LP",
+                    offset + i + 1,
+                    player.display_name(),
+                    emojis.tier(&rank.tier),
+                    capitalize(&rank.tier),
+                    rank.rank,
+                    rank.lp
+                ))
+            })
+            .collect();
+        let mut description = lines.join("\n");
+        if page + 1 == page_count && unranked > 0 {
+            description.push_str(&format!("\n\n…and {unranked} unranked players"));
+        }
+        Ok(serenity::CreateEmbed::new()
+            .title(format!("🏆 {} Leaderboard", queue.name()))
+            .description(description)
+            .color(0xc89b3c))
+    })
+    .await
+}
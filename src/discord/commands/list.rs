@@ -1,39 +1,102 @@
 use poise::serenity_prelude as serenity;
 
+use crate::db::GuildPlayer;
 use crate::discord::bot::Context;
+use crate::discord::emojis::EmojiStore;
+use crate::discord::image_gen::capitalize;
 use crate::error::AppError;
 
+use super::paginate::paginate;
+
+/// Players per page, keeps the embed under Discord's description limit
+const PLAYERS_PER_PAGE: usize = 15;
+
 /// List all tracked players in this server
-#[poise::command(slash_command, guild_only)]
+#[poise::command(slash_command, prefix_command, guild_only)]
 pub async fn list(ctx: Context<'_>) -> Result<(), AppError> {
+    send_list(ctx, false).await
+}
+
+/// List tracked players with their admin notes, only shown to you
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    ephemeral,
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn list_admin(ctx: Context<'_>) -> Result<(), AppError> {
+    send_list(ctx, true).await
+}
+
+async fn send_list(ctx: Context<'_>, with_notes: bool) -> Result<(), AppError> {
     let guild_id = ctx
         .guild_id()
         .ok_or(AppError::Config("Must be used in a guild".into()))?;
 
-    let players = ctx.data().db.get_guild_players(guild_id.get()).await?;
+    let db = &ctx.data().db;
+    let count = db.count_guild_players(guild_id.get()).await?;
 
-    if players.is_empty() {
+    if count == 0 {
         ctx.say("No players are being tracked in this server.\nUse `/track` to add players.")
             .await?;
         return Ok(());
     }
 
-    let mut description = String::new();
-    for player in &players {
-        description.push_str(&format!(
-            "- **{}#{}** ({})\n",
-            player.game_name,
-            player.tag_line,
-            player.region.to_uppercase()
-        ));
-    }
-
-    let embed = serenity::CreateEmbed::new()
-        .title(format!("Tracked Players ({})", players.len()))
-        .description(description)
-        .color(0x0099ff);
-
-    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    // Large rosters are read a page at a time, as the user browses
+    let page_count = (count as usize).div_ceil(PLAYERS_PER_PAGE);
+    paginate(ctx, page_count, |page| async move {
+        let players = db
+            .get_guild_players_page(
+                guild_id.get(),
+                (page * PLAYERS_PER_PAGE) as u32,
+                PLAYERS_PER_PAGE as u32,
+            )
+            .await?;
+        let lines: Vec<String> = players
+            .iter()
+            .map(|player| player_line(&ctx.data().emojis, player, with_notes))
+            .collect();
+        Ok(serenity::CreateEmbed::new()
+            .title(format!("Tracked Players ({count})"))
+            .description(lines.join("\n"))
+            .color(0x0099ff))
+    })
+    .await
+}
 
-    Ok(())
+fn player_line(emojis: &EmojiStore, tracked: &GuildPlayer, with_notes: bool) -> String {
+    let player = &tracked.player;
+    let rank = player
+        .solo_rank_info()
+        .map(|r| {
+            format!(
+                " • {} {} {} {} LP",
+                emojis.tier(&r.tier),
+                capitalize(&r.tier),
+                r.rank,
+                r.lp
+            )
+        })
+        .or_else(|| {
+            player
+                .no_matches_since
+                .map(|_| " • no games yet".to_string())
+        })
+        .unwrap_or_default();
+    let since = format!(" • tracked since <t:{}:D>", tracked.added_at);
+    let note = tracked
+        .admin_note
+        .as_ref()
+        .filter(|_| with_notes)
+        .map(|note| format!("\n  📝 {note}"))
+        .unwrap_or_default();
+    format!(
+        "- **{}** ({}){}{}{}",
+        player.display_name(),
+        player.region.to_uppercase(),
+        rank,
+        since,
+        note
+    )
 }
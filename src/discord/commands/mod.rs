@@ -1,11 +1,43 @@
+mod about;
+mod api_usage;
+mod bounds;
+mod broadcast;
 mod config;
+mod confirm;
 mod dev;
+mod feature;
+mod inhouse;
+mod ladder;
+mod leaderboard;
 mod list;
+mod mvp;
+mod notes;
+mod paginate;
+mod permissions;
+mod preview;
+mod privacy;
+mod purge;
+mod sample;
+mod stats;
 mod track;
 mod untrack;
 
+pub use about::about;
+pub use api_usage::api_usage;
+pub use broadcast::broadcast;
 pub use config::config;
 pub use dev::dev_test_alert;
-pub use list::list;
-pub use track::track;
-pub use untrack::untrack;
+pub use feature::feature;
+pub use inhouse::create_inhouse;
+pub use ladder::ladder;
+pub use leaderboard::leaderboard;
+pub use list::{list, list_admin};
+pub use mvp::mvp_history;
+pub use notes::set_admin_note;
+pub use permissions::check_permissions;
+pub use preview::preview_alert;
+pub use privacy::{quiet_when_online, set_privacy};
+pub use purge::{purge_guild_data, purge_my_data};
+pub use stats::stats;
+pub use track::{track, track_multiple};
+pub use untrack::{undo_untrack, untrack, untrack_all};
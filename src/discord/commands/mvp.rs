@@ -0,0 +1,59 @@
+use poise::serenity_prelude as serenity;
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+use crate::stats::MVP_MIN_GAMES;
+
+use super::paginate::paginate;
+
+/// Number of past weeks listed
+const HISTORY_WEEKS: i64 = 52;
+
+const WEEKS_PER_PAGE: usize = 10;
+
+/// Show the weekly MVPs of this server
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn mvp_history(ctx: Context<'_>) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+
+    let db = &ctx.data().db;
+    let history = db.get_mvp_history(guild_id.get(), HISTORY_WEEKS).await?;
+
+    if history.is_empty() {
+        ctx.say(format!(
+            "No MVP yet. The weekly MVP is the tracked player with the best average score over at least {MVP_MIN_GAMES} normal games."
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let tz = db
+        .get_guild(guild_id.get())
+        .await?
+        .map(|guild| guild.timezone())
+        .unwrap_or(chrono_tz::Tz::UTC);
+    let lines: Vec<String> = history
+        .iter()
+        .map(|mvp| {
+            format!(
+                "- Week of {}: **{}** ({:.1} avg over {} games)",
+                mvp.week_label(tz),
+                mvp.display_name(),
+                mvp.avg_score,
+                mvp.games
+            )
+        })
+        .collect();
+
+    let pages: Vec<&[String]> = lines.chunks(WEEKS_PER_PAGE).collect();
+    paginate(ctx, pages.len(), |page| {
+        let embed = serenity::CreateEmbed::new()
+            .title("🏅 Weekly MVPs")
+            .description(pages[page].join("\n"))
+            .color(0xc89b3c);
+        async { Ok(embed) }
+    })
+    .await
+}
@@ -0,0 +1,86 @@
+use poise::serenity_prelude as serenity;
+use tracing::{info, instrument};
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+use crate::riot::normalize_riot_id;
+
+/// Keeps a page of `/list_admin` under Discord's description limit
+const MAX_NOTE_LEN: usize = 100;
+
+/// Note on a tracked account only server admins see, e.g. "smurf of X"
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    ephemeral,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(
+    skip(ctx, note),
+    fields(
+        guild_id,
+        user_id = %ctx.author().id,
+        riot_id = %format!("{}#{}", game_name, tag_line)
+    )
+)]
+pub async fn set_admin_note(
+    ctx: Context<'_>,
+    #[description = "Game name (before the #)"] game_name: String,
+    #[description = "Tag line (after the #)"] tag_line: String,
+    #[description = "Note shown in /list_admin (leave empty to remove it)"] note: Option<String>,
+) -> Result<(), AppError> {
+    let (game_name, tag_line) = normalize_riot_id(&game_name, &tag_line)?;
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let note = note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+    if note.is_some_and(|note| note.chars().count() > MAX_NOTE_LEN) {
+        ctx.say(format!(
+            "The note can be at most {MAX_NOTE_LEN} characters long."
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let db = &ctx.data().db;
+    let player = db
+        .get_player_by_riot_id(&game_name, &tag_line)
+        .await?
+        .ok_or(AppError::PlayerNotFound {
+            game_name: game_name.clone(),
+            tag_line: tag_line.clone(),
+        })?;
+    if !db.set_admin_note(guild_id.get(), player.id, note).await? {
+        return Err(AppError::PlayerNotTracked);
+    }
+
+    let description = match note {
+        Some(note) => format!(
+            "Note on **{}#{}**: {note}",
+            player.game_name, player.tag_line
+        ),
+        None => format!(
+            "Note on **{}#{}** removed",
+            player.game_name, player.tag_line
+        ),
+    };
+    let embed = serenity::CreateEmbed::new()
+        .title("Admin Note Updated")
+        .description(description)
+        .color(0x0099ff);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(
+        player_id = player.id,
+        noted = note.is_some(),
+        "Admin note updated"
+    );
+
+    Ok(())
+}
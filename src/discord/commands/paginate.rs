@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::{
+    self as serenity, ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton,
+    CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+
+/// How long the buttons stay after the last page turn
+const PAGE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Send page 0 of `page_count` pages rendered by `render`, with Previous/Next
+/// buttons for the invoking user. A single page is sent without buttons,
+/// they are removed once the user stops browsing. Pages are rendered when
+/// shown, so `render` can load just the rows of its page.
+pub(super) async fn paginate<F>(
+    ctx: Context<'_>,
+    page_count: usize,
+    render: impl Fn(usize) -> F,
+) -> Result<(), AppError>
+where
+    F: Future<Output = Result<serenity::CreateEmbed, AppError>>,
+{
+    let page_embed = async |page: usize| -> Result<serenity::CreateEmbed, AppError> {
+        let embed = render(page).await?;
+        Ok(if page_count > 1 {
+            embed.footer(CreateEmbedFooter::new(format!(
+                "Page {}/{page_count}",
+                page + 1
+            )))
+        } else {
+            embed
+        })
+    };
+
+    if page_count <= 1 {
+        ctx.send(poise::CreateReply::default().embed(page_embed(0).await?))
+            .await?;
+        return Ok(());
+    }
+
+    let prev_id = format!("{}-prev", ctx.id());
+    let next_id = format!("{}-next", ctx.id());
+    let buttons = |page: usize| {
+        vec![CreateActionRow::Buttons(vec![
+            CreateButton::new(&prev_id)
+                .label("Previous")
+                .style(ButtonStyle::Secondary)
+                .disabled(page == 0),
+            CreateButton::new(&next_id)
+                .label("Next")
+                .style(ButtonStyle::Secondary)
+                .disabled(page + 1 == page_count),
+        ])]
+    };
+
+    let mut page = 0;
+    let mut embed = page_embed(page).await?;
+    let reply = ctx
+        .send(
+            poise::CreateReply::default()
+                .embed(embed.clone())
+                .components(buttons(page)),
+        )
+        .await?;
+
+    let prefix = ctx.id().to_string();
+    while let Some(interaction) = ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(PAGE_TIMEOUT)
+        .filter({
+            let prefix = prefix.clone();
+            move |mci| mci.data.custom_id.starts_with(&prefix)
+        })
+        .await
+    {
+        page = if interaction.data.custom_id == prev_id {
+            page.saturating_sub(1)
+        } else {
+            (page + 1).min(page_count - 1)
+        };
+        embed = page_embed(page).await?;
+        interaction
+            .create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .embed(embed.clone())
+                        .components(buttons(page)),
+                ),
+            )
+            .await?;
+    }
+
+    reply
+        .edit(
+            ctx,
+            poise::CreateReply::default()
+                .embed(embed)
+                .components(vec![]),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use poise::serenity_prelude::{self as serenity, Mentionable, Permissions};
+use tracing::instrument;
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+
+/// What posting alerts takes in the alert channel
+const ALERT_PERMISSIONS: [(Permissions, &str); 4] = [
+    (Permissions::VIEW_CHANNEL, "View Channel"),
+    (Permissions::SEND_MESSAGES, "Send Messages"),
+    (Permissions::EMBED_LINKS, "Embed Links"),
+    (Permissions::ATTACH_FILES, "Attach Files"),
+];
+
+/// Check the bot can post alerts in the alert channel
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    ephemeral,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn check_permissions(ctx: Context<'_>) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let channel_id = ctx
+        .data()
+        .db
+        .get_guild(guild_id.get())
+        .await?
+        .and_then(|guild| guild.alert_channel_id);
+    let Some(channel_id) = channel_id.map(|id| serenity::ChannelId::new(id.get())) else {
+        ctx.say("No alert channel set, pick one with `/config channel`.")
+            .await?;
+        return Ok(());
+    };
+
+    let Some(channel) = channel_id
+        .to_channel(ctx)
+        .await
+        .ok()
+        .and_then(|c| c.guild())
+    else {
+        ctx.say(format!(
+            "The alert channel {} can't be seen by the bot, it may have been deleted. Pick a new one with `/config channel`.",
+            channel_id.mention()
+        ))
+        .await?;
+        return Ok(());
+    };
+    let guild = guild_id.to_partial_guild(ctx).await?;
+    let member = guild_id.member(ctx, ctx.framework().bot_id).await?;
+    let granted = guild.user_permissions_in(&channel, &member);
+
+    let report = ALERT_PERMISSIONS
+        .iter()
+        .map(|&(permission, name)| {
+            let mark = if granted.contains(permission) {
+                "✅"
+            } else {
+                "❌"
+            };
+            format!("{mark} {name}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let all_granted = ALERT_PERMISSIONS
+        .iter()
+        .all(|&(permission, _)| granted.contains(permission));
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Alert channel permissions")
+        .description(format!("{}\n\n{report}", channel.mention()))
+        .color(if all_granted { 0x00ff00 } else { 0xff6600 })
+        .footer(serenity::CreateEmbedFooter::new(if all_granted {
+            "Alerts can be posted"
+        } else {
+            "Grant the missing permissions to the bot in this channel"
+        }));
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use poise::serenity_prelude::CreateAttachment;
+use tracing::instrument;
+
+use crate::discord::alert_layout::AlertStyle;
+use crate::discord::bot::Context;
+use crate::error::AppError;
+
+use super::sample::{SampleMatch, TestQueueType};
+
+/// Preview this server's alert style on a sample game of each queue
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn preview_alert(
+    ctx: Context<'_>,
+    #[description = "Only this queue (all queues by default)"] queue_type: Option<TestQueueType>,
+    #[description = "Preview a loss instead of a win"] loss: Option<bool>,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+    ctx.defer_ephemeral().await?;
+
+    let guild = ctx.data().db.get_guild(guild_id.get()).await?;
+    let queues = match queue_type {
+        Some(queue_type) => vec![queue_type],
+        None => TestQueueType::ALL.to_vec(),
+    };
+
+    for queue_type in queues {
+        let sample = SampleMatch::new(queue_type, !loss.unwrap_or(false), false);
+        // A server that never ran /config gets the default style, like its alerts
+        let style = guild
+            .as_ref()
+            .map(|guild| AlertStyle::for_guild(guild, sample.match_info()))
+            .unwrap_or_default();
+        let image = ctx
+            .data()
+            .image_gen
+            .generate_match_image(&sample.image_context(&style))
+            .await?;
+
+        ctx.send(
+            poise::CreateReply::default()
+                .content(format!("Preview: **{}**", queue_type.queue().name()))
+                .attachment(CreateAttachment::bytes(image, "match_result.png"))
+                .ephemeral(true),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
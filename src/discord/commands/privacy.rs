@@ -0,0 +1,185 @@
+use poise::serenity_prelude as serenity;
+use tracing::{info, instrument};
+
+use crate::discord::bot::Context;
+use crate::error::AppError;
+use crate::riot::normalize_riot_id;
+
+/// Alias shown when hiding a player without naming one
+const DEFAULT_ALIAS: &str = "Hidden player";
+
+const MAX_ALIAS_LEN: usize = 32;
+
+/// Hide a player's riot id in alerts, for streamers
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(
+    skip(ctx),
+    fields(
+        guild_id,
+        user_id = %ctx.author().id,
+        riot_id = %format!("{}#{}", game_name, tag_line)
+    )
+)]
+pub async fn set_privacy(
+    ctx: Context<'_>,
+    #[description = "Game name (before the #)"] game_name: String,
+    #[description = "Tag line (after the #)"] tag_line: String,
+    #[description = "Show the alias instead of the riot id"] hidden: bool,
+    #[description = "Name shown while hidden (default \"Hidden player\")"] alias: Option<String>,
+) -> Result<(), AppError> {
+    let (game_name, tag_line) = normalize_riot_id(&game_name, &tag_line)?;
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let db = &ctx.data().db;
+    let player = db
+        .get_player_by_riot_id(&game_name, &tag_line)
+        .await?
+        .ok_or(AppError::PlayerNotFound {
+            game_name: game_name.clone(),
+            tag_line: tag_line.clone(),
+        })?;
+    if db
+        .get_tracked_since(guild_id.get(), player.id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::PlayerNotTracked);
+    }
+
+    let alias = match alias.as_deref().map(str::trim) {
+        _ if !hidden => None,
+        Some(alias) if alias.chars().count() > MAX_ALIAS_LEN => {
+            ctx.say(format!(
+                "The alias can be at most {MAX_ALIAS_LEN} characters long."
+            ))
+            .await?;
+            return Ok(());
+        }
+        Some(alias) if !alias.is_empty() => Some(alias.to_string()),
+        _ => Some(DEFAULT_ALIAS.to_string()),
+    };
+
+    db.set_player_privacy_alias(player.id, alias.as_deref())
+        .await?;
+
+    let description = match &alias {
+        Some(alias) => format!(
+            "**{}#{}** now shows up as **{alias}** in alerts",
+            player.game_name, player.tag_line
+        ),
+        None => format!(
+            "**{}#{}** is shown in alerts again",
+            player.game_name, player.tag_line
+        ),
+    };
+    let embed = serenity::CreateEmbed::new()
+        .title("Privacy Updated")
+        .description(description)
+        .color(0x0099ff);
+
+    // The reply echoes the riot id, keep it to the caller
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    info!(player_id = player.id, hidden, "Player privacy updated");
+
+    Ok(())
+}
+
+/// Hold back your own alerts in this server while you're online
+#[poise::command(slash_command, prefix_command, guild_only)]
+#[instrument(
+    skip(ctx),
+    fields(
+        guild_id,
+        user_id = %ctx.author().id,
+        riot_id = %format!("{}#{}", game_name, tag_line)
+    )
+)]
+pub async fn quiet_when_online(
+    ctx: Context<'_>,
+    #[description = "Game name (before the #)"] game_name: String,
+    #[description = "Tag line (after the #)"] tag_line: String,
+    #[description = "Skip the alerts while you're online here"] enabled: bool,
+) -> Result<(), AppError> {
+    let (game_name, tag_line) = normalize_riot_id(&game_name, &tag_line)?;
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let Some(online) = &ctx.data().online else {
+        ctx.send(
+            poise::CreateReply::default()
+                .content("Quieting alerts while online isn't enabled on this bot.")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let db = &ctx.data().db;
+    let player = db
+        .get_player_by_riot_id(&game_name, &tag_line)
+        .await?
+        .ok_or(AppError::PlayerNotFound {
+            game_name: game_name.clone(),
+            tag_line: tag_line.clone(),
+        })?;
+    // Whoever tracked the account stands for it, others can't mute it
+    if !db
+        .set_quiet_user(guild_id.get(), player.id, ctx.author().id.get(), enabled)
+        .await?
+    {
+        ctx.send(
+            poise::CreateReply::default()
+                .content(format!(
+                    "**{}#{}** isn't tracked in this server by you.",
+                    player.game_name, player.tag_line
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+    // Their presence so far, until the next update comes in
+    let status = ctx.guild().and_then(|guild| {
+        guild
+            .presences
+            .get(&ctx.author().id)
+            .map(|presence| presence.status)
+    });
+    online
+        .refresh(db, guild_id.get(), ctx.author().id.get(), status)
+        .await?;
+
+    let description = if enabled {
+        format!(
+            "Alerts of **{}#{}** are held back while you're online in this server",
+            player.game_name, player.tag_line
+        )
+    } else {
+        format!(
+            "Alerts of **{}#{}** are sent whether you're online or not",
+            player.game_name, player.tag_line
+        )
+    };
+    let embed = serenity::CreateEmbed::new()
+        .title("Privacy Updated")
+        .description(description)
+        .color(0x0099ff);
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    info!(player_id = player.id, enabled, "Quiet when online updated");
+
+    Ok(())
+}
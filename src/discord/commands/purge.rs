@@ -0,0 +1,93 @@
+use poise::serenity_prelude as serenity;
+use tracing::{info, instrument};
+
+use crate::activity::ActivityEvent;
+use crate::discord::bot::Context;
+use crate::error::AppError;
+
+use super::confirm::confirm;
+
+/// Delete what the bot keeps about you
+#[poise::command(slash_command, prefix_command, ephemeral)]
+#[instrument(skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn purge_my_data(ctx: Context<'_>) -> Result<(), AppError> {
+    let prompt = serenity::CreateEmbed::new()
+        .title("Delete your data?")
+        .description(
+            "The bot only keeps your Discord id next to the accounts you started tracking \
+             or quieted while online. It will be erased, the accounts stay tracked by their servers.",
+        )
+        .color(0xff6600);
+    if !confirm(ctx, prompt, "Delete").await? {
+        return Ok(());
+    }
+
+    let scrubbed = ctx.data().db.purge_user_data(ctx.author().id.get()).await?;
+    if let Some(online) = &ctx.data().online {
+        online.forget_user(ctx.author().id.get());
+    }
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Data Deleted")
+        .description(format!(
+            "Your Discord id was removed from **{scrubbed}** tracked account(s)"
+        ))
+        .color(0xff6600);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(scrubbed, "User data purged");
+
+    Ok(())
+}
+
+/// Delete everything the bot keeps about this server
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    ephemeral,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn purge_guild_data(ctx: Context<'_>) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let prompt = serenity::CreateEmbed::new()
+        .title("Delete this server's data?")
+        .description(
+            "This deletes the settings, tracked accounts, ladder watches, MVP history and \
+             alert history of this server. Accounts no other server tracks are deleted \
+             with their rank history and aliases. This can't be undone.",
+        )
+        .color(0xff0000);
+    if !confirm(ctx, prompt, "Delete everything").await? {
+        return Ok(());
+    }
+
+    let data = ctx.data();
+    let players = data.db.purge_guild_data(guild_id.get()).await?;
+    data.prefixes.forget_guild(guild_id.get());
+    data.features.forget_guild(guild_id.get());
+    if let Some(online) = &data.online {
+        online.forget_guild(guild_id.get());
+    }
+    // The guild and its alert channel are gone, cached alert targets with them
+    data.activity.publish(ActivityEvent::AlertChannelChanged {
+        guild_id: guild_id.get(),
+    });
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Server Data Deleted")
+        .description(format!(
+            "All data of this server was deleted, along with **{players}** account(s) no other server tracks"
+        ))
+        .color(0xff0000);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(players, "Guild data purged");
+
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use poise::ChoiceParameter;
+
+use crate::db::{Player, RankInfo};
+use crate::discord::alert_layout::AlertStyle;
+use crate::discord::image_gen::MatchImageContext;
+use crate::riot::{InfoDto, ObjectiveDto, ObjectivesDto, ParticipantDto, Queue, TeamDto};
+use crate::stats::{ChampionNote, DivisionForecast, SessionStats};
+
+/// Queues test and preview alerts can be rendered for
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum TestQueueType {
+    #[name = "Normal Blind (430)"]
+    NormalBlind,
+    #[name = "Normal Draft (400)"]
+    NormalDraft,
+    #[name = "Quickplay (490)"]
+    Quickplay,
+    #[name = "Ranked Solo/Duo (420)"]
+    RankedSolo,
+    #[name = "Ranked Flex (440)"]
+    RankedFlex,
+    #[name = "ARAM (450)"]
+    Aram,
+    #[name = "Clash (700)"]
+    Clash,
+}
+
+impl TestQueueType {
+    pub const ALL: [Self; 7] = [
+        Self::NormalBlind,
+        Self::NormalDraft,
+        Self::Quickplay,
+        Self::RankedSolo,
+        Self::RankedFlex,
+        Self::Aram,
+        Self::Clash,
+    ];
+
+    pub fn queue(self) -> Queue {
+        match self {
+            TestQueueType::NormalBlind => Queue::NormalBlind,
+            TestQueueType::NormalDraft => Queue::NormalDraft,
+            TestQueueType::Quickplay => Queue::Quickplay,
+            TestQueueType::RankedSolo => Queue::RankedSolo,
+            TestQueueType::RankedFlex => Queue::RankedFlex,
+            TestQueueType::Aram => Queue::Aram,
+            TestQueueType::Clash => Queue::Clash,
+        }
+    }
+}
+
+/// Made-up game of a mid laner, for test and preview alerts
+pub(super) struct SampleMatch {
+    player: Player,
+    participant: ParticipantDto,
+    match_info: InfoDto,
+    old_rank: Option<RankInfo>,
+    new_rank: Option<RankInfo>,
+    session: SessionStats,
+    champion_note: ChampionNote,
+    forecast: DivisionForecast,
+}
+
+impl SampleMatch {
+    pub(super) fn new(queue_type: TestQueueType, win: bool, remake: bool) -> Self {
+        let queue_id = queue_type.queue().id();
+
+        // Fake player data
+        let player = Player {
+            id: 0,
+            puuid: "test-puuid-12345".to_string(),
+            game_name: "TestPlayer".to_string(),
+            tag_line: "EUW".to_string(),
+            region: "EUW1".to_string(),
+            profile_icon_id: Some(4658),
+            last_match_id: None,
+            last_rank_solo_tier: Some("GOLD".to_string()),
+            last_rank_solo_rank: Some("II".to_string()),
+            last_rank_solo_lp: Some(45),
+            last_rank_flex_tier: Some("SILVER".to_string()),
+            last_rank_flex_rank: Some("I".to_string()),
+            last_rank_flex_lp: Some(75),
+            privacy_alias: None,
+            no_matches_since: None,
+        };
+
+        // Fake participant data
+        let participant = ParticipantDto {
+            puuid: "test-puuid-12345".to_string(),
+            team_id: 100,
+            team_position: "MIDDLE".to_string(),
+            individual_position: "MIDDLE".to_string(),
+            lane: "MIDDLE".to_string(),
+            lane_role: "SOLO".to_string(),
+            // Flash, Ignite
+            summoner1_id: 4,
+            summoner2_id: 14,
+            champion_name: "Ahri".to_string(),
+            kills: 8,
+            deaths: 3,
+            assists: 12,
+            total_damage_dealt_to_champions: 28500,
+            total_minions_killed: 185,
+            neutral_minions_killed: 12,
+            vision_score: 42,
+            gold_earned: 12450,
+            win,
+            // Example items: Luden's, Sorc Shoes, Shadowflame, Rabadon, Void Staff, Zhonya, ward
+            item0: 6655,
+            item1: 3020,
+            item2: 4645,
+            item3: 3089,
+            item4: 3135,
+            item5: 3157,
+            item6: 3364,
+        };
+
+        // Fake match info
+        let match_info = InfoDto {
+            game_creation: 0,
+            game_end_timestamp: None,
+            game_duration: if remake { 180 } else { 1847 },
+            game_version: "14.24.632.8043".to_string(),
+            game_ended_in_early_surrender: remake,
+            game_ended_in_surrender: remake,
+            participants: vec![participant.clone()],
+            queue_id,
+            teams: vec![TeamDto {
+                team_id: 100,
+                objectives: ObjectivesDto {
+                    baron: ObjectiveDto { kills: 1 },
+                    dragon: ObjectiveDto { kills: 3 },
+                    tower: ObjectiveDto { kills: 8 },
+                },
+            }],
+        };
+
+        // Old/new rank for ranked games
+        let (old_rank, new_rank) = if queue_id == 420 {
+            let old = RankInfo {
+                tier: "GOLD".to_string(),
+                rank: "II".to_string(),
+                lp: 45,
+            };
+            let new = RankInfo {
+                tier: "GOLD".to_string(),
+                rank: "II".to_string(),
+                lp: if win { 67 } else { 28 },
+            };
+            (Some(old), Some(new))
+        } else if queue_id == 440 {
+            let old = RankInfo {
+                tier: "SILVER".to_string(),
+                rank: "I".to_string(),
+                lp: 75,
+            };
+            let new = RankInfo {
+                tier: if win {
+                    "GOLD".to_string()
+                } else {
+                    "SILVER".to_string()
+                },
+                rank: if win {
+                    "IV".to_string()
+                } else {
+                    "I".to_string()
+                },
+                lp: if win { 15 } else { 58 },
+            };
+            (Some(old), Some(new))
+        } else {
+            (None, None)
+        };
+
+        Self {
+            session: SessionStats {
+                wins: 3,
+                losses: 1,
+                lp_delta: 42,
+            },
+            champion_note: ChampionNote::FirstTime(participant.champion_name.clone()),
+            forecast: DivisionForecast {
+                games: 6,
+                next: "GOLD I".to_string(),
+                winrate: 58,
+            },
+            player,
+            participant,
+            match_info,
+            old_rank,
+            new_rank,
+        }
+    }
+
+    pub(super) fn match_info(&self) -> &InfoDto {
+        &self.match_info
+    }
+
+    /// What the image generator needs to render the game in `style`
+    pub(super) fn image_context<'a>(&'a self, style: &'a AlertStyle) -> MatchImageContext<'a> {
+        MatchImageContext {
+            player: &self.player,
+            participant: &self.participant,
+            match_info: &self.match_info,
+            old_rank: self.old_rank.as_ref(),
+            new_rank: self.new_rank.as_ref(),
+            style,
+            session: Some(&self.session),
+            champion_note: Some(&self.champion_note),
+            forecast: Some(&self.forecast),
+            clash_team: Some("Baron Buddies"),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use poise::serenity_prelude as serenity;
+use tracing::instrument;
+
+use crate::db::{MatchResult, RankSnapshot};
+use crate::discord::bot::Context;
+use crate::discord::emojis::EmojiStore;
+use crate::discord::image_gen::capitalize;
+use crate::error::AppError;
+
+/// Games shown as the player's recent form
+const RECENT_GAMES: i64 = 10;
+
+/// Show the ranks and recent form of a tracked player
+#[poise::command(slash_command, prefix_command, guild_only)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn stats(
+    ctx: Context<'_>,
+    #[description = "Riot ID game name (before the #)"] game_name: String,
+    #[description = "Riot ID tag (after the #)"] tag: String,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let db = &ctx.data().db;
+    let tag = tag.trim_start_matches('#');
+    // Hidden accounts stay behind their alias, even to whoever knows the Riot ID
+    let Some(player) = db
+        .get_guild_player_by_riot_id(guild_id.get(), &game_name, tag)
+        .await?
+        .filter(|player| !player.is_hidden())
+    else {
+        ctx.say(format!(
+            "**{game_name}#{tag}** isn't tracked in this server, see `/list`."
+        ))
+        .await?;
+        return Ok(());
+    };
+
+    let solo = db
+        .get_latest_rank_snapshot(player.id, "RANKED_SOLO_5x5")
+        .await?;
+    let flex = db
+        .get_latest_rank_snapshot(player.id, "RANKED_FLEX_SR")
+        .await?;
+    let recent = db.get_recent_match_results(player.id, RECENT_GAMES).await?;
+
+    let emojis = &ctx.data().emojis;
+    let embed = serenity::CreateEmbed::new()
+        .title(format!("📈 {}", player.display_name()))
+        .description(player.region.to_uppercase())
+        .field("Solo/Duo", rank_line(emojis, solo.as_ref()), true)
+        .field("Flex", rank_line(emojis, flex.as_ref()), true)
+        .field("Recent form", form_line(&recent), false)
+        .color(0x0099ff);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Rank and season win rate of a queue
+fn rank_line(emojis: &EmojiStore, snapshot: Option<&RankSnapshot>) -> String {
+    let Some(snapshot) = snapshot else {
+        return "Unranked".to_string();
+    };
+    let games = snapshot.wins + snapshot.losses;
+    let win_rate = if games > 0 {
+        format!(" ({}%)", snapshot.wins * 100 / games)
+    } else {
+        String::new()
+    };
+    format!(
+        "{} {} {} {} LP\n{}W-{}L{win_rate}",
+        emojis.tier(&snapshot.tier),
+        capitalize(&snapshot.tier),
+        snapshot.rank,
+        snapshot.lp,
+        snapshot.wins,
+        snapshot.losses
+    )
+}
+
+/// Results of the last alerted games, oldest first, with the average KDA
+fn form_line(recent: &[MatchResult]) -> String {
+    if recent.is_empty() {
+        return "No games recorded yet".to_string();
+    }
+    let results: String = recent
+        .iter()
+        .rev()
+        .map(|game| if game.win { "🟩" } else { "🟥" })
+        .collect();
+    let wins = recent.iter().filter(|game| game.win).count();
+    let average = |stat: fn(&MatchResult) -> i32| {
+        f64::from(recent.iter().map(stat).sum::<i32>()) / recent.len() as f64
+    };
+    format!(
+        "{results}\n{wins}W-{}L • {:.1}/{:.1}/{:.1} average",
+        recent.len() - wins,
+        average(|game| game.kills),
+        average(|game| game.deaths),
+        average(|game| game.assists)
+    )
+}
@@ -1,12 +1,23 @@
+use std::collections::HashSet;
+
 use poise::serenity_prelude as serenity;
+use tokio::task::JoinSet;
 use tracing::{info, instrument, warn};
 
+use crate::activity::ActivityEvent;
+use crate::db::{Game, ResolvedAccount};
 use crate::discord::bot::Context;
+use crate::discord::image_gen::capitalize;
 use crate::error::AppError;
-use crate::riot::Platform;
+
+use super::confirm::confirm;
+use crate::riot::{Platform, RiotClient, normalize_riot_id, parse_riot_id_list};
+
+/// Most Riot IDs one /track_multiple takes, a Clash team and then some
+const MAX_BATCH: usize = 20;
 
 /// Track a League of Legends player
-#[poise::command(slash_command, guild_only)]
+#[poise::command(slash_command, prefix_command, guild_only)]
 #[instrument(
     skip(ctx),
     fields(
@@ -22,6 +33,7 @@ pub async fn track(
     #[description = "Tag line (after the #)"] tag_line: String,
     #[description = "Server region"] region: Platform,
 ) -> Result<(), AppError> {
+    let (game_name, tag_line) = normalize_riot_id(&game_name, &tag_line)?;
     let guild_id = ctx
         .guild_id()
         .ok_or(AppError::Config("Must be used in a guild".into()))?;
@@ -32,8 +44,9 @@ pub async fn track(
     let platform = region;
     let riot_region = platform.to_region();
 
-    // Defer response since API calls might take a moment
-    ctx.defer().await?;
+    // Defer response since API calls might take a moment; the preview is only
+    // shown to the invoking user
+    ctx.defer_ephemeral().await?;
 
     // Get account from Riot API
     let account = ctx
@@ -43,8 +56,16 @@ pub async fn track(
         .await?;
 
     let puuid = &account.puuid;
-    let actual_game_name = account.game_name.as_deref().unwrap_or(&game_name);
-    let actual_tag_line = account.tag_line.as_deref().unwrap_or(&tag_line);
+    let actual_game_name = account
+        .game_name
+        .as_deref()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(&game_name);
+    let actual_tag_line = account
+        .tag_line
+        .as_deref()
+        .filter(|tag| !tag.is_empty())
+        .unwrap_or(&tag_line);
 
     // Get summoner info for profile icon
     let summoner = ctx
@@ -53,6 +74,92 @@ pub async fn track(
         .get_summoner_by_puuid(platform, puuid)
         .await?;
 
+    // A known puuid under another name means the Riot ID was changed
+    let known = ctx
+        .data()
+        .db
+        .get_player_by_identity(Game::Lol, puuid)
+        .await?;
+    if let Some(known) = &known {
+        if let Some(since) = ctx
+            .data()
+            .db
+            .get_tracked_since(guild_id.get(), known.id)
+            .await?
+        {
+            return already_tracked(ctx, actual_game_name, actual_tag_line, since).await;
+        }
+        if !known
+            .riot_id()
+            .eq_ignore_ascii_case(&format!("{actual_game_name}#{actual_tag_line}"))
+        {
+            info!(
+                player_id = known.id,
+                previous = %known.riot_id(),
+                "Player changed Riot ID"
+            );
+        }
+    }
+
+    // Every tracked account costs Riot calls on each poll
+    if let Some(limit) = track_limit(ctx, guild_id.get()).await?
+        && ctx.data().db.count_guild_players(guild_id.get()).await? >= limit
+    {
+        info!(limit, "Track limit reached");
+        ctx.send(
+            poise::CreateReply::default()
+                .content(format!(
+                    "This server already tracks **{limit}** accounts, the most it can. \
+                     Untrack someone with `/untrack` to make room."
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // Preview the account before saving anything, so a typo doesn't track a stranger
+    let entries = ctx
+        .data()
+        .riot
+        .get_league_entries_by_puuid(platform, puuid, true)
+        .await?;
+    let emojis = &ctx.data().emojis;
+    let rank_line = |solo: bool| {
+        entries
+            .iter()
+            .find(|e| {
+                if solo {
+                    e.is_solo_queue()
+                } else {
+                    e.is_flex_queue()
+                }
+            })
+            .map(|e| {
+                format!(
+                    "{} {} {} • {} LP",
+                    emojis.tier(&e.tier),
+                    capitalize(&e.tier),
+                    e.rank,
+                    e.league_points
+                )
+            })
+            .unwrap_or_else(|| "Unranked".to_string())
+    };
+    let preview = serenity::CreateEmbed::new()
+        .title("Track this player?")
+        .description(format!("**{actual_game_name}#{actual_tag_line}**"))
+        .color(0x0099ff)
+        .field("Level", summoner.summoner_level.to_string(), true)
+        .field("Region", platform.display_name(), true)
+        .field("Solo/Duo", rank_line(true), false)
+        .field("Flex", rank_line(false), false);
+
+    if !confirm(ctx, preview, "Track").await? {
+        info!("Tracking cancelled");
+        return Ok(());
+    }
+
     // Save to database
     let player = ctx
         .data()
@@ -82,6 +189,12 @@ pub async fn track(
                         .update_player_last_match(player.id, last_match_id)
                         .await?;
                     info!(last_match_id, "Initialized player's last_match_id");
+                } else {
+                    ctx.data()
+                        .db
+                        .mark_player_without_matches(player.id, ctx.data().clock.unix_now())
+                        .await?;
+                    info!("Player has no games yet");
                 }
             }
             Err(e) => {
@@ -91,26 +204,31 @@ pub async fn track(
         }
     }
 
-    // Check if already tracked in this guild
-    if ctx
+    // Link player to guild; someone may have tracked it while the preview was open
+    let added = ctx
         .data()
         .db
-        .is_player_tracked_in_guild(guild_id.get(), player.id)
-        .await?
-    {
-        ctx.say(format!(
-            "**{}#{}** is already being tracked in this server.",
-            actual_game_name, actual_tag_line
-        ))
+        .add_player_to_guild(
+            guild_id.get(),
+            player.id,
+            user_id.get(),
+            ctx.data().clock.unix_now(),
+        )
         .await?;
-        return Ok(());
+    if !added {
+        let since = ctx
+            .data()
+            .db
+            .get_tracked_since(guild_id.get(), player.id)
+            .await?
+            .unwrap_or_default();
+        return already_tracked(ctx, actual_game_name, actual_tag_line, since).await;
     }
-
-    // Link player to guild
-    ctx.data()
-        .db
-        .add_player_to_guild(guild_id.get(), player.id, user_id.get())
-        .await?;
+    ctx.data().activity.publish(ActivityEvent::AccountTracked {
+        guild_id: guild_id.get(),
+        player_id: player.id,
+        by: user_id.get(),
+    });
 
     // Build response embed
     let embed = serenity::CreateEmbed::new()
@@ -131,3 +249,206 @@ pub async fn track(
 
     Ok(())
 }
+
+/// Track several League of Legends players of one region at once
+#[poise::command(slash_command, prefix_command, guild_only)]
+#[instrument(
+    skip(ctx, players),
+    fields(guild_id, user_id = %ctx.author().id, region = %region)
+)]
+pub async fn track_multiple(
+    ctx: Context<'_>,
+    #[description = "Riot IDs as Name#TAG, separated by commas or new lines"] players: String,
+    #[description = "Server region"] region: Platform,
+) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    let user_id = ctx.author().id;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let entries = parse_riot_id_list(&players);
+    if entries.is_empty() || entries.len() > MAX_BATCH {
+        ctx.send(
+            poise::CreateReply::default()
+                .content(format!(
+                    "Give between 1 and {MAX_BATCH} Riot IDs as `Name#TAG`, separated by commas or new lines."
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    ctx.defer().await?;
+
+    // Look every account up at once, the Riot client paces the calls
+    let mut results: Vec<(String, Result<ResolvedAccount, String>)> = Vec::new();
+    let mut lookups = JoinSet::new();
+    for (index, (entry, riot_id)) in entries.into_iter().enumerate() {
+        let result = match riot_id {
+            Ok((game_name, tag_line)) => {
+                let riot = ctx.data().riot.clone();
+                lookups.spawn(async move {
+                    let account = resolve_account(&riot, region, &game_name, &tag_line).await;
+                    (index, account)
+                });
+                Err("lookup failed".to_string())
+            }
+            Err(e) => Err(e.to_string()),
+        };
+        results.push((entry, result));
+    }
+    while let Some(joined) = lookups.join_next().await {
+        if let Ok((index, account)) = joined {
+            results[index].1 = account.map_err(|e| e.to_string());
+        }
+    }
+
+    // Accounts already here or listed twice don't count against the limit,
+    // the others fill it in order
+    let db = &ctx.data().db;
+    let tracked: HashSet<String> = db
+        .get_guild_players(guild_id.get())
+        .await?
+        .into_iter()
+        .map(|player| player.puuid)
+        .collect();
+    let mut room = track_limit(ctx, guild_id.get())
+        .await?
+        .map(|limit| limit.saturating_sub(tracked.len() as u32));
+    let mut listed = HashSet::new();
+    for (_, result) in &mut results {
+        let Ok(account) = result else {
+            continue;
+        };
+        if tracked.contains(&account.puuid) {
+            *result = Err("already tracked here".to_string());
+        } else if !listed.insert(account.puuid.clone()) {
+            *result = Err("listed more than once".to_string());
+        } else if room == Some(0) {
+            *result = Err("the server reached its track limit".to_string());
+        } else {
+            room = room.map(|room| room - 1);
+        }
+    }
+
+    let accounts: Vec<ResolvedAccount> = results
+        .iter()
+        .filter_map(|(_, result)| result.as_ref().ok().cloned())
+        .collect();
+    let added: HashSet<String> = db
+        .track_accounts(
+            guild_id.get(),
+            user_id.get(),
+            &accounts,
+            ctx.data().clock.unix_now(),
+        )
+        .await?
+        .into_iter()
+        .filter(|(_, added)| *added)
+        .map(|(player, _)| {
+            ctx.data().activity.publish(ActivityEvent::AccountTracked {
+                guild_id: guild_id.get(),
+                player_id: player.id,
+                by: user_id.get(),
+            });
+            player.puuid
+        })
+        .collect();
+
+    let lines: Vec<String> = results
+        .iter()
+        .map(|(entry, result)| match result {
+            Ok(account) if added.contains(&account.puuid) => {
+                format!("✅ **{}#{}**", account.game_name, account.tag_line)
+            }
+            Ok(account) => format!(
+                "➖ **{}#{}**: already tracked here",
+                account.game_name, account.tag_line
+            ),
+            Err(e) => format!("❌ `{entry}`: {e}"),
+        })
+        .collect();
+    let embed = serenity::CreateEmbed::new()
+        .title(format!(
+            "Tracked {} of {} players on {}",
+            added.len(),
+            results.len(),
+            region.display_name()
+        ))
+        .description(lines.join("\n"))
+        .color(if added.len() == results.len() {
+            0x00ff00
+        } else {
+            0xffa500
+        });
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(
+        requested = results.len(),
+        added = added.len(),
+        "Players tracked in batch"
+    );
+
+    Ok(())
+}
+
+/// Account, icon and latest game of a Riot ID, as a single /track saves them
+async fn resolve_account(
+    riot: &RiotClient,
+    platform: Platform,
+    game_name: &str,
+    tag_line: &str,
+) -> Result<ResolvedAccount, AppError> {
+    let account = riot
+        .get_account_by_riot_id(platform.to_region(), game_name, tag_line)
+        .await?;
+    let summoner = riot.get_summoner_by_puuid(platform, &account.puuid).await?;
+    let last_match_id = riot
+        .get_match_ids(platform.to_region(), &account.puuid, 1)
+        .await?
+        .into_iter()
+        .next();
+
+    Ok(ResolvedAccount {
+        game_name: account
+            .game_name
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| game_name.to_string()),
+        tag_line: account
+            .tag_line
+            .filter(|tag| !tag.is_empty())
+            .unwrap_or_else(|| tag_line.to_string()),
+        puuid: account.puuid,
+        region: platform.as_str().to_string(),
+        profile_icon_id: summoner.profile_icon_id,
+        last_match_id,
+    })
+}
+
+/// Accounts the guild may track, `None` without a limit
+pub(super) async fn track_limit(ctx: Context<'_>, guild_id: u64) -> Result<Option<u32>, AppError> {
+    let instance_limit = ctx.data().config.track_limit;
+    Ok(match ctx.data().db.get_guild(guild_id).await? {
+        Some(guild) => guild.track_limit(instance_limit),
+        None => instance_limit,
+    })
+}
+
+async fn already_tracked(
+    ctx: Context<'_>,
+    game_name: &str,
+    tag_line: &str,
+    since: i64,
+) -> Result<(), AppError> {
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!(
+                "**{game_name}#{tag_line}** is already tracked here since <t:{since}:D>."
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
@@ -1,11 +1,17 @@
 use poise::serenity_prelude as serenity;
 use tracing::{info, instrument};
 
+use crate::activity::ActivityEvent;
+use crate::db::UNTRACK_GRACE_DAYS;
 use crate::discord::bot::Context;
 use crate::error::AppError;
+use crate::riot::normalize_riot_id;
+
+use super::confirm::confirm;
+use super::track::track_limit;
 
 /// Stop tracking a League of Legends player
-#[poise::command(slash_command, guild_only)]
+#[poise::command(slash_command, prefix_command, guild_only)]
 #[instrument(
     skip(ctx),
     fields(
@@ -19,6 +25,7 @@ pub async fn untrack(
     #[description = "Game name (before the #)"] game_name: String,
     #[description = "Tag line (after the #)"] tag_line: String,
 ) -> Result<(), AppError> {
+    let (game_name, tag_line) = normalize_riot_id(&game_name, &tag_line)?;
     let guild_id = ctx
         .guild_id()
         .ok_or(AppError::Config("Must be used in a guild".into()))?;
@@ -39,17 +46,23 @@ pub async fn untrack(
     let removed = ctx
         .data()
         .db
-        .remove_player_from_guild(guild_id.get(), player.id)
+        .remove_player_from_guild(guild_id.get(), player.id, ctx.data().clock.unix_now())
         .await?;
 
     if !removed {
         return Err(AppError::PlayerNotTracked);
     }
+    ctx.data()
+        .activity
+        .publish(ActivityEvent::AccountUntracked {
+            guild_id: guild_id.get(),
+            player_id: player.id,
+        });
 
     let embed = serenity::CreateEmbed::new()
         .title("Player Untracked")
         .description(format!(
-            "Stopped tracking **{}#{}**",
+            "Stopped tracking **{}#{}**\nUse `/undo_untrack` within {UNTRACK_GRACE_DAYS} days to restore it",
             player.game_name, player.tag_line
         ))
         .color(0xff6600);
@@ -60,3 +73,122 @@ pub async fn untrack(
 
     Ok(())
 }
+
+/// Restore the player most recently untracked in this server
+#[poise::command(slash_command, prefix_command, guild_only)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn undo_untrack(ctx: Context<'_>) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let db = &ctx.data().db;
+    let Some(player) = db
+        .get_last_untracked_player(guild_id.get(), ctx.data().clock.unix_now())
+        .await?
+    else {
+        ctx.say(format!(
+            "Nothing to restore: no player was untracked here in the last {UNTRACK_GRACE_DAYS} days."
+        ))
+        .await?;
+        return Ok(());
+    };
+
+    // Restoring takes a slot like /track does
+    if let Some(limit) = track_limit(ctx, guild_id.get()).await?
+        && db.count_guild_players(guild_id.get()).await? >= limit
+    {
+        info!(limit, "Track limit reached");
+        ctx.send(
+            poise::CreateReply::default()
+                .content(format!(
+                    "This server already tracks **{limit}** accounts, the most it can. \
+                     Untrack someone with `/untrack` to make room for **{}#{}**.",
+                    player.game_name, player.tag_line
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    db.restore_player_in_guild(guild_id.get(), player.id, ctx.data().clock.unix_now())
+        .await?;
+    ctx.data().activity.publish(ActivityEvent::AccountTracked {
+        guild_id: guild_id.get(),
+        player_id: player.id,
+        by: ctx.author().id.get(),
+    });
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Player Restored")
+        .description(format!(
+            "Tracking **{}#{}** again",
+            player.game_name, player.tag_line
+        ))
+        .color(0x00ff00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(player_id = player.id, "Player untrack undone");
+
+    Ok(())
+}
+
+/// Stop tracking every player in this server
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+#[instrument(skip(ctx), fields(guild_id, user_id = %ctx.author().id))]
+pub async fn untrack_all(ctx: Context<'_>) -> Result<(), AppError> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or(AppError::Config("Must be used in a guild".into()))?;
+    tracing::Span::current().record("guild_id", guild_id.get());
+
+    let db = &ctx.data().db;
+    let players = db.get_guild_players(guild_id.get()).await?;
+    let tracked = players.len();
+    if tracked == 0 {
+        ctx.say("No players are being tracked in this server.")
+            .await?;
+        return Ok(());
+    }
+
+    let prompt = serenity::CreateEmbed::new()
+        .title("Untrack everyone?")
+        .description(format!(
+            "This stops tracking all **{tracked}** player(s) of this server."
+        ))
+        .color(0xff6600);
+    if !confirm(ctx, prompt, "Untrack all").await? {
+        return Ok(());
+    }
+
+    let removed = db
+        .remove_all_players_from_guild(guild_id.get(), ctx.data().clock.unix_now())
+        .await?;
+    for player in &players {
+        ctx.data()
+            .activity
+            .publish(ActivityEvent::AccountUntracked {
+                guild_id: guild_id.get(),
+                player_id: player.id,
+            });
+    }
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Players Untracked")
+        .description(format!("Stopped tracking **{removed}** player(s)"))
+        .color(0xff6600);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    info!(removed, "All players untracked");
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use base64::Engine;
+use poise::serenity_prelude::{GuildId, Http};
+use tracing::{debug, info, instrument, warn};
+
+use crate::db::Repository;
+use crate::error::AppError;
+
+/// Ranked tiers, lowest first, with the unicode shown when no custom emoji exists
+const TIERS: [(&str, &str); 10] = [
+    ("IRON", "🔩"),
+    ("BRONZE", "🥉"),
+    ("SILVER", "🥈"),
+    ("GOLD", "🥇"),
+    ("PLATINUM", "💠"),
+    ("EMERALD", "💚"),
+    ("DIAMOND", "💎"),
+    ("MASTER", "🟣"),
+    ("GRANDMASTER", "🔴"),
+    ("CHALLENGER", "👑"),
+];
+
+const UNRANKED: &str = "▫️";
+
+const CREST_URL: &str = "https://raw.communitydragon.org/latest/plugins/rcp-fe-lol-static-assets/global/default/images/ranked-mini-crests";
+
+fn tier_emoji_name(tier: &str) -> String {
+    format!("tier_{}", tier.to_lowercase())
+}
+
+/// Custom emojis the bot can use in embeds, falling back to unicode
+#[derive(Debug, Default)]
+pub struct EmojiStore {
+    /// Emoji name -> emoji id
+    emojis: RwLock<HashMap<String, u64>>,
+}
+
+impl EmojiStore {
+    pub async fn load(db: &Repository) -> Result<Self, AppError> {
+        let emojis = db
+            .get_emojis()
+            .await?
+            .into_iter()
+            .map(|(name, id)| (name, id.get()))
+            .collect();
+        Ok(Self {
+            emojis: RwLock::new(emojis),
+        })
+    }
+
+    /// Emoji for a ranked tier (`GOLD`, `gold`...), `<:tier_gold:id>` when uploaded
+    pub fn tier(&self, tier: &str) -> String {
+        let name = tier_emoji_name(tier);
+        let custom = self
+            .emojis
+            .read()
+            .ok()
+            .and_then(|emojis| emojis.get(&name).copied());
+
+        match custom {
+            Some(id) => format!("<:{name}:{id}>"),
+            None => TIERS
+                .iter()
+                .find(|(t, _)| t.eq_ignore_ascii_case(tier))
+                .map_or(UNRANKED, |(_, unicode)| unicode)
+                .to_string(),
+        }
+    }
+
+    /// Make sure every tier has an emoji on the emoji server: reuse one already
+    /// named `tier_<tier>`, otherwise upload the crest from Community Dragon.
+    #[instrument(skip_all, fields(guild_id = %guild_id))]
+    pub async fn sync(
+        &self,
+        db: &Repository,
+        http: &Http,
+        guild_id: GuildId,
+    ) -> Result<(), AppError> {
+        let existing: HashMap<String, u64> = guild_id
+            .emojis(http)
+            .await?
+            .into_iter()
+            .map(|emoji| (emoji.name, emoji.id.get()))
+            .collect();
+        let client = reqwest::Client::new();
+
+        let mut uploaded = 0;
+        for (tier, _) in TIERS {
+            let name = tier_emoji_name(tier);
+
+            let id = match existing.get(&name) {
+                Some(&id) => id,
+                None => {
+                    let url = format!("{CREST_URL}/{}.png", tier.to_lowercase());
+                    let bytes = client
+                        .get(&url)
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .bytes()
+                        .await?;
+                    let image = format!(
+                        "data:image/png;base64,{}",
+                        base64::engine::general_purpose::STANDARD.encode(&bytes)
+                    );
+
+                    match guild_id.create_emoji(http, &name, &image).await {
+                        Ok(emoji) => {
+                            uploaded += 1;
+                            emoji.id.get()
+                        }
+                        Err(e) => {
+                            // Usually the server is out of emoji slots, keep the unicode
+                            warn!(error = ?e, tier, "😀 ⚠️ Failed to upload tier emoji");
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            db.set_emoji(&name, id).await?;
+            if let Ok(mut emojis) = self.emojis.write() {
+                emojis.insert(name, id);
+            }
+            debug!(tier, emoji_id = id, "😀 Tier emoji ready");
+        }
+
+        info!(uploaded, "😀 Tier emojis synced");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+
+    use super::EmojiStore;
+
+    #[test]
+    fn tier_prefers_custom_emoji() {
+        let store = EmojiStore {
+            emojis: RwLock::new(HashMap::from([("tier_gold".to_string(), 42)])),
+        };
+        assert_eq!(store.tier("GOLD"), "<:tier_gold:42>");
+        assert_eq!(store.tier("diamond"), "💎");
+        assert_eq!(store.tier(""), "▫️");
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use poise::BoxFuture;
+use poise::serenity_prelude as serenity;
+use tracing::warn;
+
+use crate::error::AppError;
+
+use super::bot::Data;
+
+/// A feature reacting to gateway events, registered on the `EventBus`
+pub trait EventSubscriber: Send + Sync {
+    /// Name of the subscriber in logs
+    fn name(&self) -> &'static str;
+
+    /// Events it handles, by `FullEvent::snake_case_name` (e.g. "guild_delete")
+    fn events(&self) -> &'static [&'static str];
+
+    fn handle<'a>(
+        &'a self,
+        ctx: &'a serenity::Context,
+        event: &'a serenity::FullEvent,
+        data: &'a Data,
+    ) -> BoxFuture<'a, Result<(), AppError>>;
+}
+
+/// Routes each gateway event to the subscribers interested in it, so
+/// features hook into events without growing a single handler.
+///
+/// Shard stage changes never reach the framework, see `GatewayMonitor`.
+#[derive(Default)]
+pub struct EventBus {
+    /// Event name -> subscribers, in registration order
+    subscribers: HashMap<&'static str, Vec<Arc<dyn EventSubscriber>>>,
+}
+
+impl EventBus {
+    pub fn register(mut self, subscriber: Arc<dyn EventSubscriber>) -> Self {
+        for &event in subscriber.events() {
+            self.subscribers
+                .entry(event)
+                .or_default()
+                .push(Arc::clone(&subscriber));
+        }
+        self
+    }
+
+    fn subscribers_of(&self, event: &str) -> &[Arc<dyn EventSubscriber>] {
+        self.subscribers.get(event).map_or(&[], Vec::as_slice)
+    }
+
+    /// Hand the event to its subscribers, a failing one doesn't stop the others
+    pub async fn dispatch(
+        &self,
+        ctx: &serenity::Context,
+        event: &serenity::FullEvent,
+        data: &Data,
+    ) {
+        let name = event.snake_case_name();
+        for subscriber in self.subscribers_of(name) {
+            if let Err(e) = subscriber.handle(ctx, event, data).await {
+                warn!(
+                    error = ?e,
+                    event = name,
+                    subscriber = subscriber.name(),
+                    "🎮 ⚠️ Event subscriber failed"
+                );
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut events: Vec<_> = self.subscribers.keys().collect();
+        events.sort();
+        f.debug_struct("EventBus").field("events", &events).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use poise::BoxFuture;
+    use poise::serenity_prelude as serenity;
+
+    use super::{EventBus, EventSubscriber};
+    use crate::discord::bot::Data;
+    use crate::error::AppError;
+
+    struct Probe(&'static [&'static str]);
+
+    impl EventSubscriber for Probe {
+        fn name(&self) -> &'static str {
+            "probe"
+        }
+
+        fn events(&self) -> &'static [&'static str] {
+            self.0
+        }
+
+        fn handle<'a>(
+            &'a self,
+            _ctx: &'a serenity::Context,
+            _event: &'a serenity::FullEvent,
+            _data: &'a Data,
+        ) -> BoxFuture<'a, Result<(), AppError>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[test]
+    fn subscribers_only_get_their_events() {
+        let bus = EventBus::default()
+            .register(Arc::new(Probe(&["resume", "guild_delete"])))
+            .register(Arc::new(Probe(&["resume"])));
+
+        assert_eq!(bus.subscribers_of("resume").len(), 2);
+        assert_eq!(bus.subscribers_of("guild_delete").len(), 1);
+        assert!(bus.subscribers_of("message").is_empty());
+    }
+}
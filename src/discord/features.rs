@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use poise::ChoiceParameter;
+use tracing::warn;
+
+use crate::db::Repository;
+use crate::error::AppError;
+
+/// Guild id of the switches applying to the whole bot
+const BOT_WIDE: u64 = 0;
+
+/// Features that can be switched off at runtime, all on unless a switch says
+/// otherwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ChoiceParameter)]
+pub enum Feature {
+    #[name = "Image alerts"]
+    ImageAlerts,
+    #[name = "Live tracking"]
+    LiveTracking,
+    #[name = "Digests"]
+    Digests,
+}
+
+impl Feature {
+    pub const ALL: [Self; 3] = [Self::ImageAlerts, Self::LiveTracking, Self::Digests];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ImageAlerts => "image_alerts",
+            Self::LiveTracking => "live_tracking",
+            Self::Digests => "digests",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.as_str() == key)
+    }
+}
+
+/// Feature switches set with /feature, for the whole bot or a single guild.
+/// A guild switch wins over the bot-wide one. Kept in memory since pollers
+/// check it for every guild they post to.
+#[derive(Debug, Default)]
+pub struct FeatureGate {
+    /// (feature, guild id or `BOT_WIDE`) -> enabled
+    flags: RwLock<HashMap<(Feature, u64), bool>>,
+}
+
+impl FeatureGate {
+    pub async fn load(db: &Repository) -> Result<Self, AppError> {
+        let mut flags = HashMap::new();
+        for (key, guild_id, enabled) in db.get_feature_flags().await? {
+            match Feature::from_key(&key) {
+                Some(feature) => {
+                    flags.insert((feature, guild_id.get()), enabled);
+                }
+                None => warn!(feature = %key, "🎮 ⚠️ Ignoring unknown feature flag"),
+            }
+        }
+        Ok(Self {
+            flags: RwLock::new(flags),
+        })
+    }
+
+    /// Whether the feature is on in the guild
+    pub fn is_enabled(&self, feature: Feature, guild_id: u64) -> bool {
+        let Ok(flags) = self.flags.read() else {
+            return true;
+        };
+        flags
+            .get(&(feature, guild_id))
+            .or_else(|| flags.get(&(feature, BOT_WIDE)))
+            .copied()
+            .unwrap_or(true)
+    }
+
+    /// Whether the feature is on for the bot or at least one guild, so jobs
+    /// can skip a whole cycle
+    pub fn is_enabled_anywhere(&self, feature: Feature) -> bool {
+        let Ok(flags) = self.flags.read() else {
+            return true;
+        };
+        flags.get(&(feature, BOT_WIDE)).copied().unwrap_or(true)
+            || flags
+                .iter()
+                .any(|(&(f, guild_id), &enabled)| f == feature && guild_id != BOT_WIDE && enabled)
+    }
+
+    /// Switches currently set, as (feature, guild id or `None` for the bot, enabled)
+    pub fn switches(&self) -> Vec<(Feature, Option<u64>, bool)> {
+        let Ok(flags) = self.flags.read() else {
+            return Vec::new();
+        };
+        let mut switches: Vec<_> = flags
+            .iter()
+            .map(|(&(feature, guild_id), &enabled)| {
+                (feature, (guild_id != BOT_WIDE).then_some(guild_id), enabled)
+            })
+            .collect();
+        switches.sort_by_key(|&(feature, guild_id, _)| (feature.as_str(), guild_id));
+        switches
+    }
+
+    /// Save a switch for a guild, or the whole bot when `guild_id` is `None`.
+    /// `None` for `enabled` removes the switch.
+    pub async fn set(
+        &self,
+        db: &Repository,
+        feature: Feature,
+        guild_id: Option<u64>,
+        enabled: Option<bool>,
+    ) -> Result<(), AppError> {
+        let guild_id = guild_id.unwrap_or(BOT_WIDE);
+        db.set_feature_flag(feature.as_str(), guild_id, enabled)
+            .await?;
+
+        if let Ok(mut flags) = self.flags.write() {
+            match enabled {
+                Some(enabled) => flags.insert((feature, guild_id), enabled),
+                None => flags.remove(&(feature, guild_id)),
+            };
+        }
+        Ok(())
+    }
+
+    /// Drop the switches of a guild whose data was purged
+    pub fn forget_guild(&self, guild_id: u64) {
+        if let Ok(mut flags) = self.flags.write() {
+            flags.retain(|&(_, flag_guild), _| flag_guild != guild_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+
+    use super::{BOT_WIDE, Feature, FeatureGate};
+
+    #[test]
+    fn guild_switches_win_over_bot_wide_ones() {
+        let gate = FeatureGate {
+            flags: RwLock::new(HashMap::from([
+                ((Feature::LiveTracking, BOT_WIDE), false),
+                ((Feature::LiveTracking, 42), true),
+                ((Feature::Digests, 7), false),
+            ])),
+        };
+
+        assert!(gate.is_enabled(Feature::ImageAlerts, 42));
+        assert!(gate.is_enabled(Feature::LiveTracking, 42));
+        assert!(!gate.is_enabled(Feature::LiveTracking, 7));
+        assert!(gate.is_enabled_anywhere(Feature::LiveTracking));
+        assert!(!gate.is_enabled(Feature::Digests, 7));
+        assert!(gate.is_enabled(Feature::Digests, 42));
+    }
+}
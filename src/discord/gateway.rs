@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use poise::BoxFuture;
+use poise::serenity_prelude::{self as serenity, ConnectionStage};
+use tracing::{info, warn};
+
+use crate::clock::Clock;
+use crate::error::AppError;
+
+use super::bot::Data;
+use super::events::EventSubscriber;
+use super::ops::OpsNotifier;
+
+/// Disconnects older than this no longer count toward flapping
+const FLAP_WINDOW_SECS: i64 = 600;
+
+/// Disconnects within the window before the ops channel is alerted
+const FLAP_THRESHOLD: usize = 5;
+
+/// Gateway disconnects and resumes seen since start
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GatewaySnapshot {
+    pub disconnects: u64,
+    pub resumes: u64,
+}
+
+/// Watches shard connection changes and warns the ops channel when the
+/// gateway keeps dropping. Pollers and the dispatcher only use the HTTP API,
+/// so alerts keep flowing while the gateway reconnects.
+#[derive(Debug)]
+pub struct GatewayMonitor {
+    ops_channel: Option<serenity::ChannelId>,
+    clock: Arc<dyn Clock>,
+    disconnects: AtomicU64,
+    resumes: AtomicU64,
+    flaps: Mutex<FlapDetector>,
+}
+
+impl GatewayMonitor {
+    pub fn new(ops_channel: Option<serenity::ChannelId>, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            ops_channel,
+            clock,
+            disconnects: AtomicU64::new(0),
+            resumes: AtomicU64::new(0),
+            flaps: Mutex::new(FlapDetector::default()),
+        }
+    }
+
+    pub fn snapshot(&self) -> GatewaySnapshot {
+        GatewaySnapshot {
+            disconnects: self.disconnects.load(Ordering::Relaxed),
+            resumes: self.resumes.load(Ordering::Relaxed),
+        }
+    }
+
+    async fn record_disconnect(
+        &self,
+        ctx: &serenity::Context,
+        shard_id: serenity::ShardId,
+        stage: ConnectionStage,
+    ) {
+        let total = self.disconnects.fetch_add(1, Ordering::Relaxed) + 1;
+        warn!(shard_id = shard_id.0, %stage, total, "🎮 ⚠️ Gateway connection lost");
+
+        let flapping = self
+            .flaps
+            .lock()
+            .map(|mut flaps| flaps.record(self.clock.unix_now()))
+            .unwrap_or(false);
+        if flapping {
+            OpsNotifier::new(Arc::clone(&ctx.http), self.ops_channel)
+                .notify(&format!(
+                    "⚠️ Discord gateway is flapping: {FLAP_THRESHOLD}+ disconnects in the last {} minutes ({total} since start)",
+                    FLAP_WINDOW_SECS / 60
+                ))
+                .await;
+        }
+    }
+}
+
+/// Sliding window of recent disconnects, firing once per flapping episode
+#[derive(Debug, Default)]
+struct FlapDetector {
+    recent: VecDeque<i64>,
+    alerted: bool,
+}
+
+impl FlapDetector {
+    /// Record a disconnect at `now`, true when it starts a flapping episode
+    fn record(&mut self, now: i64) -> bool {
+        self.recent.push_back(now);
+        while self
+            .recent
+            .front()
+            .is_some_and(|&at| now - at >= FLAP_WINDOW_SECS)
+        {
+            self.recent.pop_front();
+        }
+
+        if self.recent.len() < FLAP_THRESHOLD {
+            self.alerted = false;
+            return false;
+        }
+        !std::mem::replace(&mut self.alerted, true)
+    }
+}
+
+/// Stage changes only reach event handlers, not the poise framework
+#[serenity::async_trait]
+impl serenity::EventHandler for GatewayMonitor {
+    async fn shard_stage_update(
+        &self,
+        ctx: serenity::Context,
+        event: serenity::ShardStageUpdateEvent,
+    ) {
+        match (event.old, event.new) {
+            (ConnectionStage::Connected, stage) => {
+                self.record_disconnect(&ctx, event.shard_id, stage).await;
+            }
+            (_, ConnectionStage::Connected) => {
+                info!(shard_id = event.shard_id.0, "🎮 ✅ Gateway connected");
+            }
+            _ => {}
+        }
+    }
+}
+
+impl EventSubscriber for GatewayMonitor {
+    fn name(&self) -> &'static str {
+        "gateway_monitor"
+    }
+
+    fn events(&self) -> &'static [&'static str] {
+        &["resume"]
+    }
+
+    fn handle<'a>(
+        &'a self,
+        _ctx: &'a serenity::Context,
+        _event: &'a serenity::FullEvent,
+        _data: &'a Data,
+    ) -> BoxFuture<'a, Result<(), AppError>> {
+        let total = self.resumes.fetch_add(1, Ordering::Relaxed) + 1;
+        info!(total, "🎮 Gateway session resumed");
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FLAP_THRESHOLD, FLAP_WINDOW_SECS, FlapDetector};
+
+    #[test]
+    fn flapping_alerts_once_per_episode() {
+        let mut flaps = FlapDetector::default();
+        let fired: Vec<bool> = (0..FLAP_THRESHOLD as i64 + 2)
+            .map(|i| flaps.record(i * 10))
+            .collect();
+        assert_eq!(fired.iter().filter(|&&f| f).count(), 1);
+        assert!(fired[FLAP_THRESHOLD - 1]);
+
+        // Once the window drains, the next episode alerts again
+        let later = FLAP_WINDOW_SECS * 10;
+        assert!(!flaps.record(later));
+        let refired = (1..FLAP_THRESHOLD as i64).any(|i| flaps.record(later + i));
+        assert!(refired);
+    }
+}
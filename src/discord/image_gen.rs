@@ -4,24 +4,49 @@ use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
 use base64::Engine;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
 use tiny_skia::Pixmap;
 use tokio::fs;
-use tokio::sync::RwLock;
+use tokio::sync::{OnceCell, RwLock};
 use tracing::{debug, info, trace, warn};
 use usvg::fontdb::Database;
 use usvg::{Options, Tree};
 
 use crate::db::{Player, RankInfo};
+use crate::discord::alert_layout::{AlertStyle, StatField};
+use crate::discord::assets::{Asset, AssetResolver};
+use crate::discord::lp_chart::{LpSeries, chart_svg, escape};
+use crate::discord::moderation::ContentFilter;
 use crate::error::AppError;
-use crate::riot::{InfoDto, ParticipantDto};
+use crate::riot::{GameEnding, InfoDto, ParticipantDto};
+use crate::stats::{
+    ChampionNote, DivisionForecast, RankChange, SessionStats, rank_change, rank_to_lp,
+};
 
 const SVG_TEMPLATE: &str = include_str!("../../assets/match_template.svg");
+/// Drawn in place of an asset no CDN has
+const PLACEHOLDER_ICON: &str = include_str!("../../assets/placeholder_icon.svg");
+/// Width of the template, in SVG units
+const IMAGE_WIDTH: u32 = 800;
+/// Longest Clash team name drawn, keeps it clear of the result banner
+const MAX_TEAM_NAME_CHARS: usize = 20;
 
 // Cache configuration
 const CACHE_TTL_HOURS: u64 = 24 * 7; // 7 days
 const CACHE_MAX_SIZE_MB: u64 = 100; // 100 MB max
-const CACHE_DIR: &str = ".cache/images";
+const MISSING_TTL_SECS: u64 = 3600; // Retry missing assets after an hour
+
+/// Data Dragon `item.json`, only the names are read
+#[derive(Debug, Deserialize)]
+struct ItemData {
+    data: HashMap<String, ItemEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ItemEntry {
+    name: String,
+}
 
 /// Metadata for cached images
 #[derive(Debug, Clone)]
@@ -34,15 +59,15 @@ struct CacheEntry {
 /// Cache for Data Dragon images with disk persistence, TTL, and size limit
 pub struct ImageCache {
     memory_cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
+    /// URLs that answered 404, with when they did
+    missing: Arc<RwLock<HashMap<String, SystemTime>>>,
     cache_dir: PathBuf,
     ttl: Duration,
     max_size_bytes: u64,
 }
 
 impl ImageCache {
-    pub async fn new() -> Self {
-        let cache_dir = PathBuf::from(CACHE_DIR);
-
+    pub async fn new(cache_dir: PathBuf) -> Self {
         // Create cache directory if it doesn't exist
         if let Err(e) = fs::create_dir_all(&cache_dir).await {
             warn!(error = ?e, "🖼️ ⚠️ Failed to create cache directory");
@@ -50,6 +75,7 @@ impl ImageCache {
 
         let cache = Self {
             memory_cache: Arc::new(RwLock::new(HashMap::new())),
+            missing: Arc::new(RwLock::new(HashMap::new())),
             cache_dir,
             ttl: Duration::from_secs(CACHE_TTL_HOURS * 3600),
             max_size_bytes: CACHE_MAX_SIZE_MB * 1024 * 1024,
@@ -155,7 +181,7 @@ impl ImageCache {
             .iter()
             .map(|(key, entry)| (key.clone(), entry.created_at))
             .collect();
-        entries.sort_by(|a, b| a.1.cmp(&b.1));
+        entries.sort_by_key(|(_, created_at)| *created_at);
 
         let mut freed: u64 = 0;
         let target_free = current_size - (self.max_size_bytes * 80 / 100); // Free to 80% capacity
@@ -201,6 +227,18 @@ impl ImageCache {
             }
         }
 
+        // A new patch can take a while to reach the CDN, don't ask again
+        // for every alert in the meantime
+        {
+            let missing = self.missing.read().await;
+            if let Some(since) = missing.get(url)
+                && since.elapsed().unwrap_or(Duration::MAX).as_secs() < MISSING_TTL_SECS
+            {
+                trace!(url, "🖼️ Known missing asset");
+                return None;
+            }
+        }
+
         // Check disk cache
         let cache_path = self.get_cache_path(&key);
         if cache_path.exists()
@@ -271,6 +309,10 @@ impl ImageCache {
                 }
             }
             Ok(response) => {
+                if response.status() == StatusCode::NOT_FOUND {
+                    let mut missing = self.missing.write().await;
+                    missing.insert(url.to_string(), SystemTime::now());
+                }
                 warn!(
                     url,
                     status = response.status().as_u16(),
@@ -292,17 +334,34 @@ pub struct MatchImageContext<'a> {
     pub match_info: &'a InfoDto,
     pub old_rank: Option<&'a RankInfo>,
     pub new_rank: Option<&'a RankInfo>,
+    /// Guild-specific rendering options
+    pub style: &'a AlertStyle,
+    /// Ranked results of the day, in the guild's timezone
+    pub session: Option<&'a SessionStats>,
+    /// First time or signature champion remark
+    pub champion_note: Option<&'a ChampionNote>,
+    /// Games to the next division, shown when the style asks for it
+    pub forecast: Option<&'a DivisionForecast>,
+    /// Clash team of the player, when Riot still has the registration
+    pub clash_team: Option<&'a str>,
 }
 
 pub struct ImageGenerator {
     http: Client,
     cache: ImageCache,
-    ddragon_version: String,
+    assets: AssetResolver,
+    /// Item names by id, loaded on first use
+    item_names: OnceCell<HashMap<i32, String>>,
     fontdb: Database,
+    content_filter: Arc<ContentFilter>,
 }
 
 impl ImageGenerator {
-    pub async fn new(ddragon_version: String) -> Result<Self, AppError> {
+    pub async fn new(
+        ddragon_version: String,
+        cache_dir: PathBuf,
+        content_filter: Arc<ContentFilter>,
+    ) -> Result<Self, AppError> {
         let http = Client::builder().user_agent("Tentrackule/2.0").build()?;
 
         // Load system fonts
@@ -312,16 +371,23 @@ impl ImageGenerator {
         info!(font_count, "🖼️ Loaded system fonts");
 
         // Initialize cache (loads from disk)
-        let cache = ImageCache::new().await;
+        let cache = ImageCache::new(cache_dir).await;
 
         Ok(Self {
             http,
             cache,
-            ddragon_version,
+            assets: AssetResolver::new(ddragon_version),
+            item_names: OnceCell::new(),
             fontdb,
+            content_filter,
         })
     }
 
+    /// Rules applied to the names written on alerts
+    pub fn content_filter(&self) -> &ContentFilter {
+        &self.content_filter
+    }
+
     pub async fn generate_match_image(
         &self,
         ctx: &MatchImageContext<'_>,
@@ -333,23 +399,21 @@ impl ImageGenerator {
     async fn build_svg(&self, ctx: &MatchImageContext<'_>) -> String {
         let participant = ctx.participant;
         let match_info = ctx.match_info;
-        let is_win = participant.win;
-        let is_remake = match_info.game_ended_in_early_surrender;
 
         // Result styling based on outcome
-        let (banner_gradient, result_glow, result_text) = if is_remake {
-            ("url(#remakeGradient)", "", "REMAKE")
-        } else if is_win {
-            ("url(#victoryGradient)", "url(#victoryGlow)", "VICTORY")
-        } else {
-            ("url(#defeatGradient)", "url(#defeatGlow)", "DEFEAT")
-        };
+        let (banner_gradient, result_glow, result_text) =
+            result_banner(match_info.ending(), participant.win);
 
         // Fetch images in parallel
-        let champion_fut = self.fetch_champion_image(&participant.champion_name);
+        let champion_fut = self.fetch_asset(Asset::Champion(&participant.champion_name));
         let profile_fut = async {
-            if let Some(icon_id) = ctx.player.profile_icon_id {
-                self.fetch_profile_icon(icon_id).await
+            // The profile icon would give a hidden account away
+            if let Some(icon_id) = ctx
+                .player
+                .profile_icon_id
+                .filter(|_| !ctx.player.is_hidden())
+            {
+                self.fetch_asset(Asset::ProfileIcon(icon_id)).await
             } else {
                 None
             }
@@ -357,7 +421,11 @@ impl ImageGenerator {
         let items = participant.items();
         let fetch_item = |item_id| async move {
             if item_id > 0 {
-                self.fetch_item_image(item_id).await
+                Some(
+                    self.fetch_asset(Asset::Item(item_id))
+                        .await
+                        .unwrap_or_else(placeholder_image),
+                )
             } else {
                 None
             }
@@ -374,15 +442,20 @@ impl ImageGenerator {
             fetch_item(items[6])
         );
         let item_images = [item0, item1, item2, item3, item4, item5, item6];
-        let champion_image = champion_image.unwrap_or_default();
-        let profile_icon = profile_icon.unwrap_or_default();
+        let champion_image = champion_image.unwrap_or_else(placeholder_image);
+        // Hidden accounts keep an empty frame rather than a stand-in face
+        let profile_icon = match profile_icon {
+            Some(icon) => icon,
+            None if ctx.player.is_hidden() => String::new(),
+            None => placeholder_image(),
+        };
 
         // Stats
         let cs = participant.cs_total();
         let cs_per_min = format!("{:.1}", participant.cs_per_minute(match_info.game_duration));
         let damage = format_damage(participant.total_damage_dealt_to_champions);
         let vision = participant.vision_score.to_string();
-        let role = participant.position_display();
+        let role = participant.role().to_string();
         let gold = participant.gold_formatted();
 
         // Rank info
@@ -399,7 +472,7 @@ impl ImageGenerator {
         svg = svg.replace("{{profile_icon}}", &profile_icon);
         svg = svg.replace(
             "{{player_name}}",
-            &format!("{}#{}", ctx.player.game_name, ctx.player.tag_line),
+            &self.content_filter.apply(&ctx.player.display_name()),
         );
         svg = svg.replace("{{queue_type}}", match_info.queue_name());
         svg = svg.replace("{{duration}}", &match_info.duration_formatted());
@@ -412,7 +485,7 @@ impl ImageGenerator {
         svg = svg.replace("{{cs_per_min}}", &cs_per_min);
         svg = svg.replace("{{damage}}", &damage);
         svg = svg.replace("{{vision}}", &vision);
-        svg = svg.replace("{{role}}", role);
+        svg = svg.replace("{{role}}", &role);
         svg = svg.replace("{{gold}}", &gold);
         svg = svg.replace("{{rank_display}}", &rank_display);
         svg = svg.replace("{{lp_change}}", &lp_change);
@@ -440,10 +513,100 @@ impl ImageGenerator {
             }
         }
 
-        // Handle ARAM-specific layout (2 stats) vs normal layout (4 stats)
-        let is_aram = match_info.queue_id == 450;
-        svg = Self::handle_conditional_block(&svg, "stats_normal", !is_aram);
-        svg = Self::handle_conditional_block(&svg, "stats_aram", is_aram);
+        // Team objectives line on ranked games
+        let objectives = (ctx.style.show_objectives && match_info.is_ranked())
+            .then(|| match_info.team(participant.team_id))
+            .flatten()
+            .map(|team| team.objectives.summary());
+        svg = Self::handle_conditional_block(&svg, "objectives", objectives.is_some());
+        svg = svg.replace("{{objectives}}", &objectives.unwrap_or_default());
+
+        // Session line on ranked games
+        let session = ctx.session.filter(|_| match_info.is_ranked());
+        svg = Self::handle_conditional_block(&svg, "session", session.is_some());
+        svg = svg.replace(
+            "{{session}}",
+            &session.map(SessionStats::summary).unwrap_or_default(),
+        );
+
+        let forecast = ctx
+            .forecast
+            .filter(|_| ctx.style.show_forecast && match_info.is_ranked());
+        svg = Self::handle_conditional_block(&svg, "forecast", forecast.is_some());
+        svg = svg.replace(
+            "{{forecast}}",
+            &forecast.map(DivisionForecast::summary).unwrap_or_default(),
+        );
+
+        // Division and tier changes called out on the banner, the LP alone
+        // reads oddly across them
+        let change = ctx
+            .old_rank
+            .zip(ctx.new_rank)
+            .filter(|_| match_info.is_ranked())
+            .and_then(|(old, new)| Some((rank_change(old, new)?, new)));
+        svg = Self::handle_conditional_block(&svg, "rank_change", change.is_some());
+        if let Some((change, new)) = change {
+            let color = match change {
+                RankChange::Promoted => "#f0c040",
+                RankChange::Demoted => "#ffffff",
+            };
+            svg = svg.replace("{{rank_change}}", &change.banner(new));
+            svg = svg.replace("{{rank_change_color}}", color);
+        }
+
+        // Clash games are tournament games, framed and named after the team
+        let tournament = match_info.is_clash().then(|| match ctx.clash_team {
+            Some(team) => {
+                let team: String = self
+                    .content_filter
+                    .apply(team)
+                    .chars()
+                    .take(MAX_TEAM_NAME_CHARS)
+                    .collect();
+                format!("CLASH • {}", escape(&team))
+            }
+            None => "CLASH TOURNAMENT".to_string(),
+        });
+        svg = Self::handle_conditional_block(&svg, "tournament", tournament.is_some());
+        svg = svg.replace("{{tournament}}", &tournament.unwrap_or_default());
+
+        svg = Self::handle_conditional_block(&svg, "champion_note", ctx.champion_note.is_some());
+        svg = svg.replace(
+            "{{champion_note}}",
+            &ctx.champion_note
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        );
+
+        Self::layout_stat_fields(svg, &ctx.style.fields)
+    }
+
+    /// Spread the chosen stat columns evenly across the stats bar, in order,
+    /// and drop the blocks of the others
+    fn layout_stat_fields(mut svg: String, fields: &[StatField]) -> String {
+        let column_width = IMAGE_WIDTH / fields.len().max(1) as u32;
+
+        let separators = (1..fields.len() as u32)
+            .map(|i| {
+                let x = i * column_width;
+                format!(
+                    r##"<line x1="{x}" y1="260" x2="{x}" y2="315" stroke="#2a3a4a" stroke-width="1"/>"##
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n  ");
+        svg = svg.replace("{{stats_separators}}", &separators);
+
+        for field in StatField::ALL {
+            let name = format!("field_{}", field.key());
+            let position = fields.iter().position(|f| *f == field);
+            svg = Self::handle_conditional_block(&svg, &name, position.is_some());
+            if let Some(index) = position {
+                let x = index as u32 * column_width + column_width / 2;
+                svg = svg.replace(&format!("{{{{{name}_x}}}}"), &x.to_string());
+            }
+        }
 
         svg
     }
@@ -496,28 +659,65 @@ impl ImageGenerator {
         (rank_display, lp_change, lp_color, lp_x.to_string())
     }
 
-    async fn fetch_champion_image(&self, champion_name: &str) -> Option<String> {
-        let url = format!(
-            "https://ddragon.leagueoflegends.com/cdn/{}/img/champion/{}.png",
-            self.ddragon_version, champion_name
-        );
-        self.cache.get_or_fetch(&self.http, &url).await
+    /// First CDN that has the asset, as a data URI
+    async fn fetch_asset(&self, asset: Asset<'_>) -> Option<String> {
+        for url in self.assets.urls(asset) {
+            if let Some(data_uri) = self.cache.get_or_fetch(&self.http, &url).await {
+                return Some(data_uri);
+            }
+        }
+        None
     }
 
-    async fn fetch_profile_icon(&self, icon_id: i32) -> Option<String> {
-        let url = format!(
-            "https://ddragon.leagueoflegends.com/cdn/{}/img/profileicon/{}.png",
-            self.ddragon_version, icon_id
-        );
-        self.cache.get_or_fetch(&self.http, &url).await
+    /// Item names by id, `None` while Data Dragon can't be reached
+    pub async fn item_names(&self) -> Option<&HashMap<i32, String>> {
+        let loaded = self
+            .item_names
+            .get_or_try_init(|| async {
+                let data: ItemData = self
+                    .http
+                    .get(self.assets.item_data_url())
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                let names: HashMap<i32, String> = data
+                    .data
+                    .into_iter()
+                    .filter_map(|(id, item)| Some((id.parse().ok()?, item.name)))
+                    .collect();
+                debug!(count = names.len(), "🖼️ Item names loaded");
+                Ok::<_, reqwest::Error>(names)
+            })
+            .await;
+        match loaded {
+            Ok(names) => Some(names),
+            Err(e) => {
+                warn!(error = ?e, "🖼️ ⚠️ Failed to load item names");
+                None
+            }
+        }
     }
 
-    async fn fetch_item_image(&self, item_id: i32) -> Option<String> {
-        let url = format!(
-            "https://ddragon.leagueoflegends.com/cdn/{}/img/item/{}.png",
-            self.ddragon_version, item_id
-        );
-        self.cache.get_or_fetch(&self.http, &url).await
+    /// LP chart of `[from, to)` as a PNG, `None` when there is nothing to draw
+    pub fn generate_lp_chart(
+        &self,
+        series: &[LpSeries],
+        from: i64,
+        to: i64,
+    ) -> Result<Option<Vec<u8>>, AppError> {
+        // Legend names go through the rules like every other rendered name
+        let series: Vec<LpSeries> = series
+            .iter()
+            .map(|line| LpSeries {
+                name: self.content_filter.apply(&line.name),
+                points: line.points.clone(),
+            })
+            .collect();
+        chart_svg(&series, from, to)
+            .map(|svg| self.render_svg_to_png(&svg))
+            .transpose()
     }
 
     fn render_svg_to_png(&self, svg_content: &str) -> Result<Vec<u8>, AppError> {
@@ -555,6 +755,12 @@ impl ImageGenerator {
     }
 }
 
+/// Generic icon, as a data URI, for assets that couldn't be fetched
+fn placeholder_image() -> String {
+    let b64 = base64::engine::general_purpose::STANDARD.encode(PLACEHOLDER_ICON);
+    format!("data:image/svg+xml;base64,{}", b64)
+}
+
 fn format_damage(damage: i64) -> String {
     if damage >= 1_000_000 {
         format!("{:.1}M", damage as f64 / 1_000_000.0)
@@ -565,39 +771,31 @@ fn format_damage(damage: i64) -> String {
     }
 }
 
-fn calculate_lp_diff(old_rank: Option<&RankInfo>, new_rank: Option<&RankInfo>) -> Option<i32> {
+/// Banner (gradient, glow, text) for the outcome of the game
+fn result_banner(ending: GameEnding, win: bool) -> (&'static str, &'static str, &'static str) {
+    match ending {
+        GameEnding::Remake => ("url(#remakeGradient)", "", "REMAKE"),
+        _ if win => ("url(#victoryGradient)", "url(#victoryGlow)", "VICTORY"),
+        // The player's team FF'd
+        GameEnding::Surrender => (
+            "url(#surrenderGradient)",
+            "url(#defeatGlow)",
+            "DEFEAT (SURRENDERED)",
+        ),
+        GameEnding::Full => ("url(#defeatGradient)", "url(#defeatGlow)", "DEFEAT"),
+    }
+}
+
+pub(crate) fn calculate_lp_diff(
+    old_rank: Option<&RankInfo>,
+    new_rank: Option<&RankInfo>,
+) -> Option<i32> {
     let old = old_rank?;
     let new = new_rank?;
     Some(rank_to_lp(new) - rank_to_lp(old))
 }
 
-fn rank_to_lp(rank: &RankInfo) -> i32 {
-    let tier_value = match rank.tier.to_uppercase().as_str() {
-        "IRON" => 0,
-        "BRONZE" => 400,
-        "SILVER" => 800,
-        "GOLD" => 1200,
-        "PLATINUM" => 1600,
-        "EMERALD" => 2000,
-        "DIAMOND" => 2400,
-        "MASTER" => 2800,
-        "GRANDMASTER" => 3200,
-        "CHALLENGER" => 3600,
-        _ => 0,
-    };
-
-    let division_value = match rank.rank.as_str() {
-        "IV" => 0,
-        "III" => 100,
-        "II" => 200,
-        "I" => 300,
-        _ => 0,
-    };
-
-    tier_value + division_value + rank.lp
-}
-
-fn capitalize(s: &str) -> String {
+pub(crate) fn capitalize(s: &str) -> String {
     let lower = s.to_lowercase();
     let mut chars = lower.chars();
     match chars.next() {
@@ -608,7 +806,12 @@ fn capitalize(s: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{RankInfo, calculate_lp_diff, format_damage, rank_to_lp};
+    use super::{
+        PLACEHOLDER_ICON, RankInfo, calculate_lp_diff, format_damage, placeholder_image,
+        result_banner,
+    };
+    use crate::riot::GameEnding;
+    use crate::stats::rank_to_lp;
 
     #[test]
     fn format_damage_suffixes() {
@@ -617,6 +820,25 @@ mod tests {
         assert_eq!(format_damage(1_000_000), "1.0M");
     }
 
+    #[test]
+    fn placeholder_is_a_drawable_svg() {
+        assert!(placeholder_image().starts_with("data:image/svg+xml;base64,"));
+        assert!(usvg::Tree::from_str(PLACEHOLDER_ICON, &usvg::Options::default()).is_ok());
+    }
+
+    #[test]
+    fn result_banner_marks_surrendered_defeats() {
+        assert_eq!(result_banner(GameEnding::Remake, true).2, "REMAKE");
+        assert_eq!(result_banner(GameEnding::Remake, false).2, "REMAKE");
+        // The enemy FF'd, a plain victory
+        assert_eq!(result_banner(GameEnding::Surrender, true).2, "VICTORY");
+
+        let (gradient, _, text) = result_banner(GameEnding::Surrender, false);
+        assert_eq!(text, "DEFEAT (SURRENDERED)");
+        assert_eq!(gradient, "url(#surrenderGradient)");
+        assert_eq!(result_banner(GameEnding::Full, false).2, "DEFEAT");
+    }
+
     #[test]
     fn rank_lp_math() {
         let gold_ii = RankInfo {
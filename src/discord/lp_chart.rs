@@ -0,0 +1,214 @@
+//! LP over time of a guild's players, drawn as an SVG line chart for the
+//! weekly recap
+
+use std::fmt::Write;
+
+use crate::db::RankPoint;
+use crate::stats::rank_to_lp;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 400.0;
+/// Room for the division labels on the left and the legend at the bottom
+const PLOT_LEFT: f64 = 110.0;
+const PLOT_RIGHT: f64 = 780.0;
+const PLOT_TOP: f64 = 30.0;
+const PLOT_BOTTOM: f64 = 320.0;
+/// Players drawn, the ones with the most LP movement first
+const MAX_SERIES: usize = 8;
+const COLORS: [&str; MAX_SERIES] = [
+    "#c89b3c", "#0ac8b9", "#e84057", "#4caf50", "#9c6ade", "#f5a623", "#5ab0f5", "#f06292",
+];
+const FONT: &str = "Noto Sans CJK SC, Noto Sans, DejaVu Sans, sans-serif";
+
+/// LP of one player over time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LpSeries {
+    pub name: String,
+    /// (Unix time, LP on the single ladder of `rank_to_lp`), oldest first
+    pub points: Vec<(i64, i32)>,
+}
+
+impl LpSeries {
+    fn movement(&self) -> i32 {
+        let lps = self.points.iter().map(|(_, lp)| *lp);
+        lps.clone().max().unwrap_or(0) - lps.min().unwrap_or(0)
+    }
+}
+
+/// One series per player with at least two standings, the most moving first
+pub fn lp_series(points: &[RankPoint]) -> Vec<LpSeries> {
+    let mut series: Vec<(i64, LpSeries)> = Vec::new();
+    for point in points {
+        let lp = rank_to_lp(&point.rank_info());
+        match series.iter_mut().find(|(id, _)| *id == point.player_id) {
+            Some((_, line)) => line.points.push((point.recorded_at, lp)),
+            None => series.push((
+                point.player_id,
+                LpSeries {
+                    name: point.display_name(),
+                    points: vec![(point.recorded_at, lp)],
+                },
+            )),
+        }
+    }
+    let mut series: Vec<LpSeries> = series
+        .into_iter()
+        .map(|(_, line)| line)
+        .filter(|line| line.points.len() >= 2)
+        .collect();
+    series.sort_by_key(|line| std::cmp::Reverse(line.movement()));
+    series.truncate(MAX_SERIES);
+    series
+}
+
+/// Name of the division starting at `lp`, e.g. "Gold II", or the LP above
+/// Master for apex tiers
+fn division_label(lp: i32) -> String {
+    const TIERS: [&str; 7] = [
+        "Iron", "Bronze", "Silver", "Gold", "Platinum", "Emerald", "Diamond",
+    ];
+    const DIVISIONS: [&str; 4] = ["IV", "III", "II", "I"];
+    if lp >= 2800 {
+        return format!("Master+ {}", lp - 2800);
+    }
+    let lp = lp.max(0);
+    format!(
+        "{} {}",
+        TIERS[(lp / 400) as usize],
+        DIVISIONS[((lp % 400) / 100) as usize]
+    )
+}
+
+/// The chart of `[from, to)`, `None` when no player has a line to draw
+pub fn chart_svg(series: &[LpSeries], from: i64, to: i64) -> Option<String> {
+    if series.is_empty() || to <= from {
+        return None;
+    }
+    let lps = series
+        .iter()
+        .flat_map(|line| line.points.iter().map(|(_, lp)| *lp));
+    // Whole divisions, so gridlines fall on promotions
+    let low = (lps.clone().min()? / 100) * 100;
+    let high = (lps.max()? / 100 + 1) * 100;
+    let step = if high - low > 800 { 400 } else { 100 };
+
+    let x = |at: i64| {
+        PLOT_LEFT
+            + (at.clamp(from, to) - from) as f64 / (to - from) as f64 * (PLOT_RIGHT - PLOT_LEFT)
+    };
+    let y = |lp: i32| {
+        PLOT_BOTTOM - f64::from(lp - low) / f64::from(high - low) * (PLOT_BOTTOM - PLOT_TOP)
+    };
+
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}"><rect width="{WIDTH}" height="{HEIGHT}" fill="#0f1923"/>"##
+    );
+    let mut grid = low - low.rem_euclid(step);
+    while grid <= high {
+        if grid >= low {
+            let _ = write!(
+                svg,
+                r##"<line x1="{PLOT_LEFT}" y1="{y:.1}" x2="{PLOT_RIGHT}" y2="{y:.1}" stroke="#2a3a4a" stroke-width="1"/><text x="{lx}" y="{ty:.1}" fill="#a09b8c" font-size="13" font-family="{FONT}" text-anchor="end">{label}</text>"##,
+                y = y(grid),
+                lx = PLOT_LEFT - 10.0,
+                ty = y(grid) + 4.0,
+                label = division_label(grid),
+            );
+        }
+        grid += step;
+    }
+
+    for (i, (line, color)) in series.iter().zip(COLORS).enumerate() {
+        let points = line
+            .points
+            .iter()
+            .map(|(at, lp)| format!("{:.1},{:.1}", x(*at), y(*lp)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = write!(
+            svg,
+            r#"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="3" stroke-linejoin="round"/>"#
+        );
+
+        // Legend, four names a row
+        let lx = PLOT_LEFT + (i % 4) as f64 * 170.0;
+        let ly = PLOT_BOTTOM + 35.0 + (i / 4) as f64 * 25.0;
+        let _ = write!(
+            svg,
+            r##"<rect x="{lx}" y="{ry}" width="12" height="12" rx="2" fill="{color}"/><text x="{tx}" y="{ly}" fill="#f0e6d2" font-size="13" font-family="{FONT}">{name}</text>"##,
+            ry = ly - 11.0,
+            tx = lx + 18.0,
+            name = escape(&line.name),
+        );
+    }
+    svg.push_str("</svg>");
+    Some(svg)
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LpSeries, chart_svg, division_label, lp_series};
+    use crate::db::RankPoint;
+
+    fn point(player_id: i64, recorded_at: i64, rank: &str, lp: i32) -> RankPoint {
+        RankPoint {
+            player_id,
+            game_name: format!("P{player_id}"),
+            tag_line: "EUW".into(),
+            privacy_alias: None,
+            recorded_at,
+            tier: "GOLD".into(),
+            rank: rank.into(),
+            lp,
+        }
+    }
+
+    #[test]
+    fn series_need_two_standings() {
+        let series = lp_series(&[
+            point(1, 10, "II", 50),
+            point(2, 20, "IV", 0),
+            point(1, 30, "I", 10),
+            point(3, 40, "III", 0),
+            point(3, 50, "III", 20),
+        ]);
+        assert_eq!(
+            series,
+            [
+                LpSeries {
+                    name: "P1#EUW".into(),
+                    points: vec![(10, 1450), (30, 1510)],
+                },
+                LpSeries {
+                    name: "P3#EUW".into(),
+                    points: vec![(40, 1300), (50, 1320)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn chart_draws_divisions_and_players() {
+        assert_eq!(division_label(1200), "Gold IV");
+        assert_eq!(division_label(1500), "Gold I");
+        assert_eq!(division_label(2950), "Master+ 150");
+
+        let series = [LpSeries {
+            name: "<Faker>".into(),
+            points: vec![(0, 1450), (100, 1510)],
+        }];
+        let svg = chart_svg(&series, 0, 100).unwrap();
+        assert!(svg.contains(">Gold II<"));
+        assert!(svg.contains(">Gold I<"));
+        assert!(svg.contains("&lt;Faker&gt;"));
+        assert!(usvg::Tree::from_str(&svg, &usvg::Options::default()).is_ok());
+
+        assert!(chart_svg(&[], 0, 100).is_none());
+    }
+}
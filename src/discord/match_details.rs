@@ -0,0 +1,324 @@
+//! "Details" button under match alerts. The poller stores the extended stats
+//! of each alerted game and a click on the button answers with them, only to
+//! whoever clicked.
+
+use std::collections::HashMap;
+
+use poise::BoxFuture;
+use poise::serenity_prelude::{
+    self as serenity, ButtonStyle, CreateActionRow, CreateButton, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::error::AppError;
+use crate::riot::{InfoDto, ParticipantDto};
+
+use super::bot::Data;
+use super::events::EventSubscriber;
+
+const BUTTON_PREFIX: &str = "match_details:";
+/// Players listed per side, the ones who dealt the most damage
+const DAMAGE_LINES: usize = 5;
+const BAR_WIDTH: usize = 12;
+
+/// Damage one champion dealt to champions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChampionDamage {
+    pub champion: String,
+    pub damage: i64,
+}
+
+/// What the Details button shows, stored as JSON when the game is alerted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchDetails {
+    pub champion: String,
+    pub queue: String,
+    pub duration: String,
+    /// Item ids, trinket last, empty slots left out
+    pub items: Vec<i32>,
+    pub allies: Vec<ChampionDamage>,
+    pub enemies: Vec<ChampionDamage>,
+    /// Objective summaries of the player's team and the enemy one, when Riot
+    /// reports teams for the mode
+    pub objectives: Option<(String, String)>,
+}
+
+impl MatchDetails {
+    pub fn from_match(info: &InfoDto, participant: &ParticipantDto) -> Self {
+        let side = |allies: bool| {
+            let mut lines: Vec<ChampionDamage> = info
+                .participants
+                .iter()
+                .filter(|p| (p.team_id == participant.team_id) == allies)
+                .map(|p| ChampionDamage {
+                    champion: p.champion_name.clone(),
+                    damage: p.total_damage_dealt_to_champions,
+                })
+                .collect();
+            lines.sort_by_key(|line| std::cmp::Reverse(line.damage));
+            lines.truncate(DAMAGE_LINES);
+            lines
+        };
+        let enemy_team = info
+            .teams
+            .iter()
+            .find(|team| team.team_id != participant.team_id);
+        let objectives = info
+            .team(participant.team_id)
+            .zip(enemy_team)
+            .map(|(ours, theirs)| (ours.objectives.summary(), theirs.objectives.summary()));
+
+        Self {
+            champion: participant.champion_name.clone(),
+            queue: info.queue_name().to_string(),
+            duration: info.duration_formatted(),
+            items: participant
+                .items()
+                .into_iter()
+                .filter(|id| *id > 0)
+                .collect(),
+            allies: side(true),
+            enemies: side(false),
+            objectives,
+        }
+    }
+
+    /// `names` maps item ids to names, unknown items show their id
+    pub fn embed(&self, names: Option<&HashMap<i32, String>>) -> CreateEmbed {
+        let items = if self.items.is_empty() {
+            "No items".to_string()
+        } else {
+            self.items
+                .iter()
+                .map(|id| {
+                    names
+                        .and_then(|names| names.get(id).cloned())
+                        .unwrap_or_else(|| format!("Item {id}"))
+                })
+                .collect::<Vec<_>>()
+                .join(" • ")
+        };
+        let max_damage = self
+            .allies
+            .iter()
+            .chain(&self.enemies)
+            .map(|line| line.damage)
+            .max()
+            .unwrap_or(0);
+
+        let mut embed = CreateEmbed::new()
+            .title(format!(
+                "🔍 {} • {} • {}",
+                self.champion, self.queue, self.duration
+            ))
+            .field("Build", items, false)
+            .field(
+                "Team damage",
+                damage_chart(&self.allies, max_damage, Some(&self.champion)),
+                false,
+            )
+            .field(
+                "Enemy damage",
+                damage_chart(&self.enemies, max_damage, None),
+                false,
+            )
+            .color(0x0099ff);
+        if let Some((ours, theirs)) = &self.objectives {
+            embed = embed.field(
+                "Objectives",
+                format!("Team: {ours}\nEnemy: {theirs}"),
+                false,
+            );
+        }
+        embed
+    }
+}
+
+/// Damage bars scaled on the top damage of the game, `player` marked
+fn damage_chart(lines: &[ChampionDamage], max_damage: i64, player: Option<&str>) -> String {
+    if lines.is_empty() {
+        return "-".to_string();
+    }
+    let width = lines
+        .iter()
+        .map(|line| line.champion.chars().count())
+        .max()
+        .unwrap_or(0);
+    let rows: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            let filled = if max_damage > 0 {
+                (line.damage * BAR_WIDTH as i64 / max_damage) as usize
+            } else {
+                0
+            };
+            let marker = if player == Some(line.champion.as_str()) {
+                "▸"
+            } else {
+                " "
+            };
+            format!(
+                "{marker}{:<width$} {}{} {:.1}k",
+                line.champion,
+                "█".repeat(filled),
+                "░".repeat(BAR_WIDTH - filled),
+                line.damage as f64 / 1000.0
+            )
+        })
+        .collect();
+    format!("```\n{}\n```", rows.join("\n"))
+}
+
+/// Button placed under the alert of the game
+pub fn details_button(player_id: i64, match_id: &str) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{BUTTON_PREFIX}{player_id}:{match_id}"))
+            .label("Details")
+            .emoji('🔍')
+            .style(ButtonStyle::Secondary),
+    ])
+}
+
+fn parse_custom_id(custom_id: &str) -> Option<(i64, &str)> {
+    let (player_id, match_id) = custom_id.strip_prefix(BUTTON_PREFIX)?.split_once(':')?;
+    Some((player_id.parse().ok()?, match_id))
+}
+
+/// Answers clicks on the Details button of alerts
+#[derive(Debug)]
+pub struct MatchDetailsButton;
+
+impl MatchDetailsButton {
+    async fn clicked(
+        ctx: &serenity::Context,
+        data: &Data,
+        interaction: &serenity::ComponentInteraction,
+    ) -> Result<(), AppError> {
+        let Some((player_id, match_id)) = parse_custom_id(&interaction.data.custom_id) else {
+            return Ok(());
+        };
+        // The writable instance answers, a second answer would fail
+        if data.config.read_only {
+            return Ok(());
+        }
+        debug!(player_id, match_id, "🎮 Match details requested");
+
+        // Item names may have to be downloaded first, longer than Discord
+        // waits for an answer
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Defer(
+                    CreateInteractionResponseMessage::new().ephemeral(true),
+                ),
+            )
+            .await?;
+
+        let details = data
+            .db
+            .get_match_details(player_id, match_id)
+            .await?
+            .and_then(|json| serde_json::from_str::<MatchDetails>(&json).ok());
+        let message = match details {
+            Some(details) => EditInteractionResponse::new()
+                .embed(details.embed(data.image_gen.item_names().await)),
+            None => EditInteractionResponse::new()
+                .content("The details of this game are no longer available."),
+        };
+        interaction.edit_response(&ctx.http, message).await?;
+
+        Ok(())
+    }
+}
+
+impl EventSubscriber for MatchDetailsButton {
+    fn name(&self) -> &'static str {
+        "match_details_button"
+    }
+
+    fn events(&self) -> &'static [&'static str] {
+        &["interaction_create"]
+    }
+
+    fn handle<'a>(
+        &'a self,
+        ctx: &'a serenity::Context,
+        event: &'a serenity::FullEvent,
+        data: &'a Data,
+    ) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            match event {
+                serenity::FullEvent::InteractionCreate {
+                    interaction: serenity::Interaction::Component(interaction),
+                } => Self::clicked(ctx, data, interaction).await,
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{ChampionDamage, MatchDetails, damage_chart, parse_custom_id};
+
+    fn damage(champion: &str, damage: i64) -> ChampionDamage {
+        ChampionDamage {
+            champion: champion.into(),
+            damage,
+        }
+    }
+
+    #[test]
+    fn button_ids_round_trip() {
+        assert_eq!(
+            parse_custom_id("match_details:42:EUW1_7012345678"),
+            Some((42, "EUW1_7012345678"))
+        );
+        assert_eq!(parse_custom_id("match_details:x:EUW1_1"), None);
+        assert_eq!(parse_custom_id("page_next:42:EUW1_1"), None);
+    }
+
+    #[test]
+    fn damage_bars_scale_on_the_top_damage() {
+        let chart = damage_chart(
+            &[damage("Ahri", 30000), damage("Lux", 15000)],
+            30000,
+            Some("Lux"),
+        );
+        assert_eq!(
+            chart,
+            "```\n Ahri ████████████ 30.0k\n▸Lux  ██████░░░░░░ 15.0k\n```"
+        );
+        assert_eq!(damage_chart(&[], 0, None), "-");
+    }
+
+    #[test]
+    fn details_survive_storage_and_name_items() {
+        let details = MatchDetails {
+            champion: "Lux".into(),
+            queue: "Ranked Solo/Duo".into(),
+            duration: "25:03".into(),
+            items: vec![3285, 9999],
+            allies: vec![damage("Lux", 15000)],
+            enemies: vec![damage("Ahri", 30000)],
+            objectives: Some((
+                "DRAGONS 3 • BARONS 1 • TOWERS 8".into(),
+                "DRAGONS 1 • BARONS 0 • TOWERS 2".into(),
+            )),
+        };
+        let json = serde_json::to_string(&details).unwrap();
+        assert_eq!(
+            serde_json::from_str::<MatchDetails>(&json).unwrap(),
+            details
+        );
+
+        let names = HashMap::from([(3285, "Luden's Companion".to_string())]);
+        let embed = serde_json::to_value(details.embed(Some(&names))).unwrap();
+        assert_eq!(embed["fields"][0]["value"], "Luden's Companion • Item 9999");
+        assert_eq!(embed["fields"].as_array().unwrap().len(), 4);
+    }
+}
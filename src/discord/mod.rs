@@ -1,6 +1,30 @@
+pub mod alert_layout;
+pub mod alert_template;
+pub mod assets;
 mod bot;
+pub mod channels;
 pub mod commands;
+pub mod emojis;
+pub mod events;
+pub mod features;
+mod gateway;
 pub mod image_gen;
+pub mod lp_chart;
+pub mod match_details;
+pub mod moderation;
+mod ops;
+pub mod prefixes;
+pub mod presence;
 
 pub use bot::{Data, create_framework};
+pub use channels::AlertChannelWatch;
+pub use emojis::EmojiStore;
+pub use events::EventBus;
+pub use features::FeatureGate;
+pub use gateway::GatewayMonitor;
 pub use image_gen::ImageGenerator;
+pub use match_details::MatchDetailsButton;
+pub use moderation::ContentFilter;
+pub use ops::OpsNotifier;
+pub use prefixes::PrefixStore;
+pub use presence::OnlineMembers;
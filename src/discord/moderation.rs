@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::error::AppError;
+
+/// Operator rules applied to player names and text of alerts before they are
+/// posted, loaded from `CONTENT_RULES_FILE`:
+///
+/// ```json
+/// {
+///   "replace": [{ "from": "OldAlias", "to": "Player" }],
+///   "block": ["word"]
+/// }
+/// ```
+///
+/// Replacements run first, then blocked words are masked with `*` whatever
+/// their case.
+#[derive(Debug, Default, Deserialize)]
+pub struct ContentFilter {
+    #[serde(default)]
+    replace: Vec<Replacement>,
+    #[serde(default)]
+    block: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Replacement {
+    from: String,
+    to: String,
+}
+
+impl ContentFilter {
+    pub async fn load(path: &Path) -> Result<Self, AppError> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let mut filter: Self = serde_json::from_str(&content).map_err(|e| {
+            AppError::Config(format!("Invalid content rules in {}: {e}", path.display()))
+        })?;
+        filter.replace.retain(|r| !r.from.is_empty());
+        filter.block.retain(|word| !word.is_empty());
+        Ok(filter)
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.replace.len() + self.block.len()
+    }
+
+    /// `text` with every rule applied
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for rule in &self.replace {
+            text = text.replace(&rule.from, &rule.to);
+        }
+        for word in &self.block {
+            text = mask(&text, word);
+        }
+        text
+    }
+}
+
+/// Replace every occurrence of `word` with as many `*` as it has characters,
+/// ignoring ASCII case (which keeps byte offsets of both strings aligned)
+fn mask(text: &str, word: &str) -> String {
+    let haystack = text.to_ascii_lowercase();
+    let needle = word.to_ascii_lowercase();
+    let stars = "*".repeat(word.chars().count());
+
+    let mut masked = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, _) in haystack.match_indices(&needle) {
+        if start < last {
+            continue;
+        }
+        masked.push_str(&text[last..start]);
+        masked.push_str(&stars);
+        last = start + needle.len();
+    }
+    masked.push_str(&text[last..]);
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContentFilter;
+
+    #[test]
+    fn replacements_then_blocked_words() {
+        let filter: ContentFilter = serde_json::from_str(
+            r#"{"replace": [{"from": "xXSlayerXx", "to": "Slayer"}], "block": ["darn"]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(filter.apply("xXSlayerXx#EUW"), "Slayer#EUW");
+        assert_eq!(filter.apply("DarnIt is DARN good"), "****It is **** good");
+        assert_eq!(filter.apply("Éclair darn"), "Éclair ****");
+        assert_eq!(ContentFilter::default().apply("untouched"), "untouched");
+    }
+}
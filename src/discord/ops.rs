@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use poise::serenity_prelude::{ChannelId, Http};
+use tracing::warn;
+
+/// Operator notices: always logged, also posted to the ops channel when configured
+#[derive(Debug, Clone)]
+pub struct OpsNotifier {
+    http: Arc<Http>,
+    channel: Option<ChannelId>,
+}
+
+impl OpsNotifier {
+    pub fn new(http: Arc<Http>, channel: Option<ChannelId>) -> Self {
+        Self { http, channel }
+    }
+
+    pub async fn notify(&self, message: &str) {
+        warn!(message, "📣 Ops notice");
+
+        if let Some(channel) = self.channel
+            && let Err(e) = channel.say(&self.http, message).await
+        {
+            warn!(error = ?e, "📣 ⚠️ Failed to post ops notice");
+        }
+    }
+}
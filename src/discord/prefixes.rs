@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::db::Repository;
+use crate::error::AppError;
+
+/// Longest prefix a guild can pick for message commands
+pub const MAX_PREFIX_LEN: usize = 5;
+
+/// Per-guild prefixes of message commands, for servers where slash commands
+/// are disabled. Kept in memory since every message is checked against it.
+#[derive(Debug, Default)]
+pub struct PrefixStore {
+    /// Whether the bot receives message content at all (`PREFIX_COMMANDS`)
+    enabled: bool,
+    /// Guild id -> prefix
+    prefixes: RwLock<HashMap<u64, String>>,
+}
+
+impl PrefixStore {
+    pub async fn load(db: &Repository, enabled: bool) -> Result<Self, AppError> {
+        let prefixes = db
+            .get_command_prefixes()
+            .await?
+            .into_iter()
+            .map(|(guild_id, prefix)| (guild_id.get(), prefix))
+            .collect();
+        Ok(Self {
+            enabled,
+            prefixes: RwLock::new(prefixes),
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn get(&self, guild_id: u64) -> Option<String> {
+        self.prefixes
+            .read()
+            .ok()
+            .and_then(|prefixes| prefixes.get(&guild_id).cloned())
+    }
+
+    /// Save the prefix of a guild, `None` turns message commands off there
+    pub async fn set(
+        &self,
+        db: &Repository,
+        guild_id: u64,
+        prefix: Option<&str>,
+    ) -> Result<(), AppError> {
+        if let Some(prefix) = prefix {
+            validate_prefix(prefix)?;
+        }
+        db.set_guild_command_prefix(guild_id, prefix).await?;
+
+        if let Ok(mut prefixes) = self.prefixes.write() {
+            match prefix {
+                Some(prefix) => prefixes.insert(guild_id, prefix.to_string()),
+                None => prefixes.remove(&guild_id),
+            };
+        }
+        Ok(())
+    }
+
+    /// Drop the prefix of a guild whose data was purged
+    pub fn forget_guild(&self, guild_id: u64) {
+        if let Ok(mut prefixes) = self.prefixes.write() {
+            prefixes.remove(&guild_id);
+        }
+    }
+}
+
+fn validate_prefix(prefix: &str) -> Result<(), AppError> {
+    let len = prefix.chars().count();
+    if len == 0 || len > MAX_PREFIX_LEN || prefix.chars().any(char::is_whitespace) {
+        return Err(AppError::Config(format!(
+            "A prefix is 1 to {MAX_PREFIX_LEN} characters without spaces"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_prefix;
+
+    #[test]
+    fn prefixes_are_short_and_without_spaces() {
+        assert!(validate_prefix("!").is_ok());
+        assert!(validate_prefix("tt!").is_ok());
+        assert!(validate_prefix("").is_err());
+        assert!(validate_prefix("t t").is_err());
+        assert!(validate_prefix("toolong").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Who is online in each server, for players holding back their own alerts
+//! while they are around. Fed by presence events, which Discord only sends
+//! with the Presence intent (`QUIET_WHEN_ONLINE`). Only the members quieting
+//! an account are kept, everyone else's presence is ignored.
+
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use poise::BoxFuture;
+use poise::serenity_prelude::{self as serenity, OnlineStatus};
+
+use crate::db::Repository;
+use crate::error::AppError;
+
+use super::bot::Data;
+use super::events::EventSubscriber;
+
+/// Members seen online, by (guild id, user id)
+#[derive(Debug, Default)]
+pub struct OnlineMembers {
+    /// Members quieting at least one account, the only ones watched
+    quiet: RwLock<HashSet<(u64, u64)>>,
+    online: RwLock<HashSet<(u64, u64)>>,
+}
+
+impl OnlineMembers {
+    pub async fn load(db: &Repository) -> Result<Self, AppError> {
+        let quiet = db
+            .get_quiet_members()
+            .await?
+            .into_iter()
+            .map(|(guild_id, user_id)| (guild_id.get(), user_id.get()))
+            .collect();
+        Ok(Self {
+            quiet: RwLock::new(quiet),
+            online: RwLock::default(),
+        })
+    }
+
+    pub fn is_online(&self, guild_id: u64, user_id: u64) -> bool {
+        self.online
+            .read()
+            .is_ok_and(|online| online.contains(&(guild_id, user_id)))
+    }
+
+    /// Start or stop watching a member after they changed what they quiet,
+    /// `status` being their presence when known
+    pub async fn refresh(
+        &self,
+        db: &Repository,
+        guild_id: u64,
+        user_id: u64,
+        status: Option<OnlineStatus>,
+    ) -> Result<(), AppError> {
+        let quiet = db.is_quiet_member(guild_id, user_id).await?;
+        if let Ok(mut members) = self.quiet.write() {
+            if quiet {
+                members.insert((guild_id, user_id));
+            } else {
+                members.remove(&(guild_id, user_id));
+            }
+        }
+        self.update(guild_id, user_id, status.unwrap_or(OnlineStatus::Offline));
+        Ok(())
+    }
+
+    /// Stop watching a user whose data was purged
+    pub fn forget_user(&self, user_id: u64) {
+        if let Ok(mut quiet) = self.quiet.write() {
+            quiet.retain(|(_, id)| *id != user_id);
+        }
+        if let Ok(mut online) = self.online.write() {
+            online.retain(|(_, id)| *id != user_id);
+        }
+    }
+
+    /// Stop watching a guild whose data was purged
+    pub fn forget_guild(&self, guild_id: u64) {
+        if let Ok(mut quiet) = self.quiet.write() {
+            quiet.retain(|(id, _)| *id != guild_id);
+        }
+        self.clear_guild(guild_id);
+    }
+
+    fn update(&self, guild_id: u64, user_id: u64, status: OnlineStatus) {
+        let watched = self
+            .quiet
+            .read()
+            .is_ok_and(|quiet| quiet.contains(&(guild_id, user_id)));
+        let Ok(mut online) = self.online.write() else {
+            return;
+        };
+        // Invisible members show up as offline, as they would to anyone
+        if !watched || matches!(status, OnlineStatus::Offline | OnlineStatus::Invisible) {
+            online.remove(&(guild_id, user_id));
+        } else {
+            online.insert((guild_id, user_id));
+        }
+    }
+
+    fn clear_guild(&self, guild_id: u64) {
+        if let Ok(mut online) = self.online.write() {
+            online.retain(|(id, _)| *id != guild_id);
+        }
+    }
+
+    /// Start the guild over from the presences it was received with
+    fn reset_guild(&self, guild: &serenity::Guild) {
+        self.clear_guild(guild.id.get());
+        for (user_id, presence) in &guild.presences {
+            self.update(guild.id.get(), user_id.get(), presence.status);
+        }
+    }
+}
+
+impl EventSubscriber for OnlineMembers {
+    fn name(&self) -> &'static str {
+        "online_members"
+    }
+
+    fn events(&self) -> &'static [&'static str] {
+        &[
+            "guild_create",
+            "guild_delete",
+            "guild_member_removal",
+            "presence_update",
+        ]
+    }
+
+    fn handle<'a>(
+        &'a self,
+        _ctx: &'a serenity::Context,
+        event: &'a serenity::FullEvent,
+        _data: &'a Data,
+    ) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            match event {
+                serenity::FullEvent::GuildCreate { guild, .. } => self.reset_guild(guild),
+                // Left, kicked or unavailable: nobody there is seen anymore
+                serenity::FullEvent::GuildDelete { incomplete, .. } => {
+                    self.clear_guild(incomplete.id.get())
+                }
+                serenity::FullEvent::GuildMemberRemoval { guild_id, user, .. } => {
+                    self.update(guild_id.get(), user.id.get(), OnlineStatus::Offline)
+                }
+                serenity::FullEvent::PresenceUpdate { new_data } => {
+                    if let Some(guild_id) = new_data.guild_id {
+                        self.update(guild_id.get(), new_data.user.id.get(), new_data.status);
+                    }
+                }
+                _ => {}
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::OnlineStatus;
+
+    use super::OnlineMembers;
+
+    #[test]
+    fn quiet_members_count_as_online_until_they_leave() {
+        let members = OnlineMembers::default();
+        members.quiet.write().unwrap().insert((1, 10));
+        members.update(1, 10, OnlineStatus::DoNotDisturb);
+        assert!(members.is_online(1, 10));
+        assert!(!members.is_online(2, 10));
+
+        members.update(1, 10, OnlineStatus::Invisible);
+        assert!(!members.is_online(1, 10));
+
+        // Nobody else's presence is kept
+        members.update(1, 11, OnlineStatus::Online);
+        assert!(!members.is_online(1, 11));
+
+        members.update(1, 10, OnlineStatus::Online);
+        members.forget_guild(1);
+        members.update(1, 10, OnlineStatus::Online);
+        assert!(!members.is_online(1, 10));
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::sync::Arc;
+
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -5,29 +8,98 @@ pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
-    #[error("Riot API error: {status} - {message}")]
-    RiotApi { status: u16, message: String },
+    #[error(
+        "Database is corrupted ({0}). Stop the bot and restore a backup, or salvage it with `sqlite3 tentrackule.db .recover`"
+    )]
+    DatabaseCorrupted(String),
+
+    #[error("Riot API error on {endpoint}: {status} - {message}")]
+    RiotApi {
+        endpoint: String,
+        status: u16,
+        message: String,
+    },
+
+    #[error("{relay} relay error: {status} - {message}")]
+    Relay {
+        relay: &'static str,
+        status: u16,
+        message: String,
+    },
 
     #[error("Discord error: {0}")]
     Discord(Box<serenity::Error>),
 
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("HTTP request error: {0}")]
     Http(#[from] reqwest::Error),
 
     #[error("Player not found: {game_name}#{tag_line}")]
     PlayerNotFound { game_name: String, tag_line: String },
 
+    #[error("Invalid Riot ID: {0}")]
+    InvalidRiotId(String),
+
     #[error("Invalid region: {0}")]
     InvalidRegion(String),
 
+    #[error("Unknown timezone: {0} (expected an IANA name like Europe/Paris)")]
+    InvalidTimezone(String),
+
     #[error("Configuration error: {0}")]
     Config(String),
 
     #[error("Player not tracked in this server")]
     PlayerNotTracked,
 
+    #[error("This instance is read-only, only commands that read are available")]
+    ReadOnly,
+
+    #[error("Alert dispatcher is not running")]
+    DispatcherStopped,
+
     #[error("Image generation error: {message}")]
     ImageGeneration { message: String },
+
+    /// Error of a request shared by several callers
+    #[error(transparent)]
+    Shared(Arc<AppError>),
+
+    /// What we were doing when `source` happened
+    #[error("{context}: {source}")]
+    Context {
+        context: String,
+        source: Box<AppError>,
+    },
+}
+
+impl AppError {
+    /// Riot answered with a 5xx, the platform is (partially) down
+    pub fn is_riot_outage(&self) -> bool {
+        match self {
+            AppError::RiotApi { status, .. } => *status >= 500,
+            AppError::Shared(e) => e.is_riot_outage(),
+            AppError::Context { source, .. } => source.is_riot_outage(),
+            _ => false,
+        }
+    }
+}
+
+/// Attach the operation that failed to an error, so the log line says what
+/// was being done and not only what went wrong
+pub trait ResultExt<T> {
+    fn with_context<C: fmt::Display>(self, context: impl FnOnce() -> C) -> Result<T, AppError>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for Result<T, E> {
+    fn with_context<C: fmt::Display>(self, context: impl FnOnce() -> C) -> Result<T, AppError> {
+        self.map_err(|e| AppError::Context {
+            context: context().to_string(),
+            source: Box::new(e.into()),
+        })
+    }
 }
 
 impl From<serenity::Error> for AppError {
@@ -1,23 +1,46 @@
+mod activity;
+#[cfg(feature = "public-api")]
+mod api;
+mod cli;
+mod clock;
 mod config;
+mod data_dir;
 mod db;
 mod discord;
 mod error;
+mod maintenance;
 mod poller;
 mod riot;
+mod scheduler;
+mod startup;
+mod stats;
 
 use std::sync::Arc;
+use std::time::Duration;
 
+use clap::Parser;
 use poise::serenity_prelude as serenity;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::sqlite::SqlitePoolOptions;
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
+use crate::activity::ActivityFeed;
+use crate::clock::{Clock, SystemClock};
 use crate::config::Config;
 use crate::db::Repository;
-use crate::discord::{Data, ImageGenerator};
+use crate::discord::{
+    AlertChannelWatch, ContentFilter, Data, EmojiStore, EventBus, FeatureGate, GatewayMonitor,
+    ImageGenerator, MatchDetailsButton, OnlineMembers, OpsNotifier, PrefixStore,
+};
 use crate::riot::RiotClient;
+use crate::scheduler::{Schedule, Scheduler};
+
+/// Spread of scheduled runs, so jobs sharing a schedule don't fire together
+const JOB_JITTER: Duration = Duration::from_secs(60);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let command = cli::Cli::parse().command();
+
     // Initialize logging
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info,tentrackule=debug"));
@@ -44,77 +67,430 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .init();
     }
 
+    // Administration commands work on the database alone, without Discord
+    if command != cli::Command::Run {
+        return Ok(cli::run(command).await?);
+    }
+
     tracing::info!("🦑 Starting Tentrackule 2.0");
 
     // Load configuration
-    let config = Config::from_env()?;
+    let config = Arc::new(Config::from_env()?);
     tracing::info!("⚙️ Configuration loaded");
 
     // Initialize database
-    let db_options: SqliteConnectOptions = config.database_url.parse()?;
-    let db_options = db_options.create_if_missing(true);
+    let db_options = config
+        .data_dir
+        .database_options(config.database_url.as_deref())?;
+    // Read-only instances (staging on a production snapshot) never write,
+    // SQLite itself rejects any write that slips through
+    let db_options = db_options
+        .create_if_missing(!config.read_only)
+        .read_only(config.read_only);
+    tracing::info!(
+        data_dir = %config.data_dir.root().display(),
+        database = %db_options.get_filename().display(),
+        "🗄️ Opening database"
+    );
+    let database_path = db_options.get_filename().to_path_buf();
+    // Keeps restores off the database while the bot runs. Read-only
+    // instances may sit on a snapshot they can't create files next to.
+    let _instance_lock = if config.read_only {
+        None
+    } else {
+        Some(db::InstanceLock::shared(&database_path)?)
+    };
 
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
         .connect_with(db_options)
         .await?;
 
-    db::run_migrations(&pool).await?;
+    db::check_integrity(&pool).await?;
+    if config.read_only {
+        db::check_schema_version(&pool).await?;
+    } else {
+        db::run_migrations(&pool).await?;
+    }
     let repository = Repository::new(pool.clone());
+
     tracing::info!("🗄️ Database initialized");
 
+    startup::StartupReport::collect(&repository, &database_path)
+        .await?
+        .log(&config, &database_path);
+
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+
     // Initialize Riot API client
     let riot_client = RiotClient::new(
         config.riot_api_key.clone(),
         config.riot_rate_limit_per_second,
+        Arc::clone(&clock),
     )?;
     tracing::info!("🔷 Riot API client initialized");
 
+    // Fail fast on a rejected key; an unreachable Riot API only warns
+    match riot_client.validate_api_key().await {
+        Ok(()) => tracing::info!("🔷 ✅ Riot API key accepted"),
+        Err(e @ error::AppError::Config(_)) => return Err(e.into()),
+        Err(e) => tracing::warn!(error = ?e, "🔷 ⚠️ Could not validate Riot API key"),
+    }
+
+    if !config.read_only {
+        let merged = maintenance::merge_duplicate_players(&repository, &riot_client).await?;
+        if merged > 0 {
+            tracing::warn!(merged, "🗄️ ⚠️ Merged duplicate player rows");
+        }
+    }
+
+    // Operator rules applied to alert text before posting
+    let content_filter = Arc::new(match &config.content_rules_file {
+        Some(path) => {
+            let filter = ContentFilter::load(path).await?;
+            tracing::info!(rules = filter.rule_count(), path = %path.display(), "🎮 Content rules loaded");
+            filter
+        }
+        None => ContentFilter::default(),
+    });
+
     // Initialize image generator
-    let image_gen = Arc::new(ImageGenerator::new(config.ddragon_version.clone()).await?);
+    let image_gen = Arc::new(
+        ImageGenerator::new(
+            config.ddragon_version.clone(),
+            config.data_dir.image_cache(),
+            Arc::clone(&content_filter),
+        )
+        .await?,
+    );
     tracing::info!(version = %config.ddragon_version, "🖼️ Image generator initialized");
 
+    let emojis = Arc::new(EmojiStore::load(&repository).await?);
+    let prefixes = Arc::new(PrefixStore::load(&repository, config.prefix_commands).await?);
+    let features = Arc::new(FeatureGate::load(&repository).await?);
+    let alert_latency = Arc::new(poller::DeliveryLatency::default());
+    let gateway = Arc::new(GatewayMonitor::new(
+        config.ops_channel_id.map(serenity::ChannelId::new),
+        Arc::clone(&clock),
+    ));
+
+    // Account activity, with the audit log as first subscriber
+    let activity = ActivityFeed::default();
+    tokio::spawn(activity::log_activity(activity.subscribe()));
+
+    // Who is online, for players quieting their own alerts
+    let online = if config.quiet_when_online {
+        Some(Arc::new(OnlineMembers::load(&repository).await?))
+    } else {
+        None
+    };
+
+    // Features reacting to gateway events
+    let mut events = EventBus::default()
+        .register(gateway.clone())
+        .register(Arc::new(AlertChannelWatch))
+        .register(Arc::new(MatchDetailsButton));
+    if let Some(online) = &online {
+        events = events.register(online.clone());
+    }
+    let events = Arc::new(events);
+
     // Create shared data for Discord bot
     let data = Data {
         db: repository.clone(),
         riot: riot_client.clone(),
         image_gen: Arc::clone(&image_gen),
+        emojis: Arc::clone(&emojis),
+        prefixes,
+        features: Arc::clone(&features),
+        alert_latency: Arc::clone(&alert_latency),
+        gateway: Arc::clone(&gateway),
+        config: Arc::clone(&config),
+        clock: Arc::clone(&clock),
+        events,
+        activity: activity.clone(),
+        online: online.clone(),
     };
 
     // Build Discord framework
     let framework = discord::create_framework(data);
 
     // Build Discord client
-    let intents = serenity::GatewayIntents::GUILDS;
+    let mut intents = serenity::GatewayIntents::GUILDS;
+    if config.prefix_commands {
+        intents |=
+            serenity::GatewayIntents::GUILD_MESSAGES | serenity::GatewayIntents::MESSAGE_CONTENT;
+        tracing::info!("🎮 Prefix commands enabled");
+    }
+    if config.quiet_when_online {
+        intents |= serenity::GatewayIntents::GUILD_PRESENCES;
+        tracing::info!("🎮 Players may quiet their alerts while online");
+    }
     let mut client = serenity::ClientBuilder::new(&config.discord_token, intents)
         .framework(framework)
+        .event_handler_arc(gateway)
         .await?;
 
-    // Get HTTP client for poller
-    let http = Arc::clone(&client.http);
+    // Fail fast on a bad token, before any background job starts
+    let bot_user = client.http.get_current_user().await.map_err(|e| {
+        let rejected = matches!(
+            &e,
+            serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response))
+                if response.status_code == serenity::StatusCode::UNAUTHORIZED
+        );
+        if rejected {
+            error::AppError::Config("DISCORD_TOKEN is invalid or revoked (HTTP 401)".into())
+        } else {
+            error::AppError::from(e)
+        }
+    })?;
+    tracing::info!(bot_name = %bot_user.name, "🎮 ✅ Discord token accepted");
+
+    let mut scheduler = Scheduler::new(repository.clone(), Arc::clone(&clock));
+
+    // Polling and every job writing to the database stay off in read-only mode
+    if config.read_only {
+        tracing::warn!("📖 Read-only mode, polling and background jobs are disabled");
+    } else {
+        // Spawn alert dispatcher, fed by the poller through a bounded queue
+        let (alert_queue, alert_rx) = poller::AlertQueue::new(config.alert_queue_capacity);
+        let alert_sink = if config.shadow_mode {
+            tracing::warn!("👻 Shadow mode enabled, alerts will not reach guild channels");
+            poller::AlertSink::Shadow {
+                output_dir: config.shadow_output_dir.clone(),
+                ops_channel: config.ops_channel_id.map(serenity::ChannelId::new),
+            }
+        } else {
+            poller::AlertSink::Guilds
+        };
+        // Shadow deployments keep alerts off every chat, relays included
+        let relay_targets = if config.shadow_mode {
+            Vec::new()
+        } else {
+            config.relays.clone()
+        };
+        let relays = Arc::new(poller::Relays::new(
+            relay_targets,
+            config.relay_rate_limit_per_minute,
+            clock.as_ref(),
+        )?);
+        if !relays.is_empty() {
+            tracing::info!(relays = ?config.relays, "🎮 Match alerts relayed outside Discord");
+        }
+        let dispatcher = poller::Dispatcher {
+            db: repository.clone(),
+            http: Arc::clone(&client.http),
+            sink: alert_sink,
+            latency: Arc::clone(&alert_latency),
+            relays,
+            activity: activity.clone(),
+            clock: Arc::clone(&clock),
+            online,
+            buffer_max_age_secs: config.alert_buffer_max_age_minutes as i64 * 60,
+        };
+        tokio::spawn(dispatcher.run(alert_rx));
+
+        let ops = OpsNotifier::new(
+            Arc::clone(&client.http),
+            config.ops_channel_id.map(serenity::ChannelId::new),
+        );
+
+        // Spawn match poller in background
+        let poller_db = repository.clone();
+        let poller_riot = riot_client.clone();
+        let poller_image_gen = Arc::clone(&image_gen);
+        let poller_clock = Arc::clone(&clock);
+        let polling = config.polling;
+
+        let poller = poller::MatchPoller {
+            db: poller_db,
+            riot: poller_riot,
+            alerts: alert_queue,
+            image_gen: poller_image_gen,
+            ops,
+            // Shadow deployments keep notices off guild channels too
+            http: (!config.shadow_mode).then(|| Arc::clone(&client.http)),
+            clock: poller_clock,
+            cadence: polling,
+            activity,
+            features: Arc::clone(&features),
+        };
+        tokio::spawn(poller.run());
+
+        tracing::info!("🔄 Match poller spawned");
+
+        // Upload or map the tier emojis on the emoji server
+        if let Some(guild_id) = config.emoji_guild_id {
+            let emoji_db = repository.clone();
+            let emoji_http = Arc::clone(&client.http);
+            let emoji_store = Arc::clone(&emojis);
+            tokio::spawn(async move {
+                let guild_id = serenity::GuildId::new(guild_id);
+                if let Err(e) = emoji_store.sync(&emoji_db, &emoji_http, guild_id).await {
+                    tracing::warn!(error = ?e, "😀 ⚠️ Failed to sync emojis, using unicode");
+                }
+            });
+        }
 
-    // Spawn match poller in background
-    let poller_db = repository.clone();
-    let poller_riot = riot_client.clone();
-    let poller_image_gen = Arc::clone(&image_gen);
-    let polling_interval = config.polling_interval_secs;
+        // Daily apex ladder watch
+        let (db, riot, http, emojis, filter) = (
+            repository.clone(),
+            riot_client.clone(),
+            (!config.shadow_mode).then(|| Arc::clone(&client.http)),
+            Arc::clone(&emojis),
+            Arc::clone(&content_filter),
+        );
+        scheduler.spawn(
+            "ladder_watch",
+            Schedule::Every(Duration::from_secs(
+                config.ladder_watch_interval_hours * 3600,
+            )),
+            JOB_JITTER,
+            move || {
+                let (db, riot, http, emojis, filter) = (
+                    db.clone(),
+                    riot.clone(),
+                    http.clone(),
+                    Arc::clone(&emojis),
+                    Arc::clone(&filter),
+                );
+                async move { poller::check_ladders(&db, &riot, http.as_deref(), &emojis, &filter).await }
+            },
+        );
 
+        // LP changes without a game (decay, dodges) when enabled
+        if let Some(hours) = config.rank_watch_interval_hours {
+            let (db, riot, http, filter, rank_clock) = (
+                repository.clone(),
+                riot_client.clone(),
+                // Shadow deployments keep notices off guild channels
+                (!config.shadow_mode).then(|| Arc::clone(&client.http)),
+                Arc::clone(&content_filter),
+                Arc::clone(&clock),
+            );
+            scheduler.spawn(
+                "rank_watch",
+                Schedule::Every(Duration::from_secs(hours * 3600)),
+                JOB_JITTER,
+                move || {
+                    let (db, riot, http, filter) =
+                        (db.clone(), riot.clone(), http.clone(), Arc::clone(&filter));
+                    let now = rank_clock.unix_now();
+                    async move {
+                        poller::check_ranks(&db, &riot, http.as_deref(), &filter, now).await
+                    }
+                },
+            );
+        }
+
+        // Spawn "game in progress" messages when enabled
+        if let Some(interval_secs) = config.live_game_interval_secs {
+            if config.shadow_mode {
+                tracing::warn!("👻 Shadow mode enabled, live game messages are disabled");
+            } else {
+                tokio::spawn(poller::start_live_games(
+                    repository.clone(),
+                    riot_client.clone(),
+                    Arc::clone(&client.http),
+                    Arc::clone(&features),
+                    Arc::clone(&content_filter),
+                    Arc::clone(&clock),
+                    interval_secs,
+                ));
+            }
+        }
+
+        // Weekly recap with the guild MVP, checked hourly as weeks end at
+        // different times across timezones
+        let (db, http, recap_features, recap_image_gen, recap_clock) = (
+            repository.clone(),
+            (!config.shadow_mode).then(|| Arc::clone(&client.http)),
+            Arc::clone(&features),
+            Arc::clone(&image_gen),
+            Arc::clone(&clock),
+        );
+        scheduler.spawn(
+            "weekly_recap",
+            Schedule::Every(Duration::from_secs(3600)),
+            Duration::ZERO,
+            move || {
+                let (db, http, features, image_gen, now) = (
+                    db.clone(),
+                    http.clone(),
+                    Arc::clone(&recap_features),
+                    Arc::clone(&recap_image_gen),
+                    recap_clock.unix_now(),
+                );
+                async move {
+                    poller::post_recaps(&db, http.as_deref(), &features, &image_gen, now).await
+                }
+            },
+        );
+
+        // Daily or weekly results, for the guilds that asked for them
+        let (db, http, recap_features, recap_filter, recap_clock) = (
+            repository.clone(),
+            (!config.shadow_mode).then(|| Arc::clone(&client.http)),
+            Arc::clone(&features),
+            Arc::clone(&content_filter),
+            Arc::clone(&clock),
+        );
+        scheduler.spawn(
+            "results_recap",
+            Schedule::Every(Duration::from_secs(3600)),
+            Duration::ZERO,
+            move || {
+                let (db, http, features, filter, now) = (
+                    db.clone(),
+                    http.clone(),
+                    Arc::clone(&recap_features),
+                    Arc::clone(&recap_filter),
+                    recap_clock.unix_now(),
+                );
+                async move {
+                    poller::post_results_recaps(&db, http.as_deref(), &features, &filter, now).await
+                }
+            },
+        );
+
+        // Vacuum job enforcing the retention policy
+        let vacuum_schedule = match config.vacuum_at {
+            Some((hour, minute)) => Schedule::DailyAt { hour, minute },
+            None => Schedule::Every(Duration::from_secs(config.vacuum_interval_hours * 3600)),
+        };
+        let (db, vacuum_clock, retention) =
+            (repository.clone(), Arc::clone(&clock), config.retention);
+        scheduler.spawn("vacuum", vacuum_schedule, JOB_JITTER, move || {
+            let (db, clock) = (db.clone(), Arc::clone(&vacuum_clock));
+            async move { maintenance::run_vacuum(&db, clock.as_ref(), &retention).await }
+        });
+    }
+
+    // Spawn the read-only public API when configured
+    #[cfg(feature = "public-api")]
+    if let Some(addr) = config.public_api_addr {
+        tokio::spawn(api::start_public_api(repository.clone(), addr));
+    }
+    #[cfg(not(feature = "public-api"))]
+    if config.public_api_addr.is_some() {
+        tracing::warn!(
+            "PUBLIC_API_ADDR is set but this build has no public-api feature, ignoring it"
+        );
+    }
+
+    // Ctrl-C stops the gateway, then scheduled jobs get to finish their run
+    let shard_manager = Arc::clone(&client.shard_manager);
     tokio::spawn(async move {
-        poller::start_polling(
-            poller_db,
-            poller_riot,
-            http,
-            poller_image_gen,
-            polling_interval,
-        )
-        .await;
+        if tokio::signal::ctrl_c().await.is_ok() {
+            tracing::info!("🦑 Shutting down...");
+            shard_manager.shutdown_all().await;
+        }
     });
 
-    tracing::info!("🔄 Match poller spawned");
-
     // Start the bot
     tracing::info!("🎮 Starting Discord bot...");
     client.start().await?;
+    scheduler.shutdown().await;
 
     Ok(())
 }
@@ -0,0 +1,69 @@
+use tracing::{debug, info, instrument, warn};
+
+use crate::db::{Player, Repository};
+use crate::error::AppError;
+use crate::riot::{Platform, RiotClient};
+
+/// Merge player rows sharing a Riot ID and region once Riot confirms they
+/// are one account: the row holding the account's current PUUID is kept and
+/// the rows whose PUUID Riot no longer knows, left by past PUUID migrations,
+/// are folded into it. Rows whose PUUID belongs to a live account are left
+/// alone. Returns the number of rows removed.
+#[instrument(skip_all)]
+pub async fn merge_duplicate_players(db: &Repository, riot: &RiotClient) -> Result<u64, AppError> {
+    let mut merged = 0;
+    for group in db.get_riot_id_duplicates().await? {
+        let riot_id = group[0].riot_id();
+        match confirmed_duplicates(riot, &group).await {
+            Ok(Some((keeper, duplicates))) => {
+                for duplicate in duplicates {
+                    db.merge_player_into(duplicate.id, keeper.id).await?;
+                    info!(
+                        riot_id,
+                        duplicate_id = duplicate.id,
+                        keeper_id = keeper.id,
+                        "🗄️ Merged duplicate player row"
+                    );
+                    merged += 1;
+                }
+            }
+            Ok(None) => debug!(riot_id, "🗄️ Riot ID shared by distinct accounts"),
+            // Riot being unreachable only delays the repair to the next start
+            Err(e) => warn!(error = ?e, riot_id, "🗄️ ⚠️ Failed to confirm duplicate players"),
+        }
+    }
+    Ok(merged)
+}
+
+/// The row to keep and the rows to fold into it, `None` when no row holds
+/// the PUUID Riot currently gives the Riot ID
+async fn confirmed_duplicates<'a>(
+    riot: &RiotClient,
+    group: &'a [Player],
+) -> Result<Option<(&'a Player, Vec<&'a Player>)>, AppError> {
+    let first = &group[0];
+    let region = first.region.parse::<Platform>()?.to_region();
+    let account = match riot
+        .get_account_by_riot_id(region, &first.game_name, &first.tag_line)
+        .await
+    {
+        Ok(account) => account,
+        Err(AppError::PlayerNotFound { .. }) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let Some(keeper) = group.iter().find(|p| p.puuid == account.puuid) else {
+        return Ok(None);
+    };
+
+    let mut duplicates = Vec::new();
+    for player in group.iter().filter(|p| p.id != keeper.id) {
+        if riot
+            .get_account_by_puuid(region, &player.puuid)
+            .await?
+            .is_none()
+        {
+            duplicates.push(player);
+        }
+    }
+    Ok(Some((keeper, duplicates)))
+}
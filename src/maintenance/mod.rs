@@ -0,0 +1,5 @@
+mod merge;
+mod vacuum;
+
+pub use merge::merge_duplicate_players;
+pub use vacuum::run_vacuum;
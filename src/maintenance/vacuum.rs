@@ -0,0 +1,22 @@
+use tracing::{info, instrument};
+
+use crate::clock::Clock;
+use crate::config::RetentionPolicy;
+use crate::db::Repository;
+use crate::error::AppError;
+
+/// Enforce the retention policy, then compact the database
+#[instrument(skip_all)]
+pub async fn run_vacuum(
+    db: &Repository,
+    clock: &dyn Clock,
+    retention: &RetentionPolicy,
+) -> Result<(), AppError> {
+    let now = clock.unix_now();
+    let pruned = db.prune_history(retention, now).await?;
+    let purged_players = db.purge_untracked(now).await?;
+    db.vacuum().await?;
+
+    info!(pruned, purged_players, "🧹 ✅ Vacuum completed");
+    Ok(())
+}
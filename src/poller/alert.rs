@@ -0,0 +1,195 @@
+use poise::serenity_prelude::{
+    CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateMessage, EditMessage,
+};
+use serde::{Deserialize, Serialize};
+
+/// What an alert says, independent of where it is posted. The pollers build
+/// these and only the rendering below knows about Discord, so another sink
+/// (webhook, chat bridge) can post the same content.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertModel {
+    pub title: String,
+    pub description: Option<String>,
+    pub color: u32,
+    pub fields: Vec<AlertField>,
+    pub footer: Option<String>,
+    pub image_url: Option<String>,
+    pub buttons: Vec<AlertButton>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertField {
+    pub name: String,
+    pub value: String,
+    pub inline: bool,
+}
+
+/// A link shown under the alert
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertButton {
+    pub label: String,
+    pub url: String,
+}
+
+impl AlertModel {
+    pub fn new(title: impl Into<String>, color: u32) -> Self {
+        Self {
+            title: title.into(),
+            color,
+            ..Default::default()
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn field(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+        inline: bool,
+    ) -> Self {
+        self.fields.push(AlertField {
+            name: name.into(),
+            value: value.into(),
+            inline,
+        });
+        self
+    }
+
+    pub fn footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    // === Discord rendering ===
+
+    pub fn embed(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::new().title(&self.title).color(self.color);
+        if let Some(description) = &self.description {
+            embed = embed.description(description);
+        }
+        for field in &self.fields {
+            embed = embed.field(&field.name, &field.value, field.inline);
+        }
+        if let Some(footer) = &self.footer {
+            embed = embed.footer(CreateEmbedFooter::new(footer));
+        }
+        if let Some(url) = &self.image_url {
+            embed = embed.image(url);
+        }
+        embed
+    }
+
+    /// Link buttons, five to a row as Discord allows
+    pub fn components(&self) -> Vec<CreateActionRow> {
+        self.buttons
+            .chunks(5)
+            .map(|row| {
+                CreateActionRow::Buttons(
+                    row.iter()
+                        .map(|button| CreateButton::new_link(&button.url).label(&button.label))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn message(&self) -> CreateMessage {
+        CreateMessage::new()
+            .embed(self.embed())
+            .components(self.components())
+    }
+
+    pub fn edit(&self) -> EditMessage {
+        EditMessage::new()
+            .embed(self.embed())
+            .components(self.components())
+    }
+
+    // === Plain text rendering ===
+
+    /// The alert as plain text, for relays without embeds. Discord bold
+    /// markers are dropped and links are listed after the text.
+    pub fn plain_text(&self) -> String {
+        let mut lines = vec![self.title.clone()];
+        lines.extend(self.description.clone());
+        for field in &self.fields {
+            lines.push(format!("{}: {}", field.name, field.value));
+        }
+        lines.extend(self.footer.clone());
+        for button in &self.buttons {
+            lines.push(format!("{}: {}", button.label, button.url));
+        }
+        lines.join("\n").replace("**", "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{AlertButton, AlertModel};
+
+    #[test]
+    fn renders_every_part_of_the_model() {
+        let alert = AlertModel {
+            image_url: Some("https://example.com/a.png".into()),
+            buttons: vec![AlertButton {
+                label: "Open".into(),
+                url: "https://example.com".into(),
+            }],
+            ..AlertModel::new("Title", 0x123456)
+                .description("Body")
+                .field("Name", "Value", true)
+                .footer("Footer")
+        };
+
+        assert_eq!(
+            serde_json::to_value(alert.embed()).unwrap(),
+            json!({
+                "type": "rich",
+                "title": "Title",
+                "description": "Body",
+                "color": 0x123456,
+                "fields": [{ "name": "Name", "value": "Value", "inline": true }],
+                "footer": { "text": "Footer" },
+                "image": {
+                    "url": "https://example.com/a.png",
+                    "proxy_url": null,
+                    "height": null,
+                    "width": null
+                }
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(alert.components()).unwrap(),
+            json!([{
+                "type": 1,
+                "components": [{
+                    "type": 2,
+                    "style": 5,
+                    "url": "https://example.com",
+                    "label": "Open",
+                    "disabled": false
+                }]
+            }])
+        );
+        assert!(AlertModel::new("Title", 0).components().is_empty());
+    }
+
+    #[test]
+    fn plain_text_drops_discord_markup() {
+        let alert = AlertModel::new("🏆 Faker#KR1 won", 0)
+            .description("**Ahri** • 10/2/8")
+            .field("Rank", "Master 120 LP", true)
+            .footer("Ranked Solo/Duo");
+
+        assert_eq!(
+            alert.plain_text(),
+            "🏆 Faker#KR1 won\nAhri • 10/2/8\nRank: Master 120 LP\nRanked Solo/Duo"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use crate::config::AutoTune;
+
+/// Share of the interval a cycle may take before the interval grows
+const BUSY_RATIO: f64 = 0.8;
+/// Share of the interval under which a quiet cycle lets the interval shrink
+const IDLE_RATIO: f64 = 0.4;
+
+/// Why the poll interval changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjustment {
+    /// Riot answered 429 during the cycle
+    RateLimited,
+    /// The cycle took most of the interval
+    SlowCycle,
+    /// Short cycle without 429, polling can speed up
+    Headroom,
+}
+
+/// Moves the poll interval within the configured bounds after each full
+/// cycle: backing off on 429s and long cycles, speeding up when idle
+#[derive(Debug)]
+pub struct AutoTuner {
+    bounds: AutoTune,
+    interval_secs: u64,
+}
+
+impl AutoTuner {
+    pub fn new(bounds: AutoTune, interval_secs: u64) -> Self {
+        Self {
+            interval_secs: interval_secs.clamp(bounds.min_interval_secs, bounds.max_interval_secs),
+            bounds,
+        }
+    }
+
+    pub fn interval_secs(&self) -> u64 {
+        self.interval_secs
+    }
+
+    /// New interval after a cycle of `cycle_secs` that got `rate_limited` 429s,
+    /// `None` when it stays the same
+    pub fn adjust(&mut self, cycle_secs: f64, rate_limited: u64) -> Option<(u64, Adjustment)> {
+        let current = self.interval_secs as f64;
+        let (target, reason) = if rate_limited > 0 {
+            (current * 1.5, Adjustment::RateLimited)
+        } else if cycle_secs > current * BUSY_RATIO {
+            (cycle_secs / BUSY_RATIO, Adjustment::SlowCycle)
+        } else if cycle_secs < current * IDLE_RATIO {
+            (current * 0.8, Adjustment::Headroom)
+        } else {
+            return None;
+        };
+
+        let target = (target.round() as u64)
+            .clamp(self.bounds.min_interval_secs, self.bounds.max_interval_secs);
+        if target == self.interval_secs {
+            return None;
+        }
+        self.interval_secs = target;
+        Some((target, reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Adjustment, AutoTuner};
+    use crate::config::AutoTune;
+
+    #[test]
+    fn interval_follows_load_within_bounds() {
+        let bounds = AutoTune {
+            min_interval_secs: 30,
+            max_interval_secs: 300,
+        };
+        let mut tuner = AutoTuner::new(bounds, 60);
+
+        assert_eq!(tuner.adjust(30.0, 0), None);
+        assert_eq!(tuner.adjust(5.0, 0), Some((48, Adjustment::Headroom)));
+        assert_eq!(tuner.adjust(5.0, 2), Some((72, Adjustment::RateLimited)));
+        assert_eq!(tuner.adjust(100.0, 0), Some((125, Adjustment::SlowCycle)));
+
+        // Never past the bounds
+        assert_eq!(tuner.adjust(1000.0, 0), Some((300, Adjustment::SlowCycle)));
+        assert_eq!(tuner.adjust(1000.0, 0), None);
+        for _ in 0..20 {
+            tuner.adjust(1.0, 0);
+        }
+        assert_eq!(tuner.interval_secs(), 30);
+    }
+}
@@ -0,0 +1,753 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, CreateAllowedMentions, CreateAttachment, CreateEmbed,
+    CreateMessage, EditMessage, Http, HttpError, MessageId,
+};
+use tokio::fs;
+use tokio::sync::mpsc;
+use tokio::task::{self, JoinSet};
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::{debug, error, info, instrument, warn};
+
+use crate::activity::{ActivityEvent, ActivityFeed};
+use crate::clock::Clock;
+use crate::db::{BufferedAlert, Repository};
+use crate::discord::match_details::details_button;
+use crate::discord::presence::OnlineMembers;
+use crate::error::{AppError, ResultExt};
+
+use super::alert::AlertModel;
+use super::latency::DeliveryLatency;
+use super::relay::Relays;
+use super::throttle::{AlertThrottle, Overflow};
+
+/// How often alerts buffered while Discord was unreachable are retried
+const FLUSH_INTERVAL_SECS: u64 = 60;
+/// Guilds an alert is sent to at once. Serenity's ratelimiter still spaces
+/// out requests to the same channel.
+const SEND_CONCURRENCY: usize = 8;
+
+/// A guild channel an alert goes to, with the image rendered in the guild's style
+#[derive(Debug)]
+pub struct AlertTarget {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    /// Most alerts the guild gets per hour, unlimited when unset
+    pub rate_limit: Option<u32>,
+    /// The guild's own wording, posted with the image
+    pub text: Option<String>,
+    /// Shared between guilds using the same style, `None` for guilds with
+    /// image alerts switched off, which get the summary as an embed
+    pub image: Option<Arc<[u8]>>,
+}
+
+/// A rendered alert waiting to be sent to every guild tracking the player
+#[derive(Debug)]
+pub struct PendingAlert {
+    pub player_id: i64,
+    pub match_id: String,
+    /// Unix time the game ended, for delivery latency
+    pub game_ended_at: i64,
+    pub targets: Vec<AlertTarget>,
+    /// Text of the result, for relays outside Discord
+    pub summary: AlertModel,
+}
+
+/// Sending half of the bounded queue between the poller and the dispatcher.
+/// Pushing waits while the queue is full, so slow Discord sends slow down polling
+/// instead of piling up in memory.
+#[derive(Debug, Clone)]
+pub struct AlertQueue {
+    tx: mpsc::Sender<PendingAlert>,
+}
+
+impl AlertQueue {
+    pub fn new(capacity: usize) -> (Self, mpsc::Receiver<PendingAlert>) {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        (Self { tx }, rx)
+    }
+
+    pub async fn push(&self, alert: PendingAlert) -> Result<(), AppError> {
+        if self.tx.capacity() == 0 {
+            warn!(
+                depth = self.depth(),
+                "🎮 ⚠️ Alert queue full, waiting for dispatcher"
+            );
+        }
+
+        self.tx
+            .send(alert)
+            .await
+            .map_err(|_| AppError::DispatcherStopped)
+    }
+
+    /// Number of alerts waiting to be dispatched
+    pub fn depth(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
+    }
+}
+
+/// Where dispatched alerts end up
+#[derive(Debug, Clone)]
+pub enum AlertSink {
+    /// Each tracking guild's alert channel
+    Guilds,
+    /// Shadow deployment: alerts are only written to disk and/or an ops channel
+    Shadow {
+        output_dir: Option<PathBuf>,
+        ops_channel: Option<ChannelId>,
+    },
+}
+
+/// Sends the alerts queued by the poller
+pub struct Dispatcher {
+    pub db: Repository,
+    pub http: Arc<Http>,
+    pub sink: AlertSink,
+    pub latency: Arc<DeliveryLatency>,
+    pub relays: Arc<Relays>,
+    pub activity: ActivityFeed,
+    pub clock: Arc<dyn Clock>,
+    /// Members online in each guild, when players may quiet their own alerts
+    pub online: Option<Arc<OnlineMembers>>,
+    /// Alerts that could not reach Discord for longer than this are dropped
+    pub buffer_max_age_secs: i64,
+}
+
+impl Dispatcher {
+    pub async fn run(self, mut rx: mpsc::Receiver<PendingAlert>) {
+        let Self {
+            db,
+            http,
+            sink,
+            latency,
+            relays,
+            activity,
+            clock,
+            online,
+            buffer_max_age_secs,
+        } = self;
+        let mut flush = interval(Duration::from_secs(FLUSH_INTERVAL_SECS));
+        flush.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut throttle = AlertThrottle::default();
+
+        info!(
+            capacity = rx.max_capacity(),
+            shadow = matches!(sink, AlertSink::Shadow { .. }),
+            "🎮 Alert dispatcher started"
+        );
+
+        loop {
+            let mut alert = tokio::select! {
+                alert = rx.recv() => match alert {
+                    Some(alert) => alert,
+                    None => break,
+                },
+                _ = flush.tick() => {
+                    if matches!(sink, AlertSink::Guilds) {
+                        if let Err(e) =
+                            flush_buffered(&db, &http, &activity, clock.unix_now(), buffer_max_age_secs).await
+                        {
+                            warn!(error = ?e, "🎮 ⚠️ Failed to flush buffered alerts");
+                        }
+                        for overflow in throttle.due_overflows(clock.unix_now()) {
+                            send_overflow(&http, &overflow).await;
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            let result = match &sink {
+                AlertSink::Guilds => {
+                    // Every guild gets the same match, relays get the first rendering
+                    let relay_image = alert.targets.iter().find_map(|target| target.image.clone());
+                    if let Some(online) = &online {
+                        hold_back_online(&db, &http, online, &mut alert).await;
+                    }
+                    let result = dispatch(
+                        &db,
+                        &http,
+                        &activity,
+                        &mut throttle,
+                        clock.unix_now(),
+                        &alert,
+                    )
+                    .await;
+                    // Relays run on their own, a slow chat doesn't hold up
+                    // the next Discord alert
+                    if !relays.is_empty() {
+                        let (relays, summary) = (Arc::clone(&relays), alert.summary.clone());
+                        tokio::spawn(async move {
+                            relays.broadcast(&summary, relay_image.as_deref()).await;
+                        });
+                    }
+                    result
+                }
+                AlertSink::Shadow {
+                    output_dir,
+                    ops_channel,
+                } => dispatch_shadow(&http, output_dir.as_ref(), *ops_channel, &alert)
+                    .await
+                    .map(|()| false),
+            };
+
+            match result {
+                Ok(true) => {
+                    let delay_secs = clock.unix_now() - alert.game_ended_at;
+                    latency.record(delay_secs);
+                    debug!(match_id = %alert.match_id, delay_secs, "🎮 Alert delivered");
+                }
+                Ok(false) => {}
+                Err(e) => error!(
+                    error = ?e,
+                    player_id = alert.player_id,
+                    match_id = %alert.match_id,
+                    "🎮 ❌ Failed to dispatch alert"
+                ),
+            }
+
+            // Failed sends are not retried, only alerts cut short by a restart
+            if let Err(e) = db
+                .mark_match_dispatched(alert.player_id, &alert.match_id, clock.unix_now())
+                .await
+            {
+                warn!(error = ?e, match_id = %alert.match_id, "🗄️ ⚠️ Failed to mark match dispatched");
+            }
+            activity.publish(ActivityEvent::MatchProcessed {
+                player_id: alert.player_id,
+                match_id: alert.match_id,
+            });
+        }
+
+        warn!("🎮 ⚠️ Alert dispatcher stopped");
+    }
+}
+
+/// Drop the guilds where the player asked to be left out while online, and is.
+/// Their "game in progress" message goes too, it would never get the result.
+async fn hold_back_online(
+    db: &Repository,
+    http: &Http,
+    online: &OnlineMembers,
+    alert: &mut PendingAlert,
+) {
+    let quiet = match db.get_quiet_users(alert.player_id).await {
+        Ok(quiet) => quiet,
+        Err(e) => {
+            warn!(error = ?e, player_id = alert.player_id, "🗄️ ⚠️ Failed to load quiet users");
+            return;
+        }
+    };
+    let mut held_back_guilds = Vec::new();
+    alert.targets.retain(|target| {
+        let held_back = quiet.iter().any(|(guild_id, user_id)| {
+            guild_id.get() == target.guild_id && online.is_online(target.guild_id, user_id.get())
+        });
+        if held_back {
+            debug!(
+                guild_id = target.guild_id,
+                player_id = alert.player_id,
+                "🎮 Player online, holding back their alert"
+            );
+            held_back_guilds.push(target.guild_id);
+        }
+        !held_back
+    });
+    if held_back_guilds.is_empty() {
+        return;
+    }
+
+    let live_messages = match db.get_live_messages(alert.player_id).await {
+        Ok(messages) => messages,
+        Err(e) => {
+            warn!(error = ?e, player_id = alert.player_id, "🗄️ ⚠️ Failed to load live game messages");
+            return;
+        }
+    };
+    for live in live_messages
+        .iter()
+        .filter(|m| m.match_id == alert.match_id && held_back_guilds.contains(&m.guild_id.get()))
+    {
+        let guild_id = live.guild_id.get();
+        if let Err(e) = ChannelId::new(live.channel_id.get())
+            .delete_message(http, MessageId::new(live.message_id.get()))
+            .await
+        {
+            warn!(error = ?e, guild_id, "🎮 ⚠️ Failed to delete live game message");
+        }
+        if let Err(e) = db.remove_live_message(alert.player_id, guild_id).await {
+            warn!(error = ?e, guild_id, "🗄️ ⚠️ Failed to forget live game message");
+        }
+    }
+}
+
+/// One guild's copy of an alert, ready to send. The image is shared with the
+/// other guilds using the same style.
+#[derive(Clone)]
+struct Delivery {
+    guild_id: u64,
+    channel_id: u64,
+    text: Option<String>,
+    body: AlertBody,
+    /// (channel, message) of the "game in progress" message it replaces
+    live_message: Option<(u64, u64)>,
+}
+
+/// What the guild is posted: the rendered image, or the result summary as an
+/// embed for guilds without image alerts
+#[derive(Clone)]
+enum AlertBody {
+    Image(Arc<[u8]>),
+    Embed(AlertModel),
+}
+
+impl Delivery {
+    /// The delivery as kept while Discord is unreachable
+    fn buffered(&self, player_id: i64, match_id: &str, queued_at: i64) -> BufferedAlert {
+        let (image, embed) = match &self.body {
+            AlertBody::Image(image) => (Some(image.to_vec()), None),
+            AlertBody::Embed(summary) => (None, serde_json::to_string(summary).ok()),
+        };
+        BufferedAlert {
+            player_id,
+            guild_id: self.guild_id.into(),
+            match_id: match_id.to_string(),
+            channel_id: self.channel_id.into(),
+            image,
+            embed,
+            content: self.text.clone(),
+            live_channel_id: self.live_message.map(|(channel, _)| channel.into()),
+            live_message_id: self.live_message.map(|(_, message)| message.into()),
+            queued_at,
+        }
+    }
+
+    /// A buffered alert ready to be sent again, `None` when it can't be read
+    fn from_buffered(alert: BufferedAlert) -> Option<Self> {
+        let body = match (alert.image, alert.embed) {
+            (Some(image), _) => AlertBody::Image(image.into()),
+            (None, embed) => AlertBody::Embed(serde_json::from_str(&embed?).ok()?),
+        };
+        Some(Self {
+            guild_id: alert.guild_id.get(),
+            channel_id: alert.channel_id.get(),
+            text: alert.content,
+            body,
+            live_message: alert
+                .live_channel_id
+                .zip(alert.live_message_id)
+                .map(|(channel, message)| (channel.get(), message.get())),
+        })
+    }
+}
+
+/// Send the alert to each target, true when at least one got it
+#[instrument(skip_all, fields(player_id = alert.player_id, match_id = %alert.match_id))]
+async fn dispatch(
+    db: &Repository,
+    http: &Arc<Http>,
+    activity: &ActivityFeed,
+    throttle: &mut AlertThrottle,
+    now: i64,
+    alert: &PendingAlert,
+) -> Result<bool, AppError> {
+    let live_messages = db
+        .get_live_messages(alert.player_id)
+        .await
+        .with_context(|| "loading live game messages")?;
+
+    let mut deliveries = Vec::with_capacity(alert.targets.len());
+    for target in &alert.targets {
+        let (guild_id, channel_id) = (target.guild_id, target.channel_id);
+
+        // Already sent before a restart interrupted the alert
+        if db
+            .was_alert_sent(alert.player_id, guild_id, &alert.match_id)
+            .await
+            .with_context(|| format!("checking alert log of guild {guild_id}"))?
+        {
+            debug!(guild_id, channel_id, "🎮 Alert already sent, skipping");
+            continue;
+        }
+
+        if !throttle.allow(guild_id, channel_id, target.rate_limit, now) {
+            debug!(
+                guild_id,
+                channel_id, "🎮 Hourly alert limit reached, skipping"
+            );
+            continue;
+        }
+
+        // The "game in progress" message of this match becomes the result
+        let live_message = live_messages
+            .iter()
+            .find(|m| m.guild_id.get() == guild_id && m.match_id == alert.match_id)
+            .map(|live| (live.channel_id.get(), live.message_id.get()));
+        deliveries.push(Delivery {
+            guild_id,
+            channel_id,
+            text: target.text.clone(),
+            body: match &target.image {
+                Some(image) => AlertBody::Image(Arc::clone(image)),
+                None => AlertBody::Embed(alert.summary.clone()),
+            },
+            live_message,
+        });
+    }
+
+    // Guilds are sent to side by side, a few at a time
+    let mut sends = JoinSet::new();
+    let mut tasks = HashMap::new();
+    let mut outcomes: Vec<Option<Result<(), Box<serenity::Error>>>> =
+        deliveries.iter().map(|_| None).collect();
+    for (index, delivery) in deliveries.iter().enumerate() {
+        if sends.len() >= SEND_CONCURRENCY
+            && let Some(joined) = sends.join_next_with_id().await
+        {
+            settle(joined, &tasks, &mut outcomes);
+        }
+        let (http, delivery, player_id, match_id) = (
+            Arc::clone(http),
+            delivery.clone(),
+            alert.player_id,
+            alert.match_id.clone(),
+        );
+        let task =
+            sends.spawn(async move { deliver(&http, &delivery, (player_id, &match_id)).await });
+        tasks.insert(task.id(), index);
+    }
+    while let Some(joined) = sends.join_next_with_id().await {
+        settle(joined, &tasks, &mut outcomes);
+    }
+
+    // Every outcome is recorded before a failure is returned, so guilds that
+    // got the alert aren't sent it again on retry
+    let mut delivered = false;
+    let mut failure = None;
+    for (delivery, outcome) in deliveries.iter().zip(outcomes) {
+        let (guild_id, channel_id) = (delivery.guild_id, delivery.channel_id);
+        if delivery.live_message.is_some()
+            && let Err(e) = db.remove_live_message(alert.player_id, guild_id).await
+        {
+            warn!(error = ?e, guild_id, "🗄️ ⚠️ Failed to remove live game message");
+        }
+
+        match outcome {
+            // The send task died, its error is logged by `settle`
+            None => warn!(
+                guild_id,
+                channel_id, "🎮 ⚠️ Alert not sent, its send task failed"
+            ),
+            Some(Err(e)) if is_unreachable(&e) => {
+                warn!(
+                    error = ?e,
+                    guild_id,
+                    channel_id,
+                    "🎮 ⚠️ Discord unreachable, buffering alert"
+                );
+                let buffered = delivery.buffered(alert.player_id, &alert.match_id, now);
+                if let Err(e) = db
+                    .buffer_alert(&buffered)
+                    .await
+                    .with_context(|| format!("buffering alert of guild {guild_id}"))
+                {
+                    error!(error = ?e, guild_id, "🗄️ ❌ Failed to buffer alert");
+                    failure.get_or_insert(e);
+                }
+            }
+            Some(Err(e)) => error!(
+                error = ?e,
+                guild_id,
+                channel_id,
+                "🎮 ❌ Failed to send alert message"
+            ),
+            Some(Ok(())) => {
+                debug!(guild_id, channel_id, "🎮 ✅ Alert sent");
+                delivered = true;
+                activity.publish(ActivityEvent::AlertSent {
+                    guild_id,
+                    player_id: alert.player_id,
+                    match_id: alert.match_id.clone(),
+                });
+                if let Err(e) = db
+                    .record_alert(alert.player_id, guild_id, &alert.match_id)
+                    .await
+                    .with_context(|| format!("recording alert in guild {guild_id}"))
+                {
+                    warn!(error = ?e, guild_id, "🗄️ ⚠️ Failed to record alert");
+                }
+            }
+        }
+    }
+
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    Ok(delivered)
+}
+
+/// Store the outcome of a finished send task under its delivery. A task that
+/// panicked or was cancelled leaves no outcome, the guild counts as failed.
+fn settle(
+    joined: Result<(task::Id, Result<(), Box<serenity::Error>>), task::JoinError>,
+    tasks: &HashMap<task::Id, usize>,
+    outcomes: &mut [Option<Result<(), Box<serenity::Error>>>],
+) {
+    match joined {
+        Ok((id, sent)) => {
+            if let Some(&index) = tasks.get(&id) {
+                outcomes[index] = Some(sent);
+            }
+        }
+        Err(e) => error!(error = ?e, "🎮 ❌ Alert send task failed"),
+    }
+}
+
+/// Post one guild's alert, replacing its live game message when there is one
+async fn deliver(
+    http: &Http,
+    delivery: &Delivery,
+    (player_id, match_id): (i64, &str),
+) -> Result<(), Box<serenity::Error>> {
+    let attachment = match &delivery.body {
+        AlertBody::Image(image) => {
+            Some(CreateAttachment::bytes(image.as_ref(), "match_result.png"))
+        }
+        AlertBody::Embed(_) => None,
+    };
+    let embed = match &delivery.body {
+        AlertBody::Image(_) => None,
+        AlertBody::Embed(summary) => Some(summary.embed()),
+    };
+
+    if let Some((live_channel, live_id)) = delivery.live_message {
+        let mut edit = EditMessage::new().components(vec![details_button(player_id, match_id)]);
+        edit = match (&attachment, &embed) {
+            (Some(attachment), _) => edit.embeds(Vec::new()).new_attachment(attachment.clone()),
+            (None, embed) => edit.embeds(embed.iter().cloned().collect()),
+        };
+        if let Some(text) = &delivery.text {
+            edit = edit
+                .content(text)
+                .allowed_mentions(CreateAllowedMentions::new());
+        }
+        match ChannelId::new(live_channel)
+            .edit_message(http, MessageId::new(live_id), edit)
+            .await
+        {
+            Ok(_) => return Ok(()),
+            Err(e) => warn!(
+                error = ?e,
+                guild_id = delivery.guild_id,
+                "🎮 ⚠️ Failed to replace live game message, sending a new one"
+            ),
+        }
+    }
+
+    let mut message = CreateMessage::new().components(vec![details_button(player_id, match_id)]);
+    message = match (attachment, embed) {
+        (Some(attachment), _) => message.add_file(attachment),
+        (None, embed) => message.embeds(embed.into_iter().collect()),
+    };
+    if let Some(text) = &delivery.text {
+        // Guild wording is posted as is, it must not ping anyone
+        message = message
+            .content(text)
+            .allowed_mentions(CreateAllowedMentions::new());
+    }
+    ChannelId::new(delivery.channel_id)
+        .send_message(http, message)
+        .await
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+/// Discord being down or out of reach, as opposed to a rejected message
+fn is_unreachable(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(HttpError::Request(_)) => true,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response)) => {
+            response.status_code.is_server_error()
+        }
+        _ => false,
+    }
+}
+
+/// Tell the guild how many games its hourly limit held back
+async fn send_overflow(http: &Http, overflow: &Overflow) {
+    let embed = CreateEmbed::new()
+        .description(format!(
+            "…and **{}** more game{} (this server gets at most {} alerts per hour)",
+            overflow.skipped,
+            if overflow.skipped == 1 { "" } else { "s" },
+            overflow.limit
+        ))
+        .color(0x5865f2);
+    match ChannelId::new(overflow.channel_id)
+        .send_message(http, CreateMessage::new().embed(embed))
+        .await
+    {
+        Ok(_) => debug!(
+            guild_id = overflow.guild_id,
+            skipped = overflow.skipped,
+            "🎮 Alert overflow posted"
+        ),
+        Err(e) => warn!(
+            error = ?e,
+            guild_id = overflow.guild_id,
+            "🎮 ⚠️ Failed to post alert overflow"
+        ),
+    }
+}
+
+/// Send the alerts buffered while Discord was unreachable, oldest first,
+/// dropping the ones too old to still be interesting
+async fn flush_buffered(
+    db: &Repository,
+    http: &Http,
+    activity: &ActivityFeed,
+    now: i64,
+    max_age_secs: i64,
+) -> Result<(), AppError> {
+    let dropped = db
+        .drop_stale_buffered_alerts(now - max_age_secs)
+        .await
+        .with_context(|| "dropping stale buffered alerts")?;
+    if dropped > 0 {
+        warn!(dropped, "🎮 ⚠️ Dropped buffered alerts too old to send");
+    }
+
+    for alert in db.get_buffered_alerts().await? {
+        let (player_id, guild_id, match_id, queued_at) = (
+            alert.player_id,
+            alert.guild_id.get(),
+            alert.match_id.clone(),
+            alert.queued_at,
+        );
+
+        if db.was_alert_sent(player_id, guild_id, &match_id).await? {
+            debug!(guild_id, match_id, "🎮 Buffered alert already sent");
+        } else if let Some(delivery) = Delivery::from_buffered(alert) {
+            match deliver(http, &delivery, (player_id, &match_id)).await {
+                Ok(()) => {
+                    info!(
+                        guild_id,
+                        match_id,
+                        delay_secs = now - queued_at,
+                        "🎮 ✅ Buffered alert sent"
+                    );
+                    activity.publish(ActivityEvent::AlertSent {
+                        guild_id,
+                        player_id,
+                        match_id: match_id.clone(),
+                    });
+                    if let Err(e) = db.record_alert(player_id, guild_id, &match_id).await {
+                        warn!(error = ?e, guild_id, "🗄️ ⚠️ Failed to record alert");
+                    }
+                }
+                // Still down, keep everything for the next flush
+                Err(e) if is_unreachable(&e) => {
+                    debug!(error = ?e, "🎮 Discord still unreachable");
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!(error = ?e, guild_id, match_id, "🎮 ⚠️ Dropping buffered alert");
+                }
+            }
+        } else {
+            warn!(
+                guild_id,
+                match_id, "🎮 ⚠️ Dropping unreadable buffered alert"
+            );
+        }
+
+        db.remove_buffered_alert(player_id, guild_id, &match_id)
+            .await
+            .with_context(|| format!("removing buffered alert of guild {guild_id}"))?;
+    }
+
+    Ok(())
+}
+
+#[instrument(skip_all, fields(player_id = alert.player_id, match_id = %alert.match_id))]
+async fn dispatch_shadow(
+    http: &Http,
+    output_dir: Option<&PathBuf>,
+    ops_channel: Option<ChannelId>,
+    alert: &PendingAlert,
+) -> Result<(), AppError> {
+    for target in &alert.targets {
+        info!(
+            guild_id = target.guild_id,
+            channel_id = target.channel_id,
+            "🎮 👻 Shadow alert (not sent)"
+        );
+    }
+
+    // Every guild gets the same match, keep the first rendering as the sample.
+    // Without one, the summary embed the guilds would get stands in for it
+    let body = match alert
+        .targets
+        .iter()
+        .find_map(|target| target.image.as_ref())
+    {
+        Some(image) => AlertBody::Image(Arc::clone(image)),
+        None => AlertBody::Embed(alert.summary.clone()),
+    };
+    let text = alert
+        .targets
+        .iter()
+        .find_map(|target| target.text.as_deref());
+    let stem = format!("{}_{}", alert.match_id, alert.player_id);
+
+    if let Some(dir) = output_dir {
+        fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("creating shadow output dir {}", dir.display()))?;
+        let mut files = Vec::new();
+        match &body {
+            AlertBody::Image(image) => files.push((format!("{stem}.png"), image.to_vec())),
+            AlertBody::Embed(summary) => {
+                let json = serde_json::to_vec_pretty(summary)
+                    .map_err(std::io::Error::from)
+                    .with_context(|| "serializing shadow alert summary")?;
+                files.push((format!("{stem}.json"), json));
+            }
+        }
+        if let Some(text) = text {
+            files.push((format!("{stem}.txt"), text.as_bytes().to_vec()));
+        }
+        for (file_name, contents) in files {
+            let path = dir.join(file_name);
+            if let Err(e) = fs::write(&path, contents).await {
+                warn!(error = ?e, path = %path.display(), "🎮 ⚠️ Failed to write shadow alert");
+            }
+        }
+    }
+
+    if let Some(channel) = ops_channel {
+        let mut content = format!("👻 Shadow alert for {} guild(s)", alert.targets.len());
+        if let Some(text) = text {
+            content.push_str(&format!("\n{text}"));
+        }
+        let message = CreateMessage::new().content(content);
+        let message = match &body {
+            AlertBody::Image(image) => message.add_file(CreateAttachment::bytes(
+                image.as_ref(),
+                format!("{stem}.png"),
+            )),
+            AlertBody::Embed(summary) => message.embed(summary.embed()),
+        };
+        channel
+            .send_message(http, message)
+            .await
+            .with_context(|| format!("posting shadow alert to channel {channel}"))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+/// Failed checks in a row before an account is reported and slowed down
+const FAILURE_BUDGET: u32 = 5;
+/// Time between checks of an account past its budget
+pub const RETRY_AFTER_SECS: i64 = 15 * 60;
+
+#[derive(Debug, Default)]
+struct Failures {
+    in_a_row: u32,
+    /// Unix time of the next check, once past the budget
+    retry_at: Option<i64>,
+}
+
+/// Consecutive check failures of each account. An account that keeps
+/// failing (deleted, moved, Riot bug) is reported once to the guilds tracking
+/// it and checked less often, until a check goes through again. Kept in
+/// memory: a restart gives every account a fresh budget.
+#[derive(Debug, Default)]
+pub struct ErrorBudget {
+    players: HashMap<i64, Failures>,
+}
+
+impl ErrorBudget {
+    /// Whether the account is due for a check
+    pub fn should_check(&self, player_id: i64, now: i64) -> bool {
+        self.players
+            .get(&player_id)
+            .and_then(|failures| failures.retry_at)
+            .is_none_or(|retry_at| now >= retry_at)
+    }
+
+    /// Count a failed check, `true` when it used up the budget and the
+    /// guilds should hear about it
+    pub fn record_failure(&mut self, player_id: i64, now: i64) -> bool {
+        let failures = self.players.entry(player_id).or_default();
+        failures.in_a_row += 1;
+        if failures.in_a_row < FAILURE_BUDGET {
+            return false;
+        }
+        let exhausted = failures.retry_at.is_none();
+        failures.retry_at = Some(now + RETRY_AFTER_SECS);
+        exhausted
+    }
+
+    /// Forget the failures of an account, `true` if it was past its budget
+    pub fn record_success(&mut self, player_id: i64) -> bool {
+        self.players
+            .remove(&player_id)
+            .is_some_and(|failures| failures.retry_at.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorBudget, FAILURE_BUDGET, RETRY_AFTER_SECS};
+
+    #[test]
+    fn reports_once_then_checks_less_often() {
+        let mut budget = ErrorBudget::default();
+        for _ in 1..FAILURE_BUDGET {
+            assert!(!budget.record_failure(1, 0));
+            assert!(budget.should_check(1, 0));
+        }
+        assert!(budget.record_failure(1, 0));
+        assert!(!budget.should_check(1, 60));
+        assert!(budget.should_check(2, 60));
+
+        // Still failing at the retry, no second report
+        assert!(budget.should_check(1, RETRY_AFTER_SECS));
+        assert!(!budget.record_failure(1, RETRY_AFTER_SECS));
+        assert!(!budget.should_check(1, RETRY_AFTER_SECS + 60));
+
+        assert!(budget.record_success(1));
+        assert!(budget.should_check(1, RETRY_AFTER_SECS + 60));
+        assert!(!budget.record_success(1));
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::{HashMap, HashSet};
+
+use poise::serenity_prelude::{ChannelId, Http};
+use tracing::{debug, instrument, warn};
+
+use crate::db::{LadderWatch, Player, Repository};
+use crate::discord::EmojiStore;
+use crate::discord::moderation::ContentFilter;
+use crate::error::AppError;
+use crate::riot::{ApexTier, LeagueListDto, Platform, RiotClient};
+
+use super::alert::AlertModel;
+
+/// Number of ladder spots whose cutoff is reported
+const TOP_N: usize = 10;
+
+/// Report the cutoff changes of every watched apex ladder. Without `http`
+/// (shadow mode) the watches are updated but nothing is posted.
+#[instrument(skip_all, fields(watch_count))]
+pub async fn check_ladders(
+    db: &Repository,
+    riot: &RiotClient,
+    http: Option<&Http>,
+    emojis: &EmojiStore,
+    filter: &ContentFilter,
+) -> Result<(), AppError> {
+    let watches = db.get_ladder_watches().await?;
+    if watches.is_empty() {
+        debug!("🏆 No ladder watched, skipping");
+        return Ok(());
+    }
+    tracing::Span::current().record("watch_count", watches.len());
+
+    // Each ladder is fetched once, however many guilds watch it
+    let mut ladders: HashMap<(String, String), Option<LeagueListDto>> = HashMap::new();
+
+    for watch in watches {
+        let key = (watch.region.clone(), watch.tier.clone());
+        if !ladders.contains_key(&key) {
+            let ladder = fetch_ladder(riot, &watch).await;
+            ladders.insert(key.clone(), ladder);
+        }
+        let Some(Some(ladder)) = ladders.get(&key) else {
+            continue;
+        };
+
+        if let Err(e) = check_watch(db, http, emojis, filter, &watch, ladder).await {
+            warn!(
+                error = ?e,
+                guild_id = watch.guild_id.get(),
+                region = %watch.region,
+                tier = %watch.tier,
+                "🏆 ⚠️ Failed to update ladder watch"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn fetch_ladder(riot: &RiotClient, watch: &LadderWatch) -> Option<LeagueListDto> {
+    let (Ok(platform), Ok(tier)) = (
+        watch.region.parse::<Platform>(),
+        watch.tier.parse::<ApexTier>(),
+    ) else {
+        warn!(region = %watch.region, tier = %watch.tier, "🏆 ⚠️ Invalid ladder watch");
+        return None;
+    };
+
+    match riot.get_apex_league(platform, tier).await {
+        Ok(ladder) => Some(ladder),
+        Err(e) => {
+            warn!(error = ?e, region = %watch.region, tier = %watch.tier, "🏆 ⚠️ Failed to fetch ladder");
+            None
+        }
+    }
+}
+
+async fn check_watch(
+    db: &Repository,
+    http: Option<&Http>,
+    emojis: &EmojiStore,
+    filter: &ContentFilter,
+    watch: &LadderWatch,
+    ladder: &LeagueListDto,
+) -> Result<(), AppError> {
+    let cutoff = ladder.top_cutoff(TOP_N);
+
+    let ladder_puuids: HashSet<&str> = ladder.entries.iter().map(|e| e.puuid.as_str()).collect();
+    let tracked: Vec<Player> = db
+        .get_guild_players(watch.guild_id.get())
+        .await?
+        .into_iter()
+        .filter(|p| p.region.eq_ignore_ascii_case(&watch.region))
+        .collect();
+    let members: Vec<&Player> = tracked
+        .iter()
+        .filter(|p| ladder_puuids.contains(p.puuid.as_str()))
+        .collect();
+
+    let previous: HashSet<i64> = db
+        .get_ladder_watch_members(watch)
+        .await?
+        .into_iter()
+        .collect();
+    let entered: Vec<&Player> = members
+        .iter()
+        .filter(|p| !previous.contains(&p.id))
+        .copied()
+        .collect();
+    let left: Vec<&Player> = tracked
+        .iter()
+        .filter(|p| previous.contains(&p.id) && !members.iter().any(|m| m.id == p.id))
+        .collect();
+
+    match (watch.alert_channel_id, http) {
+        (Some(channel_id), Some(http)) => {
+            let alert = build_alert(emojis, filter, watch, cutoff, &entered, &left);
+            ChannelId::new(channel_id.get())
+                .send_message(http, alert.message())
+                .await?;
+        }
+        (Some(_), None) => debug!(
+            guild_id = watch.guild_id.get(),
+            "👻 Shadow mode, ladder update not posted"
+        ),
+        (None, _) => {}
+    }
+
+    let member_ids: Vec<i64> = members.iter().map(|p| p.id).collect();
+    db.update_ladder_watch(watch, cutoff, &member_ids).await?;
+
+    Ok(())
+}
+
+fn build_alert(
+    emojis: &EmojiStore,
+    filter: &ContentFilter,
+    watch: &LadderWatch,
+    cutoff: Option<i32>,
+    entered: &[&Player],
+    left: &[&Player],
+) -> AlertModel {
+    let region = watch.region.to_uppercase();
+    let tier = crate::discord::image_gen::capitalize(&watch.tier);
+
+    let cutoff_line = match (cutoff, watch.top_cutoff_lp) {
+        (Some(now), Some(before)) if now != before => {
+            format!("Top {TOP_N} cutoff: **{now} LP** ({:+})", now - before)
+        }
+        (Some(now), _) => format!("Top {TOP_N} cutoff: **{now} LP**"),
+        (None, _) => "The ladder is empty".to_string(),
+    };
+
+    let mut alert = AlertModel::new(
+        format!("{} {tier} ladder • {region}", emojis.tier(&watch.tier)),
+        0xf4c874,
+    )
+    .description(cutoff_line);
+
+    if !entered.is_empty() {
+        alert = alert.field("Entered", display_names(filter, entered), false);
+    }
+    if !left.is_empty() {
+        alert = alert.field("Left", display_names(filter, left), false);
+    }
+
+    alert
+}
+
+fn display_names(filter: &ContentFilter, players: &[&Player]) -> String {
+    players
+        .iter()
+        .map(|p| format!("- **{}**", filter.apply(&p.display_name())))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_alert;
+    use crate::db::fixtures::player;
+    use crate::db::{LadderWatch, Player, SnowflakeColumn};
+    use crate::discord::EmojiStore;
+    use crate::discord::moderation::ContentFilter;
+
+    #[test]
+    fn ladder_embed_snapshot() {
+        let watch = LadderWatch {
+            guild_id: SnowflakeColumn::from(1),
+            alert_channel_id: None,
+            region: "euw1".into(),
+            tier: "CHALLENGER".into(),
+            top_cutoff_lp: Some(1500),
+        };
+        let entered = player("Caps", "EUW");
+        let left = Player {
+            privacy_alias: Some("Mystery".into()),
+            ..player("Streamer", "EUW")
+        };
+        let alert = build_alert(
+            &EmojiStore::default(),
+            &ContentFilter::default(),
+            &watch,
+            Some(1532),
+            &[&entered],
+            &[&left],
+        );
+        insta::assert_json_snapshot!(alert.embed());
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Deliveries kept for the percentiles
+const MAX_SAMPLES: usize = 500;
+
+/// Delay percentiles over the recent deliveries, in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencySnapshot {
+    pub p50: i64,
+    pub p95: i64,
+    pub samples: usize,
+}
+
+/// Time between the end of a game and its alert reaching Discord, over the
+/// last `MAX_SAMPLES` alerts, to tell how much the poll interval adds
+#[derive(Debug, Default)]
+pub struct DeliveryLatency {
+    samples: Mutex<VecDeque<i64>>,
+}
+
+impl DeliveryLatency {
+    pub fn record(&self, delay_secs: i64) {
+        if let Ok(mut samples) = self.samples.lock() {
+            if samples.len() == MAX_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back(delay_secs.max(0));
+        }
+    }
+
+    /// `None` until an alert was delivered
+    pub fn snapshot(&self) -> Option<LatencySnapshot> {
+        let mut sorted: Vec<i64> = self.samples.lock().ok()?.iter().copied().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_unstable();
+
+        // Nearest rank
+        let percentile = |p: usize| sorted[(sorted.len() * p).div_ceil(100).max(1) - 1];
+        Some(LatencySnapshot {
+            p50: percentile(50),
+            p95: percentile(95),
+            samples: sorted.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeliveryLatency, LatencySnapshot, MAX_SAMPLES};
+
+    #[test]
+    fn percentiles_over_recent_deliveries() {
+        let latency = DeliveryLatency::default();
+        assert_eq!(latency.snapshot(), None);
+
+        for delay in 1..=100 {
+            latency.record(delay);
+        }
+        assert_eq!(
+            latency.snapshot(),
+            Some(LatencySnapshot {
+                p50: 50,
+                p95: 95,
+                samples: 100
+            })
+        );
+
+        // Old deliveries fall out of the window
+        for _ in 0..MAX_SAMPLES {
+            latency.record(7);
+        }
+        let snapshot = latency.snapshot().unwrap();
+        assert_eq!((snapshot.p50, snapshot.p95), (7, 7));
+    }
+}
@@ -0,0 +1,193 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use poise::serenity_prelude::{ChannelId, Http, MessageId};
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::{debug, error, info, instrument, warn};
+
+use crate::clock::Clock;
+use crate::db::{Player, Repository};
+use crate::discord::features::{Feature, FeatureGate};
+use crate::discord::moderation::ContentFilter;
+use crate::error::{AppError, ResultExt};
+use crate::riot::{CurrentGameInfo, Platform, RiotClient};
+
+use super::alert::AlertModel;
+
+/// Live messages older than this are forgotten when no result alert replaced
+/// them (unsupported queue, remake never fetched...)
+const STALE_AFTER_SECS: i64 = 3 * 3600;
+
+pub async fn start_live_games(
+    db: Repository,
+    riot: RiotClient,
+    http: Arc<Http>,
+    features: Arc<FeatureGate>,
+    filter: Arc<ContentFilter>,
+    clock: Arc<dyn Clock>,
+    interval_secs: u64,
+) {
+    let mut interval = interval(Duration::from_secs(interval_secs));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    info!(interval_secs, "📺 Live game tracking started");
+
+    loop {
+        interval.tick().await;
+
+        if !features.is_enabled_anywhere(Feature::LiveTracking) {
+            debug!("📺 Live tracking switched off, skipping cycle");
+            continue;
+        }
+        if let Err(e) =
+            update_live_games(&db, &riot, &http, &features, &filter, clock.unix_now()).await
+        {
+            error!(error = ?e, "📺 ❌ Live game cycle failed");
+        }
+    }
+}
+
+#[instrument(skip_all, fields(player_count))]
+async fn update_live_games(
+    db: &Repository,
+    riot: &RiotClient,
+    http: &Http,
+    features: &FeatureGate,
+    filter: &ContentFilter,
+    now: i64,
+) -> Result<(), AppError> {
+    let players = db.get_all_tracked_players().await?;
+    tracing::Span::current().record("player_count", players.len());
+
+    for player in &players {
+        if let Err(e) = update_player(db, riot, http, features, filter, now, player).await {
+            warn!(
+                error = ?e,
+                player_id = player.id,
+                riot_id = %player.riot_id(),
+                "📺 ⚠️ Failed to update live game"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Post a "game in progress" message in every guild tracking the player, or
+/// refresh the game length on the ones already posted
+async fn update_player(
+    db: &Repository,
+    riot: &RiotClient,
+    http: &Http,
+    features: &FeatureGate,
+    filter: &ContentFilter,
+    now: i64,
+    player: &Player,
+) -> Result<(), AppError> {
+    let platform: Platform = player.region.parse()?;
+    let game = riot
+        .get_active_game(platform, &player.puuid)
+        .await
+        .with_context(|| "fetching active game")?;
+    let messages = db.get_live_messages(player.id).await?;
+
+    let Some(game) = game.filter(CurrentGameInfo::is_supported) else {
+        // The result alert replaces the message once the match is over
+        for message in messages
+            .iter()
+            .filter(|m| now - m.started_at > STALE_AFTER_SECS)
+        {
+            db.remove_live_message(player.id, message.guild_id.get())
+                .await?;
+        }
+        return Ok(());
+    };
+
+    let match_id = game.match_id();
+    let guilds = db
+        .get_guilds_tracking_player(player.id)
+        .await
+        .with_context(|| "listing guilds tracking the player")?;
+
+    for guild in &guilds {
+        let Some(channel_id) = guild.alert_channel_id else {
+            continue;
+        };
+        let guild_id = guild.id.get();
+        if !features.is_enabled(Feature::LiveTracking, guild_id) {
+            continue;
+        }
+        let alert = build_alert(filter, player, &game);
+
+        let posted = messages
+            .iter()
+            .find(|m| m.guild_id.get() == guild_id && m.match_id == match_id);
+        let result = match posted {
+            Some(message) => ChannelId::new(message.channel_id.get())
+                .edit_message(http, MessageId::new(message.message_id.get()), alert.edit())
+                .await
+                .map(|_| ()),
+            None => {
+                match ChannelId::new(channel_id.get())
+                    .send_message(http, alert.message())
+                    .await
+                {
+                    Ok(message) => {
+                        debug!(guild_id, match_id, "📺 Live game message posted");
+                        db.set_live_message(
+                            player.id,
+                            guild_id,
+                            &match_id,
+                            channel_id.get(),
+                            message.id.get(),
+                            now,
+                        )
+                        .await?;
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+        };
+
+        if let Err(e) = result {
+            warn!(error = ?e, guild_id, "📺 ⚠️ Failed to post live game message");
+        }
+    }
+
+    Ok(())
+}
+
+fn build_alert(filter: &ContentFilter, player: &Player, game: &CurrentGameInfo) -> AlertModel {
+    let length = game.game_length.max(0);
+    AlertModel::new(
+        filter.apply(&format!("🎮 {} is in game", player.display_name())),
+        0x0ac8b9,
+    )
+    .description(format!(
+        "{} • {}:{:02}",
+        game.queue_name(),
+        length / 60,
+        length % 60
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_alert;
+    use crate::db::fixtures::player;
+    use crate::discord::moderation::ContentFilter;
+    use crate::riot::CurrentGameInfo;
+
+    #[test]
+    fn live_embed_snapshot() {
+        let game = CurrentGameInfo {
+            game_id: 1,
+            platform_id: "KR".into(),
+            game_queue_config_id: 420,
+            game_length: 754,
+        };
+        let alert = build_alert(&ContentFilter::default(), &player("Faker", "KR1"), &game);
+        insta::assert_json_snapshot!(alert.embed());
+    }
+}
@@ -1,14 +1,36 @@
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 use std::time::Duration;
 
-use poise::serenity_prelude::{ChannelId, CreateAttachment, CreateMessage, Http};
-use tokio::time::interval;
+use poise::serenity_prelude::{ChannelId, Http};
+use tokio::time::{Instant, MissedTickBehavior, interval, interval_at};
 use tracing::{Span, debug, error, info, instrument, warn};
 
-use crate::db::{Player, RankInfo, Repository};
-use crate::discord::image_gen::{ImageGenerator, MatchImageContext};
-use crate::error::AppError;
-use crate::riot::{Platform, RiotClient};
+use crate::activity::ActivityFeed;
+use crate::clock::Clock;
+use crate::config::PollingCadence;
+use crate::db::{MatchResult, Player, RankInfo, RankSnapshot, Repository};
+use crate::discord::OpsNotifier;
+use crate::discord::alert_layout::AlertStyle;
+use crate::discord::alert_template::{Outcome, TemplateQueue, TemplateValues, render};
+use crate::discord::features::{Feature, FeatureGate};
+use crate::discord::image_gen::{ImageGenerator, MatchImageContext, calculate_lp_diff, capitalize};
+use crate::discord::match_details::MatchDetails;
+use crate::discord::moderation::ContentFilter;
+use crate::error::{AppError, ResultExt};
+use crate::riot::{
+    GameEnding, InfoDto, ParticipantDto, Platform, Queue, RiotClient, league_queue_name,
+};
+use crate::stats::{
+    RankChange, SessionStats, champion_note, division_forecast, is_apex, performance_score,
+    rank_change, session_stats, start_of_day,
+};
+
+use super::alert::AlertModel;
+use super::auto_tune::AutoTuner;
+use super::dispatcher::{AlertQueue, AlertTarget, PendingAlert};
+use super::error_budget::{ErrorBudget, RETRY_AFTER_SECS};
+use super::targets::AlertTargets;
 
 #[derive(Debug, thiserror::Error)]
 enum PollerError {
@@ -21,93 +43,445 @@ enum PollerError {
     },
 }
 
-pub async fn start_polling(
-    db: Repository,
-    riot: RiotClient,
-    http: Arc<Http>,
-    image_gen: Arc<ImageGenerator>,
-    interval_secs: u64,
-) {
-    let mut interval = interval(Duration::from_secs(interval_secs));
+impl PollerError {
+    fn is_riot_outage(&self) -> bool {
+        matches!(self, PollerError::App(e) if e.is_riot_outage())
+    }
+}
+
+/// Polls tracked players for new games and queues their alerts
+pub struct MatchPoller {
+    pub db: Repository,
+    pub riot: RiotClient,
+    pub alerts: AlertQueue,
+    pub image_gen: Arc<ImageGenerator>,
+    pub ops: OpsNotifier,
+    /// Posts notices about failing accounts to guilds, unset in shadow mode
+    pub http: Option<Arc<Http>>,
+    pub clock: Arc<dyn Clock>,
+    pub cadence: PollingCadence,
+    /// Tracking and alert channel changes, to refresh cached alert targets
+    pub activity: ActivityFeed,
+    pub features: Arc<FeatureGate>,
+}
+
+/// What the poller carries from one cycle to the next
+struct PollState {
+    /// Matches whose alert was queued but not sent before the last shutdown,
+    /// with the rank their player had before the game
+    interrupted: HashMap<(i64, String), Option<RankInfo>>,
+    failures: ErrorBudget,
+    targets: AlertTargets,
+    /// Guilds with image alerts switched off are not rendered for
+    features: Arc<FeatureGate>,
+}
+
+impl MatchPoller {
+    pub async fn run(self) {
+        let Self {
+            db,
+            riot,
+            alerts,
+            image_gen,
+            ops,
+            http,
+            clock,
+            cadence,
+            activity,
+            features,
+        } = self;
+        let PollingCadence {
+            interval_secs,
+            burst,
+            auto_tune,
+        } = cadence;
+        let mut tuner = auto_tune.map(|bounds| AutoTuner::new(bounds, interval_secs));
+        let interval_secs = tuner
+            .as_ref()
+            .map_or(interval_secs, AutoTuner::interval_secs);
+        let mut burst_interval = burst.map(|burst| {
+            let mut burst_interval = interval(Duration::from_secs(burst.interval_secs));
+            burst_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            burst_interval
+        });
+        let mut interval = interval(Duration::from_secs(interval_secs));
+        // Regions answering 5xx during the previous cycle
+        let mut down_regions = BTreeSet::new();
+        // Player id -> unix time its burst ends
+        let mut bursting: HashMap<i64, i64> = HashMap::new();
+        let interrupted = match db.get_undispatched_matches().await {
+            Ok(matches) => matches
+                .into_iter()
+                .map(|interrupted| {
+                    let old_rank = interrupted.old_rank_info();
+                    ((interrupted.player_id, interrupted.match_id), old_rank)
+                })
+                .collect(),
+            Err(e) => {
+                warn!(error = ?e, "🔄 ⚠️ Failed to load interrupted alerts");
+                HashMap::new()
+            }
+        };
+        let mut state = PollState {
+            interrupted,
+            failures: ErrorBudget::default(),
+            targets: AlertTargets::new(activity.subscribe()),
+            features,
+        };
+        if !state.interrupted.is_empty() {
+            info!(
+                count = state.interrupted.len(),
+                "🔄 Resuming alerts interrupted by a restart"
+            );
+        }
+
+        info!(interval_secs, burst = ?burst, auto_tune = ?auto_tune, "🔄 Match poller started");
+
+        loop {
+            let full_cycle = tokio::select! {
+                _ = interval.tick() => true,
+                _ = async {
+                    match burst_interval.as_mut() {
+                        Some(burst_interval) => burst_interval.tick().await,
+                        None => std::future::pending().await,
+                    }
+                } => false,
+            };
+
+            let now = clock.unix_now();
+            bursting.retain(|_, until| *until > now);
+            if !full_cycle && bursting.is_empty() {
+                continue;
+            }
+            let only = (!full_cycle).then_some(&bursting);
+
+            let started = Instant::now();
+            let rate_limited_before = riot.metrics().snapshot().total_rate_limited;
+            let polled = poll_players(
+                &db,
+                &riot,
+                &alerts,
+                &image_gen,
+                clock.as_ref(),
+                only,
+                &mut state,
+            )
+            .await;
+
+            if full_cycle && let Some(tuner) = tuner.as_mut() {
+                let cycle_secs = started.elapsed().as_secs_f64();
+                let rate_limited =
+                    riot.metrics().snapshot().total_rate_limited - rate_limited_before;
+                let from_secs = tuner.interval_secs();
+                if let Some((to_secs, reason)) = tuner.adjust(cycle_secs, rate_limited) {
+                    info!(
+                        from_secs,
+                        to_secs,
+                        ?reason,
+                        cycle_secs,
+                        rate_limited,
+                        "🔄 Poll interval auto-tuned"
+                    );
+                    let period = Duration::from_secs(to_secs);
+                    interval = interval_at(Instant::now() + period, period);
+                }
+            }
 
-    info!(interval_secs, "🔄 Match poller started");
+            match polled {
+                Ok(cycle) => {
+                    if let Some(burst) = burst {
+                        let until = now + burst.window_secs as i64;
+                        for player_id in cycle.detected {
+                            bursting.insert(player_id, until);
+                        }
+                    }
+                    // A burst cycle only sees part of the players, keep outage state
+                    // driven by full cycles
+                    if full_cycle {
+                        report_outages(&ops, &down_regions, &cycle.down_regions).await;
+                        down_regions = cycle.down_regions;
+                    }
+                    if let Some(http) = &http {
+                        for player in &cycle.exhausted {
+                            report_failing_player(&db, http, image_gen.content_filter(), player)
+                                .await;
+                        }
+                    }
+                }
+                Err(e) => error!(error = ?e, "🔄 ❌ Polling cycle failed"),
+            }
+        }
+    }
+}
 
-    loop {
-        interval.tick().await;
+/// What a polling cycle found
+struct PollCycle {
+    /// Regions that had an outage
+    down_regions: BTreeSet<String>,
+    /// Players with a new game
+    detected: Vec<i64>,
+    /// Players whose checks just used up their error budget
+    exhausted: Vec<Player>,
+}
+
+/// One ops notice when a region starts failing and one when it recovers,
+/// rather than an error per account every cycle
+async fn report_outages(ops: &OpsNotifier, before: &BTreeSet<String>, now: &BTreeSet<String>) {
+    let started: Vec<_> = now.difference(before).map(|r| r.to_uppercase()).collect();
+    if !started.is_empty() {
+        ops.notify(&format!(
+            "⚠️ Riot API outage on {}, players there are skipped until it recovers",
+            started.join(", ")
+        ))
+        .await;
+    }
 
-        if let Err(e) = poll_players(&db, &riot, &http, &image_gen).await {
-            error!(error = ?e, "🔄 ❌ Polling cycle failed");
+    let recovered: Vec<_> = before.difference(now).map(|r| r.to_uppercase()).collect();
+    if !recovered.is_empty() {
+        ops.notify(&format!(
+            "✅ Riot API recovered on {}",
+            recovered.join(", ")
+        ))
+        .await;
+    }
+}
+
+/// Tell the guilds tracking a player that its checks keep failing, once
+/// until they work again
+async fn report_failing_player(
+    db: &Repository,
+    http: &Http,
+    filter: &ContentFilter,
+    player: &Player,
+) {
+    warn!(
+        player_id = player.id,
+        riot_id = %player.riot_id(),
+        "🔄 ⚠️ Player keeps failing, checking it less often"
+    );
+    let guilds = match db.get_guilds_tracking_player(player.id).await {
+        Ok(guilds) => guilds,
+        Err(e) => {
+            warn!(error = ?e, player_id = player.id, "🔄 ⚠️ Failed to list guilds tracking the player");
+            return;
+        }
+    };
+    let notice = AlertModel::new(
+        filter.apply(&format!(
+            "⚠️ We can't fetch data for {}",
+            player.display_name()
+        )),
+        0xffa500,
+    )
+    .description(format!(
+        "The Riot API keeps failing for this account, its games may go unnoticed. \
+         It's retried every {} minutes until it works again.",
+        RETRY_AFTER_SECS / 60
+    ));
+    for guild in guilds {
+        let Some(channel_id) = guild.alert_channel_id else {
+            continue;
+        };
+        if let Err(e) = ChannelId::new(channel_id.get())
+            .send_message(http, notice.message())
+            .await
+        {
+            warn!(error = ?e, guild_id = guild.id.get(), "🔄 ⚠️ Failed to post failing player notice");
         }
     }
 }
 
-#[instrument(skip_all, fields(player_count))]
+/// Poll every tracked player, or only those in `only` for a burst cycle
+#[instrument(skip_all, fields(player_count, burst = only.is_some()))]
 async fn poll_players(
     db: &Repository,
     riot: &RiotClient,
-    http: &Http,
+    alerts: &AlertQueue,
     image_gen: &ImageGenerator,
-) -> Result<(), PollerError> {
-    let players = db.get_all_tracked_players().await?;
+    clock: &dyn Clock,
+    only: Option<&HashMap<i64, i64>>,
+    state: &mut PollState,
+) -> Result<PollCycle, PollerError> {
+    let mut players = db.get_all_tracked_players().await?;
+    if let Some(only) = only {
+        players.retain(|player| only.contains_key(&player.id));
+    }
+    let mut cycle = PollCycle {
+        down_regions: BTreeSet::new(),
+        detected: Vec::new(),
+        exhausted: Vec::new(),
+    };
+    let down_regions = &mut cycle.down_regions;
 
     if players.is_empty() {
         debug!("🔄 No players tracked, skipping poll cycle");
-        return Ok(());
+        return Ok(cycle);
     }
 
     Span::current().record("player_count", players.len());
-    info!(count = players.len(), "🔄 Polling {} player(s)", players.len());
+    info!(
+        count = players.len(),
+        "🔄 Polling {} player(s)",
+        players.len()
+    );
 
+    let mut skipped = 0;
+    let mut resting = 0;
+    let now = clock.unix_now();
     for player in players {
-        if let Err(e) = check_player_match(db, riot, http, image_gen, &player).await {
-            warn!(
-                error = ?e,
+        if down_regions.contains(&player.region) {
+            skipped += 1;
+            continue;
+        }
+        if !state.failures.should_check(player.id, now) {
+            resting += 1;
+            continue;
+        }
+
+        let checked = check_player_match(db, riot, alerts, image_gen, clock, &player, state).await;
+        if checked.is_ok() && state.failures.record_success(player.id) {
+            info!(
                 player_id = player.id,
                 riot_id = %player.riot_id(),
-                "🔄 ⚠️ Failed to check player match"
+                "🔄 ✅ Player checks work again"
             );
         }
+        match checked {
+            Ok(true) => cycle.detected.push(player.id),
+            Ok(false) => {}
+            Err(e) if e.is_riot_outage() => {
+                warn!(
+                    error = ?e,
+                    region = %player.region,
+                    "🔄 ⚠️ Riot API failing for region, skipping it this cycle"
+                );
+                down_regions.insert(player.region.clone());
+            }
+            Err(e) => {
+                warn!(
+                    error = ?e,
+                    player_id = player.id,
+                    riot_id = %player.riot_id(),
+                    "🔄 ⚠️ Failed to check player match"
+                );
+                if state.failures.record_failure(player.id, now) {
+                    cycle.exhausted.push(player);
+                }
+            }
+        }
     }
+    if resting > 0 {
+        debug!(
+            resting,
+            "🔄 Skipped players failing repeatedly until their retry"
+        );
+    }
+
+    if skipped > 0 {
+        info!(
+            skipped,
+            regions = ?down_regions,
+            "🔄 Skipped players in regions with an outage"
+        );
+    }
+
+    let usage = riot.metrics().snapshot();
+    for endpoint in &usage.endpoints {
+        debug!(
+            family = %endpoint.family,
+            last_minute = endpoint.last_minute,
+            last_two_hours = endpoint.last_two_hours,
+            avg_latency_ms = endpoint.avg_latency.as_millis() as u64,
+            "🔷 Endpoint usage"
+        );
+    }
+    info!(
+        last_minute = usage.last_minute,
+        last_two_hours = usage.last_two_hours,
+        rate_limited_last_two_hours = usage.rate_limited_last_two_hours,
+        league_cache_hits = usage.cache_hits,
+        league_cache_misses = usage.cache_misses,
+        alert_queue_depth = alerts.depth(),
+        "🔷 API usage"
+    );
 
-    Ok(())
+    Ok(cycle)
 }
 
 #[instrument(
-    skip(db, riot, http, image_gen, player),
+    skip(db, riot, alerts, image_gen, clock, player, state),
     fields(
         player_id = player.id,
         riot_id = %player.riot_id(),
-        region = %player.region
+        region = %player.region,
+        match_id,
+        queue
     )
 )]
 async fn check_player_match(
     db: &Repository,
     riot: &RiotClient,
-    http: &Http,
+    alerts: &AlertQueue,
     image_gen: &ImageGenerator,
+    clock: &dyn Clock,
     player: &Player,
-) -> Result<(), PollerError> {
+    state: &mut PollState,
+) -> Result<bool, PollerError> {
     let platform: Platform = player.region.parse()?;
     let region = platform.to_region();
 
     // Get latest match ID
-    let match_ids = riot.get_match_ids(region, &player.puuid, 1).await?;
+    let match_ids = riot
+        .get_match_ids(region, &player.puuid, 1)
+        .await
+        .with_context(|| "listing recent matches")?;
 
     let Some(latest_match_id) = match_ids.first() else {
-        debug!("🔄 No matches found");
-        return Ok(());
+        // Brand-new accounts stay empty for a while, only say it once
+        if player.no_matches_since.is_none()
+            && db
+                .mark_player_without_matches(player.id, clock.unix_now())
+                .await
+                .with_context(|| "marking account without matches")?
+        {
+            info!("🔄 Account has no games yet");
+        }
+        return Ok(false);
     };
 
-    // Check if this is a new match
-    if player.last_match_id.as_deref() == Some(latest_match_id) {
-        return Ok(());
+    // Check if this is a new match, or one whose alert a restart cut short.
+    // Resumed ones stay listed until queued again, a failed check retries them
+    let interrupted_key = (player.id, latest_match_id.clone());
+    let resumed = state.interrupted.get(&interrupted_key).cloned();
+    if resumed.is_none() {
+        if player.last_match_id.as_deref() == Some(latest_match_id) {
+            return Ok(false);
+        }
+
+        // Already queued, only saving it as the last match failed
+        let processed = db
+            .get_match_dispatched(player.id, latest_match_id)
+            .await
+            .with_context(|| format!("checking processed match {latest_match_id}"))?;
+        if processed.is_some() {
+            db.update_player_last_match(player.id, latest_match_id)
+                .await
+                .with_context(|| format!("saving last match {latest_match_id}"))?;
+            return Ok(false);
+        }
     }
 
-    // Get match details
-    let match_data = riot.get_match(region, latest_match_id).await?;
+    Span::current().record("match_id", latest_match_id.as_str());
 
-    // Skip unsupported game modes
-    if !match_data.info.is_supported() {
+    // Get match details
+    let match_data = riot
+        .get_match(region, latest_match_id)
+        .await
+        .with_context(|| format!("fetching match {latest_match_id}"))?;
+    Span::current().record("queue", match_data.info.queue_name());
+
+    // Skip custom games, rotating modes go to the guilds that want them
+    if !match_data.info.is_supported() && !match_data.info.is_rotating() {
         debug!(
             queue_id = match_data.info.queue_id,
             match_id = latest_match_id,
@@ -115,8 +489,10 @@ async fn check_player_match(
         );
         // Still update last_match_id so we don't check this match again
         db.update_player_last_match(player.id, latest_match_id)
-            .await?;
-        return Ok(());
+            .await
+            .with_context(|| format!("saving last match {latest_match_id}"))?;
+        state.interrupted.remove(&interrupted_key);
+        return Ok(true);
     }
 
     info!(
@@ -136,8 +512,11 @@ async fn check_player_match(
             match_id: latest_match_id.to_string(),
         })?;
 
-    // Get current rank if ranked game
-    let old_rank = if match_data.info.is_solo_queue() {
+    // Get current rank if ranked game, the row already moved on for a
+    // resumed alert
+    let old_rank = if let Some(old_rank) = resumed {
+        old_rank
+    } else if match_data.info.is_solo_queue() {
         player.solo_rank_info()
     } else if match_data.info.queue_id == 440 {
         player.flex_rank_info()
@@ -146,7 +525,19 @@ async fn check_player_match(
     };
 
     // Fetch new rank info and profile icon
-    let (new_solo_rank, new_flex_rank) = fetch_rank_info(riot, platform, &player.puuid).await?;
+    let (solo_snapshot, flex_snapshot) = fetch_rank_info(riot, platform, &player.puuid).await?;
+    let new_solo_rank = solo_snapshot.as_ref().map(RankSnapshot::rank_info);
+    let new_flex_rank = flex_snapshot.as_ref().map(RankSnapshot::rank_info);
+    for snapshot in solo_snapshot.iter().chain(&flex_snapshot) {
+        db.record_rank_snapshot(player.id, snapshot, clock.unix_now())
+            .await
+            .with_context(|| {
+                format!(
+                    "recording {} rank snapshot",
+                    league_queue_name(&snapshot.queue)
+                )
+            })?;
+    }
 
     // Update profile icon (may have changed)
     if let Ok(summoner) = riot.get_summoner_by_puuid(platform, &player.puuid).await {
@@ -163,80 +554,435 @@ async fn check_player_match(
         None
     };
 
-    // Build image
-    let ctx = MatchImageContext {
-        player,
-        participant,
-        match_info: &match_data.info,
-        old_rank: old_rank.as_ref(),
-        new_rank,
+    let queue_snapshot = if match_data.info.is_solo_queue() {
+        solo_snapshot.as_ref()
+    } else if match_data.info.queue_id == 440 {
+        flex_snapshot.as_ref()
+    } else {
+        None
     };
 
-    let image_data = match image_gen.generate_match_image(&ctx).await {
-        Ok(data) => data,
-        Err(e) => {
-            error!(error = ?e, "🖼️ ❌ Failed to generate match image");
-            return Err(e.into());
-        }
+    db.record_champion_play(player.id, &participant.champion_name, latest_match_id)
+        .await
+        .with_context(|| "recording champion play")?;
+    let champion_pool = db
+        .get_champion_pool(player.id)
+        .await
+        .with_context(|| "loading champion pool")?;
+    let champion_note = champion_note(&champion_pool, &participant.champion_name);
+    let forecast = queue_snapshot.and_then(division_forecast);
+    // The team is a nice touch, a Clash outage shouldn't hold the alert back
+    let clash_team = if match_data.info.is_clash() {
+        riot.get_clash_team_name(platform, &player.puuid)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = ?e, "🔄 ⚠️ Failed to look up Clash team");
+                None
+            })
+    } else {
+        None
     };
 
-    let image_data: Arc<[u8]> = image_data.into();
+    // Normal games count towards the weekly MVP
+    if match_data.info.is_normal() {
+        let score = performance_score(participant, match_data.info.game_duration);
+        db.record_game_score(player.id, latest_match_id, score, clock.unix_now())
+            .await
+            .with_context(|| "recording game score")?;
+    }
+    // Remakes say nothing about how the day went
+    if match_data.info.ending() != GameEnding::Remake {
+        let result = MatchResult {
+            match_id: latest_match_id.clone(),
+            queue_id: match_data.info.queue_id,
+            win: participant.win,
+            kills: participant.kills,
+            deaths: participant.deaths,
+            assists: participant.assists,
+            lp_delta: calculate_lp_diff(old_rank.as_ref(), new_rank)
+                .filter(|_| match_data.info.is_ranked()),
+            played_at: match_data.info.ended_at(),
+        };
+        db.record_match_result(player.id, &result)
+            .await
+            .with_context(|| "recording match result")?;
+    }
+    // Shown by the alert's Details button
+    let details = MatchDetails::from_match(&match_data.info, participant);
+    if let Ok(details) = serde_json::to_string(&details) {
+        db.record_match_details(player.id, latest_match_id, &details, clock.unix_now())
+            .await
+            .with_context(|| "recording match details")?;
+    }
 
-    // Get all guilds tracking this player
-    let guilds = db.get_guilds_tracking_player(player.id).await?;
+    // Render once per distinct guild style and day (guilds have their own timezone)
+    let now = clock.unix_now();
+    let guilds = state
+        .targets
+        .guilds(db, player.id, now)
+        .await
+        .with_context(|| "listing guilds tracking the player")?;
+    let mut sessions: HashMap<i64, Option<SessionStats>> = HashMap::new();
+    let mut rendered: HashMap<(AlertStyle, Option<SessionStats>), Arc<[u8]>> = HashMap::new();
+    let queue_key = Queue::from_id(match_data.info.queue_id).map_or("", Queue::key);
+    let template_key = TemplateQueue::for_match(&match_data.info)
+        .zip(Outcome::for_game(&match_data.info, participant));
+    let template_values = TemplateValues {
+        player: &player.display_name(),
+        champion: &participant.champion_name,
+        kda: format!(
+            "{}/{}/{}",
+            participant.kills, participant.deaths, participant.assists
+        ),
+        lp: calculate_lp_diff(old_rank.as_ref(), new_rank)
+            .filter(|_| match_data.info.is_ranked())
+            .map(|diff| format!("{diff:+} LP"))
+            .unwrap_or_default(),
+        queue: match_data.info.queue_name(),
+    };
+    let mut targets = Vec::with_capacity(guilds.len());
+    for guild in &guilds {
+        let Some(channel_id) = guild.alert_channel_id else {
+            continue;
+        };
+        if match_data.info.is_rotating() && !guild.rotating_alerts {
+            continue;
+        }
+        let muted = db
+            .get_muted_queues(guild.id.get())
+            .await
+            .with_context(|| format!("loading muted queues of guild {}", guild.id.get()))?;
+        if muted.iter().any(|queue| queue == queue_key) {
+            continue;
+        }
 
-    // Send image to all guilds
-    for guild in guilds {
-        if let Some(channel_id) = guild.alert_channel_id {
-            let channel = ChannelId::new(channel_id as u64);
-            let attachment = CreateAttachment::bytes(image_data.as_ref(), "match_result.png");
-            let message = CreateMessage::new().add_file(attachment);
+        let session = match queue_snapshot {
+            Some(latest) => {
+                let day_start = start_of_day(guild.timezone(), now);
+                match sessions.get(&day_start) {
+                    Some(session) => *session,
+                    None => {
+                        let session = db
+                            .get_session_baseline(player.id, &latest.queue, day_start)
+                            .await
+                            .with_context(|| {
+                                format!(
+                                    "loading {} session baseline",
+                                    league_queue_name(&latest.queue)
+                                )
+                            })?
+                            .and_then(|baseline| session_stats(&baseline, latest));
+                        sessions.insert(day_start, session);
+                        session
+                    }
+                }
+            }
+            None => None,
+        };
 
-            if let Err(e) = channel.send_message(http, message).await {
-                error!(
-                    error = ?e,
-                    guild_id = guild.id,
-                    channel_id,
-                    "🎮 ❌ Failed to send alert message"
-                );
-            } else {
-                debug!(guild_id = guild.id, channel_id, "🎮 ✅ Alert sent");
+        let key = (AlertStyle::for_guild(guild, &match_data.info), session);
+        let images = state
+            .features
+            .is_enabled(Feature::ImageAlerts, guild.id.get());
+        let image = match rendered.get(&key) {
+            // The dispatcher falls back to an embed
+            _ if !images => None,
+            Some(image) => Some(image.clone()),
+            None => {
+                let ctx = MatchImageContext {
+                    player,
+                    participant,
+                    match_info: &match_data.info,
+                    old_rank: old_rank.as_ref(),
+                    new_rank,
+                    style: &key.0,
+                    session: key.1.as_ref(),
+                    champion_note: champion_note.as_ref(),
+                    forecast: forecast.as_ref(),
+                    clash_team: clash_team.as_deref(),
+                };
+                let image: Arc<[u8]> = image_gen
+                    .generate_match_image(&ctx)
+                    .await
+                    .with_context(|| format!("rendering alert for guild {}", guild.id.get()))?
+                    .into();
+                rendered.insert(key, image.clone());
+                Some(image)
             }
-        }
+        };
+
+        let text = match template_key {
+            Some((queue, outcome)) => db
+                .get_alert_template(guild.id.get(), queue.as_str(), outcome.as_str())
+                .await
+                .with_context(|| format!("loading alert template of guild {}", guild.id.get()))?
+                .map(|template| {
+                    image_gen
+                        .content_filter()
+                        .apply(&render(&template, &template_values))
+                }),
+            None => None,
+        };
+
+        targets.push(AlertTarget {
+            guild_id: guild.id.get(),
+            channel_id: channel_id.get(),
+            rate_limit: guild.alert_rate_limit,
+            text,
+            image,
+        });
+    }
+
+    if targets.is_empty() {
+        debug!("🔄 No alert channel configured, skipping alert");
+    } else {
+        debug!(
+            targets = targets.len(),
+            renders = rendered.len(),
+            "🖼️ Rendered alert images"
+        );
+        db.mark_match_processed(player.id, latest_match_id, old_rank.as_ref())
+            .await
+            .with_context(|| format!("marking match {latest_match_id} processed"))?;
+        alerts
+            .push(PendingAlert {
+                player_id: player.id,
+                match_id: latest_match_id.clone(),
+                game_ended_at: match_data.info.ended_at(),
+                targets,
+                summary: build_summary(
+                    image_gen.content_filter(),
+                    player,
+                    participant,
+                    &match_data.info,
+                    old_rank.as_ref(),
+                    new_rank,
+                ),
+            })
+            .await
+            .with_context(|| "queueing alert")?;
     }
 
     // Update player in database
     db.update_player_last_match(player.id, latest_match_id)
-        .await?;
+        .await
+        .with_context(|| format!("saving last match {latest_match_id}"))?;
     db.update_player_rank(player.id, new_solo_rank.as_ref(), new_flex_rank.as_ref())
-        .await?;
+        .await
+        .with_context(|| "saving ranks")?;
+    state.interrupted.remove(&interrupted_key);
 
-    Ok(())
+    Ok(true)
+}
+
+/// The result as text, what relays outside Discord post with the image
+fn build_summary(
+    filter: &ContentFilter,
+    player: &Player,
+    participant: &ParticipantDto,
+    info: &InfoDto,
+    old_rank: Option<&RankInfo>,
+    new_rank: Option<&RankInfo>,
+) -> AlertModel {
+    let (icon, outcome) = match (info.ending(), participant.win) {
+        (GameEnding::Remake, _) => ("🔁", "remade"),
+        (_, true) => ("✅", "won"),
+        (_, false) => ("❌", "lost"),
+    };
+    // Crossing a division or tier makes the headline, the LP alone reads oddly
+    let change = old_rank
+        .zip(new_rank)
+        .filter(|_| info.is_ranked())
+        .and_then(|(old, new)| Some((rank_change(old, new)?, new)));
+    let (title, color) = match change {
+        Some((change, rank)) => {
+            let mut standing = capitalize(&rank.tier);
+            if !is_apex(rank) {
+                standing = format!("{standing} {}", rank.rank);
+            }
+            match change {
+                RankChange::Promoted => (
+                    format!("🎉 {} was promoted to {standing}!", player.display_name()),
+                    0xc89b3c,
+                ),
+                RankChange::Demoted => (
+                    format!("📉 {} was demoted to {standing}", player.display_name()),
+                    0xe84057,
+                ),
+            }
+        }
+        None => (
+            format!(
+                "{icon} {} {outcome} a {} game",
+                player.display_name(),
+                info.queue_name()
+            ),
+            if participant.win { 0x4caf50 } else { 0xe84057 },
+        ),
+    };
+    let mut summary = AlertModel::new(filter.apply(&title), color).description(format!(
+        "**{}** • {}/{}/{} • {}",
+        participant.champion_name,
+        participant.kills,
+        participant.deaths,
+        participant.assists,
+        info.duration_formatted()
+    ));
+
+    if let Some(rank) = new_rank.filter(|_| info.is_ranked()) {
+        let diff = calculate_lp_diff(old_rank, new_rank)
+            .map(|diff| format!(" ({diff:+})"))
+            .unwrap_or_default();
+        summary = summary.field(
+            "Rank",
+            format!(
+                "{} {} • {} LP{diff}",
+                capitalize(&rank.tier),
+                rank.rank,
+                rank.lp
+            ),
+            true,
+        );
+    }
+
+    summary
 }
 
 async fn fetch_rank_info(
     riot: &RiotClient,
     platform: Platform,
     puuid: &str,
-) -> Result<(Option<RankInfo>, Option<RankInfo>), PollerError> {
-    let entries = riot.get_league_entries_by_puuid(platform, puuid).await?;
+) -> Result<(Option<RankSnapshot>, Option<RankSnapshot>), PollerError> {
+    // A standing cached before the game would read as the post-game rank
+    let entries = riot
+        .get_league_entries_by_puuid(platform, puuid, true)
+        .await
+        .with_context(|| "fetching ranks")?;
 
     let mut solo_rank = None;
     let mut flex_rank = None;
 
     for entry in entries {
-        let rank_info = RankInfo {
+        let snapshot = RankSnapshot {
+            queue: entry.queue_type.clone(),
             tier: entry.tier.clone(),
             rank: entry.rank.clone(),
             lp: entry.league_points,
+            wins: entry.wins,
+            losses: entry.losses,
         };
 
         if entry.is_solo_queue() {
-            solo_rank = Some(rank_info);
+            solo_rank = Some(snapshot);
         } else if entry.is_flex_queue() {
-            flex_rank = Some(rank_info);
+            flex_rank = Some(snapshot);
         }
     }
 
     Ok((solo_rank, flex_rank))
 }
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::CreateEmbed;
+
+    use super::build_summary;
+    use crate::db::RankInfo;
+    use crate::db::fixtures::player;
+    use crate::discord::moderation::ContentFilter;
+    use crate::riot::{InfoDto, ParticipantDto, Queue};
+
+    fn rank(tier: &str, division: &str, lp: i32) -> RankInfo {
+        RankInfo {
+            tier: tier.to_string(),
+            rank: division.to_string(),
+            lp,
+        }
+    }
+
+    /// Alert of a mid lane Ahri game in `queue`
+    fn alert(
+        queue: Queue,
+        win: bool,
+        remake: bool,
+        ranks: Option<(RankInfo, RankInfo)>,
+    ) -> CreateEmbed {
+        let participant = ParticipantDto {
+            puuid: "Faker-puuid".to_string(),
+            team_id: 100,
+            team_position: "MIDDLE".to_string(),
+            individual_position: "MIDDLE".to_string(),
+            lane: "MIDDLE".to_string(),
+            lane_role: "SOLO".to_string(),
+            summoner1_id: 4,
+            summoner2_id: 14,
+            champion_name: "Ahri".to_string(),
+            kills: 8,
+            deaths: 3,
+            assists: 12,
+            total_damage_dealt_to_champions: 28500,
+            total_minions_killed: 185,
+            neutral_minions_killed: 12,
+            vision_score: 42,
+            gold_earned: 12450,
+            win,
+            item0: 6655,
+            item1: 3020,
+            item2: 4645,
+            item3: 3089,
+            item4: 3135,
+            item5: 3157,
+            item6: 3364,
+        };
+        let info = InfoDto {
+            game_creation: 0,
+            game_end_timestamp: None,
+            game_duration: if remake { 180 } else { 1847 },
+            game_version: "14.24.632.8043".to_string(),
+            game_ended_in_early_surrender: remake,
+            game_ended_in_surrender: remake,
+            participants: vec![participant.clone()],
+            queue_id: queue.id(),
+            teams: Vec::new(),
+        };
+        let (old_rank, new_rank) = ranks.unzip();
+        build_summary(
+            &ContentFilter::default(),
+            &player("Faker", "KR1"),
+            &participant,
+            &info,
+            old_rank.as_ref(),
+            new_rank.as_ref(),
+        )
+        .embed()
+    }
+
+    #[test]
+    fn solo_win_alert_snapshot() {
+        let ranks = (rank("GOLD", "II", 45), rank("GOLD", "II", 67));
+        insta::assert_json_snapshot!(alert(Queue::RankedSolo, true, false, Some(ranks)));
+    }
+
+    #[test]
+    fn flex_loss_alert_snapshot() {
+        let ranks = (rank("SILVER", "I", 75), rank("SILVER", "I", 58));
+        insta::assert_json_snapshot!(alert(Queue::RankedFlex, false, false, Some(ranks)));
+    }
+
+    #[test]
+    fn aram_alert_snapshot() {
+        insta::assert_json_snapshot!(alert(Queue::Aram, true, false, None));
+    }
+
+    #[test]
+    fn remake_alert_snapshot() {
+        let ranks = (rank("GOLD", "II", 45), rank("GOLD", "II", 45));
+        insta::assert_json_snapshot!(alert(Queue::RankedSolo, false, true, Some(ranks)));
+    }
+
+    #[test]
+    fn promotion_alert_snapshot() {
+        let ranks = (rank("GOLD", "I", 88), rank("PLATINUM", "IV", 10));
+        insta::assert_json_snapshot!(alert(Queue::RankedSolo, true, false, Some(ranks)));
+    }
+}
@@ -1,3 +1,24 @@
+mod alert;
+mod auto_tune;
+mod dispatcher;
+mod error_budget;
+mod ladder_watch;
+mod latency;
+mod live_games;
 mod match_poller;
+mod rank_watch;
+mod recap;
+mod relay;
+mod targets;
+mod throttle;
+mod weekly_recap;
 
-pub use match_poller::start_polling;
+pub use dispatcher::{AlertQueue, AlertSink, Dispatcher};
+pub use ladder_watch::check_ladders;
+pub use latency::DeliveryLatency;
+pub use live_games::start_live_games;
+pub use match_poller::MatchPoller;
+pub use rank_watch::check_ranks;
+pub use recap::{RecapFrequency, post_results_recaps};
+pub use relay::Relays;
+pub use weekly_recap::post_recaps;
@@ -0,0 +1,218 @@
+use poise::serenity_prelude::{ChannelId, Http};
+use tracing::{debug, info, instrument, warn};
+
+use crate::db::{Player, RankInfo, RankSnapshot, Repository};
+use crate::discord::image_gen::capitalize;
+use crate::discord::moderation::ContentFilter;
+use crate::error::{AppError, ResultExt};
+use crate::riot::{Platform, RiotClient, league_queue_name};
+use crate::stats::rank_to_lp;
+
+use super::alert::AlertModel;
+
+/// Post the LP changes of tracked players that no game explains (decay,
+/// dodges). Games are left to the match poller, which alerts on them.
+/// Without `http` (shadow mode) ranks are still recorded but nothing is posted.
+#[instrument(skip_all, fields(player_count))]
+pub async fn check_ranks(
+    db: &Repository,
+    riot: &RiotClient,
+    http: Option<&Http>,
+    filter: &ContentFilter,
+    now: i64,
+) -> Result<(), AppError> {
+    let players = db.get_all_tracked_players().await?;
+    tracing::Span::current().record("player_count", players.len());
+
+    for player in &players {
+        if let Err(e) = check_player(db, riot, http, filter, player, now).await {
+            warn!(error = ?e, player_id = player.id, "🏆 ⚠️ Failed to check rank");
+        }
+    }
+
+    Ok(())
+}
+
+async fn check_player(
+    db: &Repository,
+    riot: &RiotClient,
+    http: Option<&Http>,
+    filter: &ContentFilter,
+    player: &Player,
+    now: i64,
+) -> Result<(), AppError> {
+    let platform: Platform = player.region.parse()?;
+    let entries = riot
+        .get_league_entries_by_puuid(platform, &player.puuid, false)
+        .await
+        .with_context(|| "fetching ranks")?;
+
+    let (mut solo, mut flex) = (player.solo_rank_info(), player.flex_rank_info());
+    let mut changes = Vec::new();
+    for entry in entries {
+        if !entry.is_solo_queue() && !entry.is_flex_queue() {
+            continue;
+        }
+        let current = RankSnapshot {
+            queue: entry.queue_type.clone(),
+            tier: entry.tier.clone(),
+            rank: entry.rank.clone(),
+            lp: entry.league_points,
+            wins: entry.wins,
+            losses: entry.losses,
+        };
+        // Without a baseline the next alerted game records one
+        let Some(previous) = db
+            .get_latest_rank_snapshot(player.id, &current.queue)
+            .await?
+        else {
+            continue;
+        };
+        let Some(diff) = change_without_game(&previous, &current) else {
+            continue;
+        };
+
+        db.record_rank_snapshot(player.id, &current, now)
+            .await
+            .with_context(|| {
+                format!(
+                    "recording {} rank snapshot",
+                    league_queue_name(&current.queue)
+                )
+            })?;
+        if entry.is_solo_queue() {
+            solo = Some(current.rank_info());
+        } else {
+            flex = Some(current.rank_info());
+        }
+        changes.push((previous, current, diff));
+    }
+    if changes.is_empty() {
+        return Ok(());
+    }
+    db.update_player_rank(player.id, solo.as_ref(), flex.as_ref())
+        .await
+        .with_context(|| "saving rank")?;
+
+    let guilds = db
+        .get_guilds_tracking_player(player.id)
+        .await
+        .with_context(|| "listing guilds tracking the player")?;
+    for (previous, current, diff) in &changes {
+        info!(
+            player_id = player.id,
+            queue = league_queue_name(&current.queue),
+            diff,
+            "🏆 LP changed without a game"
+        );
+        let Some(http) = http else {
+            debug!(
+                player_id = player.id,
+                "👻 Shadow mode, rank notice not posted"
+            );
+            continue;
+        };
+        let alert = build_alert(filter, player, previous, current, *diff);
+        for guild in guilds.iter().filter(|guild| guild.rank_notices) {
+            let Some(channel_id) = guild.alert_channel_id else {
+                continue;
+            };
+            if let Err(e) = ChannelId::new(channel_id.get())
+                .send_message(http, alert.message())
+                .await
+            {
+                warn!(error = ?e, guild_id = guild.id.get(), "🏆 ⚠️ Failed to post rank notice");
+            } else {
+                debug!(guild_id = guild.id.get(), "🏆 Rank notice posted");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// LP difference between two standings of a queue when no game was played in
+/// between, `None` if nothing changed or games explain the change
+fn change_without_game(previous: &RankSnapshot, current: &RankSnapshot) -> Option<i32> {
+    let games = |s: &RankSnapshot| s.wins + s.losses;
+    if games(previous) != games(current) {
+        return None;
+    }
+    let diff = rank_to_lp(&current.rank_info()) - rank_to_lp(&previous.rank_info());
+    // Apex tiers share one scale, a renamed tier at the same LP still counts
+    let same_rank = (&previous.tier, &previous.rank) == (&current.tier, &current.rank);
+    (diff != 0 || !same_rank).then_some(diff)
+}
+
+fn build_alert(
+    filter: &ContentFilter,
+    player: &Player,
+    previous: &RankSnapshot,
+    current: &RankSnapshot,
+    diff: i32,
+) -> AlertModel {
+    let name = player.display_name();
+    let (title, color) = if diff < 0 {
+        (
+            format!("📉 {name} lost {} LP without playing", -diff),
+            0xe84057,
+        )
+    } else {
+        (
+            format!("📈 {name} gained {diff} LP without playing"),
+            0x4caf50,
+        )
+    };
+    let rank = |r: RankInfo| format!("{} {} • {} LP", capitalize(&r.tier), r.rank, r.lp);
+
+    let alert = AlertModel::new(filter.apply(&title), color).description(format!(
+        "{}: {} → **{}**",
+        league_queue_name(&current.queue),
+        rank(previous.rank_info()),
+        rank(current.rank_info())
+    ));
+    if diff < 0 {
+        alert.footer("LP decay or a queue dodge")
+    } else {
+        alert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::change_without_game;
+    use crate::db::RankSnapshot;
+
+    fn snapshot(tier: &str, rank: &str, lp: i32, games: i32) -> RankSnapshot {
+        RankSnapshot {
+            queue: "RANKED_SOLO_5x5".into(),
+            tier: tier.into(),
+            rank: rank.into(),
+            lp,
+            wins: games,
+            losses: 0,
+        }
+    }
+
+    #[test]
+    fn only_changes_without_games_count() {
+        let before = snapshot("DIAMOND", "II", 50, 100);
+
+        // Dodge
+        assert_eq!(
+            change_without_game(&before, &snapshot("DIAMOND", "II", 45, 100)),
+            Some(-5)
+        );
+        // Decay through a demotion
+        assert_eq!(
+            change_without_game(&before, &snapshot("DIAMOND", "III", 75, 100)),
+            Some(-75)
+        );
+        // A game was played, the match alert covers it
+        assert_eq!(
+            change_without_game(&before, &snapshot("DIAMOND", "II", 30, 101)),
+            None
+        );
+        assert_eq!(change_without_game(&before, &before), None);
+    }
+}
@@ -0,0 +1,316 @@
+use chrono_tz::Tz;
+use poise::ChoiceParameter;
+use poise::serenity_prelude::{ChannelId, Http};
+use tracing::{debug, info, instrument, warn};
+
+use crate::db::{Guild, RecapGame, Repository};
+use crate::discord::features::{Feature, FeatureGate};
+use crate::discord::moderation::ContentFilter;
+use crate::error::{AppError, ResultExt};
+use crate::stats::{start_of_day, start_of_week};
+
+use super::alert::AlertModel;
+
+/// Players listed on a recap, the most active first
+const RECAP_MAX_PLAYERS: usize = 15;
+
+/// How often a guild gets the results of its players
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum RecapFrequency {
+    Daily,
+    Weekly,
+}
+
+impl RecapFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "daily" => Some(Self::Daily),
+            "weekly" => Some(Self::Weekly),
+            _ => None,
+        }
+    }
+
+    /// Last period that ended by `now`, as `[start, end)` Unix seconds.
+    /// Periods end at `hour` local time, weekly ones on Mondays.
+    pub fn last_period(self, tz: Tz, hour: u32, now: i64) -> (i64, i64) {
+        let (length, period_start) = match self {
+            Self::Daily => (86400, start_of_day(tz, now)),
+            Self::Weekly => (7 * 86400, start_of_week(tz, now)),
+        };
+        let mut end = period_start + i64::from(hour) * 3600;
+        if end > now {
+            end -= length;
+        }
+        (end - length, end)
+    }
+}
+
+/// Results of one player over a recap period
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PlayerLine {
+    name: String,
+    wins: u32,
+    losses: u32,
+    lp_delta: Option<i32>,
+}
+
+/// Post the results recap of every guild whose period ended since the last
+/// one. Meant to run hourly, like the weekly MVP recap. Without `http`
+/// (shadow mode) recaps are marked done but not posted.
+#[instrument(skip_all)]
+pub async fn post_results_recaps(
+    db: &Repository,
+    http: Option<&Http>,
+    features: &FeatureGate,
+    filter: &ContentFilter,
+    now: i64,
+) -> Result<(), AppError> {
+    for guild in db.get_guilds_with_recap_channel().await? {
+        if !features.is_enabled(Feature::Digests, guild.id.get()) {
+            continue;
+        }
+        if let Err(e) = post_results_recap(db, http, filter, now, &guild).await {
+            warn!(
+                error = ?e,
+                guild_id = guild.id.get(),
+                "📅 ⚠️ Failed to post results recap"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn post_results_recap(
+    db: &Repository,
+    http: Option<&Http>,
+    filter: &ContentFilter,
+    now: i64,
+    guild: &Guild,
+) -> Result<(), AppError> {
+    let (Some(frequency), Some(channel_id)) = (
+        guild
+            .recap_frequency
+            .as_deref()
+            .and_then(RecapFrequency::from_key),
+        guild.recap_channel(),
+    ) else {
+        return Ok(());
+    };
+    let guild_id = guild.id.get();
+    let (start, end) = frequency.last_period(guild.timezone(), guild.recap_hour, now);
+    if guild.last_results_recap >= Some(end) {
+        return Ok(());
+    }
+
+    let games = db
+        .get_recap_games(guild_id, start, end)
+        .await
+        .with_context(|| "loading recap games")?;
+    // Quiet periods are skipped rather than posting an empty recap
+    match (build_alert(filter, frequency, &games), http) {
+        (Some(alert), Some(http)) => {
+            ChannelId::new(channel_id.get())
+                .send_message(http, alert.message())
+                .await
+                .with_context(|| {
+                    format!("posting results recap to channel {}", channel_id.get())
+                })?;
+            info!(
+                guild_id,
+                games = games.len(),
+                frequency = frequency.as_str(),
+                "📅 ✅ Results recap posted"
+            );
+        }
+        (Some(_), None) => debug!(guild_id, "👻 Shadow mode, results recap not posted"),
+        (None, _) => debug!(guild_id, "📅 No games, skipping results recap"),
+    }
+
+    db.record_results_recap(guild_id, end)
+        .await
+        .with_context(|| "recording results recap")?;
+
+    Ok(())
+}
+
+/// Wins, losses and LP of each player, the most active first
+fn player_lines(games: &[RecapGame]) -> Vec<PlayerLine> {
+    let mut lines: Vec<(i64, PlayerLine)> = Vec::new();
+    for game in games {
+        let index = match lines.iter().position(|(id, _)| *id == game.player_id) {
+            Some(index) => index,
+            None => {
+                lines.push((
+                    game.player_id,
+                    PlayerLine {
+                        name: game.display_name(),
+                        wins: 0,
+                        losses: 0,
+                        lp_delta: None,
+                    },
+                ));
+                lines.len() - 1
+            }
+        };
+        let line = &mut lines[index].1;
+        if game.win {
+            line.wins += 1;
+        } else {
+            line.losses += 1;
+        }
+        if let Some(delta) = game.lp_delta {
+            line.lp_delta = Some(line.lp_delta.unwrap_or(0) + delta);
+        }
+    }
+    let mut lines: Vec<PlayerLine> = lines.into_iter().map(|(_, line)| line).collect();
+    lines.sort_by_key(|line| std::cmp::Reverse(line.wins + line.losses));
+    lines
+}
+
+/// KDA ratio of a game, deathless games count as one death
+fn kda(game: &RecapGame) -> f64 {
+    f64::from(game.kills + game.assists) / f64::from(game.deaths.max(1))
+}
+
+fn build_alert(
+    filter: &ContentFilter,
+    frequency: RecapFrequency,
+    games: &[RecapGame],
+) -> Option<AlertModel> {
+    let best = games.iter().max_by(|a, b| kda(a).total_cmp(&kda(b)))?;
+    let lines = player_lines(games);
+    let (wins, losses) = lines
+        .iter()
+        .fold((0, 0), |(w, l), line| (w + line.wins, l + line.losses));
+
+    let mut description: Vec<String> = lines
+        .iter()
+        .take(RECAP_MAX_PLAYERS)
+        .map(|line| {
+            let lp = match line.lp_delta {
+                Some(delta) => format!(" • {delta:+} LP"),
+                None => String::new(),
+            };
+            format!("**{}**: {}W-{}L{lp}", line.name, line.wins, line.losses)
+        })
+        .collect();
+    if lines.len() > RECAP_MAX_PLAYERS {
+        description.push(format!(
+            "…and {} more players",
+            lines.len() - RECAP_MAX_PLAYERS
+        ));
+    }
+
+    let title = match frequency {
+        RecapFrequency::Daily => "📊 Daily Recap",
+        RecapFrequency::Weekly => "📊 Weekly Recap",
+    };
+    Some(
+        AlertModel::new(title, 0x0099ff)
+            .description(filter.apply(&description.join("\n")))
+            .field(
+                "Best KDA",
+                filter.apply(&format!(
+                    "**{}** • {}/{}/{} ({:.1})",
+                    best.display_name(),
+                    best.kills,
+                    best.deaths,
+                    best.assists,
+                    kda(best)
+                )),
+                true,
+            )
+            .field("Games", format!("{}W-{}L", wins, losses), true)
+            .footer("/config recap_schedule to change or stop it"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Tz;
+
+    use super::{PlayerLine, RecapFrequency, build_alert, player_lines};
+    use crate::db::RecapGame;
+    use crate::discord::moderation::ContentFilter;
+
+    fn game(player_id: i64, win: bool, kda: (i32, i32, i32), lp_delta: Option<i32>) -> RecapGame {
+        RecapGame {
+            player_id,
+            game_name: format!("Player{player_id}"),
+            tag_line: "EUW".into(),
+            privacy_alias: None,
+            win,
+            kills: kda.0,
+            deaths: kda.1,
+            assists: kda.2,
+            lp_delta,
+        }
+    }
+
+    #[test]
+    fn periods_end_at_the_local_hour() {
+        // Tuesday 2024-01-02 12:00 UTC, 13:00 in Paris
+        let now = 1_704_196_800;
+        let tz = Tz::Europe__Paris;
+
+        // 20:00 isn't there yet, the last day ended Monday 20:00 Paris
+        let (start, end) = RecapFrequency::Daily.last_period(tz, 20, now);
+        assert_eq!(end, 1_704_135_600);
+        assert_eq!(end - start, 86400);
+        // 9:00 already passed today
+        let (_, end) = RecapFrequency::Daily.last_period(tz, 9, now);
+        assert_eq!(end, 1_704_182_400);
+
+        // Weeks end on Monday, Monday 20:00 Paris is past
+        let (start, end) = RecapFrequency::Weekly.last_period(tz, 20, now);
+        assert_eq!(end, 1_704_135_600);
+        assert_eq!(end - start, 7 * 86400);
+    }
+
+    #[test]
+    fn players_are_summed_up_most_active_first() {
+        let games = [
+            game(1, true, (2, 2, 2), Some(20)),
+            game(2, true, (10, 1, 5), None),
+            game(2, false, (1, 5, 1), None),
+            game(1, false, (3, 3, 3), Some(-18)),
+            game(2, true, (4, 4, 4), None),
+        ];
+        assert_eq!(
+            player_lines(&games),
+            [
+                PlayerLine {
+                    name: "Player2#EUW".into(),
+                    wins: 2,
+                    losses: 1,
+                    lp_delta: None,
+                },
+                PlayerLine {
+                    name: "Player1#EUW".into(),
+                    wins: 1,
+                    losses: 1,
+                    lp_delta: Some(2),
+                },
+            ]
+        );
+
+        let alert = build_alert(&ContentFilter::default(), RecapFrequency::Daily, &games).unwrap();
+        assert_eq!(alert.title, "📊 Daily Recap");
+        assert_eq!(
+            alert.description.as_deref(),
+            Some("**Player2#EUW**: 2W-1L\n**Player1#EUW**: 1W-1L • +2 LP")
+        );
+        assert_eq!(alert.fields[0].value, "**Player2#EUW** • 10/1/5 (15.0)");
+        assert_eq!(alert.fields[1].value, "3W-2L");
+
+        assert!(build_alert(&ContentFilter::default(), RecapFrequency::Weekly, &[]).is_none());
+    }
+}
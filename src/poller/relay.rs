@@ -0,0 +1,205 @@
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, UNIX_EPOCH};
+
+use governor::clock::DefaultClock;
+use governor::state::{InMemoryState, NotKeyed};
+use governor::{Quota, RateLimiter};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, RequestBuilder};
+use serde_json::{Value, json};
+use tracing::{debug, warn};
+
+use crate::clock::Clock;
+use crate::config::RelayTarget;
+use crate::error::AppError;
+
+use super::alert::AlertModel;
+
+/// Telegram rejects longer photo captions
+const TELEGRAM_CAPTION_MAX_CHARS: usize = 1024;
+/// A chat not answering within this loses the alert
+const RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+type GovernorRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+
+struct Relay {
+    target: RelayTarget,
+    rate_limiter: GovernorRateLimiter,
+}
+
+/// Chats outside Discord mirroring the match alerts, each with its own rate
+/// limit. A relay failing or over its limit loses the alert without holding
+/// up Discord deliveries.
+pub struct Relays {
+    http: Client,
+    relays: Vec<Relay>,
+    /// Matrix transaction ids, unique for the access token
+    next_txn: AtomicU64,
+}
+
+impl Relays {
+    pub fn new(
+        targets: Vec<RelayTarget>,
+        per_minute: NonZeroU32,
+        clock: &dyn Clock,
+    ) -> Result<Self, AppError> {
+        let http = Client::builder()
+            .user_agent("Tentrackule/2.0")
+            .timeout(RELAY_TIMEOUT)
+            .build()?;
+        let relays = targets
+            .into_iter()
+            .map(|target| Relay {
+                target,
+                rate_limiter: RateLimiter::direct(Quota::per_minute(per_minute)),
+            })
+            .collect();
+        // Start past the ids a previous run used
+        let started_ms = clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+
+        Ok(Self {
+            http,
+            relays,
+            next_txn: AtomicU64::new(started_ms),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.relays.is_empty()
+    }
+
+    /// Send the alert to every relay, logging the ones that fail
+    pub async fn broadcast(&self, alert: &AlertModel, image: Option<&[u8]>) {
+        let text = alert.plain_text();
+        for relay in &self.relays {
+            let name = relay.target.name();
+            if relay.rate_limiter.check().is_err() {
+                warn!(
+                    relay = name,
+                    "🎮 ⚠️ Relay rate limit reached, dropping alert"
+                );
+                continue;
+            }
+
+            let sent = match &relay.target {
+                RelayTarget::Telegram { bot_token, chat_id } => {
+                    self.send_telegram(bot_token, chat_id, &text, image).await
+                }
+                RelayTarget::Matrix {
+                    homeserver,
+                    access_token,
+                    room_id,
+                } => {
+                    self.send_matrix(homeserver, access_token, room_id, &text, image)
+                        .await
+                }
+            };
+            match sent {
+                Ok(()) => debug!(relay = name, "🎮 ✅ Alert relayed"),
+                Err(e) => warn!(error = ?e, relay = name, "🎮 ⚠️ Failed to relay alert"),
+            }
+        }
+    }
+
+    /// The image with the text as caption, or the text alone
+    async fn send_telegram(
+        &self,
+        bot_token: &str,
+        chat_id: &str,
+        text: &str,
+        image: Option<&[u8]>,
+    ) -> Result<(), AppError> {
+        let base = format!("https://api.telegram.org/bot{bot_token}");
+        let request = match image {
+            Some(image) => {
+                let caption: String = text.chars().take(TELEGRAM_CAPTION_MAX_CHARS).collect();
+                let photo = Part::bytes(image.to_vec())
+                    .file_name("match_result.png")
+                    .mime_str("image/png")?;
+                let form = Form::new()
+                    .text("chat_id", chat_id.to_string())
+                    .text("caption", caption)
+                    .part("photo", photo);
+                self.http.post(format!("{base}/sendPhoto")).multipart(form)
+            }
+            None => self
+                .http
+                .post(format!("{base}/sendMessage"))
+                .json(&json!({ "chat_id": chat_id, "text": text })),
+        };
+
+        send("telegram", request).await.map(|_| ())
+    }
+
+    /// The text, then the image uploaded to the homeserver
+    async fn send_matrix(
+        &self,
+        homeserver: &str,
+        access_token: &str,
+        room_id: &str,
+        text: &str,
+        image: Option<&[u8]>,
+    ) -> Result<(), AppError> {
+        let room = urlencoding::encode(room_id);
+        let mut events = vec![json!({ "msgtype": "m.text", "body": text })];
+
+        if let Some(image) = image {
+            let uploaded = send(
+                "matrix",
+                self.http
+                    .post(format!(
+                        "{homeserver}/_matrix/media/v3/upload?filename=match_result.png"
+                    ))
+                    .bearer_auth(access_token)
+                    .header(reqwest::header::CONTENT_TYPE, "image/png")
+                    .body(image.to_vec()),
+            )
+            .await?;
+            let content_uri = uploaded["content_uri"].as_str().ok_or(AppError::Relay {
+                relay: "matrix",
+                status: 200,
+                message: "upload answered without a content_uri".into(),
+            })?;
+            events.push(json!({
+                "msgtype": "m.image",
+                "body": "match_result.png",
+                "url": content_uri,
+                "info": { "mimetype": "image/png", "size": image.len() },
+            }));
+        }
+
+        for event in events {
+            let txn = self.next_txn.fetch_add(1, Ordering::Relaxed);
+            send(
+                "matrix",
+                self.http
+                    .put(format!(
+                        "{homeserver}/_matrix/client/v3/rooms/{room}/send/m.room.message/{txn}"
+                    ))
+                    .bearer_auth(access_token)
+                    .json(&event),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Send the request, a non-2xx answer becoming an error with its body
+async fn send(relay: &'static str, request: RequestBuilder) -> Result<Value, AppError> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(AppError::Relay {
+            relay,
+            status: status.as_u16(),
+            message: response.text().await.unwrap_or_default(),
+        });
+    }
+    Ok(response.json().await.unwrap_or(Value::Null))
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+use crate::activity::ActivityEvent;
+use crate::db::{Guild, Repository};
+use crate::error::AppError;
+
+/// How long the guilds of an account are reused before asking the database again
+const TARGETS_TTL_SECS: i64 = 60;
+
+/// Guilds the alerts of each account go to, kept for a short while rather
+/// than queried for every alert. Tracking and alert channel changes from the
+/// activity feed drop the stale entries right away, other guild settings
+/// catch up within `TARGETS_TTL_SECS`.
+#[derive(Debug)]
+pub struct AlertTargets {
+    /// Player id -> (Unix time it was loaded, guilds with an alert channel)
+    entries: HashMap<i64, (i64, Vec<Guild>)>,
+    events: broadcast::Receiver<ActivityEvent>,
+}
+
+impl AlertTargets {
+    pub fn new(events: broadcast::Receiver<ActivityEvent>) -> Self {
+        Self {
+            entries: HashMap::new(),
+            events,
+        }
+    }
+
+    /// Guilds tracking the player with an alert channel
+    pub async fn guilds(
+        &mut self,
+        db: &Repository,
+        player_id: i64,
+        now: i64,
+    ) -> Result<Vec<Guild>, AppError> {
+        self.invalidate();
+        if let Some((loaded_at, guilds)) = self.entries.get(&player_id)
+            && now - loaded_at < TARGETS_TTL_SECS
+        {
+            return Ok(guilds.clone());
+        }
+
+        let guilds = db.get_guilds_tracking_player(player_id).await?;
+        self.entries.insert(player_id, (now, guilds.clone()));
+        Ok(guilds)
+    }
+
+    /// Drop the entries the events received since the last lookup made stale
+    fn invalidate(&mut self) {
+        loop {
+            match self.events.try_recv() {
+                Ok(
+                    ActivityEvent::AccountTracked { player_id, .. }
+                    | ActivityEvent::AccountUntracked { player_id, .. },
+                ) => {
+                    self.entries.remove(&player_id);
+                }
+                // A guild getting its first channel isn't in any entry yet
+                Ok(ActivityEvent::AlertChannelChanged { .. }) | Err(TryRecvError::Lagged(_)) => {
+                    self.entries.clear();
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::{AlertTargets, TARGETS_TTL_SECS};
+    use crate::activity::{ActivityEvent, ActivityFeed};
+    use crate::db::{Repository, run_migrations};
+
+    #[tokio::test]
+    async fn events_and_age_refresh_the_guilds() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let db = Repository::new(pool);
+        let feed = ActivityFeed::default();
+        let mut targets = AlertTargets::new(feed.subscribe());
+
+        let player = db
+            .get_or_create_player("a", "Player", "EUW", "euw1")
+            .await
+            .unwrap();
+        db.add_player_to_guild(1, player.id, 99, 0).await.unwrap();
+        db.set_guild_alert_channel(1, 10).await.unwrap();
+        assert_eq!(targets.guilds(&db, player.id, 0).await.unwrap().len(), 1);
+
+        // Cached until an event or the TTL says otherwise
+        db.add_player_to_guild(2, player.id, 99, 0).await.unwrap();
+        db.set_guild_alert_channel(2, 20).await.unwrap();
+        assert_eq!(targets.guilds(&db, player.id, 1).await.unwrap().len(), 1);
+        assert_eq!(
+            targets
+                .guilds(&db, player.id, TARGETS_TTL_SECS)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        db.remove_player_from_guild(1, player.id, 0).await.unwrap();
+        feed.publish(ActivityEvent::AccountUntracked {
+            guild_id: 1,
+            player_id: player.id,
+        });
+        let guilds = targets
+            .guilds(&db, player.id, TARGETS_TTL_SECS)
+            .await
+            .unwrap();
+        assert_eq!(guilds.len(), 1);
+        assert_eq!(guilds[0].id.get(), 2);
+
+        db.clear_guild_alert_channel(2, 20).await.unwrap();
+        feed.publish(ActivityEvent::AlertChannelChanged { guild_id: 2 });
+        assert!(
+            targets
+                .guilds(&db, player.id, TARGETS_TTL_SECS)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
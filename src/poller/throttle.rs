@@ -0,0 +1,111 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Window of the per-guild alert limit
+const WINDOW_SECS: i64 = 3600;
+
+/// Alerts a guild didn't get because of its hourly limit, posted as one
+/// "and N more games" message once the limit allows it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub skipped: u32,
+    pub limit: u32,
+}
+
+#[derive(Debug, Default)]
+struct GuildWindow {
+    /// Unix times of the messages posted in the last hour
+    sent: VecDeque<i64>,
+    skipped: u32,
+    channel_id: u64,
+    limit: u32,
+}
+
+impl GuildWindow {
+    fn has_room(&mut self, now: i64) -> bool {
+        while self.sent.front().is_some_and(|&at| at <= now - WINDOW_SECS) {
+            self.sent.pop_front();
+        }
+        self.sent.len() < self.limit as usize
+    }
+}
+
+/// Per-guild limit of alerts per hour, so very active rosters don't flood
+/// their channel. Kept in memory: a restart starts every window over.
+#[derive(Debug, Default)]
+pub struct AlertThrottle {
+    guilds: HashMap<u64, GuildWindow>,
+}
+
+impl AlertThrottle {
+    /// Whether the guild may get an alert now, counting it if so. Once an
+    /// alert was skipped, later ones are skipped too until the overflow
+    /// message went out, so it comes before them.
+    pub fn allow(&mut self, guild_id: u64, channel_id: u64, limit: Option<u32>, now: i64) -> bool {
+        let Some(limit) = limit else {
+            return true;
+        };
+        let window = self.guilds.entry(guild_id).or_default();
+        window.channel_id = channel_id;
+        window.limit = limit;
+        if window.skipped == 0 && window.has_room(now) {
+            window.sent.push_back(now);
+            true
+        } else {
+            window.skipped += 1;
+            false
+        }
+    }
+
+    /// Overflow messages the limits now allow, counted as sent
+    pub fn due_overflows(&mut self, now: i64) -> Vec<Overflow> {
+        let mut due = Vec::new();
+        for (&guild_id, window) in &mut self.guilds {
+            if window.skipped > 0 && window.has_room(now) {
+                window.sent.push_back(now);
+                due.push(Overflow {
+                    guild_id,
+                    channel_id: window.channel_id,
+                    skipped: std::mem::take(&mut window.skipped),
+                    limit: window.limit,
+                });
+            }
+        }
+        self.guilds
+            .retain(|_, window| window.skipped > 0 || !window.sent.is_empty());
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlertThrottle, Overflow};
+
+    #[test]
+    fn overflow_is_summarized_once_the_hour_allows() {
+        let mut throttle = AlertThrottle::default();
+        assert!(throttle.allow(1, 10, Some(2), 0));
+        assert!(throttle.allow(1, 10, Some(2), 60));
+        for at in 120..127 {
+            assert!(!throttle.allow(1, 10, Some(2), at));
+        }
+        // No limit, no throttling
+        assert!(throttle.allow(2, 20, None, 120));
+        assert!(throttle.due_overflows(600).is_empty());
+
+        assert_eq!(
+            throttle.due_overflows(3600),
+            vec![Overflow {
+                guild_id: 1,
+                channel_id: 10,
+                skipped: 7,
+                limit: 2,
+            }]
+        );
+        // The summary took the freed slot, the next one frees at 3660
+        assert!(!throttle.allow(1, 10, Some(2), 3601));
+        assert!(throttle.due_overflows(3659).is_empty());
+        assert_eq!(throttle.due_overflows(3660).len(), 1);
+    }
+}
@@ -0,0 +1,210 @@
+use poise::serenity_prelude::{ChannelId, CreateAttachment, Http};
+use tracing::{debug, info, instrument, warn};
+
+use crate::db::{Guild, Repository, WeeklyMvp};
+use crate::discord::ImageGenerator;
+use crate::discord::features::{Feature, FeatureGate};
+use crate::discord::lp_chart::lp_series;
+use crate::discord::moderation::ContentFilter;
+use crate::error::{AppError, ResultExt};
+use crate::stats::{MVP_MIN_GAMES, start_of_week};
+
+use super::alert::AlertModel;
+
+/// Queue charted on the recap, the one most players climb
+const CHART_QUEUE: &str = "RANKED_SOLO_5x5";
+const CHART_FILENAME: &str = "lp_chart.png";
+
+/// Post the recap of every guild whose local week ended since the last one.
+/// Meant to run hourly, as weeks end at different times across timezones.
+/// Without `http` (shadow mode) recaps are recorded but not posted.
+#[instrument(skip_all)]
+pub async fn post_recaps(
+    db: &Repository,
+    http: Option<&Http>,
+    features: &FeatureGate,
+    image_gen: &ImageGenerator,
+    now: i64,
+) -> Result<(), AppError> {
+    for guild in db.get_guilds_with_recap_channel().await? {
+        if !features.is_enabled(Feature::Digests, guild.id.get()) {
+            continue;
+        }
+        if let Err(e) = post_recap(db, http, image_gen, now, &guild).await {
+            warn!(
+                error = ?e,
+                guild_id = guild.id.get(),
+                "📅 ⚠️ Failed to post weekly recap"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Recap of the last full week in the guild's timezone, unless already posted
+async fn post_recap(
+    db: &Repository,
+    http: Option<&Http>,
+    image_gen: &ImageGenerator,
+    now: i64,
+    guild: &Guild,
+) -> Result<(), AppError> {
+    let Some(channel_id) = guild.recap_channel() else {
+        return Ok(());
+    };
+    let guild_id = guild.id.get();
+    let tz = guild.timezone();
+    let week_end = start_of_week(tz, now);
+    let week_start = start_of_week(tz, week_end - 1);
+    if guild.last_recap_week >= Some(week_start) {
+        return Ok(());
+    }
+
+    let mvp = db
+        .get_weekly_mvp(guild_id, week_start, week_end, MVP_MIN_GAMES)
+        .await
+        .with_context(|| "computing weekly MVP")?;
+
+    let chart = if guild.recap_chart {
+        lp_chart(db, image_gen, guild_id, week_start, week_end).await
+    } else {
+        None
+    };
+
+    let filter = image_gen.content_filter();
+    // Quiet weeks are skipped rather than posting an empty recap
+    let alert = match (&mvp, &chart) {
+        (Some(mvp), _) => Some(build_alert(filter, guild, mvp)),
+        (None, Some(_)) => Some(chart_alert(guild, week_start)),
+        (None, None) => None,
+    };
+    match (alert, http) {
+        (Some(mut alert), Some(http)) => {
+            let mut message = alert.message();
+            if let Some(chart) = chart {
+                alert.image_url = Some(format!("attachment://{CHART_FILENAME}"));
+                message = alert
+                    .message()
+                    .add_file(CreateAttachment::bytes(chart, CHART_FILENAME));
+            }
+            ChannelId::new(channel_id.get())
+                .send_message(http, message)
+                .await
+                .with_context(|| format!("posting weekly recap to channel {}", channel_id.get()))?;
+            info!(
+                guild_id,
+                player_id = mvp.as_ref().map(|mvp| mvp.player_id),
+                "📅 ✅ Weekly recap posted"
+            );
+        }
+        (Some(_), None) => debug!(guild_id, "👻 Shadow mode, weekly recap not posted"),
+        (None, _) => debug!(guild_id, "📅 No weekly MVP nor LP movement, skipping recap"),
+    }
+
+    db.record_weekly_recap(guild_id, week_start, mvp.as_ref())
+        .await
+        .with_context(|| "recording weekly recap")?;
+
+    Ok(())
+}
+
+/// Solo queue LP of the guild's players over the week, `None` when nobody
+/// moved or the chart couldn't be drawn
+async fn lp_chart(
+    db: &Repository,
+    image_gen: &ImageGenerator,
+    guild_id: u64,
+    week_start: i64,
+    week_end: i64,
+) -> Option<Vec<u8>> {
+    let points = match db
+        .get_guild_rank_history(guild_id, CHART_QUEUE, week_start, week_end)
+        .await
+    {
+        Ok(points) => points,
+        Err(e) => {
+            warn!(error = ?e, guild_id, "📅 ⚠️ Failed to load LP history");
+            return None;
+        }
+    };
+    match image_gen.generate_lp_chart(&lp_series(&points), week_start, week_end) {
+        Ok(chart) => chart,
+        Err(e) => {
+            warn!(error = ?e, guild_id, "📅 ⚠️ Failed to draw LP chart");
+            None
+        }
+    }
+}
+
+/// Recap of a week without MVP, only carrying the LP chart
+fn chart_alert(guild: &Guild, week_start: i64) -> AlertModel {
+    let week = chrono::DateTime::from_timestamp(week_start, 0)
+        .map(|start| {
+            start
+                .with_timezone(&guild.timezone())
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .unwrap_or_default();
+    AlertModel::new("🏅 Weekly Recap", 0xc89b3c)
+        .description("Solo/Duo LP of the week")
+        .footer(format!("Week of {week}"))
+}
+
+fn build_alert(filter: &ContentFilter, guild: &Guild, mvp: &WeeklyMvp) -> AlertModel {
+    AlertModel::new("🏅 Weekly Recap", 0xc89b3c)
+        .description(filter.apply(&format!(
+            "MVP of the week: **{}**\nAverage score of **{:.1}** over {} normal games",
+            mvp.display_name(),
+            mvp.avg_score,
+            mvp.games
+        )))
+        .footer(format!(
+            "Week of {} • /mvp_history for past MVPs",
+            mvp.week_label(guild.timezone())
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_alert;
+    use crate::db::{Guild, SnowflakeColumn, WeeklyMvp};
+    use crate::discord::moderation::ContentFilter;
+
+    #[test]
+    fn recap_embed_snapshot() {
+        let guild = Guild {
+            id: SnowflakeColumn::from(1),
+            alert_channel_id: None,
+            timezone: "Europe/Paris".into(),
+            alert_details: false,
+            alert_fields: None,
+            public_api_token: None,
+            last_recap_week: None,
+            recap_channel_id: None,
+            rank_notices: true,
+            alert_rate_limit: None,
+            alert_forecast: false,
+            rotating_alerts: true,
+            announcements: true,
+            track_limit: None,
+            recap_frequency: None,
+            recap_hour: 20,
+            last_results_recap: None,
+            recap_chart: true,
+        };
+        let mvp = WeeklyMvp {
+            // Monday 2024-01-01 00:00 in Paris
+            week_start: 1_704_063_600,
+            player_id: 1,
+            game_name: "Faker".into(),
+            tag_line: "KR1".into(),
+            privacy_alias: None,
+            avg_score: 8.04,
+            games: 12,
+        };
+        let alert = build_alert(&ContentFilter::default(), &guild, &mvp);
+        insta::assert_json_snapshot!(alert.embed());
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::clock::Clock;
+
+/// Small in-memory cache whose entries expire after a fixed time
+#[derive(Debug)]
+pub struct TtlCache<K, V> {
+    clock: Arc<dyn Clock>,
+    ttl: Duration,
+    /// Key -> (unix time it expires, value)
+    entries: Mutex<HashMap<K, (i64, V)>>,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    pub fn new(clock: Arc<dyn Clock>, ttl: Duration) -> Self {
+        Self {
+            clock,
+            ttl,
+            entries: Mutex::default(),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let now = self.clock.unix_now();
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(key)
+            .filter(|(expires_at, _)| *expires_at > now)
+            .map(|(_, value)| value.clone())
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        let now = self.clock.unix_now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (expires_at, _)| *expires_at > now);
+        entries.insert(key, (now + self.ttl.as_secs() as i64, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::TtlCache;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let clock = Arc::new(ManualClock::new(1_700_000_000));
+        let cache = TtlCache::new(clock.clone(), Duration::from_secs(60));
+
+        cache.insert("puuid", 1);
+        assert_eq!(cache.get(&"puuid"), Some(1));
+        assert_eq!(cache.get(&"other"), None);
+
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(cache.get(&"puuid"), Some(1));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.get(&"puuid"), None);
+    }
+}
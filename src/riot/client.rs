@@ -1,5 +1,6 @@
 use std::num::NonZeroU32;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use governor::clock::DefaultClock;
 use governor::state::{InMemoryState, NotKeyed};
@@ -8,7 +9,15 @@ use reqwest::Client;
 use serde::de::DeserializeOwned;
 use tracing::{debug, error, trace, warn};
 
+use crate::clock::Clock;
 use crate::error::AppError;
+use crate::riot::cache::TtlCache;
+use crate::riot::coalesce::Coalescer;
+use crate::riot::metrics::{EndpointFamily, RequestMetrics};
+use crate::riot::{LeagueEntryDto, Platform};
+
+/// How long league entries are served from memory
+const LEAGUE_CACHE_TTL: Duration = Duration::from_secs(60);
 
 type GovernorRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
 
@@ -17,10 +26,20 @@ pub struct RiotClient {
     http: Client,
     api_key: String,
     rate_limiter: Arc<GovernorRateLimiter>,
+    rate_limit_per_second: NonZeroU32,
+    metrics: Arc<RequestMetrics>,
+    /// In-flight league lookups by (platform, puuid)
+    pub(super) league_requests: Arc<Coalescer<(Platform, String), Vec<LeagueEntryDto>>>,
+    /// Recent league lookups by (platform, puuid)
+    pub(super) league_cache: Arc<TtlCache<(Platform, String), Vec<LeagueEntryDto>>>,
 }
 
 impl RiotClient {
-    pub fn new(api_key: String, rate_limit_per_second: NonZeroU32) -> Result<Self, AppError> {
+    pub fn new(
+        api_key: String,
+        rate_limit_per_second: NonZeroU32,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self, AppError> {
         let quota = Quota::per_second(rate_limit_per_second);
         let rate_limiter = Arc::new(RateLimiter::direct(quota));
 
@@ -32,9 +51,21 @@ impl RiotClient {
             http,
             api_key,
             rate_limiter,
+            rate_limit_per_second,
+            metrics: Arc::new(RequestMetrics::new(Arc::clone(&clock))),
+            league_requests: Arc::default(),
+            league_cache: Arc::new(TtlCache::new(clock, LEAGUE_CACHE_TTL)),
         })
     }
 
+    pub fn metrics(&self) -> &RequestMetrics {
+        &self.metrics
+    }
+
+    pub fn rate_limit_per_second(&self) -> NonZeroU32 {
+        self.rate_limit_per_second
+    }
+
     pub async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T, AppError> {
         // Wait for rate limiter
         self.rate_limiter.until_ready().await;
@@ -46,19 +77,29 @@ impl RiotClient {
             .and_then(|s| s.split('?').next())
             .unwrap_or(url);
 
-        trace!(endpoint, "🔷 API request");
+        let family = EndpointFamily::from_path(endpoint);
+        trace!(endpoint, %family, "🔷 API request");
 
+        let started = Instant::now();
         let response = self
             .http
             .get(url)
             .header("X-Riot-Token", &self.api_key)
             .send()
             .await?;
+        let latency = started.elapsed();
+        self.metrics.record_request(family, latency);
 
         let status = response.status();
 
         if status.is_success() {
-            debug!(endpoint, status = status.as_u16(), "🔷 ✅ API success");
+            debug!(
+                endpoint,
+                %family,
+                status = status.as_u16(),
+                latency_ms = latency.as_millis() as u64,
+                "🔷 ✅ API success"
+            );
             let body = response.json::<T>().await?;
             Ok(body)
         } else {
@@ -72,6 +113,7 @@ impl RiotClient {
                     debug!(endpoint, status = 404, "🔷 Not found");
                 }
                 429 => {
+                    self.metrics.record_rate_limited();
                     warn!(endpoint, status = 429, "🔷 ⚠️ Rate limited");
                 }
                 403 => {
@@ -88,6 +130,7 @@ impl RiotClient {
             }
 
             Err(AppError::RiotApi {
+                endpoint: endpoint.to_string(),
                 status: status.as_u16(),
                 message,
             })
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast;
+use tracing::trace;
+
+use crate::error::AppError;
+
+type Shared<V> = Result<V, Arc<AppError>>;
+
+/// Lets concurrent callers asking for the same key share a single request:
+/// the first one fetches, the others wait for its result.
+#[derive(Debug)]
+pub struct Coalescer<K, V> {
+    in_flight: Mutex<HashMap<K, broadcast::Sender<Shared<V>>>>,
+}
+
+impl<K, V> Default for Coalescer<K, V> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K, V> Coalescer<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub async fn run<F, Fut>(&self, key: K, fetch: F) -> Result<V, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, AppError>>,
+    {
+        let waiting = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            match in_flight.get(&key) {
+                Some(leader) => Some(leader.subscribe()),
+                None => {
+                    in_flight.insert(key.clone(), broadcast::channel(1).0);
+                    None
+                }
+            }
+        };
+
+        if let Some(mut waiting) = waiting {
+            trace!("🔷 Joining in-flight request");
+            return match waiting.recv().await {
+                Ok(result) => result.map_err(AppError::Shared),
+                // The leading caller was cancelled before answering
+                Err(_) => fetch().await,
+            };
+        }
+
+        let guard = InFlight {
+            coalescer: self,
+            key: Some(key),
+        };
+        let result = fetch().await.map_err(Arc::new);
+
+        match guard.finish() {
+            Some(leader) if leader.receiver_count() > 0 => {
+                let _ = leader.send(result.clone());
+                result.map_err(AppError::Shared)
+            }
+            _ => result.map_err(|e| Arc::try_unwrap(e).unwrap_or_else(AppError::Shared)),
+        }
+    }
+}
+
+/// Removes the in-flight entry even when the leading caller is cancelled, so
+/// later callers don't wait on a request nobody is running
+struct InFlight<'a, K: Eq + Hash, V> {
+    coalescer: &'a Coalescer<K, V>,
+    key: Option<K>,
+}
+
+impl<K: Eq + Hash, V> InFlight<'_, K, V> {
+    fn finish(mut self) -> Option<broadcast::Sender<Shared<V>>> {
+        self.remove()
+    }
+
+    fn remove(&mut self) -> Option<broadcast::Sender<Shared<V>>> {
+        let key = self.key.take()?;
+        self.coalescer
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&key)
+    }
+}
+
+impl<K: Eq + Hash, V> Drop for InFlight<'_, K, V> {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    use super::Coalescer;
+    use crate::error::AppError;
+
+    #[tokio::test]
+    async fn concurrent_callers_share_one_fetch() {
+        let coalescer = Coalescer::<&str, u32>::default();
+        let fetches = AtomicU32::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(7)
+        };
+
+        let (a, b, c) = tokio::join!(
+            coalescer.run("puuid", fetch),
+            coalescer.run("puuid", fetch),
+            coalescer.run("other", fetch),
+        );
+        assert_eq!((a.unwrap(), b.unwrap(), c.unwrap()), (7, 7, 7));
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+        // Nothing in flight anymore, the next call fetches again
+        coalescer.run("puuid", fetch).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn errors_reach_every_caller() {
+        let coalescer = Coalescer::<&str, u32>::default();
+        let fetch = || async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err(AppError::RiotApi {
+                endpoint: "/lol/league/v4/entries/by-puuid/puuid".into(),
+                status: 503,
+                message: "down".into(),
+            })
+        };
+
+        let (a, b) = tokio::join!(coalescer.run("puuid", fetch), coalescer.run("puuid", fetch));
+        assert!(a.unwrap_err().is_riot_outage());
+        assert!(b.unwrap_err().is_riot_outage());
+    }
+}
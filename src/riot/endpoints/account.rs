@@ -30,4 +30,45 @@ impl RiotClient {
             }
         })
     }
+
+    /// Get the account holding a PUUID, `None` when Riot doesn't know it,
+    /// e.g. a PUUID issued to an older API key
+    pub async fn get_account_by_puuid(
+        &self,
+        region: Region,
+        puuid: &str,
+    ) -> Result<Option<AccountDto>, AppError> {
+        let url = format!(
+            "{}/riot/account/v1/accounts/by-puuid/{}",
+            region.base_url(),
+            urlencoding::encode(puuid)
+        );
+
+        match self.get(&url).await {
+            Ok(account) => Ok(Some(account)),
+            // Riot answers 400 for PUUIDs it can't decrypt
+            Err(AppError::RiotApi {
+                status: 400 | 404, ..
+            }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Cheap call proving the API key is accepted. Looks up a riot id that may
+    /// not exist: a 404 still means the key works, 401/403 mean it doesn't.
+    pub async fn validate_api_key(&self) -> Result<(), AppError> {
+        match self
+            .get_account_by_riot_id(Region::Europe, "Tentrackule", "KEY")
+            .await
+        {
+            Ok(_) | Err(AppError::PlayerNotFound { .. }) => Ok(()),
+            Err(AppError::RiotApi {
+                status: status @ (401 | 403),
+                ..
+            }) => Err(AppError::Config(format!(
+                "RIOT_API_KEY is invalid or expired (HTTP {status})"
+            ))),
+            Err(e) => Err(e),
+        }
+    }
 }
@@ -0,0 +1,40 @@
+use crate::error::AppError;
+use crate::riot::client::RiotClient;
+use crate::riot::region::Platform;
+use crate::riot::types::{ClashPlayerDto, ClashTeamDto};
+
+impl RiotClient {
+    /// Name of the Clash team the player is registered with, `None` outside
+    /// a tournament. Registrations end with the tournament, so this only
+    /// finds the team of games played during it.
+    /// Uses platform routing (euw1, na1, kr, etc.)
+    pub async fn get_clash_team_name(
+        &self,
+        platform: Platform,
+        puuid: &str,
+    ) -> Result<Option<String>, AppError> {
+        let url = format!(
+            "{}/lol/clash/v1/players/by-puuid/{}",
+            platform.base_url(),
+            puuid
+        );
+        let registrations: Vec<ClashPlayerDto> = self.get(&url).await?;
+        let Some(team_id) = registrations
+            .into_iter()
+            .find_map(|registration| registration.team_id)
+        else {
+            return Ok(None);
+        };
+
+        let url = format!(
+            "{}/lol/clash/v1/teams/{}",
+            platform.base_url(),
+            urlencoding::encode(&team_id)
+        );
+        match self.get::<ClashTeamDto>(&url).await {
+            Ok(team) => Ok(Some(team.name)),
+            Err(AppError::RiotApi { status: 404, .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
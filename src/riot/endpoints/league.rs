@@ -1,22 +1,55 @@
 use crate::error::AppError;
 use crate::riot::client::RiotClient;
 use crate::riot::region::Platform;
-use crate::riot::types::LeagueEntryDto;
+use crate::riot::types::{ApexTier, LeagueEntryDto, LeagueListDto};
 
 impl RiotClient {
     /// Get league entries (ranked info) for a player by PUUID
-    /// Uses platform routing (euw1, na1, kr, etc.)
+    /// Uses platform routing (euw1, na1, kr, etc.). Answers are cached for a
+    /// minute unless `bypass_cache` is set, and concurrent lookups of the same
+    /// account share a single request.
     pub async fn get_league_entries_by_puuid(
         &self,
         platform: Platform,
         puuid: &str,
+        bypass_cache: bool,
     ) -> Result<Vec<LeagueEntryDto>, AppError> {
+        let key = (platform, puuid.to_string());
+        if !bypass_cache {
+            if let Some(entries) = self.league_cache.get(&key) {
+                self.metrics().record_cache_hit();
+                return Ok(entries);
+            }
+            self.metrics().record_cache_miss();
+        }
+
         let url = format!(
             "{}/lol/league/v4/entries/by-puuid/{}",
             platform.base_url(),
             puuid
         );
 
+        let entries = self
+            .league_requests
+            .run(key.clone(), || self.get(&url))
+            .await?;
+        self.league_cache.insert(key, entries.clone());
+        Ok(entries)
+    }
+
+    /// Get the full Solo/Duo ladder of an apex tier
+    /// Uses platform routing (euw1, na1, kr, etc.)
+    pub async fn get_apex_league(
+        &self,
+        platform: Platform,
+        tier: ApexTier,
+    ) -> Result<LeagueListDto, AppError> {
+        let url = format!(
+            "{}/lol/league/v4/{}/by-queue/RANKED_SOLO_5x5",
+            platform.base_url(),
+            tier.endpoint()
+        );
+
         self.get(&url).await
     }
 }
@@ -1,6 +1,6 @@
 use crate::error::AppError;
 use crate::riot::client::RiotClient;
-use crate::riot::region::Region;
+use crate::riot::region::{Platform, Region};
 use crate::riot::types::MatchDto;
 
 impl RiotClient {
@@ -23,8 +23,11 @@ impl RiotClient {
     }
 
     /// Get match details by match ID
-    /// Uses regional routing (americas, europe, asia, sea)
+    /// Uses regional routing (americas, europe, asia, sea). The match ID prefix
+    /// decides the routing, `region` is only the fallback: matches played before
+    /// a region transfer still live on their original routing.
     pub async fn get_match(&self, region: Region, match_id: &str) -> Result<MatchDto, AppError> {
+        let region = Platform::from_match_id(match_id).map_or(region, Platform::to_region);
         let url = format!("{}/lol/match/v5/matches/{}", region.base_url(), match_id);
 
         self.get(&url).await
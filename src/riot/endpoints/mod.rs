@@ -1,4 +1,6 @@
 mod account;
+mod clash;
 mod league;
 mod match_v5;
+mod spectator;
 mod summoner;
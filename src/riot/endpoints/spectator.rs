@@ -0,0 +1,26 @@
+use crate::error::AppError;
+use crate::riot::client::RiotClient;
+use crate::riot::region::Platform;
+use crate::riot::types::CurrentGameInfo;
+
+impl RiotClient {
+    /// Get the game a player is currently in, `None` when not in game
+    /// Uses platform routing (euw1, na1, kr, etc.)
+    pub async fn get_active_game(
+        &self,
+        platform: Platform,
+        puuid: &str,
+    ) -> Result<Option<CurrentGameInfo>, AppError> {
+        let url = format!(
+            "{}/lol/spectator/v5/active-games/by-summoner/{}",
+            platform.base_url(),
+            puuid
+        );
+
+        match self.get(&url).await {
+            Ok(game) => Ok(Some(game)),
+            Err(AppError::RiotApi { status: 404, .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::clock::Clock;
+
+/// Longest window we keep data for
+const MAX_WINDOW: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// Per-second buckets covering the last `MAX_WINDOW`
+#[derive(Debug, Default)]
+struct WindowedCounter {
+    /// (unix second, count), oldest first
+    buckets: VecDeque<(i64, u32)>,
+    total: u64,
+}
+
+impl WindowedCounter {
+    fn record(&mut self, now: i64) {
+        match self.buckets.back_mut() {
+            Some((second, count)) if *second == now => *count += 1,
+            _ => self.buckets.push_back((now, 1)),
+        }
+        self.total += 1;
+
+        while self
+            .buckets
+            .front()
+            .is_some_and(|(second, _)| second + MAX_WINDOW.as_secs() as i64 <= now)
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Events recorded in the last `window` (the current second included)
+    fn count(&self, now: i64, window: Duration) -> u64 {
+        self.buckets
+            .iter()
+            .rev()
+            .take_while(|(second, _)| second + window.as_secs() as i64 > now)
+            .map(|(_, count)| u64::from(*count))
+            .sum()
+    }
+}
+
+/// Riot API endpoint groups sharing a quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EndpointFamily {
+    Account,
+    Summoner,
+    League,
+    Match,
+    Spectator,
+    Clash,
+    Other,
+}
+
+impl EndpointFamily {
+    /// Classify an endpoint path such as `/lol/match/v5/matches/...`
+    pub fn from_path(path: &str) -> Self {
+        if path.starts_with("/riot/account/") {
+            Self::Account
+        } else if path.starts_with("/lol/summoner/") {
+            Self::Summoner
+        } else if path.starts_with("/lol/league/") {
+            Self::League
+        } else if path.starts_with("/lol/match/") {
+            Self::Match
+        } else if path.starts_with("/lol/spectator/") {
+            Self::Spectator
+        } else if path.starts_with("/lol/clash/") {
+            Self::Clash
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Account => "account",
+            Self::Summoner => "summoner",
+            Self::League => "league",
+            Self::Match => "match",
+            Self::Spectator => "spectator",
+            Self::Clash => "clash",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for EndpointFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+struct EndpointState {
+    requests: WindowedCounter,
+    total_latency: Duration,
+    max_latency: Duration,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    requests: WindowedCounter,
+    rate_limited: WindowedCounter,
+    cache_hits: u64,
+    cache_misses: u64,
+    endpoints: BTreeMap<EndpointFamily, EndpointState>,
+}
+
+/// Usage of a single endpoint family
+#[derive(Debug, Clone, Copy)]
+pub struct EndpointUsage {
+    pub family: EndpointFamily,
+    pub last_minute: u64,
+    pub last_two_hours: u64,
+    pub total: u64,
+    pub avg_latency: Duration,
+    pub max_latency: Duration,
+}
+
+/// Point-in-time view of the Riot API usage
+#[derive(Debug, Clone)]
+pub struct UsageSnapshot {
+    pub last_second: u64,
+    pub last_minute: u64,
+    pub last_two_hours: u64,
+    pub rate_limited_last_minute: u64,
+    pub rate_limited_last_two_hours: u64,
+    pub total_requests: u64,
+    pub total_rate_limited: u64,
+    /// League lookups answered from the cache, bypassed lookups excluded
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Sorted by requests over the last 2 hours, busiest first
+    pub endpoints: Vec<EndpointUsage>,
+}
+
+impl UsageSnapshot {
+    /// Share of cacheable lookups answered from the cache, 0 when there were none
+    pub fn cache_hit_rate(&self) -> f64 {
+        let lookups = self.cache_hits + self.cache_misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Sliding-window request counters for the Riot API client
+#[derive(Debug)]
+pub struct RequestMetrics {
+    clock: Arc<dyn Clock>,
+    state: Mutex<MetricsState>,
+}
+
+impl RequestMetrics {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            state: Mutex::default(),
+        }
+    }
+
+    fn now(&self) -> i64 {
+        self.clock.unix_now()
+    }
+
+    /// Record a completed request and how long it took
+    pub fn record_request(&self, family: EndpointFamily, latency: Duration) {
+        let now = self.now();
+        if let Ok(mut state) = self.state.lock() {
+            state.requests.record(now);
+
+            let endpoint = state.endpoints.entry(family).or_default();
+            endpoint.requests.record(now);
+            endpoint.total_latency += latency;
+            endpoint.max_latency = endpoint.max_latency.max(latency);
+        }
+    }
+
+    pub fn record_rate_limited(&self) {
+        let now = self.now();
+        if let Ok(mut state) = self.state.lock() {
+            state.rate_limited.record(now);
+        }
+    }
+
+    pub fn record_cache_hit(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.cache_hits += 1;
+        }
+    }
+
+    pub fn record_cache_miss(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.cache_misses += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> UsageSnapshot {
+        let now = self.now();
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        const SECOND: Duration = Duration::from_secs(1);
+        const MINUTE: Duration = Duration::from_secs(60);
+
+        let mut endpoints: Vec<_> = state
+            .endpoints
+            .iter()
+            .map(|(family, endpoint)| EndpointUsage {
+                family: *family,
+                last_minute: endpoint.requests.count(now, MINUTE),
+                last_two_hours: endpoint.requests.count(now, MAX_WINDOW),
+                total: endpoint.requests.total,
+                avg_latency: endpoint
+                    .total_latency
+                    .checked_div(endpoint.requests.total as u32)
+                    .unwrap_or_default(),
+                max_latency: endpoint.max_latency,
+            })
+            .collect();
+        endpoints.sort_by_key(|usage| std::cmp::Reverse(usage.last_two_hours));
+
+        UsageSnapshot {
+            last_second: state.requests.count(now, SECOND),
+            last_minute: state.requests.count(now, MINUTE),
+            last_two_hours: state.requests.count(now, MAX_WINDOW),
+            rate_limited_last_minute: state.rate_limited.count(now, MINUTE),
+            rate_limited_last_two_hours: state.rate_limited.count(now, MAX_WINDOW),
+            total_requests: state.requests.total,
+            total_rate_limited: state.rate_limited.total,
+            cache_hits: state.cache_hits,
+            cache_misses: state.cache_misses,
+            endpoints,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{EndpointFamily, MAX_WINDOW, RequestMetrics, WindowedCounter};
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn endpoint_family_from_path() {
+        assert_eq!(
+            EndpointFamily::from_path("/lol/match/v5/matches/EUW1_1"),
+            EndpointFamily::Match
+        );
+        assert_eq!(
+            EndpointFamily::from_path("/riot/account/v1/accounts/by-riot-id/a/b"),
+            EndpointFamily::Account
+        );
+        assert_eq!(
+            EndpointFamily::from_path("/lol/league/v4/entries/by-puuid/x"),
+            EndpointFamily::League
+        );
+        assert_eq!(
+            EndpointFamily::from_path("/lol/spectator/v5/active-games/by-summoner/x"),
+            EndpointFamily::Spectator
+        );
+        assert_eq!(
+            EndpointFamily::from_path("/lol/clash/v1/players/by-puuid/x"),
+            EndpointFamily::Clash
+        );
+        assert_eq!(EndpointFamily::from_path("/unknown"), EndpointFamily::Other);
+    }
+
+    #[test]
+    fn windowed_counter_expires_old_buckets() {
+        let mut counter = WindowedCounter::default();
+        counter.record(0);
+        counter.record(0);
+        counter.record(59);
+        counter.record(60);
+
+        assert_eq!(counter.count(60, Duration::from_secs(1)), 1);
+        assert_eq!(counter.count(60, Duration::from_secs(60)), 2);
+        assert_eq!(counter.count(60, MAX_WINDOW), 4);
+
+        let end = MAX_WINDOW.as_secs() as i64;
+        counter.record(end);
+        assert_eq!(counter.count(end, MAX_WINDOW), 3);
+        assert_eq!(counter.total, 5);
+    }
+
+    #[test]
+    fn snapshot_follows_clock() {
+        let clock = Arc::new(ManualClock::new(1_700_000_000));
+        let metrics = RequestMetrics::new(clock.clone());
+
+        metrics.record_request(EndpointFamily::Match, Duration::from_millis(100));
+        metrics.record_request(EndpointFamily::Match, Duration::from_millis(300));
+        metrics.record_rate_limited();
+        metrics.record_cache_hit();
+        metrics.record_cache_miss();
+        metrics.record_cache_miss();
+
+        let usage = metrics.snapshot();
+        assert_eq!((usage.cache_hits, usage.cache_misses), (1, 2));
+        assert_eq!(usage.last_second, 2);
+        assert_eq!(usage.rate_limited_last_minute, 1);
+        assert_eq!(usage.endpoints[0].avg_latency, Duration::from_millis(200));
+
+        clock.advance(Duration::from_secs(61));
+        let usage = metrics.snapshot();
+        assert_eq!(usage.last_second, 0);
+        assert_eq!(usage.last_minute, 0);
+        assert_eq!(usage.rate_limited_last_minute, 0);
+        assert_eq!(usage.last_two_hours, 2);
+        assert_eq!(usage.total_requests, 2);
+    }
+}
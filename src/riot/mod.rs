@@ -1,8 +1,17 @@
+mod cache;
 mod client;
+mod coalesce;
 pub mod endpoints;
+mod metrics;
+mod queue;
 mod region;
+mod riot_id;
+mod role;
 mod types;
 
 pub use client::RiotClient;
+pub use queue::{Queue, league_queue_name};
 pub use region::Platform;
+pub use riot_id::{normalize_riot_id, parse_riot_id_list};
+pub use role::Role;
 pub use types::*;
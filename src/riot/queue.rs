@@ -0,0 +1,141 @@
+/// Queues alerts are sent for, with the names shown to users
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Queue {
+    NormalDraft,
+    RankedSolo,
+    NormalBlind,
+    RankedFlex,
+    Aram,
+    Quickplay,
+    Clash,
+    /// One of the `ROTATING_QUEUES` PvP modes (Arena, URF...), alerted with
+    /// a generic layout until it gets a dedicated one
+    Rotating(u16),
+}
+
+/// Rotating PvP queues worth an alert. Co-op vs AI, tutorials and PvE modes
+/// such as Swarm are left out.
+const ROTATING_QUEUES: [u16; 8] = [900, 1010, 1020, 1300, 1400, 1700, 1710, 1900];
+
+impl Queue {
+    pub const ALL: [Self; 7] = [
+        Self::NormalDraft,
+        Self::RankedSolo,
+        Self::NormalBlind,
+        Self::RankedFlex,
+        Self::Aram,
+        Self::Quickplay,
+        Self::Clash,
+    ];
+
+    /// Match-v5 and Spectator-v5 queue id, `None` for custom games and
+    /// queues without alerts
+    pub fn from_id(queue_id: i32) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|queue| queue.id() == queue_id)
+            .or_else(|| {
+                u16::try_from(queue_id)
+                    .ok()
+                    .filter(|id| ROTATING_QUEUES.contains(id))
+                    .map(Self::Rotating)
+            })
+    }
+
+    /// Stable identifier stored in guild settings
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::NormalDraft => "normal_draft",
+            Self::RankedSolo => "ranked_solo",
+            Self::NormalBlind => "normal_blind",
+            Self::RankedFlex => "ranked_flex",
+            Self::Aram => "aram",
+            Self::Quickplay => "quickplay",
+            Self::Clash => "clash",
+            Self::Rotating(_) => "rotating",
+        }
+    }
+
+    pub fn is_rotating(self) -> bool {
+        matches!(self, Self::Rotating(_))
+    }
+
+    /// League-v4 queue type, e.g. `RANKED_SOLO_5x5`
+    pub fn from_league_type(queue_type: &str) -> Option<Self> {
+        match queue_type {
+            "RANKED_SOLO_5x5" => Some(Self::RankedSolo),
+            "RANKED_FLEX_SR" => Some(Self::RankedFlex),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> i32 {
+        match self {
+            Self::NormalDraft => 400,
+            Self::RankedSolo => 420,
+            Self::NormalBlind => 430,
+            Self::RankedFlex => 440,
+            Self::Aram => 450,
+            Self::Quickplay => 490,
+            Self::Clash => 700,
+            Self::Rotating(id) => id.into(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::NormalDraft => "Normal Draft",
+            Self::RankedSolo => "Ranked Solo/Duo",
+            Self::NormalBlind => "Normal Blind",
+            Self::RankedFlex => "Ranked Flex",
+            Self::Aram => "ARAM",
+            Self::Quickplay => "Quickplay",
+            Self::Clash => "Clash",
+            Self::Rotating(900 | 1010) => "ARURF",
+            Self::Rotating(1900) => "URF",
+            Self::Rotating(1020) => "One for All",
+            Self::Rotating(1300) => "Nexus Blitz",
+            Self::Rotating(1400) => "Ultimate Spellbook",
+            Self::Rotating(1700 | 1710) => "Arena",
+            Self::Rotating(_) => "Special Mode",
+        }
+    }
+}
+
+/// Name of a queue id, "Other" for custom games and queues without alerts
+pub(super) fn queue_name(queue_id: i32) -> &'static str {
+    Queue::from_id(queue_id).map_or("Other", Queue::name)
+}
+
+/// Name of a League-v4 queue type, the raw type for unknown ones
+pub fn league_queue_name(queue_type: &str) -> &str {
+    match Queue::from_league_type(queue_type) {
+        Some(queue) => queue.name(),
+        None => queue_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Queue, league_queue_name, queue_name};
+
+    #[test]
+    fn queues_have_human_names() {
+        for queue in Queue::ALL {
+            assert_eq!(Queue::from_id(queue.id()), Some(queue));
+        }
+        assert_eq!(queue_name(440), "Ranked Flex");
+        assert_eq!(Queue::from_id(700), Some(Queue::Clash));
+        assert_eq!(Queue::from_id(1700), Some(Queue::Rotating(1700)));
+        assert_eq!(queue_name(1700), "Arena");
+        assert_eq!(queue_name(1900), "URF");
+        assert_eq!(queue_name(1020), "One for All");
+        assert_eq!(queue_name(0), "Other");
+        // Co-op vs AI, tutorials and Swarm don't alert
+        for id in [830, 870, 2000, 1810] {
+            assert_eq!(Queue::from_id(id), None);
+        }
+        assert_eq!(league_queue_name("RANKED_SOLO_5x5"), "Ranked Solo/Duo");
+        assert_eq!(league_queue_name("RANKED_TFT"), "RANKED_TFT");
+    }
+}
@@ -71,6 +71,12 @@ impl Platform {
         }
     }
 
+    /// Platform a match was played on, from the prefix of its id (`EUW1_7123456789`)
+    pub fn from_match_id(match_id: &str) -> Option<Self> {
+        let (prefix, _) = match_id.split_once('_')?;
+        prefix.parse().ok()
+    }
+
     pub fn to_region(self) -> Region {
         match self {
             Self::BR1 | Self::LA1 | Self::LA2 | Self::NA1 => Region::Americas,
@@ -165,3 +171,23 @@ impl fmt::Display for Region {
         write!(f, "{}", self.as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Platform, Region};
+
+    #[test]
+    fn match_id_prefix_gives_platform() {
+        assert_eq!(
+            Platform::from_match_id("EUW1_7123456789"),
+            Some(Platform::EUW1)
+        );
+        assert_eq!(
+            Platform::from_match_id("NA1_5012345678").map(Platform::to_region),
+            Some(Region::Americas)
+        );
+        assert_eq!(Platform::from_match_id("KR_6912345678"), Some(Platform::KR));
+        assert_eq!(Platform::from_match_id("XX9_1"), None);
+        assert_eq!(Platform::from_match_id("7123456789"), None);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::error::AppError;
+
+const GAME_NAME_LEN: std::ops::RangeInclusive<usize> = 3..=16;
+const TAG_LINE_LEN: std::ops::RangeInclusive<usize> = 3..=5;
+
+/// Trim a Riot ID typed by a user and check it follows Riot's rules, so
+/// obvious typos are answered without spending an API call on a 404
+pub fn normalize_riot_id(game_name: &str, tag_line: &str) -> Result<(String, String), AppError> {
+    let game_name = game_name.trim();
+    let tag_line = tag_line.trim().trim_start_matches('#');
+
+    if let Some((name, tag)) = game_name.split_once('#') {
+        return Err(AppError::InvalidRiotId(format!(
+            "put the tag line in its own field: game name `{}`, tag line `{}`",
+            name.trim(),
+            tag.trim()
+        )));
+    }
+
+    let name_len = game_name.chars().count();
+    if !GAME_NAME_LEN.contains(&name_len) {
+        return Err(AppError::InvalidRiotId(format!(
+            "the game name is {} to {} characters long, `{game_name}` has {name_len}",
+            GAME_NAME_LEN.start(),
+            GAME_NAME_LEN.end()
+        )));
+    }
+    if let Some(c) = game_name
+        .chars()
+        .find(|&c| !(c.is_alphanumeric() || matches!(c, ' ' | '.' | '_')))
+    {
+        return Err(AppError::InvalidRiotId(format!(
+            "`{c}` can't be part of a game name"
+        )));
+    }
+
+    let tag_len = tag_line.chars().count();
+    if !TAG_LINE_LEN.contains(&tag_len) || !tag_line.chars().all(char::is_alphanumeric) {
+        return Err(AppError::InvalidRiotId(format!(
+            "the tag line is {} to {} letters or digits, got `{tag_line}`",
+            TAG_LINE_LEN.start(),
+            TAG_LINE_LEN.end()
+        )));
+    }
+
+    Ok((game_name.to_string(), tag_line.to_string()))
+}
+
+/// An entry as typed, with its checked game name and tag line
+type RiotIdEntry = (String, Result<(String, String), AppError>);
+
+/// Split a pasted list of `Name#TAG`, one per line or comma separated.
+/// Repeats are dropped.
+pub fn parse_riot_id_list(input: &str) -> Vec<RiotIdEntry> {
+    let mut seen = std::collections::HashSet::new();
+    input
+        .split(['\n', ','])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty() && seen.insert(entry.to_lowercase()))
+        .map(|entry| {
+            let riot_id = match entry.split_once('#') {
+                Some((name, tag)) => normalize_riot_id(name, tag),
+                None => Err(AppError::InvalidRiotId(format!(
+                    "write it as `Name#TAG`, got `{entry}`"
+                ))),
+            };
+            (entry.to_string(), riot_id)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_riot_id, parse_riot_id_list};
+
+    #[test]
+    fn riot_ids_follow_riot_rules() {
+        assert_eq!(
+            normalize_riot_id(" Faker ", "#KR1").unwrap(),
+            ("Faker".to_string(), "KR1".to_string())
+        );
+        assert!(normalize_riot_id("Hide on bush", "KR1").is_ok());
+        assert!(normalize_riot_id("잘생긴세나", "0000").is_ok());
+
+        assert!(normalize_riot_id("Faker#KR1", "KR1").is_err());
+        assert!(normalize_riot_id("Fa", "KR1").is_err());
+        assert!(normalize_riot_id("SeventeenCharsLon", "KR1").is_err());
+        assert!(normalize_riot_id("Fa<ker>", "KR1").is_err());
+        assert!(normalize_riot_id("Faker", "K1").is_err());
+        assert!(normalize_riot_id("Faker", "KR 1").is_err());
+    }
+
+    #[test]
+    fn riot_id_lists_split_on_lines_and_commas() {
+        let entries = parse_riot_id_list("Faker#KR1, Chovy#KR2\n\n faker#kr1 \nNoTag");
+        assert_eq!(
+            entries
+                .iter()
+                .map(|(entry, riot_id)| (entry.as_str(), riot_id.is_ok()))
+                .collect::<Vec<_>>(),
+            [("Faker#KR1", true), ("Chovy#KR2", true), ("NoTag", false)]
+        );
+        assert_eq!(
+            entries[1].1.as_ref().unwrap(),
+            &("Chovy".to_string(), "KR2".to_string())
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+
+/// Summoner spell id of Smite
+const SMITE: i32 = 11;
+
+/// Lane a participant played
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Role {
+    Top,
+    Jungle,
+    Mid,
+    Bottom,
+    Support,
+    /// Position Riot reported but we don't know, kept for display
+    Unknown(String),
+}
+
+impl Role {
+    /// Parse a Riot position (`teamPosition`, `individualPosition` or `lane`)
+    pub fn from_position(position: &str) -> Option<Self> {
+        match position.to_ascii_uppercase().as_str() {
+            "TOP" => Some(Self::Top),
+            "JUNGLE" => Some(Self::Jungle),
+            "MIDDLE" | "MID" => Some(Self::Mid),
+            "BOTTOM" | "BOT" => Some(Self::Bottom),
+            "UTILITY" | "SUPPORT" => Some(Self::Support),
+            _ => None,
+        }
+    }
+
+    /// Best guess of the role from everything the match reports.
+    ///
+    /// `teamPosition` wins when set, so a support running Smite stays a support.
+    /// It is empty in blind pick and some remakes, where we fall back to Smite,
+    /// then `individualPosition`, then the legacy lane/role pair.
+    pub fn infer(
+        team_position: &str,
+        individual_position: &str,
+        lane: &str,
+        lane_role: &str,
+        summoner_spells: [i32; 2],
+    ) -> Self {
+        if let Some(role) = Self::from_position(team_position) {
+            return role;
+        }
+        if summoner_spells.contains(&SMITE) {
+            return Self::Jungle;
+        }
+        if let Some(role) = Self::from_position(individual_position) {
+            return role;
+        }
+
+        match Self::from_position(lane) {
+            Some(Self::Bottom) if lane_role.eq_ignore_ascii_case("DUO_SUPPORT") => Self::Support,
+            Some(role) => role,
+            None => {
+                let raw = [team_position, individual_position, lane]
+                    .into_iter()
+                    .find(|p| !p.is_empty() && !p.eq_ignore_ascii_case("INVALID"))
+                    .unwrap_or_default();
+                Self::Unknown(raw.to_string())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Top => write!(f, "Top"),
+            Self::Jungle => write!(f, "Jungle"),
+            Self::Mid => write!(f, "Mid"),
+            Self::Bottom => write!(f, "ADC"),
+            Self::Support => write!(f, "Support"),
+            Self::Unknown(raw) if raw.is_empty() || raw.eq_ignore_ascii_case("NONE") => {
+                write!(f, "-")
+            }
+            Self::Unknown(raw) => {
+                let mut chars = raw.chars();
+                let first = chars.next().map(|c| c.to_ascii_uppercase());
+                let rest: String = chars.as_str().to_lowercase();
+                write!(f, "{}{}", first.unwrap_or_default(), rest)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Role, SMITE};
+
+    const FLASH: i32 = 4;
+    const IGNITE: i32 = 14;
+
+    #[test]
+    fn team_position_wins_over_smite() {
+        let role = Role::infer("UTILITY", "JUNGLE", "JUNGLE", "NONE", [SMITE, FLASH]);
+        assert_eq!(role, Role::Support);
+    }
+
+    #[test]
+    fn blind_pick_falls_back_to_spells_then_lanes() {
+        assert_eq!(Role::infer("", "", "", "", [FLASH, SMITE]), Role::Jungle);
+        assert_eq!(Role::infer("", "TOP", "", "", [FLASH, IGNITE]), Role::Top);
+        assert_eq!(
+            Role::infer("", "Invalid", "BOTTOM", "DUO_SUPPORT", [FLASH, IGNITE]),
+            Role::Support
+        );
+        assert_eq!(
+            Role::infer("", "Invalid", "BOTTOM", "DUO_CARRY", [FLASH, IGNITE]),
+            Role::Bottom
+        );
+    }
+
+    #[test]
+    fn unknown_positions_still_display() {
+        let role = Role::infer("", "Invalid", "NONE", "", [FLASH, IGNITE]);
+        assert_eq!(role, Role::Unknown("NONE".into()));
+        assert_eq!(role.to_string(), "-");
+        assert_eq!(Role::Unknown("AFK".into()).to_string(), "Afk");
+    }
+}
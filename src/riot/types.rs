@@ -1,5 +1,9 @@
+use poise::ChoiceParameter;
 use serde::Deserialize;
 
+use super::Role;
+use super::queue::{Queue, queue_name};
+
 // ============================================================================
 // Account-v1
 // ============================================================================
@@ -20,6 +24,8 @@ pub struct AccountDto {
 #[serde(rename_all = "camelCase")]
 pub struct SummonerDto {
     pub profile_icon_id: i32,
+    #[serde(default)]
+    pub summoner_level: i64,
 }
 
 // ============================================================================
@@ -33,15 +39,140 @@ pub struct LeagueEntryDto {
     pub tier: String,
     pub rank: String,
     pub league_points: i32,
+    #[serde(default)]
+    pub wins: i32,
+    #[serde(default)]
+    pub losses: i32,
 }
 
 impl LeagueEntryDto {
     pub fn is_solo_queue(&self) -> bool {
-        self.queue_type == "RANKED_SOLO_5x5"
+        Queue::from_league_type(&self.queue_type) == Some(Queue::RankedSolo)
     }
 
     pub fn is_flex_queue(&self) -> bool {
-        self.queue_type == "RANKED_FLEX_SR"
+        Queue::from_league_type(&self.queue_type) == Some(Queue::RankedFlex)
+    }
+}
+
+/// Apex tiers exposing a full ladder through League-v4
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ChoiceParameter)]
+pub enum ApexTier {
+    Challenger,
+    Grandmaster,
+    Master,
+}
+
+impl ApexTier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Challenger => "CHALLENGER",
+            Self::Grandmaster => "GRANDMASTER",
+            Self::Master => "MASTER",
+        }
+    }
+
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            Self::Challenger => "challengerleagues",
+            Self::Grandmaster => "grandmasterleagues",
+            Self::Master => "masterleagues",
+        }
+    }
+}
+
+impl std::str::FromStr for ApexTier {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "CHALLENGER" => Ok(Self::Challenger),
+            "GRANDMASTER" => Ok(Self::Grandmaster),
+            "MASTER" => Ok(Self::Master),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeagueListDto {
+    pub entries: Vec<LeagueItemDto>,
+}
+
+impl LeagueListDto {
+    /// LP needed to sit in the top `n` of the ladder
+    pub fn top_cutoff(&self, n: usize) -> Option<i32> {
+        let mut lps: Vec<i32> = self.entries.iter().map(|e| e.league_points).collect();
+        lps.sort_unstable_by(|a, b| b.cmp(a));
+        lps.get(n.saturating_sub(1)).or(lps.last()).copied()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeagueItemDto {
+    pub puuid: String,
+    pub league_points: i32,
+}
+
+// ============================================================================
+// Queues
+// ============================================================================
+
+/// Queues with a dedicated layout: Normal (400, 430, 490), Ranked (420, 440),
+/// ARAM (450), Clash (700)
+fn is_supported_queue(queue_id: i32) -> bool {
+    Queue::from_id(queue_id).is_some_and(|queue| !queue.is_rotating())
+}
+
+// ============================================================================
+// Clash-v1
+// ============================================================================
+
+/// A player's registration in an upcoming or running Clash tournament
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashPlayerDto {
+    /// Missing until the player joins a team
+    #[serde(default)]
+    pub team_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashTeamDto {
+    pub name: String,
+}
+
+// ============================================================================
+// Spectator-v5
+// ============================================================================
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentGameInfo {
+    pub game_id: i64,
+    pub platform_id: String,
+    #[serde(default)]
+    pub game_queue_config_id: i32,
+    /// Seconds since the game started, counted from the loading screen
+    #[serde(default)]
+    pub game_length: i64,
+}
+
+impl CurrentGameInfo {
+    /// ID of the Match-v5 match this game becomes once over
+    pub fn match_id(&self) -> String {
+        format!("{}_{}", self.platform_id.to_uppercase(), self.game_id)
+    }
+
+    pub fn is_supported(&self) -> bool {
+        is_supported_queue(self.game_queue_config_id)
+    }
+
+    pub fn queue_name(&self) -> &'static str {
+        queue_name(self.game_queue_config_id)
     }
 }
 
@@ -58,18 +189,32 @@ pub struct MatchDto {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InfoDto {
+    /// Unix milliseconds
+    #[serde(default)]
+    pub game_creation: i64,
+    /// Unix milliseconds, missing from older matches
+    #[serde(default)]
+    pub game_end_timestamp: Option<i64>,
     pub game_duration: i64,
     pub game_version: String,
     #[serde(default)]
     pub game_ended_in_early_surrender: bool,
+    #[serde(default)]
+    pub game_ended_in_surrender: bool,
     pub participants: Vec<ParticipantDto>,
     pub queue_id: i32,
+    #[serde(default)]
+    pub teams: Vec<TeamDto>,
 }
 
 impl InfoDto {
-    /// Queue IDs we support: Normal (400, 430, 490), Ranked (420, 440), ARAM (450)
     pub fn is_supported(&self) -> bool {
-        matches!(self.queue_id, 400 | 420 | 430 | 440 | 450 | 490)
+        is_supported_queue(self.queue_id)
+    }
+
+    /// Rotating or event mode, alerted with the generic layout
+    pub fn is_rotating(&self) -> bool {
+        Queue::from_id(self.queue_id).is_some_and(Queue::is_rotating)
     }
 
     /// Extract short patch version (e.g., "14.24" from "14.24.632.8043")
@@ -81,6 +226,13 @@ impl InfoDto {
             .unwrap_or(&self.game_version)
     }
 
+    /// Unix time the game ended
+    pub fn ended_at(&self) -> i64 {
+        self.game_end_timestamp
+            .unwrap_or(self.game_creation + self.game_duration * 1000)
+            / 1000
+    }
+
     pub fn duration_formatted(&self) -> String {
         let minutes = self.game_duration / 60;
         let seconds = self.game_duration % 60;
@@ -88,15 +240,7 @@ impl InfoDto {
     }
 
     pub fn queue_name(&self) -> &'static str {
-        match self.queue_id {
-            400 => "Normal Draft",
-            420 => "Ranked Solo/Duo",
-            430 => "Normal Blind",
-            440 => "Ranked Flex",
-            450 => "ARAM",
-            490 => "Quickplay",
-            _ => "Other",
-        }
+        queue_name(self.queue_id)
     }
 
     pub fn is_ranked(&self) -> bool {
@@ -106,13 +250,95 @@ impl InfoDto {
     pub fn is_solo_queue(&self) -> bool {
         self.queue_id == 420
     }
+
+    pub fn is_clash(&self) -> bool {
+        self.queue_id == 700
+    }
+
+    /// Normal Summoner's Rift games: Draft, Blind and Quickplay
+    pub fn is_normal(&self) -> bool {
+        matches!(self.queue_id, 400 | 430 | 490)
+    }
+
+    pub fn team(&self, team_id: i32) -> Option<&TeamDto> {
+        self.teams.iter().find(|t| t.team_id == team_id)
+    }
+
+    pub fn ending(&self) -> GameEnding {
+        // An early surrender also sets the surrender flag
+        if self.game_ended_in_early_surrender {
+            GameEnding::Remake
+        } else if self.game_ended_in_surrender {
+            GameEnding::Surrender
+        } else {
+            GameEnding::Full
+        }
+    }
+}
+
+/// How a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEnding {
+    /// Early surrender (remake), the game doesn't count
+    Remake,
+    /// One team surrendered (FF15 and later votes)
+    Surrender,
+    /// Played until a nexus fell
+    Full,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamDto {
+    pub team_id: i32,
+    pub objectives: ObjectivesDto,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectivesDto {
+    #[serde(default)]
+    pub baron: ObjectiveDto,
+    #[serde(default)]
+    pub dragon: ObjectiveDto,
+    #[serde(default)]
+    pub tower: ObjectiveDto,
+}
+
+impl ObjectivesDto {
+    /// Compact summary line, e.g. "DRAGONS 3 • BARONS 1 • TOWERS 8"
+    pub fn summary(&self) -> String {
+        format!(
+            "DRAGONS {} • BARONS {} • TOWERS {}",
+            self.dragon.kills, self.baron.kills, self.tower.kills
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectiveDto {
+    pub kills: i32,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ParticipantDto {
     pub puuid: String,
+    #[serde(default)]
+    pub team_id: i32,
     pub team_position: String,
+    #[serde(default)]
+    pub individual_position: String,
+    #[serde(default)]
+    pub lane: String,
+    /// Legacy role hint paired with `lane` (`DUO_SUPPORT`, `DUO_CARRY`, ...)
+    #[serde(default, rename = "role")]
+    pub lane_role: String,
+    #[serde(default)]
+    pub summoner1_id: i32,
+    #[serde(default)]
+    pub summoner2_id: i32,
     pub champion_name: String,
     pub kills: i32,
     pub deaths: i32,
@@ -154,15 +380,14 @@ impl ParticipantDto {
         }
     }
 
-    pub fn position_display(&self) -> &'static str {
-        match self.team_position.as_str() {
-            "TOP" => "Top",
-            "JUNGLE" => "Jungle",
-            "MIDDLE" => "Mid",
-            "BOTTOM" => "ADC",
-            "UTILITY" => "Support",
-            _ => "",
-        }
+    pub fn role(&self) -> Role {
+        Role::infer(
+            &self.team_position,
+            &self.individual_position,
+            &self.lane,
+            &self.lane_role,
+            [self.summoner1_id, self.summoner2_id],
+        )
     }
 
     /// Returns all item IDs (0 = empty slot)
@@ -180,3 +405,31 @@ impl ParticipantDto {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{GameEnding, InfoDto};
+
+    fn info(early_surrender: bool, surrender: bool) -> InfoDto {
+        InfoDto {
+            game_creation: 0,
+            game_end_timestamp: None,
+            game_duration: 1200,
+            game_version: "14.24.632.8043".into(),
+            game_ended_in_early_surrender: early_surrender,
+            game_ended_in_surrender: surrender,
+            participants: Vec::new(),
+            queue_id: 420,
+            teams: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ending_tells_remake_from_surrender() {
+        assert_eq!(info(false, false).ending(), GameEnding::Full);
+        assert_eq!(info(false, true).ending(), GameEnding::Surrender);
+        // Riot flags remakes as surrenders too
+        assert_eq!(info(true, true).ending(), GameEnding::Remake);
+        assert_eq!(info(true, false).ending(), GameEnding::Remake);
+    }
+}
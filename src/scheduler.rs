@@ -0,0 +1,154 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use crate::clock::Clock;
+use crate::db::Repository;
+use crate::error::AppError;
+
+/// How long shutdown waits for running jobs before giving up on them
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// When a job runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// Fixed delay after the previous run
+    Every(Duration),
+    /// Once a day at this UTC time
+    DailyAt { hour: u32, minute: u32 },
+}
+
+impl Schedule {
+    /// Unix time of the next run given the last one. A job that never ran
+    /// starts right away, a daily one waits for its time of day.
+    pub fn next_run(&self, last_run: Option<i64>, now: i64) -> i64 {
+        match *self {
+            Self::Every(every) => last_run.map_or(now, |last| last + every.as_secs() as i64),
+            Self::DailyAt { hour, minute } => {
+                let offset = i64::from(hour * 3600 + minute * 60);
+                let after = last_run.unwrap_or(now);
+                let today = after - after.rem_euclid(86400) + offset;
+                if today > after { today } else { today + 86400 }
+            }
+        }
+    }
+}
+
+/// Runs periodic jobs, remembering their last run in the database so a
+/// restart doesn't run them all again, and lets running jobs finish on
+/// shutdown
+#[derive(Debug)]
+pub struct Scheduler {
+    db: Repository,
+    clock: Arc<dyn Clock>,
+    shutdown: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Scheduler {
+    pub fn new(db: Repository, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            db,
+            clock,
+            shutdown: watch::channel(false).0,
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Run `job` on `schedule`, each run delayed by up to `jitter` so jobs
+    /// sharing a schedule don't all fire at once
+    pub fn spawn<F, Fut>(
+        &mut self,
+        name: &'static str,
+        schedule: Schedule,
+        jitter: Duration,
+        job: F,
+    ) where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), AppError>> + Send,
+    {
+        let db = self.db.clone();
+        let clock = Arc::clone(&self.clock);
+        let mut shutdown = self.shutdown.subscribe();
+
+        info!(job = name, ?schedule, "⏰ Job scheduled");
+
+        self.tasks.push(tokio::spawn(async move {
+            loop {
+                let last_run = match db.get_job_last_run(name).await {
+                    Ok(last_run) => last_run,
+                    Err(e) => {
+                        warn!(error = ?e, job = name, "⏰ ⚠️ Failed to load last run");
+                        None
+                    }
+                };
+                let now = clock.unix_now();
+                let jitter_secs = rand::rng().random_range(0..=jitter.as_secs());
+                let wait = (schedule.next_run(last_run, now) - now).max(0) as u64 + jitter_secs;
+                debug!(job = name, wait_secs = wait, "⏰ Waiting for next run");
+
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(wait)) => {}
+                    _ = shutdown.wait_for(|&stop| stop) => break,
+                }
+
+                if let Err(e) = job().await {
+                    error!(error = ?e, job = name, "⏰ ❌ Job failed");
+                }
+                // Failed runs count too, retrying right away would likely fail again
+                if let Err(e) = db.set_job_last_run(name, clock.unix_now()).await {
+                    warn!(error = ?e, job = name, "⏰ ⚠️ Failed to record run");
+                }
+
+                if *shutdown.borrow() {
+                    break;
+                }
+            }
+            debug!(job = name, "⏰ Job stopped");
+        }));
+    }
+
+    /// Stop scheduling, waiting for runs in progress to finish
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(true);
+        let tasks = async {
+            for task in self.tasks {
+                let _ = task.await;
+            }
+        };
+        if tokio::time::timeout(SHUTDOWN_GRACE, tasks).await.is_err() {
+            warn!("⏰ ⚠️ Jobs still running at shutdown, abandoning them");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Schedule;
+
+    #[test]
+    fn next_run_follows_the_schedule() {
+        let hourly = Schedule::Every(Duration::from_secs(3600));
+        assert_eq!(hourly.next_run(None, 1000), 1000);
+        assert_eq!(hourly.next_run(Some(1000), 2000), 4600);
+
+        // 2024-01-01 00:00 UTC
+        let midnight = 1_704_067_200;
+        let daily = Schedule::DailyAt {
+            hour: 4,
+            minute: 30,
+        };
+        assert_eq!(daily.next_run(None, midnight), midnight + 4 * 3600 + 1800);
+        assert_eq!(
+            daily.next_run(Some(midnight + 4 * 3600 + 1800), midnight + 5 * 3600),
+            midnight + 86400 + 4 * 3600 + 1800
+        );
+    }
+}
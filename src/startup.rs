@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::db::Repository;
+use crate::error::AppError;
+
+/// Riot calls a tracked account costs per poll, at least the match list
+const CALLS_PER_ACCOUNT: u64 = 1;
+
+/// What the instance starts with, logged once so self-hosters can check
+/// their setup from the first lines of the log
+#[derive(Debug, Default)]
+pub struct StartupReport {
+    /// Polled accounts per platform
+    pub accounts_by_region: BTreeMap<String, u32>,
+    /// Guilds with an alert channel
+    pub alert_guilds: usize,
+    pub database_size_bytes: Option<u64>,
+}
+
+impl StartupReport {
+    pub async fn collect(db: &Repository, database: &Path) -> Result<Self, AppError> {
+        let mut accounts_by_region = BTreeMap::new();
+        for player in db.get_all_tracked_players().await? {
+            *accounts_by_region.entry(player.region).or_default() += 1;
+        }
+        let alert_guilds = db.get_guilds_with_alert_channel().await?.len();
+        let database_size_bytes = tokio::fs::metadata(database).await.ok().map(|m| m.len());
+
+        Ok(Self {
+            accounts_by_region,
+            alert_guilds,
+            database_size_bytes,
+        })
+    }
+
+    pub fn accounts(&self) -> u32 {
+        self.accounts_by_region.values().sum()
+    }
+
+    /// Setup issues worth fixing before waiting for alerts that won't come
+    pub fn warnings(&self, poll_interval_secs: u64, riot_calls_per_second: u32) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.alert_guilds == 0 {
+            warnings.push(
+                "No server has an alert channel, nothing will be polled until one runs /config channel"
+                    .to_string(),
+            );
+        }
+        let calls = u64::from(self.accounts()) * CALLS_PER_ACCOUNT;
+        let budget = poll_interval_secs * u64::from(riot_calls_per_second);
+        if calls > budget {
+            warnings.push(format!(
+                "{} accounts need at least {calls} Riot calls per poll but {budget} fit in {poll_interval_secs}s, \
+                 raise POLLING_INTERVAL_SECS or lower TRACK_LIMIT",
+                self.accounts()
+            ));
+        }
+        warnings
+    }
+
+    pub fn log(&self, config: &Config, database: &Path) {
+        info!(
+            version = env!("CARGO_PKG_VERSION"),
+            commit = env!("GIT_HASH"),
+            "🦑 Startup summary"
+        );
+        for (name, value) in config.summary() {
+            info!(setting = name, %value, "⚙️ Setting");
+        }
+        info!(
+            path = %database.display(),
+            size_kb = self.database_size_bytes.map(|bytes| bytes / 1024),
+            "🗄️ Database"
+        );
+        let regions = self
+            .accounts_by_region
+            .iter()
+            .map(|(region, count)| format!("{region}: {count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        info!(
+            accounts = self.accounts(),
+            guilds = self.alert_guilds,
+            regions,
+            "🔄 Tracked accounts"
+        );
+
+        for warning in self.warnings(
+            config.polling.interval_secs,
+            config.riot_rate_limit_per_second.get(),
+        ) {
+            warn!("⚙️ ⚠️ {warning}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StartupReport;
+
+    #[test]
+    fn warns_about_setups_that_cannot_work() {
+        let mut report = StartupReport::default();
+        assert_eq!(report.warnings(60, 20).len(), 1);
+
+        report.alert_guilds = 2;
+        report.accounts_by_region.insert("euw1".into(), 30);
+        report.accounts_by_region.insert("na1".into(), 12);
+        assert_eq!(report.accounts(), 42);
+        assert!(report.warnings(60, 20).is_empty());
+
+        // 42 calls don't fit in 2 seconds at 20 per second
+        let warnings = report.warnings(2, 20);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("42 accounts need at least 42 Riot calls"));
+    }
+}
@@ -0,0 +1,641 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Days, TimeZone};
+use chrono_tz::Tz;
+
+use crate::db::{RankInfo, RankSnapshot};
+use crate::riot::ParticipantDto;
+
+/// Total LP on a single ladder spanning every tier, so ranks can be subtracted
+pub fn rank_to_lp(rank: &RankInfo) -> i32 {
+    let tier_value = match rank.tier.to_uppercase().as_str() {
+        "IRON" => 0,
+        "BRONZE" => 400,
+        "SILVER" => 800,
+        "GOLD" => 1200,
+        "PLATINUM" => 1600,
+        "EMERALD" => 2000,
+        "DIAMOND" => 2400,
+        // Apex tiers share one open-ended LP count, which carries over as
+        // players move between them
+        "MASTER" | "GRANDMASTER" | "CHALLENGER" => return 2800 + rank.lp,
+        _ => 0,
+    };
+
+    let division_value = match rank.rank.as_str() {
+        "IV" => 0,
+        "III" => 100,
+        "II" => 200,
+        "I" => 300,
+        _ => 0,
+    };
+
+    tier_value + division_value + rank.lp
+}
+
+/// Order of the apex tiers, 0 below Master
+fn apex_tier(rank: &RankInfo) -> u8 {
+    match rank.tier.to_uppercase().as_str() {
+        "CHALLENGER" => 3,
+        "GRANDMASTER" => 2,
+        "MASTER" => 1,
+        _ => 0,
+    }
+}
+
+/// Master and above, which have no divisions
+pub fn is_apex(rank: &RankInfo) -> bool {
+    apex_tier(rank) > 0
+}
+
+/// Ladder order of two ranks, higher is better. Apex tiers share their LP
+/// count, the higher tier wins a tie.
+pub fn compare_ranks(a: &RankInfo, b: &RankInfo) -> Ordering {
+    rank_to_lp(a)
+        .cmp(&rank_to_lp(b))
+        .then_with(|| apex_tier(a).cmp(&apex_tier(b)))
+}
+
+/// Division or tier a ranked game moved the player to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankChange {
+    Promoted,
+    Demoted,
+}
+
+impl RankChange {
+    /// e.g. "▲ PROMOTED TO GOLD IV" or "▼ DEMOTED TO MASTER"
+    pub fn banner(self, rank: &RankInfo) -> String {
+        let tier = rank.tier.to_uppercase();
+        let standing = if is_apex(rank) {
+            tier
+        } else {
+            format!("{tier} {}", rank.rank)
+        };
+        match self {
+            Self::Promoted => format!("▲ PROMOTED TO {standing}"),
+            Self::Demoted => format!("▼ DEMOTED TO {standing}"),
+        }
+    }
+}
+
+/// Whether the game crossed a division or tier line, LP moves within a
+/// division (or within an apex tier) are no change
+pub fn rank_change(old: &RankInfo, new: &RankInfo) -> Option<RankChange> {
+    let floor = |rank: &RankInfo| RankInfo {
+        lp: 0,
+        ..rank.clone()
+    };
+    match compare_ranks(&floor(new), &floor(old)) {
+        Ordering::Greater => Some(RankChange::Promoted),
+        Ordering::Less => Some(RankChange::Demoted),
+        Ordering::Equal => None,
+    }
+}
+
+/// Ranked results since the start of the day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SessionStats {
+    pub wins: i32,
+    pub losses: i32,
+    pub lp_delta: i32,
+}
+
+impl SessionStats {
+    /// e.g. "TODAY 3W-1L • +42 LP"
+    pub fn summary(&self) -> String {
+        format!(
+            "TODAY {}W-{}L • {:+} LP",
+            self.wins, self.losses, self.lp_delta
+        )
+    }
+}
+
+/// Games played between two snapshots of the same queue, `None` if there were
+/// none or the counters went backwards (season reset)
+pub fn session_stats(baseline: &RankSnapshot, latest: &RankSnapshot) -> Option<SessionStats> {
+    let wins = latest.wins - baseline.wins;
+    let losses = latest.losses - baseline.losses;
+    if wins < 0 || losses < 0 || wins + losses == 0 {
+        return None;
+    }
+
+    Some(SessionStats {
+        wins,
+        losses,
+        lp_delta: rank_to_lp(&latest.rank_info()) - rank_to_lp(&baseline.rank_info()),
+    })
+}
+
+/// LP a ranked game moves on average, won or lost
+const LP_PER_GAME: i32 = 25;
+/// Ranked games on record before the winrate says anything
+const FORECAST_MIN_GAMES: i32 = 10;
+/// Forecasts further out than this are noise, not shown
+const FORECAST_MAX_GAMES: u32 = 99;
+
+/// Estimated games to the next division at the current winrate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DivisionForecast {
+    pub games: u32,
+    /// e.g. "PLATINUM IV" or "MASTER"
+    pub next: String,
+    /// Winrate of the season, in percent
+    pub winrate: u32,
+}
+
+impl DivisionForecast {
+    /// e.g. "~6 GAMES TO GOLD I • 58% WR"
+    pub fn summary(&self) -> String {
+        format!(
+            "~{} GAMES TO {} • {}% WR",
+            self.games, self.next, self.winrate
+        )
+    }
+}
+
+/// Games to the next division if wins and losses keep coming at the season's
+/// rate, each moving `LP_PER_GAME`. `None` for apex tiers, too few games, or
+/// a winrate that doesn't climb.
+pub fn division_forecast(standing: &RankSnapshot) -> Option<DivisionForecast> {
+    const TIERS: [&str; 7] = [
+        "IRON", "BRONZE", "SILVER", "GOLD", "PLATINUM", "EMERALD", "DIAMOND",
+    ];
+    const DIVISIONS: [&str; 4] = ["IV", "III", "II", "I"];
+
+    let games = standing.wins + standing.losses;
+    if games < FORECAST_MIN_GAMES {
+        return None;
+    }
+    // Net LP over the season, spread over its games
+    let net_lp = LP_PER_GAME * (standing.wins - standing.losses);
+    if net_lp <= 0 {
+        return None;
+    }
+
+    let tier_upper = standing.tier.to_uppercase();
+    let tier = TIERS.iter().position(|&t| t == tier_upper)?;
+    let division = DIVISIONS.iter().position(|&d| d == standing.rank)?;
+    let next = match (DIVISIONS.get(division + 1), TIERS.get(tier + 1)) {
+        (Some(division), _) => format!("{} {division}", TIERS[tier]),
+        (None, Some(tier)) => format!("{tier} IV"),
+        (None, None) => "MASTER".to_string(),
+    };
+
+    let needed = (100 - standing.lp).max(1);
+    let forecast = ((needed * games) as u32).div_ceil(net_lp as u32);
+    (forecast <= FORECAST_MAX_GAMES).then(|| DivisionForecast {
+        games: forecast,
+        next,
+        winrate: (standing.wins * 100 + games / 2) as u32 / games as u32,
+    })
+}
+
+/// Games a player needs on record before a champion counts as a first time,
+/// otherwise every pick of a newly tracked account would be one
+const FIRST_TIME_MIN_GAMES: i64 = 20;
+
+/// Remark about the champion played, shown on the alert
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChampionNote {
+    /// First game on this champion since the account is tracked
+    FirstTime(String),
+    /// The player's most played champion
+    Signature(String),
+}
+
+impl fmt::Display for ChampionNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FirstTime(champion) => write!(f, "First time {champion}!"),
+            Self::Signature(champion) => write!(f, "Signature {champion}"),
+        }
+    }
+}
+
+/// Note for a game on `champion`, from the champion pool (most played first)
+/// with that game already counted
+pub fn champion_note(pool: &[(String, i64)], champion: &str) -> Option<ChampionNote> {
+    let total: i64 = pool.iter().map(|(_, games)| games).sum();
+    if total < FIRST_TIME_MIN_GAMES {
+        return None;
+    }
+
+    let games = pool
+        .iter()
+        .find(|(name, _)| name == champion)
+        .map_or(0, |(_, games)| *games);
+    if games == 1 {
+        return Some(ChampionNote::FirstTime(champion.to_string()));
+    }
+
+    // A clear favourite only, not the first of several tied champions
+    match pool {
+        [(top, top_games), rest @ ..]
+            if top == champion && rest.first().is_none_or(|(_, next)| next < top_games) =>
+        {
+            Some(ChampionNote::Signature(champion.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Normal games a player needs in the week to be its MVP
+pub const MVP_MIN_GAMES: i64 = 5;
+
+/// Rough rating of a single game, higher is better: KDA weighs most, then
+/// damage, farm and vision per minute
+pub fn performance_score(participant: &ParticipantDto, game_duration_secs: i64) -> f64 {
+    let minutes = (game_duration_secs as f64 / 60.0).max(1.0);
+    participant.kda_ratio() * 2.0
+        + participant.total_damage_dealt_to_champions as f64 / minutes / 200.0
+        + participant.cs_per_minute(game_duration_secs) * 0.5
+        + participant.vision_score as f64 / minutes * 2.0
+}
+
+/// Most players `balance_teams` splits, a 5v5
+const MAX_BALANCED_PLAYERS: usize = 10;
+
+/// Split players into two teams of equal size with the closest rating totals.
+/// Returns the indices of each team; an odd player out is left out, as are
+/// players past `MAX_BALANCED_PLAYERS`.
+pub fn balance_teams(ratings: &[i32]) -> (Vec<usize>, Vec<usize>) {
+    let size = ratings.len().min(MAX_BALANCED_PLAYERS) / 2;
+    let players = size * 2;
+    let total: i32 = ratings[..players].iter().sum();
+
+    // Player 0 always goes to the first team, halving the search. With at
+    // most 10 players, at most 126 splits are tried.
+    let mut best: Option<(i32, u32)> = None;
+    for mask in 0u32..(1 << players) {
+        if mask & 1 == 0 || mask.count_ones() as usize != size {
+            continue;
+        }
+        let first: i32 = (0..players)
+            .filter(|i| mask & (1 << i) != 0)
+            .map(|i| ratings[i])
+            .sum();
+        let gap = (total - 2 * first).abs();
+        if best.is_none_or(|(best_gap, _)| gap < best_gap) {
+            best = Some((gap, mask));
+        }
+    }
+
+    let mask = best.map_or(0, |(_, mask)| mask);
+    (0..players).partition(|i| mask & (1 << i) != 0)
+}
+
+/// Unix timestamp of the last local midnight in `tz`
+pub fn start_of_day(tz: Tz, now: i64) -> i64 {
+    let Some(now_utc) = DateTime::from_timestamp(now, 0) else {
+        return now;
+    };
+    let local = now_utc.with_timezone(&tz);
+    local
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| tz.from_local_datetime(&midnight).earliest())
+        .map_or(now, |midnight| midnight.timestamp())
+}
+
+/// Unix timestamp of the last local Monday midnight in `tz`
+pub fn start_of_week(tz: Tz, now: i64) -> i64 {
+    let Some(now_utc) = DateTime::from_timestamp(now, 0) else {
+        return now;
+    };
+    let local = now_utc.with_timezone(&tz).date_naive();
+    local
+        .checked_sub_days(Days::new(local.weekday().num_days_from_monday().into()))
+        .and_then(|monday| monday.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| tz.from_local_datetime(&midnight).earliest())
+        .map_or(now, |midnight| midnight.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Tz;
+    use proptest::prelude::*;
+
+    use super::{
+        ChampionNote, RankChange, balance_teams, champion_note, compare_ranks, division_forecast,
+        rank_change, rank_to_lp, session_stats, start_of_day, start_of_week,
+    };
+    use crate::db::{RankInfo, RankSnapshot};
+    use crate::discord::image_gen::calculate_lp_diff;
+
+    const TIERS: [&str; 7] = [
+        "IRON", "BRONZE", "SILVER", "GOLD", "PLATINUM", "EMERALD", "DIAMOND",
+    ];
+    const DIVISIONS: [&str; 4] = ["IV", "III", "II", "I"];
+
+    fn rank(tier: &str, division: &str, lp: i32) -> RankInfo {
+        RankInfo {
+            tier: tier.to_string(),
+            rank: division.to_string(),
+            lp,
+        }
+    }
+
+    /// Every rank below Master, lowest first
+    fn divided_ranks() -> impl Iterator<Item = RankInfo> {
+        TIERS.into_iter().flat_map(|tier| {
+            DIVISIONS
+                .into_iter()
+                .flat_map(move |division| (0..100).map(move |lp| rank(tier, division, lp)))
+        })
+    }
+
+    /// Rank after `gain` LP the way the ladder moves: divisions hold 0-99 LP,
+    /// the rest carries over, and Master starts right above Diamond I.
+    /// `None` below Iron IV 0 LP.
+    fn climb(from: &RankInfo, gain: i32) -> Option<RankInfo> {
+        let tier = TIERS.iter().position(|&t| t == from.tier)? as i32;
+        let division = DIVISIONS.iter().position(|&d| d == from.rank)? as i32;
+        let total = (tier * 4 + division) * 100 + from.lp + gain;
+        match total {
+            ..0 => None,
+            2800.. => Some(rank("MASTER", "I", total - 2800)),
+            _ => Some(rank(
+                TIERS[(total / 400) as usize],
+                DIVISIONS[(total / 100 % 4) as usize],
+                total % 100,
+            )),
+        }
+    }
+
+    fn snapshot(rank: &str, lp: i32, wins: i32, losses: i32) -> RankSnapshot {
+        RankSnapshot {
+            queue: "RANKED_SOLO_5x5".to_string(),
+            tier: "GOLD".to_string(),
+            rank: rank.to_string(),
+            lp,
+            wins,
+            losses,
+        }
+    }
+
+    #[test]
+    fn lp_diff_matches_the_game_across_promotions() {
+        for from in divided_ranks() {
+            for gain in 1..=60 {
+                let won = climb(&from, gain).unwrap();
+                assert_eq!(
+                    rank_to_lp(&won) - rank_to_lp(&from),
+                    gain,
+                    "win {from:?} -> {won:?}"
+                );
+                if let Some(lost) = climb(&from, -gain) {
+                    assert_eq!(
+                        rank_to_lp(&lost) - rank_to_lp(&from),
+                        -gain,
+                        "loss {from:?} -> {lost:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Any rank below Master
+    fn divided_rank() -> impl Strategy<Value = RankInfo> {
+        (0..TIERS.len(), 0..DIVISIONS.len(), 0..100)
+            .prop_map(|(tier, division, lp)| rank(TIERS[tier], DIVISIONS[division], lp))
+    }
+
+    /// Any Master, Grandmaster or Challenger rank
+    fn apex_rank() -> impl Strategy<Value = RankInfo> {
+        (
+            prop::sample::select(&["MASTER", "GRANDMASTER", "CHALLENGER"][..]),
+            0..3000,
+        )
+            .prop_map(|(tier, lp)| rank(tier, "I", lp))
+    }
+
+    proptest! {
+        /// (cached league, current league, win) as the poller sees them: the
+        /// diff has the sign of the result and the size of the LP move, even
+        /// across division and tier lines
+        #[test]
+        fn lp_diff_follows_the_game(
+            cached in divided_rank(),
+            gain in 1..=60i32,
+            win in any::<bool>(),
+        ) {
+            let moved = if win { gain } else { -gain };
+            let current = climb(&cached, moved);
+            prop_assume!(current.is_some());
+            let diff = calculate_lp_diff(Some(&cached), current.as_ref()).unwrap();
+            prop_assert_eq!(diff > 0, win);
+            prop_assert_eq!(diff, moved);
+        }
+
+        /// Apex tiers share their LP, moving between them is no LP change
+        #[test]
+        fn apex_lp_diff_is_the_lp_move(cached in apex_rank(), current in apex_rank()) {
+            prop_assert_eq!(
+                calculate_lp_diff(Some(&cached), Some(&current)),
+                Some(current.lp - cached.lp)
+            );
+        }
+
+        /// Every apex rank sits above every divided one
+        #[test]
+        fn apex_ranks_top_the_ladder(below in divided_rank(), apex in apex_rank()) {
+            prop_assert!(calculate_lp_diff(Some(&below), Some(&apex)).unwrap() > 0);
+            prop_assert_eq!(compare_ranks(&apex, &below), std::cmp::Ordering::Greater);
+        }
+    }
+
+    #[test]
+    fn ladder_is_ordered_and_apex_lp_carries_over() {
+        let ladder: Vec<i32> = divided_ranks()
+            .chain((0..500).map(|lp| rank("MASTER", "I", lp)))
+            .map(|rank| rank_to_lp(&rank))
+            .collect();
+        assert!(ladder.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Promotions between apex tiers keep the LP
+        for lp in [0, 250, 1200] {
+            let master = rank_to_lp(&rank("MASTER", "I", lp));
+            assert_eq!(rank_to_lp(&rank("GRANDMASTER", "I", lp)), master);
+            assert_eq!(rank_to_lp(&rank("CHALLENGER", "I", lp)), master);
+        }
+    }
+
+    #[test]
+    fn session_counts_games_and_lp_across_divisions() {
+        let baseline = snapshot("II", 80, 10, 10);
+        let latest = snapshot("I", 22, 13, 11);
+        let stats = session_stats(&baseline, &latest).unwrap();
+        assert_eq!((stats.wins, stats.losses, stats.lp_delta), (3, 1, 42));
+        assert_eq!(stats.summary(), "TODAY 3W-1L • +42 LP");
+
+        assert!(session_stats(&latest, &latest).is_none());
+        assert!(session_stats(&latest, &snapshot("IV", 0, 0, 0)).is_none());
+    }
+
+    #[test]
+    fn forecast_follows_the_winrate() {
+        // 60% winrate: +5 LP a game on average, 30 LP to go
+        let forecast = division_forecast(&snapshot("II", 70, 30, 20)).unwrap();
+        assert_eq!(forecast.summary(), "~6 GAMES TO GOLD I • 60% WR");
+
+        let promotion = RankSnapshot {
+            tier: "DIAMOND".to_string(),
+            ..snapshot("I", 90, 60, 40)
+        };
+        assert_eq!(division_forecast(&promotion).unwrap().next, "MASTER");
+        assert_eq!(
+            division_forecast(&snapshot("I", 90, 60, 40)).unwrap().next,
+            "PLATINUM IV"
+        );
+
+        // Not climbing, too few games, or too far out
+        assert!(division_forecast(&snapshot("II", 70, 20, 20)).is_none());
+        assert!(division_forecast(&snapshot("II", 70, 5, 0)).is_none());
+        assert!(division_forecast(&snapshot("II", 0, 101, 99)).is_none());
+        let master = RankSnapshot {
+            tier: "MASTER".to_string(),
+            ..snapshot("I", 90, 60, 40)
+        };
+        assert!(division_forecast(&master).is_none());
+    }
+
+    #[test]
+    fn champion_note_needs_history() {
+        let pool = |games: &[(&str, i64)]| -> Vec<(String, i64)> {
+            games.iter().map(|(c, n)| (c.to_string(), *n)).collect()
+        };
+
+        let history = pool(&[("Ahri", 12), ("Lux", 8), ("Azir", 1)]);
+        assert_eq!(
+            champion_note(&history, "Azir"),
+            Some(ChampionNote::FirstTime("Azir".into()))
+        );
+        assert_eq!(
+            champion_note(&history, "Ahri").map(|note| note.to_string()),
+            Some("Signature Ahri".into())
+        );
+        assert_eq!(champion_note(&history, "Lux"), None);
+
+        // Not enough games on record yet
+        assert_eq!(
+            champion_note(&pool(&[("Ahri", 5), ("Azir", 1)]), "Azir"),
+            None
+        );
+        // Tied favourites are no signature
+        assert_eq!(
+            champion_note(&pool(&[("Ahri", 10), ("Lux", 10)]), "Ahri"),
+            None
+        );
+    }
+
+    #[test]
+    fn balance_teams_minimises_rating_gap() {
+        let ratings = [2400, 2000, 1600, 1200, 1200, 800, 800, 400, 400, 0];
+        let (blue, red) = balance_teams(&ratings);
+        assert_eq!((blue.len(), red.len()), (5, 5));
+        let sum = |team: &[usize]| team.iter().map(|&i| ratings[i]).sum::<i32>();
+        // 10800 LP in steps of 400 can't split evenly, one step is the best
+        assert_eq!((sum(&blue) - sum(&red)).abs(), 400);
+
+        // The odd player out sits out
+        let (blue, red) = balance_teams(&[1000, 100, 900]);
+        assert_eq!((blue, red), (vec![0], vec![1]));
+        assert_eq!(balance_teams(&[]), (vec![], vec![]));
+
+        // Oversized rosters don't overflow the split masks
+        let (blue, red) = balance_teams(&[100; 40]);
+        assert_eq!((blue.len(), red.len()), (5, 5));
+        assert!(blue.iter().chain(&red).all(|&i| i < 10));
+    }
+
+    #[test]
+    fn ranks_follow_the_ladder() {
+        use std::cmp::Ordering;
+
+        let gold = rank("GOLD", "I", 99);
+        let plat = rank("PLATINUM", "IV", 0);
+        assert_eq!(compare_ranks(&plat, &gold), Ordering::Greater);
+        assert_eq!(
+            compare_ranks(&rank("MASTER", "I", 0), &rank("DIAMOND", "I", 99)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_ranks(
+                &rank("CHALLENGER", "I", 900),
+                &rank("GRANDMASTER", "I", 900)
+            ),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_ranks(
+                &rank("GRANDMASTER", "I", 400),
+                &rank("CHALLENGER", "I", 300)
+            ),
+            Ordering::Greater
+        );
+        assert_eq!(compare_ranks(&gold, &gold.clone()), Ordering::Equal);
+    }
+
+    #[test]
+    fn rank_changes_need_a_new_division_or_tier() {
+        let change = |from: RankInfo, to: RankInfo| rank_change(&from, &to);
+        assert_eq!(
+            change(rank("GOLD", "I", 90), rank("PLATINUM", "IV", 0)),
+            Some(RankChange::Promoted)
+        );
+        assert_eq!(
+            change(rank("GOLD", "III", 0), rank("GOLD", "IV", 75)),
+            Some(RankChange::Demoted)
+        );
+        assert_eq!(change(rank("GOLD", "II", 10), rank("GOLD", "II", 90)), None);
+
+        // Apex LP is one count, only the tier moves
+        assert_eq!(
+            change(rank("DIAMOND", "I", 95), rank("MASTER", "I", 15)),
+            Some(RankChange::Promoted)
+        );
+        assert_eq!(
+            change(rank("MASTER", "I", 200), rank("MASTER", "I", 400)),
+            None
+        );
+        assert_eq!(
+            change(rank("GRANDMASTER", "I", 500), rank("MASTER", "I", 480)),
+            Some(RankChange::Demoted)
+        );
+        assert_eq!(
+            change(rank("MASTER", "I", 0), rank("DIAMOND", "I", 80)),
+            Some(RankChange::Demoted)
+        );
+
+        assert_eq!(
+            RankChange::Promoted.banner(&rank("GOLD", "IV", 0)),
+            "▲ PROMOTED TO GOLD IV"
+        );
+        assert_eq!(
+            RankChange::Demoted.banner(&rank("MASTER", "I", 0)),
+            "▼ DEMOTED TO MASTER"
+        );
+    }
+
+    #[test]
+    fn start_of_day_follows_timezone() {
+        // 2024-06-01 01:30 UTC is still May 31st in New York
+        let now = 1_717_205_400;
+        assert_eq!(start_of_day(Tz::UTC, now), 1_717_200_000);
+        assert_eq!(start_of_day(Tz::America__New_York, now), 1_717_128_000);
+    }
+
+    #[test]
+    fn start_of_week_is_local_monday() {
+        // Saturday 2024-06-01 01:30 UTC, still Friday in New York
+        let now = 1_717_205_400;
+        // Monday 2024-05-27 00:00 UTC
+        assert_eq!(start_of_week(Tz::UTC, now), 1_716_768_000);
+        // Monday 2024-05-27 00:00 EDT
+        assert_eq!(start_of_week(Tz::America__New_York, now), 1_716_782_400);
+        // A Monday midnight is its own week start
+        assert_eq!(start_of_week(Tz::UTC, 1_716_768_000), 1_716_768_000);
+    }
+}